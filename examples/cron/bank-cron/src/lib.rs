@@ -1,23 +1,11 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, from_json};
-use std::{vec::Vec};
-
-#[cw_serde]
-pub struct InstantiateMsg {}
-
-#[cw_serde]
-pub enum ExecuteMsg {}
-
-#[cw_serde]
-pub struct QueryMsg {
-    msg: Binary,
-    fis_input: Vec<FisInput>
-}
-
-#[cw_serde]
-pub struct FisInput {
-    data: Vec<Binary>
-}
+use cosmwasm_std::{to_json_binary, Coin, Deps, Env, Uint128};
+use nexus_sdk::{
+    astromesh::{FISInput, FISInstruction, MsgSend, StrategyOutput},
+    error::{NexusError, NexusResult},
+    nexus_strategy,
+};
+use std::str::FromStr;
 
 #[cw_serde]
 pub struct CronInput {
@@ -26,76 +14,29 @@ pub struct CronInput {
     denom: String,
 }
 
-#[cw_serde]
-pub struct FISInstruction {
-    plane: String,
-    action: String,
-    address: String,
-    msg: Vec<u8>,
-}
-
-#[cw_serde]
-pub struct StrategyOutput {
-    instructions: Vec<FISInstruction>,
-}
-
-#[cw_serde]
-pub struct MsgSend {
-    from_address: String,
-    to_address: String,
-    amount: Vec<BankAmount>,
-}
-
-#[cw_serde]
-pub struct BankAmount {
-    denom: String,
-    amount: String,
-}
-
-#[entry_point]
-pub fn instantiate(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "instantiate"))
-}
-
-#[entry_point]
-pub fn execute(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: ExecuteMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "execute"))
-}
-
-#[entry_point]
-pub fn query(_deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    // parse cron input
-    let command = from_json::<CronInput>(msg.msg)?;
+nexus_strategy!(CronInput, handle);
 
+pub fn handle(
+    _deps: Deps,
+    env: Env,
+    command: CronInput,
+    _fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
     // parse command, we can store it as proto bytes, encrypted binary
-    let mut instructions = vec![];
-
+    let amount = Uint128::from_str(&command.amount)
+        .map_err(|e| NexusError::invalid_input(format!("amount {}: {}", command.amount, e)))?;
     // send usdt
-    instructions.push(FISInstruction {
-        plane: "COSMOS".to_string(),
-        action: "COSMOS_BANK_SEND".to_string(),
-        address: "".to_string(),
-        msg: to_json_binary(&MsgSend {
-            from_address: env.contract.address.clone().into_string(),
-            to_address: command.receiver.to_string(),
-            amount: vec![BankAmount {
-                denom: command.denom.to_string(),
-                amount: command.amount.to_string(),
+    let instructions = vec![FISInstruction::cosmos_bank_send(
+        to_json_binary(&MsgSend::new(
+            env.contract.address.into_string(),
+            command.receiver,
+            vec![Coin {
+                denom: command.denom,
+                amount,
             }],
-        })
-        .unwrap()
+        ))?
         .to_vec(),
-    });
+    )];
 
-    Ok(to_json_binary(&StrategyOutput { instructions }).unwrap())
+    Ok(StrategyOutput::new(instructions))
 }
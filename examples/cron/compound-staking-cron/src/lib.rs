@@ -1,27 +1,11 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{
-    entry_point, from_json, to_json_binary, to_json_vec, Binary, Coin,
-    DelegationTotalRewardsResponse, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
-    Uint128,
+use cosmwasm_std::{to_json_vec, Coin, DelegationTotalRewardsResponse, Deps, Env, Uint128};
+use nexus_sdk::{
+    astromesh::{FISInput, FISInstruction, StrategyOutput},
+    error::{NexusError, NexusResult},
+    input::Slot,
+    nexus_strategy,
 };
-use std::vec::Vec;
-
-#[cw_serde]
-pub struct InstantiateMsg {}
-
-#[cw_serde]
-pub enum ExecuteMsg {}
-
-#[cw_serde]
-pub struct QueryMsg {
-    msg: Binary,
-    fis_input: Vec<FisInput>,
-}
-
-#[cw_serde]
-pub struct FisInput {
-    data: Vec<Binary>,
-}
 
 #[cw_serde]
 pub struct MsgWithdrawDelegatorReward {
@@ -40,73 +24,31 @@ pub struct MsgDelegate {
     pub amount: Coin,
 }
 
-#[cw_serde]
-pub struct FISInstruction {
-    plane: String,
-    action: String,
-    address: String,
-    msg: Vec<u8>,
-}
-
-#[cw_serde]
-pub struct StrategyOutput {
-    instructions: Vec<FISInstruction>,
-}
+nexus_strategy!(handle);
 
-#[cw_serde]
-pub struct MsgSend {
-    from_address: String,
-    to_address: String,
-    amount: Vec<Coin>,
-}
+// fis input layout: [[delegation total rewards]]
+const REWARDS: Slot<DelegationTotalRewardsResponse> = Slot::new(0, 0, "delegation rewards");
 
-#[entry_point]
-pub fn instantiate(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "instantiate"))
-}
-
-#[entry_point]
-pub fn execute(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: ExecuteMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "execute"))
-}
-
-#[entry_point]
-pub fn query(_deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn handle(_deps: Deps, env: Env, fis_input: &[FISInput]) -> NexusResult<StrategyOutput> {
     let mut instructions = vec![];
 
     // 1. parse claimable reards
-    let fis = &msg.fis_input[0];
-    let rewards_response =
-        from_json::<DelegationTotalRewardsResponse>(fis.data.first().unwrap()).unwrap();
+    let rewards_response = REWARDS.load(fis_input)?;
 
     let rewards = rewards_response.rewards;
 
-    if rewards.len() == 0 {
-        return Err(StdError::generic_err(
-            format!("No rewards to claim").as_str(),
-        ));
+    if rewards.is_empty() {
+        return Err(NexusError::NotFound("rewards to claim".to_string()));
     }
 
     let delegator_address = env.contract.address.to_string();
 
-    for idx in 0..rewards.len() {
-        let validator_address = rewards[idx].validator_address.clone();
-        let reward = &rewards[idx].reward[0];
-        let reward_amount_uint256 = reward.amount.to_uint_floor();
-        let reward_amount = reward_amount_uint256
-            .to_string()
-            .parse::<Uint128>()
-            .unwrap();
+    for delegator_reward in rewards {
+        let validator_address = delegator_reward.validator_address;
+        let Some(reward) = delegator_reward.reward.first() else {
+            continue;
+        };
+        let reward_amount = Uint128::try_from(reward.amount.to_uint_floor())?;
 
         // 2. compose cosmos msg to claim rewards
         let claim_reward = MsgWithdrawDelegatorReward {
@@ -115,12 +57,7 @@ pub fn query(_deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             validator_address: validator_address.to_string(),
         };
 
-        instructions.push(FISInstruction {
-            plane: "COSMOS".to_string(),
-            action: "COSMOS_INVOKE".to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&claim_reward).unwrap(),
-        });
+        instructions.push(FISInstruction::cosmos_invoke(to_json_vec(&claim_reward)?));
 
         // 3. compose cosmos msg to stake the claimed rewards
         let stake_reward = MsgDelegate {
@@ -129,17 +66,12 @@ pub fn query(_deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             validator_address,
             amount: Coin {
                 denom: "lux".to_string(),
-                amount: reward_amount.into(),
+                amount: reward_amount,
             },
         };
 
-        instructions.push(FISInstruction {
-            plane: "COSMOS".to_string(),
-            action: "COSMOS_INVOKE".to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&stake_reward).unwrap(),
-        });
+        instructions.push(FISInstruction::cosmos_invoke(to_json_vec(&stake_reward)?));
     }
 
-    Ok(to_json_binary(&StrategyOutput { instructions }).unwrap())
+    Ok(StrategyOutput::new(instructions))
 }
@@ -1,20 +1,11 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{
-    entry_point, from_json, to_json_binary, to_json_vec, Binary, Coin, Deps, DepsMut, Env, Int64, MessageInfo, Response, StdResult, Uint64
+use cosmwasm_std::{to_json_binary, to_json_vec, Binary, Coin, Deps, Env, Int64, Uint64};
+use nexus_sdk::{
+    astromesh::{FISInput, FISInstruction, MsgSend, StrategyOutput},
+    error::NexusResult,
+    input::Slot,
+    nexus_strategy,
 };
-use std::vec::Vec;
-
-#[cw_serde]
-pub struct InstantiateMsg {}
-
-#[cw_serde]
-pub enum ExecuteMsg {}
-
-#[cw_serde]
-pub struct QueryMsg {
-    msg: Binary,
-    fis_input: Vec<FisInput>,
-}
 
 #[cw_serde]
 pub struct InterpoolResponse {
@@ -23,9 +14,9 @@ pub struct InterpoolResponse {
 
 #[cw_serde]
 pub struct CommissionConfig {
-    pub management_fee_rate: Int64,      // Rate for management fee
-    pub management_fee_interval: Int64,  // Interval for applying management fee
-    pub trading_fee_rate: Int64,         // Rate for trading fee
+    pub management_fee_rate: Int64,     // Rate for management fee
+    pub management_fee_interval: Int64, // Interval for applying management fee
+    pub trading_fee_rate: Int64,        // Rate for trading fee
 }
 
 #[cw_serde]
@@ -38,13 +29,13 @@ pub struct CommissionFees {
 pub struct MsgUpdatePool {
     #[serde(rename = "@type")]
     pub ty: String,
-    pub sender: String,                  // Sender address
-    pub pool_id: String,                 // Pool ID
-    pub input_blob: Vec<u8>,             // Input blob for processing
-    pub output_blob: Vec<u8>,            // Output blob for results
-    pub charge_management_fee: bool,     // Flag to charge management fee
-    pub trading_fee: Vec<Coin>,          // Trading fees to be charged
-    pub cron_id: String,                 // ID of the cron job associated with this update
+    pub sender: String,              // Sender address
+    pub pool_id: String,             // Pool ID
+    pub input_blob: Vec<u8>,         // Input blob for processing
+    pub output_blob: Vec<u8>,        // Output blob for results
+    pub charge_management_fee: bool, // Flag to charge management fee
+    pub trading_fee: Vec<Coin>,      // Trading fees to be charged
+    pub cron_id: String,             // ID of the cron job associated with this update
 }
 
 #[cw_serde]
@@ -53,20 +44,15 @@ pub struct InterPool {
     pub operator_addr: String,         // Address of the pool operator
     pub inventory_snapshot: Vec<Coin>, // Ongoing assets in the pool
     pub base_capital: Vec<Coin>,       // Initial assets before any trades
-    pub operator_commission_config: Option<CommissionConfig>,   // Commission percentage for the operator
-    pub operator_commission_fees: Option<CommissionFees>,   // Commission percentage for the operator
-    pub input_blob: Option<Binary>,    // Flow control data for cron service
-    pub output_blob: Option<Binary>,   // Extra state for LP, reward tokens
-    pub cron_id: String,   // Cron job controlling the pool
+    pub operator_commission_config: Option<CommissionConfig>, // Commission percentage for the operator
+    pub operator_commission_fees: Option<CommissionFees>, // Commission percentage for the operator
+    pub input_blob: Option<Binary>,                       // Flow control data for cron service
+    pub output_blob: Option<Binary>,                      // Extra state for LP, reward tokens
+    pub cron_id: String,                                  // Cron job controlling the pool
     pub pool_account: String,
     pub next_commission_time: Uint64,
 }
 
-#[cw_serde]
-pub struct FisInput {
-    data: Vec<Binary>,
-}
-
 #[cw_serde]
 pub struct CronInput {
     receiver: String,
@@ -74,100 +60,52 @@ pub struct CronInput {
     denom: String,
 }
 
-#[cw_serde]
-pub struct FISInstruction {
-    plane: String,
-    action: String,
-    address: String,
-    msg: Vec<u8>,
-}
+nexus_strategy!(handle);
 
-#[cw_serde]
-pub struct StrategyOutput {
-    instructions: Vec<FISInstruction>,
-}
-
-#[cw_serde]
-pub struct MsgSend {
-    from_address: String,
-    to_address: String,
-    amount: Vec<Coin>,
-}
-
-#[entry_point]
-pub fn instantiate(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "instantiate"))
-}
+// fis input layout: [[interpool]]
+const POOL: Slot<InterpoolResponse> = Slot::new(0, 0, "interpool");
 
-#[entry_point]
-pub fn execute(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: ExecuteMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "execute"))
-}
-
-#[entry_point]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn handle(deps: Deps, env: Env, fis_input: &[FISInput]) -> NexusResult<StrategyOutput> {
     // parse cron input
-    let input = msg.fis_input.get(0).unwrap().data.get(0).unwrap();
+    let input = POOL.raw(fis_input)?;
     deps.api
         .debug(format!("pool input: {:?}", input.to_string()).as_str());
-    let pool_info = from_json::<InterpoolResponse>(input)?;
+    let pool_info = POOL.load(fis_input)?;
     if pool_info.pool.inventory_snapshot.is_empty() {
-        return Ok(to_json_binary(&StrategyOutput {
-            instructions: vec![],
-        })
-        .unwrap());
+        return Ok(StrategyOutput::default());
     }
 
     deps.api
         .debug(format!("pool info: {:?}", pool_info).as_str());
     // parse command, we can store it as proto bytes, encrypted binary
-    let mut instructions = vec![];
-
-    // send usdt
-    instructions.push(FISInstruction {
-        plane: "COSMOS".to_string(),
-        action: "COSMOS_BANK_SEND".to_string(),
-        address: "".to_string(),
-        msg: to_json_binary(&MsgSend {
-            from_address: pool_info.pool.pool_account.clone(),
-            to_address: pool_info.pool.operator_addr,
-            amount: vec![Coin {
-                denom: "usdt".to_string(),
-                amount: 9u128.into(),
-            }],
-        })
-        .unwrap()
-        .to_vec(),
-    });
-
-    instructions.push(FISInstruction {
-        plane: "COSMOS".to_string(),
-        action: "COSMOS_INVOKE".to_string(),
-        address: "".to_string(),
-        msg: to_json_vec(&MsgUpdatePool {
+    let instructions = vec![
+        // send usdt
+        FISInstruction::cosmos_bank_send(
+            to_json_binary(&MsgSend::new(
+                pool_info.pool.pool_account.clone(),
+                pool_info.pool.operator_addr,
+                vec![Coin {
+                    denom: "usdt".to_string(),
+                    amount: 9u128.into(),
+                }],
+            ))?
+            .to_vec(),
+        ),
+        FISInstruction::cosmos_invoke(to_json_vec(&MsgUpdatePool {
             ty: "/flux.interpool.v1beta1.MsgUpdatePool".to_string(),
             sender: pool_info.pool.pool_account.clone(),
             pool_id: pool_info.pool.pool_id,
             input_blob: vec![],
             output_blob: vec![],
-            charge_management_fee: env.block.time.nanos() > pool_info.pool.next_commission_time.u64(),
+            charge_management_fee: env.block.time.nanos()
+                > pool_info.pool.next_commission_time.u64(),
             trading_fee: vec![Coin {
                 denom: "usdt".to_string(),
                 amount: 1u128.into(),
             }],
             cron_id: "".to_string(),
-        }).unwrap(),
-    });
+        })?),
+    ];
 
-    Ok(to_json_binary(&StrategyOutput { instructions }).unwrap())
+    Ok(StrategyOutput::new(instructions))
 }
@@ -0,0 +1,29 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Deps, Uint128};
+use nexus_sdk::{error::NexusResult, strategy::load_config};
+
+/// Graduation fees, passed as `config` at instantiate and replaced with `UpdateConfig`
+#[cw_serde]
+pub struct Config {
+    pub fee_denom: String,
+    /// paid to the token creator out of the pool's sol
+    pub token_creator_fee: Uint128,
+    /// kept by this contract
+    pub creator_fee: Uint128,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            fee_denom: "sol".to_string(),
+            token_creator_fee: Uint128::new(500_000_000),
+            creator_fee: Uint128::new(1_500_000_000),
+        }
+    }
+}
+
+impl Config {
+    pub fn load(deps: Deps) -> NexusResult<Self> {
+        Ok(load_config(deps.storage)?)
+    }
+}
@@ -1,18 +1,20 @@
 pub mod uniswap {
 
-    use cosmwasm_std::{to_json_vec, Binary, StdError, Uint128, Uint256};
+    use cosmwasm_std::{to_json_vec, Binary, Int256, Uint128, Uint256};
     use serde::{Deserialize, Serialize};
 
-    use crate::astromesh::{FISInstruction, PoolManager, ACTION_VM_INVOKE, PLANE_EVM};
+    use crate::{astromesh::PoolManager, network::Network};
+    use nexus_sdk::{
+        astromesh::{FISInstruction, Plane},
+        error::{NexusError, NexusResult},
+        evm::{Function, MsgExecuteContract, Token},
+    };
 
     pub struct Uniswap {
         pub fee: u32,
         pub price: f64,
     }
 
-    pub const POOL_MANAGER: &str = "6ff00f6b2120157fca353fbe24d25536042197df";
-    pub const POOL_ACTION: &str = "366c9837f9a32cc11ac5cac1602e57b73e6bf784";
-
     #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct PoolKey {
         /// @notice The lower currency of the pool, sorted numerically
@@ -44,29 +46,14 @@ pub mod uniswap {
             }
         }
 
-        pub fn serialize(&self) -> Vec<u8> {
-            let mut serialized = Vec::with_capacity(160);
-            // Adding padding for currency0
-            serialized.extend_from_slice(&[0u8; 12]); // padding to make it 32 bytes
-            serialized.extend_from_slice(&self.currency0);
-
-            // Adding padding for currency1
-            serialized.extend_from_slice(&[0u8; 12]); // padding to make it 32 bytes
-            serialized.extend_from_slice(&self.currency1);
-
-            // Adding padding for fee
-            serialized.extend_from_slice(&[0u8; 28]); // padding to make it 32 bytes
-            serialized.extend_from_slice(&self.fee.to_be_bytes());
-
-            // Adding padding for tick_spacing
-            serialized.extend_from_slice(&[0u8; 28]); // padding to make it 32 bytes
-            serialized.extend_from_slice(&self.tick_spacing.to_be_bytes());
-
-            // Adding padding for hooks
-            serialized.extend_from_slice(&[0u8; 12]); // padding to make it 32 bytes
-            serialized.extend_from_slice(&self.hooks);
-
-            serialized
+        pub fn to_token(&self) -> Token {
+            Token::Tuple(vec![
+                Token::Address(self.currency0),
+                Token::Address(self.currency1),
+                Token::Uint(Uint256::from(self.fee)),
+                Token::Int(Int256::from(self.tick_spacing)),
+                Token::Address(self.hooks),
+            ])
         }
     }
 
@@ -88,70 +75,21 @@ pub mod uniswap {
             }
         }
 
-        pub fn serialize(&self) -> Vec<u8> {
-            let mut serialized = Vec::with_capacity(128);
-
-            // Adding padding for tick_lower
-            serialized.extend_from_slice(&[0u8; 24]); // padding to make it 32 bytes
-            serialized.extend_from_slice(&self.tick_lower.to_be_bytes());
-
-            // Adding padding for tick_upper
-            serialized.extend_from_slice(&[0u8; 24]); // padding to make it 32 bytes
-            serialized.extend_from_slice(&self.tick_upper.to_be_bytes());
-
-            // Adding padding for liquidity_delta
-            serialized.extend_from_slice(&[0u8; 28]); // padding to make it 32 bytes
-            serialized.extend_from_slice(&self.liquidity_delta.to_be_bytes());
-
-            // Adding padding for salt
-            serialized.extend_from_slice(&self.salt);
-
-            serialized
-        }
-    }
-
-    #[derive(Serialize, Deserialize, Debug, Clone)]
-    pub struct MsgExecuteContract {
-        pub sender: String,
-        pub contract_address: Binary,
-        pub calldata: Binary,
-        pub input_amount: Binary,
-    }
-
-    impl MsgExecuteContract {
-        pub fn new(
-            sender: String,
-            contract_address: Binary,
-            calldata: Binary,
-            input_amount: Binary,
-        ) -> Self {
-            MsgExecuteContract {
-                sender,
-                contract_address,
-                calldata,
-                input_amount,
-            }
-        }
-    }
-
-    pub fn left_pad(input: &[u8], expected_len: usize) -> Result<Vec<u8>, StdError> {
-        if input.len() > expected_len {
-            return Err(StdError::generic_err(
-                "input len must not exceeds expected len",
-            ));
+        pub fn to_token(&self) -> Token {
+            Token::Tuple(vec![
+                Token::Int(Int256::from(self.tick_lower)),
+                Token::Int(Int256::from(self.tick_upper)),
+                Token::Int(Int256::from(self.liquidity_delta)),
+                Token::FixedBytes(self.salt.to_vec()),
+            ])
         }
-
-        let mut padded = vec![0u8; expected_len];
-        let start_index = expected_len - input.len();
-        padded[start_index..].copy_from_slice(input);
-
-        Ok(padded)
     }
 
-    pub fn parse_addr(addr: &str) -> [u8; 20] {
+    pub fn parse_addr(addr: &str) -> NexusResult<[u8; 20]> {
         let mut res = [0u8; 20];
-        hex::decode_to_slice(addr, res.as_mut_slice()).unwrap();
-        res
+        hex::decode_to_slice(addr, res.as_mut_slice())
+            .map_err(|e| NexusError::invalid_input(format!("evm address {}: {}", addr, e)))?;
+        Ok(res)
     }
 
     fn compute_sqrt_price_x96_int(price: f64) -> Uint256 {
@@ -164,97 +102,79 @@ pub mod uniswap {
     fn compute_tick(price: f64, tick_spacing: i64) -> i64 {
         let log_base = 1.0001_f64.ln();
         let tick = (price.ln() / log_base).floor() as i64;
-        let valid_tick = tick_spacing * (tick / tick_spacing);
 
-        valid_tick
+        tick_spacing * (tick / tick_spacing)
     }
 
     fn compose_erc20_approve(
-        sender: &String,
+        sender: &str,
         erc20_addr: &[u8; 20],
         delegator: &[u8; 20],
         amount: Uint256,
-    ) -> FISInstruction {
-        let signature: [u8; 4] = (0x095ea7b3u32).to_be_bytes();
-        let padded_delegator = left_pad(delegator, 32).unwrap();
-        let amount_bytes = amount.to_be_bytes();
-        let mut calldata = Vec::new();
-        calldata.extend(signature);
-        calldata.extend(padded_delegator);
-        calldata.extend(amount_bytes);
+    ) -> NexusResult<FISInstruction> {
+        let calldata = Function::parse("approve(address spender, uint256 amount)")?
+            .encode_input(&[Token::Address(*delegator), Token::Uint(amount)])?;
 
         let msg = MsgExecuteContract::new(
-            sender.clone(),
+            sender.to_string(),
             Binary::new(erc20_addr.to_vec()),
             Binary::from(calldata),
             Binary::from(vec![]),
         );
 
-        FISInstruction {
-            plane: PLANE_EVM.to_string(),
-            action: ACTION_VM_INVOKE.to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&msg).unwrap(),
-        }
+        Ok(FISInstruction::vm_invoke(Plane::Evm, to_json_vec(&msg)?))
     }
 
     fn initialize(
+        network: &Network,
         fee: u32,
         price: f64,
         sender: String,
         denom_0: String,
         denom_1: String,
-    ) -> FISInstruction {
-        let signature: [u8; 4] = (0x695c5bf5u32).to_be_bytes();
+    ) -> NexusResult<FISInstruction> {
         let tick_spacing = 60;
 
         let pool_key = PoolKey::new(
-            parse_addr(&denom_0),
-            parse_addr(&denom_1),
+            parse_addr(&denom_0)?,
+            parse_addr(&denom_1)?,
             fee,
             tick_spacing,
             [0; 20],
         );
         let sqrt_price_x96_int = compute_sqrt_price_x96_int(price);
-        let empty_hook_data: [u8; 64] = [
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 224, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0,
-        ];
 
-        let mut calldata = Vec::new();
-        calldata.extend(signature);
-        calldata.extend(pool_key.serialize());
-        calldata.extend(sqrt_price_x96_int.to_be_bytes());
-        calldata.extend(empty_hook_data.iter());
+        let calldata = Function::parse(
+            "initialize((address,address,uint24,int24,address) key, uint160 sqrtPriceX96, bytes hookData)",
+        )?
+        .encode_input(&[
+            pool_key.to_token(),
+            Token::Uint(sqrt_price_x96_int),
+            Token::Bytes(vec![]),
+        ])?;
 
         let msg = MsgExecuteContract::new(
             sender.to_string(),
-            Binary::from(hex::decode(POOL_MANAGER).unwrap()),
+            Binary::new(parse_addr(&network.uniswap.pool_manager)?.to_vec()),
             Binary::from(calldata),
             Binary::from(vec![]),
         );
 
-        FISInstruction {
-            plane: PLANE_EVM.to_string(),
-            action: ACTION_VM_INVOKE.to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&msg).unwrap(),
-        }
+        Ok(FISInstruction::vm_invoke(Plane::Evm, to_json_vec(&msg)?))
     }
 
     fn provide_liquidity(
+        network: &Network,
         fee: u32,
         price: f64,
         sender: String,
         denom_0: String,
         denom_1: String,
-    ) -> FISInstruction {
-        let signature: [u8; 4] = (0x568846efu32).to_be_bytes();
+    ) -> NexusResult<FISInstruction> {
         let tick_spacing = 60;
         let pool_key = PoolKey::new(
-            parse_addr(&denom_0),
-            parse_addr(&denom_1),
+            parse_addr(&denom_0)?,
+            parse_addr(&denom_1)?,
             fee,
             tick_spacing,
             [0; 20],
@@ -272,76 +192,74 @@ pub mod uniswap {
 
         let modify_liquidity_params =
             ModifyLiquidityParams::new(tick_lower, tick_upper, 1000000000, salt);
-        let empty_hook_data: [u8; 64] = [
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 1, 64, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0,
-        ];
 
-        let mut calldata = Vec::new();
-        calldata.extend(signature);
-        calldata.extend(pool_key.serialize());
-        calldata.extend(modify_liquidity_params.serialize());
-        calldata.extend(empty_hook_data);
+        // the pool action contract exposes modifyLiquidity under its own selector
+        let calldata = Function::parse(
+            "modifyLiquidity((address,address,uint24,int24,address) key, (int24,int24,int256,bytes32) params, bytes hookData)",
+        )?
+        .with_selector([0x56, 0x88, 0x46, 0xef])
+        .encode_input(&[
+            pool_key.to_token(),
+            modify_liquidity_params.to_token(),
+            Token::Bytes(vec![]),
+        ])?;
 
         let msg = MsgExecuteContract::new(
             sender.to_string(),
-            Binary::from(hex::decode(POOL_ACTION).unwrap()),
+            Binary::new(parse_addr(&network.uniswap.pool_action)?.to_vec()),
             Binary::from(calldata),
             Binary::from(vec![]),
         );
 
-        FISInstruction {
-            plane: PLANE_EVM.to_string(),
-            action: ACTION_VM_INVOKE.to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&msg).unwrap(),
-        }
+        Ok(FISInstruction::vm_invoke(Plane::Evm, to_json_vec(&msg)?))
     }
 
     impl PoolManager for Uniswap {
         fn create_pool_with_initial_liquidity(
             &self,
+            network: &Network,
             sender: String,
             denom_0: String,
             _amount_0: Uint128,
             denom_1: String,
             _amount_1: Uint128,
-        ) -> Vec<FISInstruction> {
+        ) -> NexusResult<Vec<FISInstruction>> {
             let mut instructions = Vec::new();
 
             let allowance: Uint256 = Uint256::from(100000000000000000u128);
             instructions.push(compose_erc20_approve(
                 &sender.to_string(),
-                &parse_addr(&denom_0),
-                &parse_addr(&POOL_ACTION),
-                allowance.into(),
-            ));
+                &parse_addr(&denom_0)?,
+                &parse_addr(&network.uniswap.pool_action)?,
+                allowance,
+            )?);
 
             instructions.push(compose_erc20_approve(
                 &sender.to_string(),
-                &parse_addr(&denom_1),
-                &parse_addr(&POOL_ACTION),
-                allowance.into(),
-            ));
+                &parse_addr(&denom_1)?,
+                &parse_addr(&network.uniswap.pool_action)?,
+                allowance,
+            )?);
 
             instructions.push(initialize(
+                network,
                 self.fee,
                 self.price,
                 sender.to_string(),
                 denom_0.to_string(),
                 denom_1.to_string(),
-            ));
+            )?);
 
             instructions.push(provide_liquidity(
+                network,
                 self.fee,
                 self.price,
                 sender.to_string(),
                 denom_0.to_string(),
                 denom_1.to_string(),
-            ));
+            )?);
 
-            instructions
+            Ok(instructions)
         }
     }
 }
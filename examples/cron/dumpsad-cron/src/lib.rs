@@ -1,82 +1,32 @@
-use astromesh::{
-    FISInstruction, MsgAstroTransfer, PoolManager, ACTION_COSMOS_INVOKE, PLANE_COSMOS, PLANE_EVM,
-    PLANE_SVM,
-};
+use astromesh::PoolManager;
+use config::Config;
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{
-    entry_point, from_json, to_json_binary, to_json_vec, Binary, Coin, Deps, DepsMut, Env,
-    MessageInfo, Response, StdResult, Uint128,
-};
+use cosmwasm_std::{from_json, to_json_vec, Binary, Coin, Deps, Env, Uint128};
 use events::{GraduateEvent, StrategyEvent};
 use evm::uniswap::Uniswap;
-use std::vec::Vec;
+use network::Network;
+use nexus_sdk::{
+    astromesh::{FISInput, FISInstruction, MsgAstroTransfer, Plane, StrategyOutput},
+    error::NexusResult,
+    input::{Slot, Slots},
+    nexus_strategy,
+};
+use std::str::FromStr;
 use svm::raydium::Raydium;
 use wasm::astroport::Astroport;
 mod astromesh;
+mod config;
 mod events;
 mod evm;
+mod network;
 mod svm;
 mod test;
 mod wasm;
 
-const FEE_DENOM: &str = "sol";
-const TOKEN_CREATOR_FEE: Uint128 = Uint128::new(500_000_000);
-const CREATOR_FEE: Uint128 = Uint128::new(1_500_000_000);
-
-#[cw_serde]
-pub struct InstantiateMsg {}
-
-#[cw_serde]
-pub enum ExecuteMsg {}
-
-#[cw_serde]
-pub struct QueryMsg {
-    msg: Binary,
-    fis_input: Vec<FisInput>,
-}
-
-#[cw_serde]
-pub struct FisInput {
-    data: Vec<Binary>,
-}
-
-#[cw_serde]
-pub struct StrategyOutput {
-    instructions: Vec<FISInstruction>,
-}
-
-#[cw_serde]
-pub struct MsgSend {
-    from_address: String,
-    to_address: String,
-    amount: Vec<BankAmount>,
-}
-
-#[cw_serde]
-pub struct BankAmount {
-    denom: String,
-    amount: String,
-}
-
-#[entry_point]
-pub fn instantiate(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "instantiate"))
-}
-
-#[entry_point]
-pub fn execute(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: ExecuteMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "execute"))
-}
+// fis input layout: [[solver events...], [contract sequence]]
+const SOLVER_EVENTS: Slots<StrategyEvent> = Slots::new(0, "solver events");
+// big endian u64 rather than json, read with `raw`
+const CONTRACT_SEQUENCE: Slot<Binary> = Slot::new(1, 0, "wasm contract sequence");
 
 #[cw_serde]
 pub struct OracleResponse {
@@ -96,16 +46,20 @@ pub struct CronMsg {
     pub solver_id: String,
 }
 
-#[entry_point]
-pub fn query(_deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    let cron_msg = from_json::<CronMsg>(msg.msg)?;
+nexus_strategy!(CronMsg, handle, network = Network, config = Config);
 
+pub fn handle(
+    deps: Deps,
+    env: Env,
+    cron_msg: CronMsg,
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
+    let network = Network::load(deps)?;
+    let config = Config::load(deps)?;
     let creator = env.contract.address.to_string();
 
-    let event_inputs = &msg.fis_input.get(0).unwrap().data;
     let mut instructions = vec![];
-    for e in event_inputs {
-        let parsed_event = from_json::<StrategyEvent>(e)?;
+    for parsed_event in SOLVER_EVENTS.load(fis_input)? {
         if parsed_event.strategy_id != cron_msg.solver_id {
             continue;
         }
@@ -118,13 +72,14 @@ pub fn query(_deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         let vm = graduate_event.vm;
         let pool_address = graduate_event.pool_address;
 
-        let vm_str = vm.to_uppercase();
-        if vm_str.as_str() != "EVM" && vm_str.as_str() != "SVM" && vm_str.as_str() != "WASM" {
-            _deps
-                .api
-                .debug(format!("unsupported plane: {}", vm_str).as_str());
-            continue;
-        }
+        let plane = match Plane::from_str(&vm) {
+            Ok(plane) if plane != Plane::Cosmos => plane,
+            _ => {
+                deps.api
+                    .debug(format!("unsupported plane: {}", vm.to_uppercase()).as_str());
+                continue;
+            }
+        };
 
         let sol_coin = Coin {
             denom: "sol".to_string(),
@@ -137,124 +92,70 @@ pub fn query(_deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         };
 
         // handle graduate
-        let contract_sequence = msg.fis_input.get(1).unwrap().data.get(0).unwrap();
+        let contract_sequence = CONTRACT_SEQUENCE.raw(fis_input)?;
         let (mut denom_0, mut denom_1) = (sol_coin.denom, meme_coin.denom);
         let (mut amount_0, mut amount_1) = (sol_coin.amount, meme_coin.amount);
 
-        // 1. pay creator 0.5 SOL, get 1.5 SOL as fee
+        // 1. pay the token creator and keep the creator fee
         let token_creator = graduate_event.token_creator;
-        amount_0 = amount_0 - TOKEN_CREATOR_FEE - CREATOR_FEE;
+        amount_0 = amount_0
+            .checked_sub(config.token_creator_fee)?
+            .checked_sub(config.creator_fee)?;
 
         let price_uin128: u128 = graduate_event.price.into();
         let price = price_uin128 as f64;
 
         instructions.extend(vec![
-            FISInstruction {
-                plane: PLANE_COSMOS.to_string(),
-                action: ACTION_COSMOS_INVOKE.to_string(),
-                address: "".to_string(),
-                msg: to_json_vec(&MsgAstroTransfer::new(
-                    pool_address.clone(),
-                    token_creator.to_string(),
-                    PLANE_COSMOS.to_string(),
-                    PLANE_COSMOS.to_string(),
-                    Coin {
-                        denom: FEE_DENOM.to_string(),
-                        amount: TOKEN_CREATOR_FEE,
-                    },
-                ))?,
-            },
-            FISInstruction {
-                plane: PLANE_COSMOS.to_string(),
-                action: ACTION_COSMOS_INVOKE.to_string(),
-                address: "".to_string(),
-                msg: to_json_vec(&MsgAstroTransfer::new(
-                    pool_address.clone(),
-                    creator.to_string(),
-                    PLANE_COSMOS.to_string(),
-                    PLANE_COSMOS.to_string(),
-                    Coin {
-                        denom: FEE_DENOM.to_string(),
-                        amount: CREATOR_FEE,
-                    },
-                ))?,
-            },
-        ]);
-
-        if vm_str.as_str() == "SVM" {
-            instructions.extend(vec![
-                FISInstruction {
-                    plane: PLANE_COSMOS.to_string(),
-                    action: ACTION_COSMOS_INVOKE.to_string(),
-                    address: "".to_string(),
-                    msg: to_json_vec(&MsgAstroTransfer::new(
-                        pool_address.to_string(),
-                        pool_address.to_string(),
-                        PLANE_COSMOS.to_string(),
-                        PLANE_SVM.to_string(),
-                        Coin {
-                            denom: denom_0.clone(),
-                            amount: amount_0,
-                        },
-                    ))?,
+            FISInstruction::cosmos_invoke(to_json_vec(&MsgAstroTransfer::new(
+                pool_address.clone(),
+                token_creator.to_string(),
+                Plane::Cosmos,
+                Plane::Cosmos,
+                Coin {
+                    denom: config.fee_denom.clone(),
+                    amount: config.token_creator_fee,
                 },
-                FISInstruction {
-                    plane: PLANE_COSMOS.to_string(),
-                    action: ACTION_COSMOS_INVOKE.to_string(),
-                    address: "".to_string(),
-                    msg: to_json_vec(&MsgAstroTransfer::new(
-                        pool_address.to_string(),
-                        pool_address.to_string(),
-                        PLANE_COSMOS.to_string(),
-                        PLANE_SVM.to_string(),
-                        Coin {
-                            denom: denom_1.clone(),
-                            amount: amount_1,
-                        },
-                    ))?,
+            ))?),
+            FISInstruction::cosmos_invoke(to_json_vec(&MsgAstroTransfer::new(
+                pool_address.clone(),
+                creator.to_string(),
+                Plane::Cosmos,
+                Plane::Cosmos,
+                Coin {
+                    denom: config.fee_denom.clone(),
+                    amount: config.creator_fee,
                 },
-            ]);
-
-            denom_0 = "CPozhCGVaGAcPVkxERsUYat4b7NKT9QeAR9KjNH4JpDG".to_string();
-            denom_1 = graduate_event.meme_denom_link.clone();
-        }
+            ))?),
+        ]);
 
-        if vm_str.as_str() == "EVM" {
+        // move the liquidity to the target vm, the meme denom is linked there at creation
+        if plane == Plane::Svm || plane == Plane::Evm {
             instructions.extend(vec![
-                FISInstruction {
-                    plane: PLANE_COSMOS.to_string(),
-                    action: ACTION_COSMOS_INVOKE.to_string(),
-                    address: "".to_string(),
-                    msg: to_json_vec(&MsgAstroTransfer::new(
-                        pool_address.to_string(),
-                        pool_address.to_string(),
-                        PLANE_COSMOS.to_string(),
-                        PLANE_EVM.to_string(),
-                        Coin {
-                            denom: denom_0.clone(),
-                            amount: amount_0,
-                        },
-                    ))?,
-                },
-                FISInstruction {
-                    plane: PLANE_COSMOS.to_string(),
-                    action: ACTION_COSMOS_INVOKE.to_string(),
-                    address: "".to_string(),
-                    msg: to_json_vec(&MsgAstroTransfer::new(
-                        pool_address.to_string(),
-                        pool_address.to_string(),
-                        PLANE_COSMOS.to_string(),
-                        PLANE_EVM.to_string(),
-                        Coin {
-                            denom: denom_1.clone(),
-                            amount: amount_1,
-                        },
-                    ))?,
-                },
+                FISInstruction::cosmos_invoke(to_json_vec(&network.denoms.astro_transfer(
+                    pool_address.to_string(),
+                    pool_address.to_string(),
+                    Plane::Cosmos,
+                    plane,
+                    &denom_0,
+                    amount_0,
+                )?)?),
+                FISInstruction::cosmos_invoke(to_json_vec(&MsgAstroTransfer::new(
+                    pool_address.to_string(),
+                    pool_address.to_string(),
+                    Plane::Cosmos,
+                    plane,
+                    Coin {
+                        denom: denom_1.clone(),
+                        amount: amount_1,
+                    },
+                ))?),
             ]);
 
-            denom_0 = "eef74ab95099c8d1ad8de02ba6bdab9cbc9dbf93".to_string();
-            denom_1 = graduate_event.meme_denom_link;
+            amount_0 = network
+                .denoms
+                .convert(&denom_0, Plane::Cosmos, plane, amount_0)?;
+            denom_0 = network.denoms.address(&denom_0, plane)?.to_string();
+            denom_1 = graduate_event.meme_denom_link.clone();
         }
 
         if denom_0 > denom_1 {
@@ -263,45 +164,45 @@ pub fn query(_deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         }
 
         // 2. create pool in target vm
-        _deps.api.debug(
+        deps.api.debug(
             format!(
                 "graduate: vm: {}, coin0:{}{}, coin1:{}{}",
                 vm,
                 amount_0.u128(),
-                denom_0.to_string(),
+                denom_0,
                 amount_1.u128(),
-                denom_1.to_string(),
+                denom_1,
             )
             .as_str(),
         );
-        let pool: Box<dyn PoolManager> = match vm.to_uppercase().as_str() {
-            "SVM" => Box::new(Raydium {
+        let pool: Box<dyn PoolManager> = match plane {
+            Plane::Svm => Box::new(Raydium {
                 svm_creator: graduate_event.pool_svm_address,
                 open_time: env.block.time.seconds(),
             }),
-            "WASM" => Box::new(Astroport {
+            Plane::Wasm => Box::new(Astroport {
                 contract_sequence: contract_sequence.clone(),
             }),
-            "EVM" => Box::new(Uniswap {
+            Plane::Evm => Box::new(Uniswap {
                 fee: 3000, // 0.3%
-                price: price,
+                price,
             }),
             _ => {
-                _deps
-                    .api
+                deps.api
                     .debug(format!("unknown vm: {}, continue", vm).as_str());
                 continue;
             }
         };
 
         let create_pool_ixs = pool.create_pool_with_initial_liquidity(
+            &network,
             pool_address.clone(),
             denom_0.clone(),
             amount_0,
             denom_1.clone(),
             amount_1,
-        );
+        )?;
         instructions.extend(create_pool_ixs);
     }
-    Ok(to_json_binary(&StrategyOutput { instructions }).unwrap())
+    Ok(StrategyOutput::new(instructions))
 }
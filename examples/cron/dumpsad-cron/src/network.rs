@@ -0,0 +1,52 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{from_json, Deps};
+use nexus_sdk::{denom::DenomRegistry, error::NexusResult, strategy::load_network};
+
+/// Network the contract falls back to when instantiated without one
+pub const EMBEDDED_NETWORK: &str = include_str!("../networks/devnet.json");
+
+/// Dexes graduated pools are created on, passed as `network` at instantiate,
+/// see `networks/devnet.json`
+#[cw_serde]
+pub struct Network {
+    /// quote denoms paired with graduated memes
+    pub denoms: DenomRegistry,
+    pub astroport: AstroportNetwork,
+    pub raydium: RaydiumNetwork,
+    pub uniswap: UniswapNetwork,
+}
+
+#[cw_serde]
+pub struct AstroportNetwork {
+    pub factory_contract: String,
+    /// code id of the pairs instantiated by the factory, used to derive their address
+    pub pair_code_id: u64,
+}
+
+#[cw_serde]
+pub struct RaydiumNetwork {
+    pub cpmm_program_id: String,
+    pub amm_config_account: String,
+    pub authority_account: String,
+    pub pool_fee_receiver_account: String,
+}
+
+#[cw_serde]
+pub struct UniswapNetwork {
+    pub pool_manager: String,
+    pub pool_action: String,
+}
+
+impl Network {
+    /// Network stored at instantiate, or the embedded one
+    pub fn load(deps: Deps) -> NexusResult<Self> {
+        match load_network(deps.storage)? {
+            Some(network) => Ok(network),
+            None => Self::embedded(),
+        }
+    }
+
+    pub fn embedded() -> NexusResult<Self> {
+        Ok(from_json(EMBEDDED_NETWORK)?)
+    }
+}
@@ -3,14 +3,17 @@ use cosmwasm_std::Coin;
 use serde::Serialize;
 
 pub mod astroport {
-    use std::str::FromStr;
-
     use bech32::{Bech32, Hrp};
     use cosmwasm_schema::cw_serde;
     use cosmwasm_std::{to_json_vec, Addr, Binary, Coin, Decimal, Uint128};
+    use nexus_sdk::{
+        astromesh::{FISInstruction, Plane},
+        error::{NexusError, NexusResult},
+    };
 
     use crate::{
-        astromesh::{module_address, FISInstruction, PoolManager, ACTION_VM_INVOKE, PLANE_WASM},
+        astromesh::{module_address, PoolManager},
+        network::Network,
         wasm::MsgExecuteContract,
     };
 
@@ -18,10 +21,6 @@ pub mod astroport {
         pub contract_sequence: Binary,
     }
 
-    pub const FACTORY_CONTRACT: &str =
-        "lux14hj2tavq8fpesdwxxcu44rty3hh90vhujrvcmstl4zr3txmfvw9sm3tpfk";
-    pub const PAIR_CODE_ID: u64 = 2;
-
     #[cw_serde]
     #[derive(Hash)]
     pub enum AssetInfo {
@@ -75,33 +74,40 @@ pub mod astroport {
     impl PoolManager for Astroport {
         fn create_pool_with_initial_liquidity(
             &self,
+            network: &Network,
             sender: String,
             denom_0: String,
             amount_0: Uint128,
             denom_1: String,
             amount_1: Uint128,
-        ) -> Vec<FISInstruction> {
-            let sequence_number =
-                u64::from_be_bytes(self.contract_sequence.as_slice().try_into().unwrap());
+        ) -> NexusResult<Vec<FISInstruction>> {
+            let sequence_number = self
+                .contract_sequence
+                .as_slice()
+                .try_into()
+                .map(u64::from_be_bytes)
+                .map_err(|_| NexusError::InvalidFisInput {
+                    slot: "wasm contract sequence".to_string(),
+                    reason: "expected big endian u64".to_string(),
+                })?;
             let contract_id = &[
                 "wasm".as_bytes(),
                 &[0],
-                PAIR_CODE_ID.to_be_bytes().as_slice(),
+                network.astroport.pair_code_id.to_be_bytes().as_slice(),
                 sequence_number.to_be_bytes().as_slice(),
             ]
             .concat();
-            let pair_address_bz = module_address("module", &contract_id);
-            let pair_address_str =
-                bech32::encode::<Bech32>(Hrp::parse("lux").unwrap(), &pair_address_bz).unwrap();
+            let pair_address_bz = module_address("module", contract_id);
+            let hrp = Hrp::parse("lux").map_err(NexusError::invalid_input)?;
+            let pair_address_str = bech32::encode::<Bech32>(hrp, &pair_address_bz)
+                .map_err(NexusError::invalid_input)?;
 
-            vec![
-                FISInstruction {
-                    plane: PLANE_WASM.to_string(),
-                    action: ACTION_VM_INVOKE.to_string(),
-                    address: "".to_string(),
-                    msg: to_json_vec(&MsgExecuteContract::new(
+            Ok(vec![
+                FISInstruction::vm_invoke(
+                    Plane::Wasm,
+                    to_json_vec(&MsgExecuteContract::new(
                         sender.clone(),
-                        FACTORY_CONTRACT.to_string(),
+                        network.astroport.factory_contract.clone(),
                         &AstroportMsg::CreatePair {
                             pair_type: PairType::Xyk {},
                             asset_infos: vec![
@@ -115,14 +121,11 @@ pub mod astroport {
                             init_params: None,
                         },
                         vec![],
-                    ))
-                    .unwrap(),
-                },
-                FISInstruction {
-                    plane: PLANE_WASM.to_string(),
-                    action: ACTION_VM_INVOKE.to_string(),
-                    address: "".to_string(),
-                    msg: to_json_vec(&MsgExecuteContract::new(
+                    ))?,
+                ),
+                FISInstruction::vm_invoke(
+                    Plane::Wasm,
+                    to_json_vec(&MsgExecuteContract::new(
                         sender,
                         pair_address_str,
                         &AstroportMsg::ProvideLiquidity {
@@ -140,7 +143,7 @@ pub mod astroport {
                                     amount: amount_1,
                                 },
                             ],
-                            slippage_tolerance: Some(Decimal::from_str("0.5").unwrap()),
+                            slippage_tolerance: Some(Decimal::percent(50)),
                             auto_stake: Some(false),
                             receiver: None, // don't receive LP => no liquidity withdrawal
                         },
@@ -154,10 +157,9 @@ pub mod astroport {
                                 amount: amount_1,
                             },
                         ],
-                    ))
-                    .unwrap(),
-                },
-            ]
+                    ))?,
+                ),
+            ])
         }
     }
 }
@@ -1,25 +1,11 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{
-    entry_point, from_json, to_json_binary, Binary, Coin, Deps, DepsMut, Env, Int64, MessageInfo, Response, StdResult, Uint128
+use cosmwasm_std::{to_json_binary, Binary, Coin, Deps, Env, Int64, Uint128};
+use nexus_sdk::{
+    astromesh::{FISInput, FISInstruction, MsgSend, StrategyOutput},
+    error::NexusResult,
+    input::Slots,
+    nexus_strategy,
 };
-use std::vec::Vec;
-
-#[cw_serde]
-pub struct InstantiateMsg {}
-
-#[cw_serde]
-pub enum ExecuteMsg {}
-
-#[cw_serde]
-pub struct QueryMsg {
-    msg: Binary,
-    fis_input: Vec<FisInput>,
-}
-
-#[cw_serde]
-pub struct FisInput {
-    data: Vec<Binary>,
-}
 
 #[cw_serde]
 pub struct Command {
@@ -44,87 +30,41 @@ pub struct EventContractDeployed {
     contract: ContractInfo,
 }
 
-#[cw_serde]
-pub struct FISInstruction {
-    plane: String,
-    action: String,
-    address: String,
-    msg: Vec<u8>,
-}
-
-#[cw_serde]
-pub struct StrategyOutput {
-    instructions: Vec<FISInstruction>,
-}
+nexus_strategy!(Command, handle);
 
-#[cw_serde]
-pub struct MsgSend {
-    from_address: String,
-    to_address: String,
-    amount: Vec<Coin>,
-}
-
-#[entry_point]
-pub fn instantiate(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "instantiate"))
-}
+// fis input layout: [[contract deployed events...]]
+const DEPLOY_EVENTS: Slots<EventContractDeployed> = Slots::new(0, "contract deployed events");
 
-#[entry_point]
-pub fn execute(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: ExecuteMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "execute"))
-}
-
-#[entry_point]
-pub fn query(_deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn handle(
+    _deps: Deps,
+    env: Env,
+    command: Command,
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
     // no event => do nothing
-    if msg.fis_input.len() == 0 || msg.fis_input.get(0).unwrap().data.len() == 0 {
-        return Ok(to_json_binary(&StrategyOutput {
-            instructions: vec![],
-        })
-        .unwrap());
+    if DEPLOY_EVENTS.raw(fis_input).map_or(true, <[_]>::is_empty) {
+        return Ok(StrategyOutput::default());
     }
 
     // parse cron input
-    let events: Vec<EventContractDeployed> = msg
-        .fis_input
-        .get(0)
-        .unwrap()
-        .data
-        .iter()
-        .map(|m| {
-            from_json::<EventContractDeployed>(m).unwrap()
-        })
-        .collect();
-    
-    let command = from_json::<Command>(msg.msg)?;
+    let events = DEPLOY_EVENTS.load(fis_input)?;
+
     let instructions = events
         .iter()
-        .map(|e| FISInstruction {
-            plane: "COSMOS".to_string(),
-            action: "COSMOS_BANK_SEND".to_string(),
-            address: "".to_string(),
-            msg: to_json_binary(&MsgSend {
-                from_address: env.contract.address.clone().into_string(),
-                to_address: e.contract.sender.clone(),
-                amount: vec![Coin {
-                    denom: command.denom.to_string(),
-                    amount: command.amount,
-                }],
-            })
-            .unwrap()
-            .to_vec(),
+        .map(|e| {
+            Ok(FISInstruction::cosmos_bank_send(
+                to_json_binary(&MsgSend::new(
+                    env.contract.address.clone().into_string(),
+                    e.contract.sender.clone(),
+                    vec![Coin {
+                        denom: command.denom.to_string(),
+                        amount: command.amount,
+                    }],
+                ))?
+                .to_vec(),
+            ))
         })
-        .collect();
+        .collect::<NexusResult<_>>()?;
 
-    Ok(to_json_binary(&StrategyOutput { instructions }).unwrap())
+    Ok(StrategyOutput::new(instructions))
 }
@@ -1,6 +1,9 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Binary, HexBinary, StdError, Uint256};
-use std::convert::TryInto;
+use cosmwasm_std::{Binary, HexBinary, Uint256};
+use nexus_sdk::{
+    error::{NexusError, NexusResult},
+    evm::{Event, Function, MsgExecuteContract, Token},
+};
 
 #[cw_serde]
 pub struct LiquidityRequestEvent {
@@ -12,38 +15,27 @@ pub struct LiquidityRequestEvent {
 }
 
 impl LiquidityRequestEvent {
-    pub const SIGNATURE: &'static [u8] = &[
-        34, 96, 129, 198, 12, 183, 82, 238, 13, 229, 4, 156, 216, 97, 100, 123, 129, 26, 238, 74,
-        23, 107, 67, 43, 131, 193, 246, 84, 60, 60, 205, 70,
-    ];
+    pub const SIGNATURE: &'static str = "LiquidityRequest(address user, address srcToken, uint256 srcAmount, address dstToken, uint256 dstAmount)";
 
-    pub fn from_bytes(data: &[u8]) -> Result<Self, StdError> {
-        if data.len() != 160 {
-            return Err(StdError::generic_err(format!(
-                "Invalid data length: expected 160 bytes, got {}",
-                data.len()
-            )));
-        }
+    pub fn event() -> NexusResult<Event> {
+        Ok(Event::parse(Self::SIGNATURE)?)
+    }
 
-        // Extract 32-byte chunks and convert them to the appropriate types
-        let user = data[12..32]
-            .try_into()
-            .map_err(|_| StdError::generic_err("Invalid user address length"))?;
-        let src_token = data[44..64]
-            .try_into()
-            .map_err(|_| StdError::generic_err("Invalid source token address length"))?;
-        let src_amount = Uint256::from_be_bytes(data[64..96].try_into().unwrap());
-        let dst_token = data[108..128]
-            .try_into()
-            .map_err(|_| StdError::generic_err("Invalid destination token address length"))?;
-        let dst_amount = Uint256::from_be_bytes(data[128..160].try_into().unwrap());
+    pub fn from_log(topics: &[Binary], data: &[u8]) -> NexusResult<Self> {
+        let mut tokens = Self::event()?.decode_log(topics, data)?.into_iter();
+        let mut next = || {
+            tokens.next().ok_or_else(|| NexusError::InvalidFisInput {
+                slot: "liquidity request log".to_string(),
+                reason: "missing event field".to_string(),
+            })
+        };
 
         Ok(LiquidityRequestEvent {
-            user,
-            src_token,
-            src_amount,
-            dst_token,
-            dst_amount,
+            user: next()?.into_address()?,
+            src_token: next()?.into_address()?,
+            src_amount: next()?.into_uint()?,
+            dst_token: next()?.into_address()?,
+            dst_amount: next()?.into_uint()?,
         })
     }
 }
@@ -56,82 +48,28 @@ pub struct Fill {
 }
 
 impl Fill {
-    pub fn serialize(&self) -> Vec<u8> {
-        // Selector: first 4 bytes of the given signature hash
-        let selector: [u8; 4] = [167, 109, 179, 169];
-
-        // Helper function to pad and encode an address (20 bytes)
-        fn encode_address(address: &[u8; 20]) -> Vec<u8> {
-            let mut padded = vec![0u8; 12]; // 12 bytes of leading zeros
-            padded.extend_from_slice(address); // Append the 20-byte address
-            padded
-        }
-
-        // Serialize the fields
-        let mut serialized = Vec::new();
-        serialized.extend_from_slice(&selector); // Add the selector
-        serialized.extend_from_slice(&encode_address(&self.user)); // Add user
-        serialized.extend_from_slice(&encode_address(&self.src_token)); // Add src_token
-        serialized.extend_from_slice(&encode_address(&self.dst_token)); // Add dst_token
-
-        serialized
-    }
-}
-
-#[cw_serde]
-pub struct MsgExecuteContract {
-    pub sender: String,
-    pub contract_address: Binary,
-    pub calldata: Binary,
-    pub input_amount: Binary,
-}
-
-impl MsgExecuteContract {
-    pub fn new(
-        sender: String,
-        contract_address: Binary,
-        calldata: Binary,
-        input_amount: Binary,
-    ) -> Self {
-        MsgExecuteContract {
-            sender,
-            contract_address,
-            calldata,
-            input_amount,
-        }
+    pub fn serialize(&self) -> NexusResult<Vec<u8>> {
+        let calldata = Function::parse("fill(address user, address srcToken, address dstToken)")?
+            .encode_input(&[
+            Token::Address(self.user),
+            Token::Address(self.src_token),
+            Token::Address(self.dst_token),
+        ])?;
+        Ok(calldata)
     }
 }
 
-fn left_pad(input: &[u8], expected_len: usize) -> Result<Vec<u8>, StdError> {
-    if input.len() > expected_len {
-        return Err(StdError::generic_err(
-            "input len must not exceeds expected len",
-        ));
-    }
-
-    let mut padded = vec![0u8; expected_len];
-    let start_index = expected_len - input.len();
-    padded[start_index..].copy_from_slice(input);
-
-    Ok(padded)
-}
-
 pub fn erc20_approve(
-    sender: &String,
+    sender: &str,
     erc20_addr: &[u8; 20],
     delegator: &[u8; 20],
     amount: Uint256,
-) -> Result<MsgExecuteContract, StdError> {
-    let signature: [u8; 4] = (0x095ea7b3u32).to_be_bytes();
-    let padded_delegator = left_pad(delegator, 32)?;
-    let amount_bytes = amount.to_be_bytes();
-    let mut calldata = Vec::new();
-    calldata.extend(signature);
-    calldata.extend(padded_delegator);
-    calldata.extend(amount_bytes);
+) -> NexusResult<MsgExecuteContract> {
+    let calldata = Function::parse("approve(address spender, uint256 amount)")?
+        .encode_input(&[Token::Address(*delegator), Token::Uint(amount)])?;
 
     let msg = MsgExecuteContract::new(
-        sender.clone(),
+        sender.to_string(),
         Binary::new(erc20_addr.to_vec()),
         Binary::from(calldata),
         Binary::from(vec![]),
@@ -141,12 +79,12 @@ pub fn erc20_approve(
 }
 
 pub fn fill(
-    sender: &String,
+    sender: &str,
     contract_address: &[u8; 20],
     user: [u8; 20],
     src_token: [u8; 20],
     dst_token: [u8; 20],
-) -> Result<MsgExecuteContract, StdError> {
+) -> NexusResult<MsgExecuteContract> {
     let fill = Fill {
         user,
         src_token,
@@ -154,30 +92,16 @@ pub fn fill(
     };
 
     let msg = MsgExecuteContract::new(
-        sender.clone(),
+        sender.to_string(),
         Binary::new(contract_address.to_vec()),
-        Binary::from(fill.serialize()),
+        Binary::from(fill.serialize()?),
         Binary::from(vec![]),
     );
 
     Ok(msg)
 }
 
-pub fn parse_addr(addr: &str) -> [u8; 20] {
-    let hex_binary = HexBinary::from_hex(addr).unwrap();
-    hex_binary.to_array().unwrap()
-}
-
-pub fn denom_to_cosmos(alias: &str) -> Result<&str, StdError> {
-    match alias {
-        "0c7bd7e65621073f481c5a6cc33876b7fd552c2a" => Ok("btc"),
-        "07aa076883658b7ed99d25b1e6685808372c8fe2" => Ok("usdt"),
-        "eef74ab95099c8d1ad8de02ba6bdab9cbc9dbf93" => Ok("sol"),
-        "d1738300cda711f4e4c6989856c6b83326c6053e" => Ok("eth"),
-        "3d641a2791533b4a0000345ea8d509d01e1ec301" => Ok("lux"),
-        _ => Err(StdError::generic_err(format!(
-            "unknown evm denom: {}",
-            alias
-        ))),
-    }
+pub fn parse_addr(addr: &str) -> NexusResult<[u8; 20]> {
+    let hex_binary = HexBinary::from_hex(addr)?;
+    Ok(hex_binary.to_array()?)
 }
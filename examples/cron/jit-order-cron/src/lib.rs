@@ -1,27 +1,16 @@
-use astromesh::{
-    MsgAstroTransfer, ACTION_COSMOS_INVOKE, ACTION_VM_INVOKE, PLANE_COSMOS, PLANE_EVM,
-};
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{
-    coin, entry_point, from_json, to_json_binary, to_json_vec, Binary, Coin, Deps, DepsMut, Env,
-    HexBinary, Int64, MessageInfo, Response, StdResult, Uint128, Uint256, Uint64,
+use cosmwasm_std::{to_json_vec, Binary, Coin, Deps, Env, HexBinary, Int64, Uint128, Uint64};
+use evm::{erc20_approve, fill, parse_addr, LiquidityRequestEvent};
+use network::Network;
+use nexus_sdk::{
+    astromesh::{FISInput, FISInstruction, Plane, StrategyOutput},
+    error::NexusResult,
+    input::{Slot, Slots},
+    nexus_strategy,
 };
-use evm::{erc20_approve, fill, parse_addr, Fill, LiquidityRequestEvent};
-use std::{collections::BTreeMap, str::FromStr, vec::Vec};
-mod astromesh;
+use std::collections::BTreeMap;
 mod evm;
-
-#[cw_serde]
-pub struct InstantiateMsg {}
-
-#[cw_serde]
-pub enum ExecuteMsg {}
-
-#[cw_serde]
-pub struct QueryMsg {
-    msg: Binary,
-    fis_input: Vec<FisInput>,
-}
+mod network;
 
 #[cw_serde]
 pub struct InterpoolResponse {
@@ -77,11 +66,6 @@ pub struct EmitLogEvent {
     pub data: Binary,        // Data as Binary
 }
 
-#[cw_serde]
-pub struct FisInput {
-    data: Vec<Binary>,
-}
-
 #[cw_serde]
 pub struct CronInput {
     receiver: String,
@@ -89,56 +73,18 @@ pub struct CronInput {
     denom: String,
 }
 
-#[cw_serde]
-pub struct FISInstruction {
-    plane: String,
-    action: String,
-    address: String,
-    msg: Vec<u8>,
-}
-
-#[cw_serde]
-pub struct StrategyOutput {
-    instructions: Vec<FISInstruction>,
-}
-
-#[cw_serde]
-pub struct MsgSend {
-    from_address: String,
-    to_address: String,
-    amount: Vec<Coin>,
-}
-
-#[entry_point]
-pub fn instantiate(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "instantiate"))
-}
+nexus_strategy!(handle, network = Network);
 
-#[entry_point]
-pub fn execute(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: ExecuteMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "execute"))
-}
+// fis input layout: [[interpool], [evm logs...]]
+const POOL: Slot<InterpoolResponse> = Slot::new(0, 0, "interpool");
+const LOGS: Slots<EmitLogEvent> = Slots::new(1, "evm logs");
 
-#[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn handle(deps: Deps, _env: Env, fis_input: &[FISInput]) -> NexusResult<StrategyOutput> {
+    let network = Network::load(deps)?;
     // parse cron input
-    let pool_input = msg.fis_input.get(0).unwrap().data.get(0).unwrap();
-    let pool_info = from_json::<InterpoolResponse>(pool_input)?;
+    let pool_info = POOL.load(fis_input)?;
     if pool_info.pool.inventory_snapshot.is_empty() {
-        return Ok(to_json_binary(&StrategyOutput {
-            instructions: vec![],
-        })
-        .unwrap());
+        return Ok(StrategyOutput::default());
     }
 
     let mut coin_map = BTreeMap::new();
@@ -146,113 +92,93 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         coin_map.insert(snapshot.denom, snapshot.amount);
     }
 
-    let event_inputs = &msg.fis_input.get(1).unwrap().data;
+    let liquidity_request_event = LiquidityRequestEvent::event()?;
     let mut instructions = vec![];
-    for e in event_inputs {
-        let parsed_event = from_json::<EmitLogEvent>(e)?;
-        if parsed_event.topics.len() < 1 {
+    for parsed_event in LOGS.load(fis_input)? {
+        if parsed_event.topics.is_empty() {
             continue;
         }
 
         deps.api
             .debug(format!("topic: {}", parsed_event.topics[0]).as_str());
-        if !parsed_event.topics[0]
-            .to_vec()
-            .eq(LiquidityRequestEvent::SIGNATURE)
-        {
+        if !liquidity_request_event.is_emitted_by(&parsed_event.topics) {
             continue;
         }
 
-        let liquidity_request = LiquidityRequestEvent::from_bytes(&parsed_event.data)?;
-        let denom_hex = HexBinary::from(liquidity_request.dst_token).to_string();
-        let pool_denom_dst = match evm::denom_to_cosmos(denom_hex.as_str()) {
-            Ok(d) => d,
-            _ => continue,
+        let liquidity_request =
+            LiquidityRequestEvent::from_log(&parsed_event.topics, &parsed_event.data)?;
+        // only registered tokens are filled, their decimals may differ across planes
+        let dst_hex = HexBinary::from(liquidity_request.dst_token).to_hex();
+        let src_hex = HexBinary::from(liquidity_request.src_token).to_hex();
+        let (Some(dst_denom), Some(src_denom)) = (
+            network.denoms.find(Plane::Evm, &dst_hex),
+            network.denoms.find(Plane::Evm, &src_hex),
+        ) else {
+            continue;
         };
-        let existing_fund = Uint256::from_u128(coin_map.get(pool_denom_dst).unwrap().u128());
-        if existing_fund.lt(&liquidity_request.dst_amount) {
+        let dst_amount = network.denoms.convert(
+            dst_denom,
+            Plane::Evm,
+            Plane::Cosmos,
+            Uint128::try_from(liquidity_request.dst_amount)?,
+        )?;
+        // denoms missing from the inventory have no funds to fill with
+        let existing_fund = coin_map.get(dst_denom).copied().unwrap_or_default();
+        if existing_fund.lt(&dst_amount) {
             continue;
         }
 
         deps.api
             .debug(format!("accepted liquidity request: {:?}", liquidity_request).as_str());
         // fill the order = transfer funds + approve contract to spend money + fill + transfer back the amount to pool
-        // MsgAstroTransfer::new(sender, receiver, src_plane, dst_plane, coin)
-        let dst_amount =
-            Uint128::from_str(liquidity_request.dst_amount.to_string().as_str()).unwrap();
-        let src_amount =
-            Uint128::from_str(liquidity_request.src_amount.to_string().as_str()).unwrap();
-
-        let transfer_to_evm = FISInstruction {
-            plane: PLANE_COSMOS.to_string(),
-            action: ACTION_COSMOS_INVOKE.to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&MsgAstroTransfer::new(
+        let src_amount = Uint128::try_from(liquidity_request.src_amount)?;
+        let transfer_to_evm =
+            FISInstruction::cosmos_invoke(to_json_vec(&network.denoms.astro_transfer(
                 pool_info.pool.pool_account.clone(),
                 pool_info.pool.pool_account.clone(),
-                PLANE_COSMOS.to_string(),
-                PLANE_EVM.to_string(),
-                Coin {
-                    denom: pool_denom_dst.to_string(),
-                    amount: dst_amount,
-                },
-            ))
-            .unwrap(),
-        };
-
-        let liquidity_contract = parse_addr(parsed_event.address.as_str());
-        let approve = FISInstruction {
-            plane: PLANE_EVM.to_string(),
-            action: ACTION_VM_INVOKE.to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&erc20_approve(
+                Plane::Cosmos,
+                Plane::Evm,
+                dst_denom,
+                dst_amount,
+            )?)?);
+
+        let liquidity_contract = parse_addr(parsed_event.address.as_str())?;
+        let approve = FISInstruction::vm_invoke(
+            Plane::Evm,
+            to_json_vec(&erc20_approve(
                 &pool_info.pool.pool_account,
                 &liquidity_request.dst_token,
-                &parse_addr(parsed_event.address.as_str()),
+                &liquidity_contract,
                 liquidity_request.dst_amount,
-            )?)
-            .unwrap(),
-        };
+            )?)?,
+        );
 
         // Fill
-        let fill = FISInstruction {
-            plane: PLANE_EVM.to_string(),
-            action: ACTION_VM_INVOKE.to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&fill(
+        let fill = FISInstruction::vm_invoke(
+            Plane::Evm,
+            to_json_vec(&fill(
                 &pool_info.pool.pool_account,
                 &liquidity_contract,
                 liquidity_request.user,
                 liquidity_request.src_token,
                 liquidity_request.dst_token,
-            )?)
-            .unwrap(),
-        };
+            )?)?,
+        );
 
         // MsgAstroTransfer back
-        let transfer_to_cosmos = FISInstruction {
-            plane: PLANE_COSMOS.to_string(),
-            action: ACTION_COSMOS_INVOKE.to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&MsgAstroTransfer::new(
+        let transfer_to_cosmos =
+            FISInstruction::cosmos_invoke(to_json_vec(&network.denoms.astro_transfer(
                 pool_info.pool.pool_account.clone(),
                 pool_info.pool.pool_account.clone(),
-                PLANE_EVM.to_string(),
-                PLANE_COSMOS.to_string(),
-                Coin {
-                    denom: format!(
-                        "astro/{}",
-                        HexBinary::from(liquidity_request.src_token).to_hex()
-                    ),
-                    amount: src_amount,
-                },
-            ))
-            .unwrap(),
-        };
+                Plane::Evm,
+                Plane::Cosmos,
+                src_denom,
+                src_amount,
+            )?)?);
 
         instructions.extend(vec![transfer_to_evm, approve, fill, transfer_to_cosmos]);
     }
 
     // parse command, we can store it as proto bytes, encrypted binary
-    Ok(to_json_binary(&StrategyOutput { instructions }).unwrap())
+    Ok(StrategyOutput::new(instructions))
 }
@@ -0,0 +1,27 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{from_json, Deps};
+use nexus_sdk::{denom::DenomRegistry, error::NexusResult, strategy::load_network};
+
+/// Network the contract falls back to when instantiated without one
+pub const EMBEDDED_NETWORK: &str = include_str!("../networks/devnet.json");
+
+/// Tokens the cron fills liquidity requests with, passed as `network` at instantiate,
+/// see `networks/devnet.json`
+#[cw_serde]
+pub struct Network {
+    pub denoms: DenomRegistry,
+}
+
+impl Network {
+    /// Network stored at instantiate, or the embedded one
+    pub fn load(deps: Deps) -> NexusResult<Self> {
+        match load_network(deps.storage)? {
+            Some(network) => Ok(network),
+            None => Self::embedded(),
+        }
+    }
+
+    pub fn embedded() -> NexusResult<Self> {
+        Ok(from_json(EMBEDDED_NETWORK)?)
+    }
+}
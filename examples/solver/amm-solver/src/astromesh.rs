@@ -1,88 +1,304 @@
+use crate::network::Network;
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Binary, Coin, Int128, Int256, StdError, Uint256};
-use serde::{Deserialize, Serialize};
+use cosmwasm_std::{Decimal256, Int128, Int256, Uint128, Uint256};
+use nexus_sdk::{
+    astromesh::{FISInstruction, Plane},
+    error::{NexusError, NexusResult},
+};
 
-pub const ETH_DECIMAL_DIFF: u128 = 1_000_000_000u128;
+/// Slippage tolerance of swaps unless the action sets one
+pub const DEFAULT_SLIPPAGE_BPS: u16 = 100;
+pub const MAX_SLIPPAGE_BPS: u16 = 10_000;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct MsgAstroTransfer {
-    #[serde(rename = "@type")]
-    pub ty: String,
+#[cw_serde]
+pub struct Swap {
+    pub dex_name: String,
+    pub pool_name: String,
     pub sender: String,
-    pub receiver: String,
-    pub src_plane: String,
-    pub dst_plane: String,
-    pub coin: Coin,
+    pub denom: String,
+    pub amount: Int128,
+    pub sender_svm: String,
+    // the swap reverts when its output is this much below the estimate
+    pub slippage_bps: u16,
 }
 
-impl MsgAstroTransfer {
-    pub fn new(
-        sender: String,
-        receiver: String,
-        src_plane: String,
-        dst_plane: String,
-        coin: Coin,
-    ) -> Self {
-        MsgAstroTransfer {
-            ty: "/flux.astromesh.v1beta1.MsgAstroTransfer".to_string(),
-            sender,
-            receiver,
-            src_plane,
-            dst_plane,
-            coin,
+impl Swap {
+    /// Whether the swap sells a for b on `pool`
+    pub fn a_for_b(&self, pool: &dyn Pool) -> NexusResult<bool> {
+        if self.denom == pool.denom_a() {
+            Ok(true)
+        } else if self.denom == pool.denom_b() {
+            Ok(false)
+        } else {
+            Err(NexusError::UnsupportedPair(format!(
+                "{} {} does not trade {}",
+                self.dex_name, self.pool_name, self.denom
+            )))
+        }
+    }
+
+    /// Output denom and estimated output on `pool`, fails for pools that can't price the swap
+    pub fn estimate(&self, pool: &dyn Pool) -> NexusResult<(String, Int256)> {
+        let (denom, output) =
+            pool.swap_output(Int256::from(self.amount.i128()), self.a_for_b(pool)?);
+        if output <= Int256::zero() {
+            return Err(NexusError::invalid_input(format!(
+                "{} {} can't price {} {}",
+                self.dex_name, self.pool_name, self.amount, self.denom
+            )));
         }
+        Ok((denom, output))
+    }
+
+    /// Output denom and least acceptable output on `pool`, the estimate less the tolerance
+    pub fn min_output(&self, pool: &dyn Pool) -> NexusResult<(String, Int256)> {
+        let (denom, output) = self.estimate(pool)?;
+        Ok((denom, less_slippage(output, self.slippage_bps)?))
     }
 }
 
+/// Liquidity added to one pool, amounts are in cosmos decimals
 #[cw_serde]
-pub struct FISInput {
-    pub data: Vec<Binary>,
+pub struct Deposit {
+    pub dex_name: String,
+    pub pool_name: String,
+    pub sender: String,
+    pub sender_svm: String,
+    // at most these amounts of the quote and base denom are deposited, at the pool ratio
+    pub max_amount_a: Int128,
+    pub max_amount_b: Int128,
+    // the deposit reverts when it mints this much less lp than the estimate
+    pub slippage_bps: u16,
 }
 
+/// Liquidity removed from one pool
 #[cw_serde]
-pub struct Swap {
+pub struct Withdrawal {
     pub dex_name: String,
     pub pool_name: String,
     pub sender: String,
-    pub denom: String,
-    pub amount: Int128,
     pub sender_svm: String,
+    // in the lp token's own decimals, the position liquidity on uniswap
+    pub lp_amount: Int128,
+    // the withdrawal reverts when it returns this much less than the estimate
+    pub slippage_bps: u16,
+}
+
+/// Amounts moved by a deposit or withdrawal, returned in the output result
+#[cw_serde]
+pub struct LiquidityEstimate {
+    pub denom_a: String,
+    pub amount_a: Int256,
+    pub denom_b: String,
+    pub amount_b: Int256,
+    pub lp_amount: Int256,
+}
+
+impl Deposit {
+    /// Amounts at the pool ratio within the max amounts and the lp tokens they mint
+    pub fn estimate(&self, pool: &dyn Pool) -> NexusResult<LiquidityEstimate> {
+        let (a, b, supply) = (pool.a(), pool.b(), pool.lp_supply());
+        if a <= Int256::zero() || b <= Int256::zero() || supply <= Int256::zero() {
+            return Err(NexusError::invalid_input(format!(
+                "{} {} has no liquidity to deposit along",
+                self.dex_name, self.pool_name
+            )));
+        }
+        let max_a = Int256::from(self.max_amount_a.i128());
+        let max_b = Int256::from(self.max_amount_b.i128());
+        if max_a <= Int256::zero() || max_b <= Int256::zero() {
+            return Err(NexusError::invalid_input(
+                "deposit amounts must be positive",
+            ));
+        }
+
+        // the scarcer side is deposited in full
+        let (amount_a, amount_b) = if max_a.checked_mul(b)? <= max_b.checked_mul(a)? {
+            (max_a, max_a.checked_mul(b)?.checked_div(a)?)
+        } else {
+            (max_b.checked_mul(a)?.checked_div(b)?, max_b)
+        };
+        let lp_amount = amount_a
+            .checked_mul(supply)?
+            .checked_div(a)?
+            .min(amount_b.checked_mul(supply)?.checked_div(b)?);
+        if lp_amount <= Int256::zero() {
+            return Err(NexusError::invalid_input(format!(
+                "deposit is too small to mint {} {} lp tokens",
+                self.dex_name, self.pool_name
+            )));
+        }
+        Ok(LiquidityEstimate {
+            denom_a: pool.denom_a(),
+            amount_a,
+            denom_b: pool.denom_b(),
+            amount_b,
+            lp_amount,
+        })
+    }
+}
+
+impl Withdrawal {
+    /// Share of the reserves the lp tokens are redeemed for. On uniswap the supply is the in
+    /// range liquidity, not the position of the sender.
+    pub fn estimate(&self, pool: &dyn Pool) -> NexusResult<LiquidityEstimate> {
+        let supply = pool.lp_supply();
+        let lp_amount = Int256::from(self.lp_amount.i128());
+        if lp_amount <= Int256::zero() || lp_amount > supply {
+            return Err(NexusError::invalid_input(format!(
+                "lp amount must be positive and at most the {} {} supply {}",
+                self.dex_name, self.pool_name, supply
+            )));
+        }
+        Ok(LiquidityEstimate {
+            denom_a: pool.denom_a(),
+            amount_a: lp_amount.checked_mul(pool.a())?.checked_div(supply)?,
+            denom_b: pool.denom_b(),
+            amount_b: lp_amount.checked_mul(pool.b())?.checked_div(supply)?,
+            lp_amount,
+        })
+    }
+}
+
+/// `amount` less the slippage tolerance
+pub fn less_slippage(amount: Int256, slippage_bps: u16) -> NexusResult<Int256> {
+    let tolerance = MAX_SLIPPAGE_BPS.checked_sub(slippage_bps).ok_or_else(|| {
+        NexusError::invalid_input(format!("slippage must be at most {} bps", MAX_SLIPPAGE_BPS))
+    })?;
+    Ok(amount
+        .checked_mul(Int256::from(tolerance))?
+        .checked_div(Int256::from(MAX_SLIPPAGE_BPS))?)
+}
+
+/// Whether the spot price of `pool` is within `max_deviation_bps` of its twap, a larger gap
+/// hints at a price moved within the block. Pools without a twap pass.
+pub fn near_twap(pool: &dyn Pool, max_deviation_bps: u16) -> NexusResult<bool> {
+    let Some(twap) = pool.twap() else {
+        return Ok(true);
+    };
+    let deviation = pool.spot_price()?.abs_diff(twap);
+    Ok(
+        deviation.checked_mul(Decimal256::from_ratio(MAX_SLIPPAGE_BPS, 1u16))?
+            <= twap.checked_mul(Decimal256::from_ratio(max_deviation_bps, 1u16))?,
+    )
 }
 
 pub trait Pool {
     fn dex_name(&self) -> String;
-    fn denom_plane(&self) -> String;
+    fn denom_plane(&self) -> Plane;
     fn a(&self) -> Int256;
     fn b(&self) -> Int256;
+    fn denom_a(&self) -> String;
+    fn denom_b(&self) -> String;
+    // fee on the input amount, in millionths
+    fn fee_rate(&self) -> Int256;
+    // reserves of a constant product pool with the same price and depth, what spot prices and
+    // arbitrage sizing read
+    fn spot_reserves(&self) -> (Int256, Int256) {
+        (self.a(), self.b())
+    }
+    // price of b in a in cosmos decimals
+    fn spot_price(&self) -> NexusResult<Decimal256> {
+        let (a, b) = self.spot_reserves();
+        Decimal256::checked_from_ratio(to_uint256(a), to_uint256(b))
+            .map_err(|e| NexusError::Math(e.to_string()))
+    }
+    // time weighted average of the spot price, none for pools keeping no price oracle
+    fn twap(&self) -> Option<Decimal256> {
+        None
+    }
     // returns denom (within denom_plane) and the swap amount
     fn swap_output(&self, input_amount: Int256, a_for_b: bool) -> (String, Int256);
-    fn compose_swap_fis(&self, swap: &Swap) -> Result<Vec<FISInstruction>, StdError>;
+    fn compose_swap_fis(&self, network: &Network, swap: &Swap) -> NexusResult<Vec<FISInstruction>>;
+    // total lp tokens, the in range liquidity of concentrated pools
+    fn lp_supply(&self) -> Int256;
+    fn compose_add_liquidity_fis(
+        &self,
+        network: &Network,
+        deposit: &Deposit,
+    ) -> NexusResult<Vec<FISInstruction>>;
+    fn compose_remove_liquidity_fis(
+        &self,
+        network: &Network,
+        withdrawal: &Withdrawal,
+    ) -> NexusResult<Vec<FISInstruction>>;
     // other functionalities goes here
 }
 
-#[cw_serde]
-pub struct FISInstruction {
-    pub plane: String,
-    pub action: String,
-    pub address: String,
-    pub msg: Vec<u8>,
-}
-
 #[cw_serde]
 pub enum NexusAction {
+    // the start denom funds the first swap out of src_plane, then cosmos, and the profit settles
+    // on settle_plane, by default src_plane or else the plane of the first pool
     Arbitrage {
-        pair: String, // supported 3 pools and only need usdt amount as input, usdt => X => usdt
+        pair: String, // any listed pair, amount is in the start denom, e.g. usdt => btc => usdt
+        amount: Int128,
+        min_profit: Option<Int128>,
+        src_denom: String, // either denom of the pair, its balances are read on every plane
+        src_plane: Option<Plane>,
+        settle_plane: Option<Plane>,
+        // pools whose spot price is further than this from their twap are left out
+        max_twap_deviation_bps: Option<u16>,
+        // tolerance of each swap, the next one spends the minimum output of the one before
+        max_slippage_bps: Option<u16>,
+    },
+
+    // src_denom => X => ... => src_denom over the pools of every pair, at most `max_hops` swaps
+    // (default 3), funded, settled, bounded and checked against twaps like Arbitrage
+    MultiHopArbitrage {
         amount: Int128,
         min_profit: Option<Int128>,
+        max_hops: Option<u8>,
+        src_denom: String,
+        src_plane: Option<Plane>,
+        settle_plane: Option<Plane>,
+        max_twap_deviation_bps: Option<u16>,
+        max_slippage_bps: Option<u16>,
     },
 
+    // funds missing on the plane of the dex are moved there from the other planes, cosmos
+    // first, output_to_cosmos moves the minimum output back
     Swap {
         dex_name: String,
         src_denom: String,
         dst_denom: String,
         amount: Int128,
+        max_slippage_bps: Option<u16>,
+        output_to_cosmos: Option<bool>,
+    },
+
+    // swap on the pools with the best output, split across dexes to lower the price impact
+    // funds are on cosmos, max_slippage_bps bounds the output against the spot price
+    BestSwap {
+        src_denom: String,
+        dst_denom: String,
+        amount: Int128,
+        max_slippage_bps: Option<u16>,
+    },
+
+    // expected output of every dex for the amount, in the output result, no instructions
+    Quote {
+        src_denom: String,
+        dst_denom: String,
+        amount: Int128,
+    },
+
+    // deposit into the pool of the pair on one dex at the pool ratio, funds are on its plane
+    AddLiquidity {
+        dex_name: String,
+        pair: String,
+        base_amount: Int128,
+        quote_amount: Int128,
+        max_slippage_bps: Option<u16>,
+    },
+
+    // redeem lp tokens, or uniswap position liquidity, of the pool of the pair on one dex,
+    // uniswap withdrawals take no max slippage and estimate without reading the position
+    RemoveLiquidity {
+        dex_name: String,
+        pair: String,
+        lp_amount: Int128,
+        max_slippage_bps: Option<u16>,
     },
-    // More instructions to do best swap best rate
 }
 
 pub fn to_uint256(i: Int256) -> Uint256 {
@@ -93,6 +309,6 @@ pub fn to_int256(i: Uint256) -> Int256 {
     Int256::from_be_bytes(i.to_be_bytes())
 }
 
-pub fn to_u128(i: Int256) -> u128 {
-    u128::from_be_bytes(i.to_be_bytes()[16..32].try_into().expect("must be u128"))
+pub fn to_u128(i: Int256) -> NexusResult<u128> {
+    Ok(Uint128::try_from(i)?.u128())
 }
@@ -1,23 +1,11 @@
 use amm_solver::{
-    svm::{raydium, Pubkey},
-    wasm::astroport,
+    network::Network,
+    query::{PoolQuery, SwapQuery},
 };
-use cosmwasm_std::{to_json_string, Binary, StdError};
+use cosmwasm_std::to_json_string;
+use nexus_sdk::query::Query;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct QueryInstruction {
-    pub plane: String,
-    pub action: String,
-    pub address: Binary,
-    pub input: Vec<Binary>,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Query {
-    pub instructions: Vec<QueryInstruction>,
-}
-
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Prompt {
     pub template: String,
@@ -28,7 +16,12 @@ pub struct Prompt {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Prompts {
     pub swap: Prompt,
+    pub best_swap: Prompt,
+    pub quote: Prompt,
+    pub add_liquidity: Prompt,
+    pub remove_liquidity: Prompt,
     pub arbitrage: Prompt,
+    pub multi_hop_arbitrage: Prompt,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -42,112 +35,98 @@ pub struct Schema {
     pub groups: Vec<Group>,
 }
 
-pub fn raydium_accounts(pool_name: String) -> Result<Vec<Binary>, StdError> {
-    let pool = raydium::get_pool_accounts_by_name(&pool_name)?;
-    Ok(vec![
-        Binary::from(Pubkey::from_string(&pool.token0_vault)?.0),
-        Binary::from(Pubkey::from_string(&pool.token1_vault)?.0),
-        Binary::from(Pubkey::from_string(&pool.pool_state_account)?.0),
-    ])
-}
-
-pub fn decode_bech32(bech32_addr: &String) -> Binary {
-    Binary::from(bech32::decode(bech32_addr).unwrap().1)
-}
-
 fn main() {
-    let arbitrage_instructions = vec![
-        QueryInstruction {
-            plane: "WASM".to_string(),
-            action: "VM_QUERY".to_string(),
-            address: decode_bech32(
-                &astroport::get_pool_meta_by_name(&"btc-usdt".to_string())
-                    .unwrap()
-                    .contract,
-            ),
-            input: vec![Binary::new(r#"{"pool":{}}"#.as_bytes().to_vec())],
-        },
-        QueryInstruction {
-            plane: "SVM".to_string(),
-            action: "VM_QUERY".to_string(),
-            address: Binary::new(vec![]),
-            input: raydium_accounts("btc-usdt".to_string()).unwrap(),
-        },
-        QueryInstruction {
-            plane: "WASM".to_string(),
-            action: "VM_QUERY".to_string(),
-            address: decode_bech32(
-                &astroport::get_pool_meta_by_name(&"eth-usdt".to_string())
-                    .unwrap()
-                    .contract,
-            ),
-            input: vec![Binary::new(r#"{"pool":{}}"#.as_bytes().to_vec())],
-        },
-        QueryInstruction {
-            plane: "SVM".to_string(),
-            action: "VM_QUERY".to_string(),
-            address: Binary::new(vec![]),
-            input: raydium_accounts("eth-usdt".to_string()).unwrap(),
-        },
-        QueryInstruction {
-            plane: "WASM".to_string(),
-            action: "VM_QUERY".to_string(),
-            address: decode_bech32(
-                &astroport::get_pool_meta_by_name(&"sol-usdt".to_string())
-                    .unwrap()
-                    .contract,
-            ),
-            input: vec![Binary::new(r#"{"pool":{}}"#.as_bytes().to_vec())],
-        },
-        QueryInstruction {
-            plane: "SVM".to_string(),
-            action: "VM_QUERY".to_string(),
-            address: Binary::new(vec![]),
-            input: raydium_accounts("sol-usdt".to_string()).unwrap(),
-        },
-        QueryInstruction {
-            plane: "COSMOS".to_string(),
-            action: "COSMOS_QUERY".to_string(),
-            address: Binary::new(vec![]),
-            input: vec![Binary::from(
-                "/flux/svm/v1beta1/account_link/cosmos/${wallet}".as_bytes(),
-            )],
-        },
-    ];
+    let network = Network::embedded().unwrap();
 
-    // Manually constructing the "swap" and "arbitrage" prompts
+    // Manually constructing the "swap", "best_swap", "quote", "add_liquidity", "remove_liquidity", "arbitrage" and "multi_hop_arbitrage" prompts
     let swap_prompt = Prompt {
         template:
-            "swap ${amount:number} ${src_denom:string} to ${dst_denom:string} on ${dex_name:string}"
+            "swap ${amount:number} ${src_denom:string} to ${dst_denom:string} on ${dex_name:string} with max slippage ${max_slippage_bps:number} bps, output to cosmos ${output_to_cosmos:bool}"
                 .to_string(),
         msg_fields: vec![
             "amount".to_string(),
             "src_denom".to_string(),
             "dst_denom".to_string(),
             "dex_name".to_string(),
+            "max_slippage_bps".to_string(),
+            "output_to_cosmos".to_string(),
         ],
-        query: Query {
-            instructions: vec![QueryInstruction {
-                plane: "COSMOS".to_string(),
-                action: "COSMOS_QUERY".to_string(),
-                address: Binary::new(vec![]),
-                input: vec![Binary::from(
-                    "/flux/svm/v1beta1/account_link/cosmos/${wallet}".as_bytes(),
-                )],
-            }],
-        },
+        query: SwapQuery::new(&network).unwrap().pools.query,
+    };
+
+    let best_swap_prompt = Prompt {
+        template: "swap ${amount:number} ${src_denom:string} to ${dst_denom:string} at the best rate with max slippage ${max_slippage_bps:number} bps".to_string(),
+        msg_fields: vec![
+            "amount".to_string(),
+            "src_denom".to_string(),
+            "dst_denom".to_string(),
+            "max_slippage_bps".to_string(),
+        ],
+        query: PoolQuery::new(&network).unwrap().query,
+    };
+
+    let quote_prompt = Prompt {
+        template: "quote ${amount:number} ${src_denom:string} to ${dst_denom:string} on every dex"
+            .to_string(),
+        msg_fields: vec![
+            "amount".to_string(),
+            "src_denom".to_string(),
+            "dst_denom".to_string(),
+        ],
+        query: PoolQuery::new(&network).unwrap().query,
+    };
+
+    let add_liquidity_prompt = Prompt {
+        template: "add ${base_amount:number} base and ${quote_amount:number} quote denom to pair ${pair:string} on ${dex_name:string} with max slippage ${max_slippage_bps:number} bps".to_string(),
+        msg_fields: vec![
+            "base_amount".to_string(),
+            "quote_amount".to_string(),
+            "pair".to_string(),
+            "dex_name".to_string(),
+            "max_slippage_bps".to_string(),
+        ],
+        query: PoolQuery::new(&network).unwrap().query,
+    };
+
+    let remove_liquidity_prompt = Prompt {
+        template: "remove ${lp_amount:number} lp tokens from pair ${pair:string} on ${dex_name:string} with max slippage ${max_slippage_bps:number} bps".to_string(),
+        msg_fields: vec![
+            "lp_amount".to_string(),
+            "pair".to_string(),
+            "dex_name".to_string(),
+            "max_slippage_bps".to_string(),
+        ],
+        query: PoolQuery::new(&network).unwrap().query,
     };
 
     let arbitrage_prompt = Prompt {
-        template: "arbitrage ${amount:number} USDT on pair ${pair:string} with minimum profit = ${min_profit:number} USDT".to_string(),
+        template: "arbitrage ${amount:number} ${src_denom:string} from ${src_plane:string} on pair ${pair:string} with minimum profit = ${min_profit:number}, settle on ${settle_plane:string}, skip pools ${max_twap_deviation_bps:number} bps off their twap, with max slippage ${max_slippage_bps:number} bps".to_string(),
         msg_fields: vec![
             "amount".to_string(),
+            "src_denom".to_string(),
+            "src_plane".to_string(),
             "pair".to_string(),
             "min_profit".to_string(),
+            "settle_plane".to_string(),
+            "max_twap_deviation_bps".to_string(),
+            "max_slippage_bps".to_string(),
         ],
-        query: Query {
-            instructions: arbitrage_instructions,
-        },
+        query: SwapQuery::new(&network).unwrap().pools.query,
+    };
+
+    let multi_hop_arbitrage_prompt = Prompt {
+        template: "arbitrage ${amount:number} ${src_denom:string} from ${src_plane:string} in up to ${max_hops:number} swaps with minimum profit = ${min_profit:number}, settle on ${settle_plane:string}, skip pools ${max_twap_deviation_bps:number} bps off their twap, with max slippage ${max_slippage_bps:number} bps".to_string(),
+        msg_fields: vec![
+            "amount".to_string(),
+            "src_denom".to_string(),
+            "src_plane".to_string(),
+            "max_hops".to_string(),
+            "min_profit".to_string(),
+            "settle_plane".to_string(),
+            "max_twap_deviation_bps".to_string(),
+            "max_slippage_bps".to_string(),
+        ],
+        query: SwapQuery::new(&network).unwrap().pools.query,
     };
 
     // Constructing the group "AMM Solver"
@@ -155,7 +134,12 @@ fn main() {
         name: "AMM Solver".to_string(),
         prompts: Prompts {
             swap: swap_prompt,
+            best_swap: best_swap_prompt,
+            quote: quote_prompt,
+            add_liquidity: add_liquidity_prompt,
+            remove_liquidity: remove_liquidity_prompt,
             arbitrage: arbitrage_prompt,
+            multi_hop_arbitrage: multi_hop_arbitrage_prompt,
         },
     };
 
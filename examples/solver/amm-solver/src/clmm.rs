@@ -0,0 +1,483 @@
+//! Concentrated liquidity swap simulation, a port of the Uniswap v4 `TickMath`,
+//! `SqrtPriceMath`, `SwapMath` and the exact input loop of `Pool.swap`.
+//!
+//! Prices are Q64.96 square roots of token1/token0, fees are in pips (1e-6).
+
+use cosmwasm_std::{Uint128, Uint256, Uint512};
+use nexus_sdk::error::{NexusError, NexusResult};
+
+pub const MIN_TICK: i32 = -887272;
+pub const MAX_TICK: i32 = 887272;
+pub const MIN_SQRT_PRICE: Uint256 = Uint256::from_u128(4295128739);
+// 1461446703485210103287273052203988822378723970342
+pub const MAX_SQRT_PRICE: Uint256 = Uint256::from_be_bytes([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xfd, 0x89, 0x63, 0xef, 0xd1, 0xfc, 0x6a, 0x50, 0x64,
+    0x88, 0x49, 0x5d, 0x95, 0x1d, 0x52, 0x63, 0x98, 0x8d, 0x26,
+]);
+pub const MAX_SWAP_FEE: u32 = 1_000_000;
+const RESOLUTION: u32 = 96;
+
+// sqrt(1.0001^-(2^i)) as Q128.128 for i >= 1, applied for each set bit of |tick|
+const TICK_RATIOS: [u128; 19] = [
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x9aa508b5b7a84e1c677de54f3e99bc9,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe98,
+    0x48a170391f7dc42444e8fa2,
+];
+
+/// Initialized tick and the liquidity added when crossing it left to right
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tick {
+    pub index: i32,
+    pub liquidity_net: i128,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConcentratedPool {
+    pub sqrt_price_x96: Uint256,
+    pub tick: i32,
+    /// in range liquidity
+    pub liquidity: u128,
+    pub tick_spacing: i32,
+    /// initialized ticks, sorted by index
+    pub ticks: Vec<Tick>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapResult {
+    /// input consumed including fees, less than requested if the price limit was reached
+    pub amount_in: Uint256,
+    pub amount_out: Uint256,
+    pub sqrt_price_x96: Uint256,
+    pub tick: i32,
+    pub liquidity: u128,
+}
+
+impl ConcentratedPool {
+    /// Swaps exactly `amount_in` of token0 (`zero_for_one`) or token1, crossing initialized
+    /// ticks until the input is consumed or the price reaches its bound
+    pub fn swap_exact_in(
+        &self,
+        zero_for_one: bool,
+        amount_in: Uint256,
+        fee_pips: u32,
+    ) -> NexusResult<SwapResult> {
+        let sqrt_price_limit = if zero_for_one {
+            MIN_SQRT_PRICE + Uint256::one()
+        } else {
+            MAX_SQRT_PRICE - Uint256::one()
+        };
+        self.swap_exact_in_to(zero_for_one, amount_in, fee_pips, sqrt_price_limit)
+    }
+
+    /// Same as `swap_exact_in`, stopping at `sqrt_price_limit`, e.g. the end of the ticks a
+    /// swap can cross
+    pub fn swap_exact_in_to(
+        &self,
+        zero_for_one: bool,
+        amount_in: Uint256,
+        fee_pips: u32,
+        sqrt_price_limit: Uint256,
+    ) -> NexusResult<SwapResult> {
+        if fee_pips >= MAX_SWAP_FEE {
+            return Err(NexusError::invalid_input(format!(
+                "swap fee {} must be below {}",
+                fee_pips, MAX_SWAP_FEE
+            )));
+        }
+        if self.tick_spacing <= 0 {
+            return Err(NexusError::invalid_input(format!(
+                "tick spacing must be positive, got {}",
+                self.tick_spacing
+            )));
+        }
+        if (zero_for_one && sqrt_price_limit > self.sqrt_price_x96)
+            || (!zero_for_one && sqrt_price_limit < self.sqrt_price_x96)
+        {
+            return Err(NexusError::invalid_input(format!(
+                "sqrt price limit {} is on the wrong side of {}",
+                sqrt_price_limit, self.sqrt_price_x96
+            )));
+        }
+
+        let mut remaining = amount_in;
+        let mut amount_out = Uint256::zero();
+        let mut sqrt_price = self.sqrt_price_x96;
+        let mut tick = self.tick;
+        let mut liquidity = self.liquidity;
+        while !remaining.is_zero() && sqrt_price != sqrt_price_limit {
+            let sqrt_price_start = sqrt_price;
+            let (tick_next, initialized) = self.next_initialized_tick(tick, zero_for_one);
+            let tick_next = tick_next.clamp(MIN_TICK, MAX_TICK);
+            let sqrt_price_next = sqrt_price_at_tick(tick_next)?;
+            let target = if (zero_for_one && sqrt_price_next < sqrt_price_limit)
+                || (!zero_for_one && sqrt_price_next > sqrt_price_limit)
+            {
+                sqrt_price_limit
+            } else {
+                sqrt_price_next
+            };
+
+            let step = compute_swap_step(sqrt_price, target, liquidity, remaining, fee_pips)?;
+            sqrt_price = step.sqrt_price_next_x96;
+            remaining = remaining.checked_sub(step.amount_in + step.fee_amount)?;
+            amount_out = amount_out.checked_add(step.amount_out)?;
+
+            if sqrt_price == sqrt_price_next {
+                if initialized {
+                    let liquidity_net = self
+                        .ticks
+                        .iter()
+                        .find(|t| t.index == tick_next)
+                        .map_or(0, |t| t.liquidity_net);
+                    let liquidity_net = if zero_for_one {
+                        -liquidity_net
+                    } else {
+                        liquidity_net
+                    };
+                    liquidity = liquidity.checked_add_signed(liquidity_net).ok_or_else(|| {
+                        NexusError::Math(format!("liquidity underflow at tick {}", tick_next))
+                    })?;
+                }
+                tick = if zero_for_one {
+                    tick_next - 1
+                } else {
+                    tick_next
+                };
+            } else if sqrt_price != sqrt_price_start {
+                // input exhausted inside the range
+                break;
+            }
+        }
+
+        Ok(SwapResult {
+            amount_in: amount_in - remaining,
+            amount_out,
+            sqrt_price_x96: sqrt_price,
+            tick,
+            liquidity,
+        })
+    }
+
+    /// Virtual reserves of the in range liquidity, `(L / sqrt(P), L * sqrt(P))`
+    pub fn virtual_reserves(&self) -> NexusResult<(Uint256, Uint256)> {
+        let liquidity = Uint256::from(self.liquidity);
+        let reserve0 = mul_div(liquidity << RESOLUTION, Uint256::one(), self.sqrt_price_x96)?;
+        let reserve1 = mul_div(liquidity, self.sqrt_price_x96, Uint256::one() << RESOLUTION)?;
+        Ok((reserve0, reserve1))
+    }
+
+    // same as `TickBitmap.nextInitializedTickWithinOneWord`, the search stops at the bitmap
+    // word boundary so that swap steps, and their rounding, match the contract
+    fn next_initialized_tick(&self, tick: i32, lte: bool) -> (i32, bool) {
+        let spacing = self.tick_spacing;
+        let compressed = tick.div_euclid(spacing);
+        if lte {
+            let word_start = compressed - compressed.rem_euclid(256);
+            match self
+                .ticks
+                .iter()
+                .rev()
+                .map(|t| t.index.div_euclid(spacing))
+                .find(|c| *c <= compressed && *c >= word_start)
+            {
+                Some(c) => (c * spacing, true),
+                None => (word_start * spacing, false),
+            }
+        } else {
+            let next = compressed + 1;
+            let word_end = next + (255 - next.rem_euclid(256));
+            match self
+                .ticks
+                .iter()
+                .map(|t| t.index.div_euclid(spacing))
+                .find(|c| *c >= next && *c <= word_end)
+            {
+                Some(c) => (c * spacing, true),
+                None => (word_end * spacing, false),
+            }
+        }
+    }
+}
+
+pub struct SwapStep {
+    pub sqrt_price_next_x96: Uint256,
+    pub amount_in: Uint256,
+    pub amount_out: Uint256,
+    pub fee_amount: Uint256,
+}
+
+/// `SwapMath.computeSwapStep` for exact input
+pub fn compute_swap_step(
+    sqrt_price_current_x96: Uint256,
+    sqrt_price_target_x96: Uint256,
+    liquidity: u128,
+    amount_remaining: Uint256,
+    fee_pips: u32,
+) -> NexusResult<SwapStep> {
+    let zero_for_one = sqrt_price_current_x96 >= sqrt_price_target_x96;
+    let max_fee = Uint256::from(MAX_SWAP_FEE);
+    let fee = Uint256::from(fee_pips);
+
+    let amount_remaining_less_fee = mul_div(amount_remaining, max_fee - fee, max_fee)?;
+    let amount_in = if zero_for_one {
+        amount0_delta(
+            sqrt_price_target_x96,
+            sqrt_price_current_x96,
+            liquidity,
+            true,
+        )?
+    } else {
+        amount1_delta(
+            sqrt_price_current_x96,
+            sqrt_price_target_x96,
+            liquidity,
+            true,
+        )?
+    };
+    let (sqrt_price_next_x96, amount_in, fee_amount) = if amount_remaining_less_fee >= amount_in {
+        let fee_amount = mul_div_rounding_up(amount_in, fee, max_fee - fee)?;
+        (sqrt_price_target_x96, amount_in, fee_amount)
+    } else {
+        let sqrt_price_next_x96 = next_sqrt_price_from_input(
+            sqrt_price_current_x96,
+            liquidity,
+            amount_remaining_less_fee,
+            zero_for_one,
+        )?;
+        (
+            sqrt_price_next_x96,
+            amount_remaining_less_fee,
+            amount_remaining - amount_remaining_less_fee,
+        )
+    };
+    let amount_out = if zero_for_one {
+        amount1_delta(
+            sqrt_price_next_x96,
+            sqrt_price_current_x96,
+            liquidity,
+            false,
+        )?
+    } else {
+        amount0_delta(
+            sqrt_price_current_x96,
+            sqrt_price_next_x96,
+            liquidity,
+            false,
+        )?
+    };
+
+    Ok(SwapStep {
+        sqrt_price_next_x96,
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
+
+/// `TickMath.getSqrtPriceAtTick`
+pub fn sqrt_price_at_tick(tick: i32) -> NexusResult<Uint256> {
+    let abs_tick = tick.unsigned_abs();
+    if abs_tick > MAX_TICK.unsigned_abs() {
+        return Err(NexusError::invalid_input(format!(
+            "tick {} out of bounds",
+            tick
+        )));
+    }
+
+    let mut price = if abs_tick & 1 != 0 {
+        Uint256::from_u128(0xfffcb933bd6fad37aa2d162d1a594001)
+    } else {
+        Uint256::one() << 128
+    };
+    for (i, ratio) in TICK_RATIOS.iter().enumerate() {
+        if abs_tick & (2 << i) != 0 {
+            price = price.checked_mul(Uint256::from_u128(*ratio))? >> 128;
+        }
+    }
+    if tick > 0 {
+        price = Uint256::MAX / price;
+    }
+
+    let rounding = if price % (Uint256::one() << 32) == Uint256::zero() {
+        Uint256::zero()
+    } else {
+        Uint256::one()
+    };
+    Ok((price >> 32) + rounding)
+}
+
+/// `SqrtPriceMath.getAmount0Delta`, `liquidity * (sqrt(b) - sqrt(a)) / (sqrt(a) * sqrt(b))`
+pub fn amount0_delta(
+    sqrt_price_a_x96: Uint256,
+    sqrt_price_b_x96: Uint256,
+    liquidity: u128,
+    round_up: bool,
+) -> NexusResult<Uint256> {
+    let (a, b) = if sqrt_price_a_x96 > sqrt_price_b_x96 {
+        (sqrt_price_b_x96, sqrt_price_a_x96)
+    } else {
+        (sqrt_price_a_x96, sqrt_price_b_x96)
+    };
+    if a.is_zero() {
+        return Err(NexusError::Math("sqrt price must be positive".to_string()));
+    }
+    let numerator1 = Uint256::from(liquidity) << RESOLUTION;
+    let numerator2 = b - a;
+    if round_up {
+        div_rounding_up(mul_div_rounding_up(numerator1, numerator2, b)?, a)
+    } else {
+        Ok(mul_div(numerator1, numerator2, b)? / a)
+    }
+}
+
+/// `SqrtPriceMath.getAmount1Delta`, `liquidity * (sqrt(b) - sqrt(a))`
+pub fn amount1_delta(
+    sqrt_price_a_x96: Uint256,
+    sqrt_price_b_x96: Uint256,
+    liquidity: u128,
+    round_up: bool,
+) -> NexusResult<Uint256> {
+    let (a, b) = if sqrt_price_a_x96 > sqrt_price_b_x96 {
+        (sqrt_price_b_x96, sqrt_price_a_x96)
+    } else {
+        (sqrt_price_a_x96, sqrt_price_b_x96)
+    };
+    let q96 = Uint256::one() << RESOLUTION;
+    if round_up {
+        mul_div_rounding_up(Uint256::from(liquidity), b - a, q96)
+    } else {
+        mul_div(Uint256::from(liquidity), b - a, q96)
+    }
+}
+
+/// Widest ticks a position can use with `tick_spacing`
+pub fn full_range_ticks(tick_spacing: i32) -> (i32, i32) {
+    (
+        MIN_TICK / tick_spacing * tick_spacing,
+        MAX_TICK / tick_spacing * tick_spacing,
+    )
+}
+
+/// `LiquidityAmounts.getLiquidityForAmounts`, the most liquidity `amount0` and `amount1`
+/// add to a position between the sqrt prices
+pub fn liquidity_for_amounts(
+    sqrt_price_x96: Uint256,
+    sqrt_price_lower_x96: Uint256,
+    sqrt_price_upper_x96: Uint256,
+    amount0: Uint256,
+    amount1: Uint256,
+) -> NexusResult<u128> {
+    let q96 = Uint256::one() << RESOLUTION;
+    let for_amount0 = |lower: Uint256, upper: Uint256| {
+        mul_div(amount0, mul_div(lower, upper, q96)?, upper - lower)
+    };
+    let for_amount1 = |lower: Uint256, upper: Uint256| mul_div(amount1, q96, upper - lower);
+
+    let (lower, upper) = (sqrt_price_lower_x96, sqrt_price_upper_x96);
+    if lower >= upper {
+        return Err(NexusError::Math(
+            "lower sqrt price must be below the upper one".to_string(),
+        ));
+    }
+    let liquidity = if sqrt_price_x96 <= lower {
+        for_amount0(lower, upper)?
+    } else if sqrt_price_x96 < upper {
+        for_amount0(sqrt_price_x96, upper)?.min(for_amount1(lower, sqrt_price_x96)?)
+    } else {
+        for_amount1(lower, upper)?
+    };
+    Ok(Uint128::try_from(liquidity)?.u128())
+}
+
+/// `SqrtPriceMath.getNextSqrtPriceFromInput`
+pub fn next_sqrt_price_from_input(
+    sqrt_price_x96: Uint256,
+    liquidity: u128,
+    amount_in: Uint256,
+    zero_for_one: bool,
+) -> NexusResult<Uint256> {
+    if sqrt_price_x96.is_zero() || liquidity == 0 {
+        return Err(NexusError::Math(
+            "sqrt price and liquidity must be positive".to_string(),
+        ));
+    }
+    if amount_in.is_zero() {
+        return Ok(sqrt_price_x96);
+    }
+
+    let liquidity = Uint256::from(liquidity);
+    if zero_for_one {
+        // rounds up so the price moves at least as far as the input
+        let numerator1 = liquidity << RESOLUTION;
+        if let Ok(product) = amount_in.checked_mul(sqrt_price_x96) {
+            if let Ok(denominator) = numerator1.checked_add(product) {
+                return mul_div_rounding_up(numerator1, sqrt_price_x96, denominator);
+            }
+        }
+        div_rounding_up(
+            numerator1,
+            (numerator1 / sqrt_price_x96).checked_add(amount_in)?,
+        )
+    } else {
+        let quotient = mul_div(amount_in, Uint256::one() << RESOLUTION, liquidity)?;
+        Ok(sqrt_price_x96.checked_add(quotient)?)
+    }
+}
+
+/// `FullMath.mulDiv`, `a * b / denominator` with a 512 bit intermediate
+pub fn mul_div(a: Uint256, b: Uint256, denominator: Uint256) -> NexusResult<Uint256> {
+    let result = Uint512::from(a)
+        .checked_mul(Uint512::from(b))?
+        .checked_div(Uint512::from(denominator))?;
+    Ok(Uint256::try_from(result)?)
+}
+
+pub fn mul_div_rounding_up(a: Uint256, b: Uint256, denominator: Uint256) -> NexusResult<Uint256> {
+    let product = Uint512::from(a).checked_mul(Uint512::from(b))?;
+    let denominator = Uint512::from(denominator);
+    let mut result = product.checked_div(denominator)?;
+    if !product.checked_rem(denominator)?.is_zero() {
+        result += Uint512::one();
+    }
+    Ok(Uint256::try_from(result)?)
+}
+
+fn div_rounding_up(a: Uint256, b: Uint256) -> NexusResult<Uint256> {
+    let result = a.checked_div(b)?;
+    if a.checked_rem(b)?.is_zero() {
+        Ok(result)
+    } else {
+        Ok(result + Uint256::one())
+    }
+}
+
+/// Fee of a v4 swap in pips, the protocol fee is taken out of the swap fee
+pub fn swap_fee(lp_fee: u32, protocol_fee: u32, zero_for_one: bool) -> u32 {
+    let protocol_fee = if zero_for_one {
+        protocol_fee & 0xfff
+    } else {
+        (protocol_fee >> 12) & 0xfff
+    };
+    if protocol_fee == 0 {
+        return lp_fee;
+    }
+    // protocolFee + lpFee - protocolFee * lpFee / 1e6, see ProtocolFeeLibrary
+    let (protocol_fee, lp_fee) = (u64::from(protocol_fee), u64::from(lp_fee & 0xffffff));
+    (protocol_fee + lp_fee - protocol_fee * lp_fee / u64::from(MAX_SWAP_FEE)) as u32
+}
@@ -0,0 +1,169 @@
+//! Two asset invariants of the Astroport stableswap and PCL pairs, on balances in whole tokens.
+//!
+//! Stableswap is `2A (x0 + x1) + D = 2A D + D^3 / (4 x0 x1)`, with Astroport's `Ann = A n`,
+//! and PCL the cryptoswap `K D (x0 + x1) + x0 x1 = K D^2 + D^2 / 4` with
+//! `K = A γ^2 K0 / (γ + 1 - K0)^2` and `K0 = 4 x0 x1 / D^2`. Both lie between the constant sum
+//! and the constant product curves, which bracket the solvers.
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Decimal256;
+use nexus_sdk::error::{NexusError, NexusResult};
+
+// bisection of the widest bracket takes 256 steps, newton far less
+const MAX_ITERATIONS: usize = 256;
+
+#[cw_serde]
+pub enum Invariant {
+    /// Stableswap with amplification `amp`
+    Stable { amp: Decimal256 },
+    /// PCL cryptoswap with amplification `amp` and `gamma`
+    Crypto { amp: Decimal256, gamma: Decimal256 },
+}
+
+// sides of the invariant, F = lhs - rhs, and the partial derivatives of F by the balances
+struct Eval {
+    lhs: Decimal256,
+    rhs: Decimal256,
+    dx: [Decimal256; 2],
+}
+
+impl Invariant {
+    /// D of balances `xs`
+    pub fn d(&self, xs: [Decimal256; 2]) -> NexusResult<Decimal256> {
+        if xs[0].is_zero() || xs[1].is_zero() {
+            return Ok(Decimal256::zero());
+        }
+        // F is positive at the constant product D and negative at the constant sum one
+        let mut low = xs[0].checked_mul(xs[1])?.sqrt().checked_mul(two())?;
+        let mut high = xs[0].checked_add(xs[1])?;
+        for _ in 0..MAX_ITERATIONS {
+            if high - low <= Decimal256::raw(1) {
+                break;
+            }
+            let mid = low + (high - low) / two();
+            let eval = self.eval(xs, mid)?;
+            if eval.lhs >= eval.rhs {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(low)
+    }
+
+    /// The other balance on `d` once one is `x`, rounded up
+    pub fn y(&self, x: Decimal256, d: Decimal256) -> NexusResult<Decimal256> {
+        if x.is_zero() {
+            return Err(NexusError::Math(
+                "curve balance must be positive".to_string(),
+            ));
+        }
+        // F is negative on the constant sum curve and positive on the constant product one,
+        // newton steps outside of the bracket bisect it
+        let mut low = d.saturating_sub(x);
+        let mut high = div(d.checked_mul(d)?, x.checked_mul(four())?)?;
+        let mut y = high;
+        for _ in 0..MAX_ITERATIONS {
+            let eval = self.eval([x, y], d)?;
+            let above = eval.lhs >= eval.rhs;
+            if above {
+                high = y;
+            } else {
+                low = y;
+            }
+            let step = div(eval.lhs.abs_diff(eval.rhs), eval.dx[1])?;
+            if step <= Decimal256::raw(1) || high - low <= Decimal256::raw(1) {
+                return Ok(if above {
+                    y
+                } else {
+                    high.min(y + Decimal256::raw(1))
+                });
+            }
+            let next = if above {
+                y.saturating_sub(step)
+            } else {
+                y.saturating_add(step)
+            };
+            y = if next <= low || next >= high {
+                low + (high - low) / two()
+            } else {
+                next
+            };
+        }
+        Ok(high)
+    }
+
+    /// Marginal output of `xs[1]` per input of `xs[0]` on `d`
+    pub fn price(&self, xs: [Decimal256; 2], d: Decimal256) -> NexusResult<Decimal256> {
+        let eval = self.eval(xs, d)?;
+        div(eval.dx[0], eval.dx[1])
+    }
+
+    fn eval(&self, xs: [Decimal256; 2], d: Decimal256) -> NexusResult<Eval> {
+        let sum = xs[0].checked_add(xs[1])?;
+        match self {
+            Invariant::Stable { amp } => {
+                let ann = amp.checked_mul(two())?;
+                // D^3 / (4 x0 x1)
+                let d_p = div(d.checked_mul(d)?, xs[0].checked_mul(two())?)?
+                    .checked_mul(div(d, xs[1].checked_mul(two())?)?)?;
+                Ok(Eval {
+                    lhs: ann.checked_mul(sum)?.checked_add(d)?,
+                    rhs: ann.checked_mul(d)?.checked_add(d_p)?,
+                    dx: [
+                        ann.checked_add(div(d_p, xs[0])?)?,
+                        ann.checked_add(div(d_p, xs[1])?)?,
+                    ],
+                })
+            }
+            Invariant::Crypto { amp, gamma } => {
+                let mul = xs[0].checked_mul(xs[1])?;
+                let d2 = d.checked_mul(d)?;
+                let k0 = div(mul.checked_mul(four())?, d2)?;
+                let g = gamma.checked_add(Decimal256::one())?;
+                if k0 >= g {
+                    return Err(NexusError::Math(
+                        "curve balances off the invariant".to_string(),
+                    ));
+                }
+                let gk = g - k0;
+                let ratio = div(*gamma, gk)?;
+                let ratio2 = ratio.checked_mul(ratio)?;
+                let k = amp.checked_mul(k0)?.checked_mul(ratio2)?;
+                // dK/dK0 = A γ^2 (γ + 1 + K0) / (γ + 1 - K0)^3, times dK0/dx = 4 x' / D^2
+                let dk = div(
+                    amp.checked_mul(g.checked_add(k0)?)?.checked_mul(ratio2)?,
+                    gk,
+                )?;
+                let slope = d.checked_mul(sum.saturating_sub(d))?;
+                let kd = k.checked_mul(d)?;
+                let partial = |other: Decimal256| -> NexusResult<Decimal256> {
+                    let dk0 = div(other.checked_mul(four())?, d2)?;
+                    Ok(dk
+                        .checked_mul(dk0)?
+                        .checked_mul(slope)?
+                        .checked_add(kd)?
+                        .checked_add(other)?)
+                };
+                Ok(Eval {
+                    lhs: kd.checked_mul(sum)?.checked_add(mul)?,
+                    rhs: k.checked_mul(d2)?.checked_add(div(d2, four())?)?,
+                    dx: [partial(xs[1])?, partial(xs[0])?],
+                })
+            }
+        }
+    }
+}
+
+fn two() -> Decimal256 {
+    Decimal256::from_ratio(2u128, 1u128)
+}
+
+fn four() -> Decimal256 {
+    Decimal256::from_ratio(4u128, 1u128)
+}
+
+pub fn div(x: Decimal256, y: Decimal256) -> NexusResult<Decimal256> {
+    x.checked_div(y)
+        .map_err(|e| NexusError::Math(e.to_string()))
+}
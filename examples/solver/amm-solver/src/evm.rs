@@ -1,21 +1,32 @@
-use cosmwasm_std::{Binary, StdError};
+use nexus_sdk::error::{NexusError, NexusResult};
 // use fixed::{types::extra, FixedU128};
 
-use serde::{Deserialize, Serialize};
-
-const EVM: &str = "EVM";
 pub mod uniswap {
-    use std::str::FromStr;
 
-    use cosmwasm_std::{to_json_vec, Binary, Int256, StdError, Uint256};
+    use cosmwasm_std::{to_json_vec, Binary, Int128, Int256, Isqrt, Uint128, Uint256, Uint512};
     use serde::{Deserialize, Serialize};
 
-    use super::{left_pad, MsgExecuteContract, EVM};
-    use crate::astromesh::{FISInstruction, Pool, Swap};
+    use super::left_pad;
+    use crate::{
+        astromesh::{
+            less_slippage, to_int256, to_u128, to_uint256, Deposit, Pool, Swap, Withdrawal,
+            MAX_SLIPPAGE_BPS,
+        },
+        clmm::{self, ConcentratedPool, SwapResult, Tick},
+        network::{pair_denoms, Network},
+    };
+    use nexus_sdk::{
+        astromesh::{FISInput, FISInstruction, Plane},
+        error::{NexusError, NexusResult},
+        evm::{encode, keccak256, Function, MsgExecuteContract, Token},
+        input::Slot,
+        query::QueryInstruction,
+    };
 
     pub const UNISWAP: &str = "uniswap";
-    pub const POOL_MANAGER: &str = "6ff00f6b2120157fca353fbe24d25536042197df";
-    pub const POOL_ACTION: &str = "366c9837f9a32cc11ac5cac1602e57b73e6bf784";
+    // storage layout of the PoolManager, see v4 StateLibrary
+    const POOLS_SLOT: u128 = 6;
+    const LIQUIDITY_OFFSET: u128 = 3;
 
     #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct PoolKey {
@@ -46,21 +57,39 @@ pub mod uniswap {
             }
         }
 
-        pub fn serialize(&self) -> Vec<u8> {
-            let mut serialized = Vec::with_capacity(96); // 32 bytes for each field with padding
-
-            // Adding padding and then zero_for_one
-            serialized.extend_from_slice(&[0u8; 31]); // 31 bytes of padding
-            serialized.push(self.zero_for_one as u8); // 1 byte for bool
-
-            serialized.extend_from_slice(&self.amount.to_be_bytes());
+        pub fn to_token(&self) -> Token {
+            Token::Tuple(vec![
+                Token::Bool(self.zero_for_one),
+                Token::Int(self.amount),
+                Token::Uint(self.sqrt_price_limit_x96),
+            ])
+        }
+    }
 
-            serialized.extend_from_slice(&self.sqrt_price_limit_x96.to_be_bytes());
+    pub struct ModifyLiquidityParams {
+        pub tick_lower: i32,
+        pub tick_upper: i32,
+        pub liquidity_delta: Int256,
+        pub salt: [u8; 32],
+    }
 
-            serialized
+    impl ModifyLiquidityParams {
+        pub fn to_token(&self) -> Token {
+            Token::Tuple(vec![
+                Token::Int(Int256::from(self.tick_lower)),
+                Token::Int(Int256::from(self.tick_upper)),
+                Token::Int(self.liquidity_delta),
+                Token::FixedBytes(self.salt.to_vec()),
+            ])
         }
     }
 
+    /// Salt of the full range position of `sender`, positions are owned by the pool action
+    /// contract so the salt tells them apart
+    pub fn position_salt(sender: &str) -> [u8; 32] {
+        keccak256(sender.as_bytes())
+    }
+
     impl PoolKey {
         pub fn new(
             currency0: [u8; 20],
@@ -78,43 +107,63 @@ pub mod uniswap {
             }
         }
 
-        pub fn serialize(&self) -> Vec<u8> {
-            let mut serialized = Vec::with_capacity(160);
-            // Adding padding for currency0
-            serialized.extend_from_slice(&[0u8; 12]); // padding to make it 32 bytes
-            serialized.extend_from_slice(&self.currency0);
-
-            // Adding padding for currency1
-            serialized.extend_from_slice(&[0u8; 12]); // padding to make it 32 bytes
-            serialized.extend_from_slice(&self.currency1);
-
-            // Adding padding for fee
-            serialized.extend_from_slice(&[0u8; 28]); // padding to make it 32 bytes
-            serialized.extend_from_slice(&self.fee.to_be_bytes());
+        pub fn to_token(&self) -> Token {
+            Token::Tuple(vec![
+                Token::Address(self.currency0),
+                Token::Address(self.currency1),
+                Token::Uint(Uint256::from(self.fee)),
+                Token::Int(Int256::from(self.tick_spacing)),
+                Token::Address(self.hooks),
+            ])
+        }
 
-            // Adding padding for tick_spacing
-            serialized.extend_from_slice(&[0u8; 28]); // padding to make it 32 bytes
-            serialized.extend_from_slice(&self.tick_spacing.to_be_bytes());
+        /// Pool id, keccak256 of the abi encoded key
+        pub fn to_id(&self) -> [u8; 32] {
+            keccak256(&encode(&[self.to_token()]))
+        }
+    }
 
-            // Adding padding for hooks
-            serialized.extend_from_slice(&[0u8; 12]); // padding to make it 32 bytes
-            serialized.extend_from_slice(&self.hooks);
+    /// Storage slot of `pools[pool_id]` in the PoolManager, its first word is slot0
+    pub fn pool_state_slot(pool_id: &[u8; 32]) -> Uint256 {
+        let mut bz = pool_id.to_vec();
+        bz.extend(Uint256::from(POOLS_SLOT).to_be_bytes());
+        Uint256::from_be_bytes(keccak256(&bz))
+    }
 
-            serialized
-        }
+    fn serialize_swap_calldata(pool_key: PoolKey, swap_params: SwapParams) -> NexusResult<Vec<u8>> {
+        // the pool action contract exposes swap under its own selector
+        let swap = Function::parse(
+            "swap((address,address,uint24,int24,address) key, (bool,int256,uint160) params, bytes hookData)",
+        )?
+        .with_selector([0x92, 0x44, 0x37, 0x79]);
+        Ok(swap.encode_input(&[
+            pool_key.to_token(),
+            swap_params.to_token(),
+            Token::Bytes(vec![]),
+        ])?)
     }
 
-    fn serialize_swap_calldata(pook_key: PoolKey, swap_params: SwapParams) -> Vec<u8> {
-        let signature = (0x92443779u32).to_be_bytes();
-        let pool_key_bz = pook_key.serialize();
-        let swap_params_bz = swap_params.serialize();
-        let empty_hook_data = Binary::from_base64("AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAASAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA==").unwrap();
-        let mut res = Vec::new();
-        res.extend(signature);
-        res.extend(pool_key_bz);
-        res.extend(swap_params_bz);
-        res.extend(empty_hook_data.iter());
-        res
+    fn compose_modify_liquidity(
+        network: &Network,
+        sender: &str,
+        pool_name: &str,
+        params: ModifyLiquidityParams,
+    ) -> NexusResult<FISInstruction> {
+        let pool_key = get_pool_key_by_name(network, pool_name)?;
+        // the pool action contract exposes modifyLiquidity under its own selector
+        let calldata = Function::parse(
+            "modifyLiquidity((address,address,uint24,int24,address) key, (int24,int24,int256,bytes32) params, bytes hookData)",
+        )?
+        .with_selector([0x56, 0x88, 0x46, 0xef])
+        .encode_input(&[pool_key.to_token(), params.to_token(), Token::Bytes(vec![])])?;
+
+        let msg = MsgExecuteContract::new(
+            sender.to_string(),
+            Binary::from(parse_addr(&network.uniswap.pool_action)?),
+            Binary::from(calldata),
+            Binary::from(vec![]),
+        );
+        Ok(FISInstruction::vm_invoke(Plane::Evm, to_json_vec(&msg)?))
     }
 
     #[derive(Debug, Clone)]
@@ -125,32 +174,33 @@ pub mod uniswap {
         pub lp_fee: u32,
     }
 
-    fn signed_big_int_from_bytes(b: &[u8]) -> Result<Int256, StdError> {
-        Ok(Int256::from_be_bytes(
-            left_pad(b, 32)?
-                .try_into()
-                .expect("Slice with incorrect length"),
-        ))
+    // two's complement, sign extended from the top bit of `b`
+    fn signed_big_int_from_bytes(b: &[u8]) -> NexusResult<Int256> {
+        let mut padded = left_pad::<32>(b)?;
+        if b.first().is_some_and(|byte| byte & 0x80 != 0) {
+            padded[..32 - b.len()].fill(0xff);
+        }
+        Ok(Int256::from_be_bytes(padded))
     }
 
-    pub fn parse_pool_info(data: &[u8]) -> Result<PoolInfo, cosmwasm_std::StdError> {
+    pub fn parse_pool_info(data: &[u8]) -> NexusResult<PoolInfo> {
         if data.len() != 32 {
-            return Err(StdError::generic_err(
-                "input data must be 32 bytes".to_string(),
-            ));
+            return Err(NexusError::invalid_input(format!(
+                "pool info must be 32 bytes, got {}",
+                data.len()
+            )));
         }
 
-        let sqrt_price_x96 =
-            Uint256::from_be_bytes(left_pad(&data[12..32], 32)?.as_slice().try_into().unwrap());
+        let sqrt_price_x96 = Uint256::from_be_bytes(left_pad(&data[12..32])?);
 
         let tick_bytes = &data[9..12];
         let tick = signed_big_int_from_bytes(tick_bytes)?;
 
         let protocol_fee_bytes = &data[6..9];
-        let protocol_fee = u32::from_be_bytes(left_pad(protocol_fee_bytes, 4)?.try_into().unwrap());
+        let protocol_fee = u32::from_be_bytes(left_pad(protocol_fee_bytes)?);
 
         let lp_fee_bytes = &data[3..6];
-        let lp_fee = u32::from_be_bytes(left_pad(lp_fee_bytes, 4)?.try_into().unwrap());
+        let lp_fee = u32::from_be_bytes(left_pad(lp_fee_bytes)?);
 
         Ok(PoolInfo {
             sqrt_price_x96,
@@ -160,152 +210,317 @@ pub mod uniswap {
         })
     }
 
-    fn parse_addr(addr: &str) -> [u8; 20] {
+    fn parse_addr(addr: &str) -> NexusResult<[u8; 20]> {
         let mut res = [0u8; 20];
-        hex::decode_to_slice(addr, res.as_mut_slice()).unwrap();
-        res
+        hex::decode_to_slice(addr, res.as_mut_slice())
+            .map_err(|e| NexusError::invalid_input(format!("evm address {}: {}", addr, e)))?;
+        Ok(res)
     }
 
-    pub fn get_pool_key_by_name(pool_name: &str) -> Result<PoolKey, StdError> {
-        match pool_name {
-            "btc-usdt" => Ok(PoolKey {
-                currency0: get_denom("btc")?,
-                currency1: get_denom("usdt")?,
-                fee: 3000u32,
-                tick_spacing: 60i32,
-                hooks: [0; 20],
-            }),
-
-            "eth-usdt" => Ok(PoolKey {
-                currency0: get_denom("usdt")?,
-                currency1: get_denom("eth")?,
-                fee: 3000u32,
-                tick_spacing: 60i32,
-                hooks: [0; 20],
-            }),
-
-            "sol-usdt" => Ok(PoolKey {
-                currency0: get_denom("usdt")?,
-                currency1: get_denom("sol")?,
-                fee: 3000u32,
-                tick_spacing: 60i32,
-                hooks: [0; 20],
-            }),
-
-            _ => Err(StdError::generic_err(format!(
-                "unsupported pool: {}",
-                pool_name
-            ))),
-        }
+    pub fn get_pool_key_by_name(network: &Network, pool_name: &str) -> NexusResult<PoolKey> {
+        let pool = network.uniswap_pool(pool_name)?;
+        Ok(PoolKey {
+            currency0: get_denom(network, &pool.currency0)?,
+            currency1: get_denom(network, &pool.currency1)?,
+            fee: pool.fee,
+            tick_spacing: pool.tick_spacing,
+            hooks: [0; 20],
+        })
     }
 
-    pub fn get_denom(alias: &str) -> Result<[u8; 20], StdError> {
-        match alias {
-            "btc" => Ok(parse_addr("0c7bd7e65621073f481c5a6cc33876b7fd552c2a")),
-            "usdt" => Ok(parse_addr("1a38c7b3f073c038cc7e0e92648e15dd36485259")),
-            "sol" => Ok(parse_addr("eef74ab95099c8d1ad8de02ba6bdab9cbc9dbf93")),
-            "eth" => Ok(parse_addr("d1738300cda711f4e4c6989856c6b83326c6053e")),
-            _ => Err(StdError::generic_err(format!(
-                "evm denom not found: {}",
-                alias
-            ))),
-        }
+    pub fn get_denom(network: &Network, alias: &str) -> NexusResult<[u8; 20]> {
+        parse_addr(network.denoms.address(alias, Plane::Evm)?)
+    }
+
+    // swap amounts are in cosmos decimals
+    fn evm_amount(network: &Network, swap: &Swap) -> NexusResult<Uint128> {
+        network.denoms.convert(
+            &swap.denom,
+            Plane::Cosmos,
+            Plane::Evm,
+            Uint128::try_from(swap.amount)?,
+        )
     }
 
     fn compose_erc20_approve(
-        sender: &String,
+        sender: &str,
         erc20_addr: &[u8; 20],
         delegator: &[u8; 20],
         amount: Uint256,
-    ) -> Result<FISInstruction, StdError> {
-        let signature: [u8; 4] = (0x095ea7b3u32).to_be_bytes();
-        let padded_delegator = left_pad(delegator, 32)?;
-        let amount_bytes = amount.to_be_bytes();
-        let mut calldata = Vec::new();
-        calldata.extend(signature);
-        calldata.extend(padded_delegator);
-        calldata.extend(amount_bytes);
+    ) -> NexusResult<FISInstruction> {
+        let calldata = Function::parse("approve(address spender, uint256 amount)")?
+            .encode_input(&[Token::Address(*delegator), Token::Uint(amount)])?;
 
         let msg = MsgExecuteContract::new(
-            sender.clone(),
+            sender.to_string(),
             Binary::new(erc20_addr.to_vec()),
             Binary::from(calldata),
             Binary::from(vec![]),
         );
 
-        Ok(FISInstruction {
-            plane: "EVM".to_string(),
-            action: "VM_INVOKE".to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&msg)?,
-        })
+        Ok(FISInstruction::vm_invoke(Plane::Evm, to_json_vec(&msg)?))
     }
 
-    fn compose_swap(swap: &Swap) -> Result<FISInstruction, StdError> {
-        let pool_key = get_pool_key_by_name(&swap.pool_name)?;
-        let src_denom = get_denom(swap.denom.as_str())?;
+    fn compose_swap(
+        network: &Network,
+        swap: &Swap,
+        sqrt_price_limit_x96: Uint256,
+    ) -> NexusResult<FISInstruction> {
+        let pool_key = get_pool_key_by_name(network, &swap.pool_name)?;
+        let src_denom = get_denom(network, swap.denom.as_str())?;
         let zero_for_one = src_denom.eq(pool_key.currency0.as_slice());
-        let sqrt_price_limit_x96 = if zero_for_one {
-            Uint256::from_u128(4295128739u128 + 1)
-        } else {
-            Uint256::from_str("1461446703485210103287273052203988822378723970341").unwrap()
-        };
 
         let swap_params = SwapParams {
             zero_for_one,
-            amount: Int256::from_i128(-swap.amount.i128()),
+            amount: -Int256::from(evm_amount(network, swap)?.u128()),
             sqrt_price_limit_x96,
         };
 
         // compose swap
-        let calldata = serialize_swap_calldata(pool_key, swap_params);
+        let calldata = serialize_swap_calldata(pool_key, swap_params)?;
         let msg = MsgExecuteContract::new(
             swap.clone().sender,
-            Binary::from(hex::decode(POOL_ACTION).unwrap()),
+            Binary::from(parse_addr(&network.uniswap.pool_action)?),
             Binary::from(calldata),
             Binary::from(vec![]),
         );
 
-        Ok(FISInstruction {
-            plane: "EVM".to_string(),
-            action: "VM_INVOKE".to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&msg)?,
-        })
+        Ok(FISInstruction::vm_invoke(Plane::Evm, to_json_vec(&msg)?))
+    }
+
+    // sqrt prices of the tick spacing range holding the current tick, clamped to the prices
+    // swaps can reach
+    fn current_range(state: &ConcentratedPool) -> NexusResult<(Uint256, Uint256)> {
+        let lower = state
+            .tick
+            .checked_div_euclid(state.tick_spacing)
+            .filter(|_| state.tick_spacing > 0)
+            .ok_or_else(|| {
+                NexusError::invalid_input(format!("tick spacing {}", state.tick_spacing))
+            })?
+            * state.tick_spacing;
+        let upper = lower + state.tick_spacing;
+        Ok((
+            clmm::sqrt_price_at_tick(lower.max(clmm::MIN_TICK))?
+                .max(clmm::MIN_SQRT_PRICE + Uint256::one())
+                .min(state.sqrt_price_x96),
+            clmm::sqrt_price_at_tick(upper.min(clmm::MAX_TICK))?
+                .min(clmm::MAX_SQRT_PRICE - Uint256::one())
+                .max(state.sqrt_price_x96),
+        ))
     }
 
+    #[derive(Debug)]
     pub struct UniswapPool {
         pub dex_name: String,
-        pub denom_plane: String,
+        pub denom_plane: Plane,
+        // virtual reserves of the in range liquidity
         pub a: Int256,
         pub b: Int256,
+        // lp fee in pips
         pub fee_rate: Int256,
+        pub protocol_fee: u32,
         pub denom_a: String,
         pub denom_b: String,
         pub tick_spacing: i32,
+        // whether denom a is currency0 of the pool key
+        pub a_is_currency0: bool,
+        // a and b amounts are scaled by these from evm to cosmos decimals
+        pub decimal_multiplier_a: Int256,
+        pub decimal_multiplier_b: Int256,
+        // pool state to simulate swaps with, unpriced until set with `with_state`
+        pub state: Option<ConcentratedPool>,
+        // lowest and highest sqrt prices swaps are priced up to, none for no bound
+        pub sqrt_price_range: Option<(Uint256, Uint256)>,
     }
 
     impl UniswapPool {
-        pub fn new(pair: &str) -> Result<Self, StdError> {
-            match pair {
-                "btc-usdt" | "eth-usdt" | "sol-usdt" => {
-                    let parts: Vec<_> = pair.split("-").collect();
-                    Ok(Self {
-                        dex_name: UNISWAP.to_string(),
-                        denom_plane: EVM.to_string(),
-                        a: Int256::zero(),
-                        b: Int256::zero(),
-                        fee_rate: Int256::from_i128(3000),
-                        denom_a: parts.get(0).unwrap().to_string(),
-                        denom_b: parts.get(1).unwrap().to_string(),
-                        tick_spacing: 60,
-                    })
-                }
-                _ => Err(StdError::generic_err(format!(
-                    "unsupported uniswap pair: {}",
-                    pair
-                ))),
+        pub fn new(network: &Network, pair: &str) -> NexusResult<Self> {
+            let pool = network.uniswap_pool(pair)?;
+            // a is the quote denom, as for the other pools
+            let (denom_b, denom_a) = pair_denoms(pair)?;
+            let currencies = (pool.currency0.as_str(), pool.currency1.as_str());
+            if currencies != (denom_a, denom_b) && currencies != (denom_b, denom_a) {
+                return Err(NexusError::invalid_input(format!(
+                    "uniswap pool key {}-{} doesn't match pair {}",
+                    pool.currency0, pool.currency1, pair
+                )));
             }
+            Ok(Self {
+                dex_name: UNISWAP.to_string(),
+                denom_plane: Plane::Evm,
+                a: Int256::zero(),
+                b: Int256::zero(),
+                fee_rate: Int256::from(pool.fee),
+                protocol_fee: 0,
+                denom_a: denom_a.to_string(),
+                denom_b: denom_b.to_string(),
+                tick_spacing: pool.tick_spacing,
+                a_is_currency0: pool.currency0 == denom_a,
+                decimal_multiplier_a: network.decimal_multiplier(denom_a, Plane::Evm)?,
+                decimal_multiplier_b: network.decimal_multiplier(denom_b, Plane::Evm)?,
+                state: None,
+                sqrt_price_range: None,
+            })
+        }
+
+        /// Queries the `[slot0, liquidity]` storage words of the pool read by `from_fis`
+        pub fn query(network: &Network, pair: &str) -> NexusResult<QueryInstruction> {
+            let state_slot = pool_state_slot(&get_pool_key_by_name(network, pair)?.to_id());
+            let liquidity_slot = state_slot.wrapping_add(Uint256::from(LIQUIDITY_OFFSET));
+            Ok(QueryInstruction::vm_query(
+                Plane::Evm,
+                parse_addr(&network.uniswap.pool_manager)?.to_vec(),
+                vec![
+                    Binary::from(state_slot.to_be_bytes()),
+                    Binary::from(liquidity_slot.to_be_bytes()),
+                ],
+            ))
+        }
+
+        /// Parses the `[slot0, liquidity]` storage words at `fis_input[instruction]`.
+        ///
+        /// Initialized ticks are not queried, so swaps are only priced within the tick spacing
+        /// range of the current tick, where the in range liquidity can't change. Larger swaps
+        /// quote nothing, which caps the sizes arbitrage and split routing pick on the pool.
+        /// Pools that are not initialized yet are left unpriced.
+        pub fn from_fis(
+            network: &Network,
+            pair: &str,
+            fis_input: &[FISInput],
+            instruction: usize,
+        ) -> NexusResult<Self> {
+            let slot0 = Slot::<Binary>::new(instruction, 0, "uniswap slot0");
+            let liquidity = Slot::<Binary>::new(instruction, 1, "uniswap liquidity");
+            let invalid = |slot: &Slot<Binary>, e: NexusError| NexusError::InvalidFisInput {
+                slot: slot.to_string(),
+                reason: e.to_string(),
+            };
+
+            let info = left_pad::<32>(slot0.raw(fis_input)?)
+                .and_then(|word| parse_pool_info(&word))
+                .map_err(|e| invalid(&slot0, e))?;
+            let liquidity_word = left_pad::<32>(liquidity.raw(fis_input)?)
+                .and_then(|word| Ok(Uint128::try_from(Uint256::from_be_bytes(word))?))
+                .map_err(|e| invalid(&liquidity, e))?;
+
+            let pool = Self::new(network, pair)?;
+            if info.sqrt_price_x96.is_zero() {
+                return Ok(pool);
+            }
+            let mut pool = pool.with_state(&info, liquidity_word.u128(), vec![])?;
+            pool.sqrt_price_range = pool.state.as_ref().map(current_range).transpose()?;
+            Ok(pool)
+        }
+
+        /// Sets the slot0, in range liquidity and initialized ticks swaps are simulated with
+        pub fn with_state(
+            mut self,
+            info: &PoolInfo,
+            liquidity: u128,
+            mut ticks: Vec<Tick>,
+        ) -> NexusResult<Self> {
+            ticks.sort_by_key(|tick| tick.index);
+            let state = ConcentratedPool {
+                sqrt_price_x96: info.sqrt_price_x96,
+                tick: i32::try_from(Int128::try_from(info.tick)?.i128())
+                    .map_err(|_| NexusError::invalid_input(format!("tick {}", info.tick)))?,
+                liquidity,
+                tick_spacing: self.tick_spacing,
+                ticks,
+            };
+            let (reserve0, reserve1) = state.virtual_reserves()?;
+            let (a, b) = if self.a_is_currency0 {
+                (reserve0, reserve1)
+            } else {
+                (reserve1, reserve0)
+            };
+            self.a = to_int256(a).checked_mul(self.decimal_multiplier_a)?;
+            self.b = to_int256(b).checked_mul(self.decimal_multiplier_b)?;
+            self.fee_rate = Int256::from(info.lp_fee);
+            self.protocol_fee = info.protocol_fee;
+            self.state = Some(state);
+            Ok(self)
+        }
+
+        /// Exact v4 output of swapping `x`, zero for unpriced pools
+        pub fn simulate_swap(&self, x: Int256, a_for_b: bool) -> NexusResult<Int256> {
+            let multiplier_out = if a_for_b {
+                self.decimal_multiplier_b
+            } else {
+                self.decimal_multiplier_a
+            };
+            match self.simulate(x, a_for_b)? {
+                Some(result) => Ok(to_int256(result.amount_out).checked_mul(multiplier_out)?),
+                None => Ok(Int256::zero()),
+            }
+        }
+
+        // v4 swap of `x`, in cosmos decimals, none for unpriced pools
+        fn simulate(&self, x: Int256, a_for_b: bool) -> NexusResult<Option<SwapResult>> {
+            let Some(state) = &self.state else {
+                return Ok(None);
+            };
+            if x <= Int256::zero() {
+                return Ok(None);
+            }
+            let zero_for_one = a_for_b == self.a_is_currency0;
+            let lp_fee = u32::try_from(to_u128(self.fee_rate)?)
+                .map_err(|_| NexusError::invalid_input(format!("lp fee {}", self.fee_rate)))?;
+            let fee = clmm::swap_fee(lp_fee, self.protocol_fee, zero_for_one);
+            let multiplier_in = if a_for_b {
+                self.decimal_multiplier_a
+            } else {
+                self.decimal_multiplier_b
+            };
+            let amount_in = to_uint256(x.checked_div(multiplier_in)?);
+            let Some((lower, upper)) = self.sqrt_price_range else {
+                return Ok(Some(state.swap_exact_in(zero_for_one, amount_in, fee)?));
+            };
+            let limit = if zero_for_one { lower } else { upper };
+            let result = state.swap_exact_in_to(zero_for_one, amount_in, fee, limit)?;
+            // the liquidity past the range is unknown
+            if result.amount_in < amount_in {
+                return Ok(None);
+            }
+            Ok(Some(result))
+        }
+
+        /// Price limit of `swap`, the sqrt price its estimate moves the pool to moved further
+        /// by the slippage tolerance.
+        ///
+        /// v4 swaps take no minimum output, the limit stops the swap instead of reverting it. A
+        /// price moved past the tolerance fills part of the input, none of it past the limit,
+        /// and leaves the rest with the sender. Instructions after the swap spend or move its
+        /// minimum output, so a fill short of it makes them fail.
+        pub fn sqrt_price_limit(&self, swap: &Swap) -> NexusResult<Uint256> {
+            // priced, with a valid tolerance
+            swap.min_output(self)?;
+            let a_for_b = swap.a_for_b(self)?;
+            let zero_for_one = a_for_b == self.a_is_currency0;
+            let Some(result) = self.simulate(Int256::from(swap.amount.i128()), a_for_b)? else {
+                return Err(NexusError::invalid_input(format!(
+                    "uniswap {} is not priced",
+                    swap.pool_name
+                )));
+            };
+
+            // the price is the square of the sqrt price, zero for one swaps lower it
+            let price = result.sqrt_price_x96.full_mul(result.sqrt_price_x96);
+            let tolerance = Uint512::from(MAX_SLIPPAGE_BPS - swap.slippage_bps);
+            let scale = Uint512::from(MAX_SLIPPAGE_BPS);
+            let limit = if zero_for_one {
+                price.checked_mul(tolerance)?.checked_div(scale)?
+            } else if tolerance.is_zero() {
+                Uint512::MAX
+            } else {
+                price.checked_mul(scale)?.checked_div(tolerance)?
+            };
+            let limit = Uint256::try_from(limit.isqrt()).unwrap_or(Uint256::MAX);
+            Ok(limit.clamp(
+                clmm::MIN_SQRT_PRICE + Uint256::one(),
+                clmm::MAX_SQRT_PRICE - Uint256::one(),
+            ))
         }
     }
 
@@ -314,8 +529,8 @@ pub mod uniswap {
             self.dex_name.clone()
         }
 
-        fn denom_plane(&self) -> String {
-            self.denom_plane.clone()
+        fn denom_plane(&self) -> Plane {
+            self.denom_plane
         }
 
         fn a(&self) -> Int256 {
@@ -326,61 +541,154 @@ pub mod uniswap {
             self.b
         }
 
-        fn swap_output(&self, _x: Int256, _a_for_b: bool) -> (String, Int256) {
-            // TODO: Implement to estimate swap output for arbitrage
-            ("".to_string(), Int256::zero())
+        fn denom_a(&self) -> String {
+            self.denom_a.clone()
+        }
+
+        fn denom_b(&self) -> String {
+            self.denom_b.clone()
         }
 
-        fn compose_swap_fis(&self, swap: &Swap) -> Result<Vec<FISInstruction>, StdError> {
-            let denom = get_denom(&swap.denom)?;
+        fn fee_rate(&self) -> Int256 {
+            self.fee_rate
+        }
+
+        fn swap_output(&self, x: Int256, a_for_b: bool) -> (String, Int256) {
+            let denom = if a_for_b {
+                self.denom_b.clone()
+            } else {
+                self.denom_a.clone()
+            };
+            // a failed simulation quotes nothing so the pool is never picked
+            (denom, self.simulate_swap(x, a_for_b).unwrap_or_default())
+        }
+
+        fn compose_swap_fis(
+            &self,
+            network: &Network,
+            swap: &Swap,
+        ) -> NexusResult<Vec<FISInstruction>> {
+            let denom = get_denom(network, &swap.denom)?;
             let approve_instruction = compose_erc20_approve(
                 &swap.sender,
                 &denom,
-                &parse_addr(POOL_ACTION),
-                Uint256::from_u128(swap.amount.i128() as u128),
+                &parse_addr(&network.uniswap.pool_action)?,
+                Uint256::from(evm_amount(network, swap)?),
             )?;
 
-            let swap_instruction = compose_swap(swap)?;
+            let swap_instruction = compose_swap(network, swap, self.sqrt_price_limit(swap)?)?;
             Ok(vec![approve_instruction, swap_instruction])
         }
+
+        fn lp_supply(&self) -> Int256 {
+            self.state
+                .as_ref()
+                .map_or(Int256::zero(), |state| Int256::from(state.liquidity))
+        }
+
+        fn compose_add_liquidity_fis(
+            &self,
+            network: &Network,
+            deposit: &Deposit,
+        ) -> NexusResult<Vec<FISInstruction>> {
+            let estimate = deposit.estimate(self)?;
+            let Some(state) = &self.state else {
+                return Err(NexusError::invalid_input(format!(
+                    "uniswap {} is not priced",
+                    deposit.pool_name
+                )));
+            };
+            let evm_amount = |alias: &str, amount: Int256| {
+                network.denoms.convert(
+                    alias,
+                    Plane::Cosmos,
+                    Plane::Evm,
+                    Uint128::new(to_u128(amount)?),
+                )
+            };
+            let amount_a = evm_amount(&estimate.denom_a, estimate.amount_a)?;
+            let amount_b = evm_amount(&estimate.denom_b, estimate.amount_b)?;
+            let (amount0, amount1) = if self.a_is_currency0 {
+                (amount_a, amount_b)
+            } else {
+                (amount_b, amount_a)
+            };
+
+            let (tick_lower, tick_upper) = clmm::full_range_ticks(self.tick_spacing);
+            let liquidity = clmm::liquidity_for_amounts(
+                state.sqrt_price_x96,
+                clmm::sqrt_price_at_tick(tick_lower)?,
+                clmm::sqrt_price_at_tick(tick_upper)?,
+                Uint256::from(amount0),
+                Uint256::from(amount1),
+            )?;
+            // less liquidity than the amounts allow so they cover a moved price
+            let liquidity_delta = less_slippage(Int256::from(liquidity), deposit.slippage_bps)?;
+
+            let pool_action = parse_addr(&network.uniswap.pool_action)?;
+            Ok(vec![
+                compose_erc20_approve(
+                    &deposit.sender,
+                    &get_denom(network, &estimate.denom_a)?,
+                    &pool_action,
+                    Uint256::from(amount_a),
+                )?,
+                compose_erc20_approve(
+                    &deposit.sender,
+                    &get_denom(network, &estimate.denom_b)?,
+                    &pool_action,
+                    Uint256::from(amount_b),
+                )?,
+                compose_modify_liquidity(
+                    network,
+                    &deposit.sender,
+                    &deposit.pool_name,
+                    ModifyLiquidityParams {
+                        tick_lower,
+                        tick_upper,
+                        liquidity_delta,
+                        salt: position_salt(&deposit.sender),
+                    },
+                )?,
+            ])
+        }
+
+        // v4 positions have no minimum amounts out, the withdrawal is at the current price and
+        // its slippage is unbounded. The estimate is a share of the in range liquidity, the
+        // position of the sender isn't read.
+        fn compose_remove_liquidity_fis(
+            &self,
+            network: &Network,
+            withdrawal: &Withdrawal,
+        ) -> NexusResult<Vec<FISInstruction>> {
+            let estimate = withdrawal.estimate(self)?;
+            let (tick_lower, tick_upper) = clmm::full_range_ticks(self.tick_spacing);
+            Ok(vec![compose_modify_liquidity(
+                network,
+                &withdrawal.sender,
+                &withdrawal.pool_name,
+                ModifyLiquidityParams {
+                    tick_lower,
+                    tick_upper,
+                    liquidity_delta: -estimate.lp_amount,
+                    salt: position_salt(&withdrawal.sender),
+                },
+            )?])
+        }
     }
 }
 
-fn left_pad(input: &[u8], expected_len: usize) -> Result<Vec<u8>, StdError> {
-    if input.len() > expected_len {
-        return Err(StdError::generic_err(
-            "input len must not exceeds expected len",
-        ));
+fn left_pad<const N: usize>(input: &[u8]) -> NexusResult<[u8; N]> {
+    if input.len() > N {
+        return Err(NexusError::invalid_input(format!(
+            "input len {} must not exceed {}",
+            input.len(),
+            N
+        )));
     }
 
-    let mut padded = vec![0u8; expected_len];
-    let start_index = expected_len - input.len();
-    padded[start_index..].copy_from_slice(input);
+    let mut padded = [0u8; N];
+    padded[N - input.len()..].copy_from_slice(input);
 
     Ok(padded)
 }
-
-// more dapp types goes here
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct MsgExecuteContract {
-    pub sender: String,
-    pub contract_address: Binary,
-    pub calldata: Binary,
-    pub input_amount: Binary,
-}
-
-impl MsgExecuteContract {
-    pub fn new(
-        sender: String,
-        contract_address: Binary,
-        calldata: Binary,
-        input_amount: Binary,
-    ) -> Self {
-        MsgExecuteContract {
-            sender,
-            contract_address,
-            calldata,
-            input_amount,
-        }
-    }
-}
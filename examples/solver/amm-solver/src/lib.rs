@@ -1,79 +1,36 @@
 pub mod astromesh;
+pub mod clmm;
+pub mod curve;
 pub mod evm;
+pub mod network;
+pub mod query;
+pub mod route;
 pub mod svm;
 pub mod test;
 pub mod wasm;
 use astromesh::{
-    to_int256, to_u128, to_uint256, FISInput, FISInstruction, MsgAstroTransfer, NexusAction, Pool,
-    Swap, ETH_DECIMAL_DIFF,
+    near_twap, to_u128, Deposit, NexusAction, Pool, Swap, Withdrawal, DEFAULT_SLIPPAGE_BPS,
+    MAX_SLIPPAGE_BPS,
 };
-use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{
-    entry_point, to_json_binary, to_json_vec, Binary, Coin, Deps, DepsMut, Env, Int128, Int256,
-    MessageInfo, Response, StdResult, Uint128, Uint64,
+use cosmwasm_std::{to_json_string, to_json_vec, Deps, Env, Int128, Int256, Uint128};
+use evm::uniswap::UNISWAP;
+use network::{pair_denoms, Network};
+use nexus_sdk::{
+    astromesh::{FISInput, FISInstruction, Plane, StrategyOutput},
+    error::{NexusError, NexusResult},
+    nexus_strategy,
 };
-use cosmwasm_std::{from_json, Isqrt, StdError};
-use evm::uniswap::UniswapPool;
-use std::cmp::min;
-use std::vec::Vec;
-use svm::get_denom;
-use svm::raydium::RaydiumPool;
-use wasm::astroport::AstroportPool;
-
-#[cw_serde]
-pub struct InstantiateMsg {}
-
-#[cw_serde]
-pub enum ExecuteMsg {}
-
-#[entry_point]
-pub fn instantiate(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "instantiate"))
-}
-
-#[entry_point]
-pub fn execute(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: ExecuteMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "execute"))
-}
-
-#[cw_serde]
-pub struct QueryMsg {
-    msg: Binary,
-    fis_input: Vec<FISInput>,
-}
-
-#[cw_serde]
-pub struct StrategyOutput {
-    instructions: Vec<FISInstruction>,
-}
+use query::{PoolQuery, SwapQuery};
+use svm::{raydium::RAYDIUM, whirlpool::WHIRLPOOL};
+use wasm::astroport::ASTROPORT;
 
-#[cw_serde]
-pub struct Link {
-    pub cosmos_addr: String,
-    pub svm_addr: String,
-    pub height: Uint64,
-}
-
-#[cw_serde]
-pub struct AccountLink {
-    pub link: Link,
-}
+nexus_strategy!(NexusAction, handle, network = Network);
 
 // swap x from a to b in src_pool, use same b amount to swap b to a in dst_pool
 // this function returns output amount of each swap with input x
 pub fn calculate_pools_output(
-    src_pool: &Box<dyn Pool>,
-    dst_pool: &Box<dyn Pool>,
+    src_pool: &dyn Pool,
+    dst_pool: &dyn Pool,
     x: Int256,
 ) -> (String, Int256, String, Int256) {
     // swap a for b in src_pool
@@ -89,326 +46,402 @@ pub fn calculate_pools_output(
     )
 }
 
-// contract: use x a1 to get b1
-// use a1 as a2 to get b2 => profit = output (b1) - input (b1)
-pub fn get_max_profit_point(a1: Int256, b1: Int256, a2: Int256, b2: Int256) -> Int256 {
-    (to_int256(Isqrt::isqrt(to_uint256(a1 * b1)) * Isqrt::isqrt(to_uint256(a2 * b2))) - a1 * b2)
-        / (b1 + b2)
-}
-
-pub fn get_pair_output_denom(input_denom: &str, pair: &String) -> String {
-    match pair.as_str() {
-        "btc-usdt" => {
-            if input_denom == "btc" {
-                "usdt".to_string()
-            } else {
-                "btc".to_string()
-            }
-        }
-        "eth-usdt" => {
-            if input_denom == "eth" {
-                "usdt".to_string()
-            } else {
-                "eth".to_string()
-            }
-        }
-        "sol-usdt" => {
-            if input_denom == "sol" {
-                "usdt".to_string()
-            } else {
-                "sol".to_string()
-            }
-        }
-        _ => "".to_string(),
-    }
-}
-
+/// `amount` is in cosmos decimals like the pool reserves, converted to `src_plane` decimals here
 pub fn astro_transfer(
+    network: &Network,
     sender: String,
-    src_plane: &String,
-    dst_plane: &String,
-    mut denom: String,
-    mut amount: u128,
-) -> FISInstruction {
-    if src_plane == "SVM" && denom == get_denom("eth") {
-        amount = amount / ETH_DECIMAL_DIFF
-    }
-
-    // round up for eth decimal diff, chain could handle the conversion too
-    if dst_plane == "SVM" && denom == "eth" {
-        amount = (amount / ETH_DECIMAL_DIFF) * ETH_DECIMAL_DIFF
-    }
-
-    if src_plane == "EVM" || src_plane == "SVM" {
-        denom = String::from("astro/") + &denom;
-    }
+    src_plane: Plane,
+    dst_plane: Plane,
+    denom: String,
+    amount: u128,
+) -> NexusResult<FISInstruction> {
+    let amount = network
+        .denoms
+        .convert(&denom, Plane::Cosmos, src_plane, Uint128::new(amount))?;
+    let msg = network.denoms.astro_transfer(
+        sender.clone(),
+        sender,
+        src_plane,
+        dst_plane,
+        &denom,
+        amount,
+    )?;
+    Ok(FISInstruction::cosmos_invoke(to_json_vec(&msg)?))
+}
 
-    FISInstruction {
-        plane: "COSMOS".to_string(),
-        action: "COSMOS_INVOKE".to_string(),
-        address: "".to_string(),
-        msg: to_json_vec(&MsgAstroTransfer::new(
-            sender.clone(),
-            sender,
-            src_plane.clone(),
-            dst_plane.clone(),
-            Coin {
-                denom,
-                amount: Uint128::from(amount),
-            },
-        ))
-        .unwrap(),
+// dex of the names used in prompts, e.g. `svm raydium`
+fn parse_dex(dex_name: &str) -> NexusResult<&'static str> {
+    match str::to_lowercase(dex_name).as_str() {
+        "svm raydium" => Ok(RAYDIUM),
+        "svm whirlpool" => Ok(WHIRLPOOL),
+        "wasm astroport" => Ok(ASTROPORT),
+        "evm uniswap" => Ok(UNISWAP),
+        _ => Err(NexusError::UnsupportedDex(format!(
+            "{}. Supported: 'svm raydium', 'svm whirlpool', 'wasm astroport', 'evm uniswap'",
+            dex_name
+        ))),
     }
 }
 
-// this estimates optimal_x with pool fee
-pub fn adjust_optimal_x(
-    optimal_x: Int256,
-    src_pool: &Box<dyn Pool>,
-    dst_pool: &Box<dyn Pool>,
-) -> Int256 {
-    // ratio = a1/a_decimal/(b1 / b_decimal) / (a2/a_decimal/(b2/b_decimal)) - 1
-    let a1 = src_pool.a();
-    let b1 = src_pool.b();
-    let a2 = dst_pool.a();
-    let b2 = dst_pool.b();
-    let one = Int256::from_i128(1_000_000i128);
-    let ratio = a1 * b2 * one / (b1 * a2) - one;
-    // price change > -1%
-    if ratio >= Int256::from_i128(-10_000i128) {
-        optimal_x * Int256::from(90) / Int256::from(100)
-    }
-    // price change > -2%
-    else if ratio >= Int256::from_i128(-20_000i128) {
-        optimal_x * Int256::from(95) / Int256::from(100)
-    } else {
-        optimal_x * Int256::from(99) / Int256::from(100)
+// tolerance of the swaps, DEFAULT_SLIPPAGE_BPS unless set
+fn slippage_bps(max_slippage_bps: Option<u16>) -> NexusResult<u16> {
+    match max_slippage_bps {
+        Some(bps) if bps > MAX_SLIPPAGE_BPS => Err(NexusError::invalid_input(format!(
+            "max slippage must be at most {} bps",
+            MAX_SLIPPAGE_BPS
+        ))),
+        bps => Ok(bps.unwrap_or(DEFAULT_SLIPPAGE_BPS)),
     }
 }
 
-fn must_support(pair: &String) -> Result<(), StdError> {
-    if get_pair_output_denom("usdt", pair) == "" {
-        return Err(StdError::generic_err(
-            format!("unsupported pair: {}", pair).as_str(),
-        ));
-    };
-
+fn must_be_positive(amount: Int128) -> NexusResult<()> {
+    if amount <= Int128::zero() {
+        return Err(NexusError::invalid_input(format!(
+            "amount must be positive, got {}",
+            amount
+        )));
+    }
     Ok(())
 }
 
-// Arbitrage supports astroport + raydium for now
-// fis_input injects all pool for now
-// format:
-// [wasm btc-usdt, svm btc-usdt, wasm eth-usdt, svm btc-usdt, wasm sol-usdt, svm sol-usdt]
+// Arbitrage picks the cheapest and the most expensive pool of the pair among the dexes listing
+// it. The amount is in the start denom, either denom of the pair, e.g. usdt => btc => usdt or
+// btc => usdt => btc. fis_input injects all pools and the balances of the start denom, see
+// SwapQuery for the layout
+#[allow(clippy::too_many_arguments)]
 pub fn arbitrage(
     deps: Deps,
     env: Env,
     pair: String,
     amount: Int128,
     min_profit: Option<Int128>,
-    fis_input: &Vec<FISInput>,
-) -> StdResult<Binary> {
-    must_support(&pair)?;
-    let pool_index = match pair.as_str() {
-        "btc-usdt" => 0,
-        "eth-usdt" => 2,
-        "sol-usdt" => 4,
-        _ => unreachable!(),
+    src_denom: String,
+    src_plane: Option<Plane>,
+    settle_plane: Option<Plane>,
+    max_twap_deviation_bps: Option<u16>,
+    max_slippage_bps: Option<u16>,
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
+    must_be_positive(amount)?;
+    let slippage_bps = slippage_bps(max_slippage_bps)?;
+    let network = Network::load(deps)?;
+    let query = SwapQuery::new(&network)?;
+    // either way round, e.g. usdt-btc for btc-usdt
+    let pair = match pair_denoms(&pair) {
+        Ok((x, y)) => network.find_pair(x, y)?,
+        Err(_) => return Err(NexusError::UnsupportedPair(pair)),
+    };
+    let pair_input = query.pools.pair(&pair)?;
+    let (base, quote) = pair_denoms(&pair)?;
+    // the quote denom is a, starting from it buys b where it's cheapest first
+    let from_quote = if src_denom == quote {
+        true
+    } else if src_denom == base {
+        false
+    } else {
+        return Err(NexusError::UnsupportedPair(format!(
+            "{} does not trade {}",
+            pair, src_denom
+        )));
     };
-
-    let raw_pools = [
-        fis_input
-            .get(pool_index)
-            .ok_or(StdError::generic_err("astroport pool data not found"))?,
-        fis_input
-            .get(pool_index + 1)
-            .ok_or(StdError::generic_err("raydium pool data not found"))?,
-    ];
-
-    let svm_link_input = fis_input
-        .get(6)
-        .ok_or(StdError::generic_err("account link on svm not found"))?;
 
     // parse pools
-    let parsed_pools: Vec<Box<dyn Pool>> = vec![
-        Box::new(AstroportPool::from_fis(raw_pools[0])?),
-        Box::new(RaydiumPool::from_fis(raw_pools[1])?),
-    ];
+    let markets: Vec<route::Market> = pair_input
+        .pools(&network, fis_input, env.block.time.seconds())?
+        .into_iter()
+        .map(|pool| route::Market {
+            pair: pair.clone(),
+            pool,
+        })
+        .collect();
+    let markets = markets_near_twap(deps, markets, max_twap_deviation_bps)?;
     // parse account link
-    let acc_link = from_json::<AccountLink>(svm_link_input.data.get(0).unwrap())?;
-
-    deps.api.debug(
-        format!(
-            "parsed pools 0: {:#?}",
-            AstroportPool::from_fis(raw_pools[0])?
-        )
-        .as_str(),
-    );
-    deps.api.debug(
-        format!(
-            "parsed pools 1: {:#?}",
-            RaydiumPool::from_fis(raw_pools[1])?
-        )
-        .as_str(),
-    );
+    let acc_link = query.pools.svm_link.load(fis_input)?;
+
+    for (i, market) in markets.iter().enumerate() {
+        deps.api.debug(
+            format!(
+                "parsed pools {}: {} {}/{}",
+                i,
+                market.pool.dex_name(),
+                market.pool.a(),
+                market.pool.b()
+            )
+            .as_str(),
+        );
+    }
     // detect best swap route, i.e
     // buy on low rate and sell on higher rate pool
-    let mut src_pool_opt: Option<&Box<dyn Pool>> = None;
-    let mut dst_pool_opt: Option<&Box<dyn Pool>> = None;
+    let mut src_market_opt: Option<usize> = None;
+    let mut dst_market_opt: Option<usize> = None;
     let (mut lowest_rate, mut highest_rate) = (Int256::MAX, Int256::MIN);
 
     let multiplier = Int256::from_i128(1_000_000_000_000_000_000i128);
-    for i in 0..parsed_pools.len() {
-        // trick: use multiplier to get over usdt and other denom's decimal
+    for (i, market) in markets.iter().enumerate() {
+        let pool = &market.pool;
+        // unpriced or drained pools can't be traded against
+        if pool.a().is_zero() || pool.b().is_zero() {
+            continue;
+        }
+        // trick: use multiplier to get over the quote and base denom's decimal
         // it's fine to compare the ratios with same multiplier
-        let rate = parsed_pools[i].a() * multiplier / parsed_pools[i].b();
+        let (a, b) = pool.spot_reserves();
+        let rate = a.checked_mul(multiplier)?.checked_div(b)?;
         if lowest_rate > rate {
-            src_pool_opt = Some(&parsed_pools[i]);
+            src_market_opt = Some(i);
             lowest_rate = rate
         }
 
         if highest_rate < rate {
-            dst_pool_opt = Some(&parsed_pools[i]);
+            dst_market_opt = Some(i);
             highest_rate = rate
         }
     }
 
-    let (src_pool, dst_pool) = (src_pool_opt.unwrap(), dst_pool_opt.unwrap());
+    let (Some(src_market), Some(dst_market)) = (src_market_opt, dst_market_opt) else {
+        return Ok(StrategyOutput::default());
+    };
+    let (src_pool, dst_pool) = (&markets[src_market].pool, &markets[dst_market].pool);
+    if src_pool.dex_name() == dst_pool.dex_name() {
+        return Ok(StrategyOutput::default());
+    }
+
+    // buy base on the src pool, sell it on the dst pool, in the order the start denom takes
+    let buy = route::Hop {
+        market: src_market,
+        a_for_b: true,
+    };
+    let sell = route::Hop {
+        market: dst_market,
+        a_for_b: false,
+    };
+    let hops = if from_quote { [buy, sell] } else { [sell, buy] };
 
-    // calculate profit for target pool with ideal scenario (no pool fees)
-    let optimal_x = get_max_profit_point(src_pool.a(), src_pool.b(), dst_pool.a(), dst_pool.b());
-    let optimal_x = adjust_optimal_x(optimal_x, src_pool, dst_pool);
-    let (_, first_swap_output, _, second_optimal_swap_output) =
-        calculate_pools_output(src_pool, dst_pool, optimal_x);
-    let profit = second_optimal_swap_output - optimal_x;
+    // best input within the amount, with the fees and rounding of both pools
+    let (execute_amount, profit) =
+        route::optimize_cycle(&markets, &hops, Int256::from(amount.i128()))?;
+    let expected_min_profit = Int256::from(min_profit.unwrap_or_default().i128());
+    if execute_amount <= Int256::zero() || profit < expected_min_profit {
+        // do nothing if there is no profit or can't reach that amount, early stopping
+        return Ok(StrategyOutput::default());
+    }
+    let outputs = route::cycle_output(&markets, &hops, execute_amount);
     deps.api.debug(
         format!(
-            "arbitrage from {} => {}, optimal x: {}, estimate first swap output: {}, estimate profit: {}",
-            src_pool.dex_name(),
-            dst_pool.dex_name(),
-            optimal_x,
-            first_swap_output,
+            "arbitrage {} from {} => {}, x: {}, estimate first swap output: {}, estimate second swap output: {}, estimate profit: {}",
+            src_denom,
+            markets[hops[0].market].pool.dex_name(),
+            markets[hops[1].market].pool.dex_name(),
+            execute_amount,
+            outputs[0].1,
+            outputs[1].1,
             profit,
         )
         .as_str(),
     );
 
-    let expected_min_profit = if let Some(mp) = min_profit {
-        Int256::from_i128(mp.i128())
-    } else {
-        Int256::zero()
-    };
-
-    if optimal_x <= Int256::zero() || profit < expected_min_profit {
-        // do nothing if there is no profit or can't reach that amount, early stopping
-        return Ok(to_json_binary(&StrategyOutput {
-            instructions: vec![],
-        })
-        .unwrap());
-    }
+    // actions, take usdt > btc arbitrage as example
+    // 1. move usdt missing on the src pool plane there, from cosmos first
+    // 2. do swap usdt to btc src pool
+    // 3. transfer the swapped btc amount to dst pool
+    // 4. swap the btc amount to usdt in dst pool
+    // 5. transfer usdt to the settle plane
+    let instructions = compose_arbitrage(
+        &network,
+        &markets,
+        &hops,
+        &src_denom,
+        execute_amount,
+        expected_min_profit,
+        src_plane,
+        settle_plane,
+        slippage_bps,
+        query.load_balances(fis_input)?,
+        env.contract.address.to_string(),
+        acc_link.link.svm_addr,
+    )?;
+    Ok(StrategyOutput::new(instructions))
+}
 
-    // compose the swaps
-    let sender = env.contract.address.to_string();
-    let mut src_swap = Swap {
-        sender: sender.clone(),
-        dex_name: src_pool.dex_name(),
-        pool_name: pair.clone(),
-        denom: "usdt".to_string(),
-        amount,
-        sender_svm: acc_link.link.svm_addr.clone(),
+// Leaves out the markets whose spot price strays from their twap, likely moved within the block
+// to bait the arbitrage
+fn markets_near_twap(
+    deps: Deps,
+    markets: Vec<route::Market>,
+    max_twap_deviation_bps: Option<u16>,
+) -> NexusResult<Vec<route::Market>> {
+    let Some(bps) = max_twap_deviation_bps else {
+        return Ok(markets);
     };
+    let mut near = vec![];
+    for market in markets {
+        // drained pools have no spot price, the routes skip them anyway
+        if market.pool.spot_reserves().1.is_zero() || near_twap(market.pool.as_ref(), bps)? {
+            near.push(market);
+        } else {
+            deps.api.debug(
+                format!(
+                    "{} {} spot price is over {} bps off its twap",
+                    market.pool.dex_name(),
+                    market.pair,
+                    bps
+                )
+                .as_str(),
+            );
+        }
+    }
+    Ok(near)
+}
 
-    let mut dst_swap = Swap {
+/// Swaps of `x` along the cycle, the first one funded out of `src_plane` then cosmos and the
+/// other planes, see `fund_plane`. The output settles on `settle_plane`, by default the plane
+/// the funds are taken from. Each swap spends the minimum output of the one before and the
+/// last one returns at least `x` and `min_profit`, see `route::compose_cycle`.
+#[allow(clippy::too_many_arguments)]
+pub fn compose_arbitrage(
+    network: &Network,
+    markets: &[route::Market],
+    hops: &[route::Hop],
+    src_denom: &str,
+    x: Int256,
+    min_profit: Int256,
+    src_plane: Option<Plane>,
+    settle_plane: Option<Plane>,
+    slippage_bps: u16,
+    mut balances: Vec<(Plane, Uint128)>,
+    sender: String,
+    sender_svm: String,
+) -> NexusResult<Vec<FISInstruction>> {
+    // stable, the other planes keep the cosmos first order
+    balances.sort_by_key(|(plane, _)| Some(*plane) != src_plane);
+
+    let plane = markets[hops[0].market].pool.denom_plane();
+    let settle_plane = settle_plane.or(src_plane).unwrap_or(plane);
+    let mut instructions = fund_plane(
+        network,
+        &sender,
+        plane,
+        src_denom,
+        Int128::try_from(x)?,
+        &balances,
+    )?;
+    instructions.extend(route::compose_cycle(
+        network,
+        markets,
+        hops,
+        x,
+        x.checked_add(min_profit)?,
+        settle_plane,
+        slippage_bps,
         sender,
-        dex_name: dst_pool.dex_name(),
-        pool_name: pair.clone(),
-        denom: get_pair_output_denom("usdt", &pair),
-        amount: Int128::zero(), // to be updated after calculations
-        sender_svm: acc_link.link.svm_addr.clone(),
-    };
+        sender_svm,
+    )?);
+    Ok(instructions)
+}
 
-    let execute_amount = min(optimal_x, Int256::from(src_swap.amount.i128()));
-    let (first_output_denom, first_swap_output, second_output_denom, second_swap_output) =
-        calculate_pools_output(src_pool, dst_pool, execute_amount);
-    let profit = second_swap_output - execute_amount;
-    // check again because input could be less than optimal_x => less profit than using optimal_x
-    if profit < expected_min_profit {
-        return Ok(to_json_binary(&StrategyOutput {
-            instructions: vec![],
-        })
-        .unwrap());
+pub const DEFAULT_MAX_HOPS: u8 = 3;
+// bounds the cycle search, the number of cycles grows exponentially with it
+pub const MAX_HOPS: u8 = 5;
+
+// Arbitrage over cycles of the pool graph starting and ending in the start denom, usdt by
+// default, e.g. usdt => btc on astroport, btc => eth on raydium, eth => usdt on uniswap
+// the most profitable cycle, sized with the actual pool outputs, is executed
+#[allow(clippy::too_many_arguments)]
+pub fn multi_hop_arbitrage(
+    deps: Deps,
+    env: Env,
+    amount: Int128,
+    min_profit: Option<Int128>,
+    max_hops: Option<u8>,
+    src_denom: String,
+    src_plane: Option<Plane>,
+    settle_plane: Option<Plane>,
+    max_twap_deviation_bps: Option<u16>,
+    max_slippage_bps: Option<u16>,
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
+    must_be_positive(amount)?;
+    let slippage_bps = slippage_bps(max_slippage_bps)?;
+    let max_hops = max_hops.unwrap_or(DEFAULT_MAX_HOPS);
+    if !(2..=MAX_HOPS).contains(&max_hops) {
+        return Err(NexusError::invalid_input(format!(
+            "max hops must be between 2 and {}, got {}",
+            MAX_HOPS, max_hops
+        )));
+    }
+    let network = Network::load(deps)?;
+    let query = SwapQuery::new(&network)?;
+    let markets = route::load_markets(&network, &query.pools, fis_input, env.block.time.seconds())?;
+    let markets = markets_near_twap(deps, markets, max_twap_deviation_bps)?;
+    let acc_link = query.pools.svm_link.load(fis_input)?;
+    // cycles start from any denom of the registry
+    network.denoms.address(&src_denom, Plane::Cosmos)?;
+
+    let max_input = Int256::from(amount.i128());
+    let mut best: Option<(Vec<route::Hop>, Int256, Int256)> = None;
+    for hops in route::find_cycles(&markets, &src_denom, usize::from(max_hops)) {
+        let (x, profit) = route::optimize_cycle(&markets, &hops, max_input)?;
+        if best.as_ref().is_none_or(|(_, _, best)| profit > *best) {
+            best = Some((hops, x, profit));
+        }
     }
 
-    let sender = env.contract.address.to_string();
-    src_swap.amount =
-        Int128::from_be_bytes(execute_amount.to_be_bytes()[16..32].try_into().unwrap());
-    dst_swap.amount =
-        Int128::from_be_bytes(first_swap_output.to_be_bytes()[16..32].try_into().unwrap());
+    let expected_min_profit = Int256::from(min_profit.unwrap_or_default().i128());
+    let Some((hops, x, profit)) = best else {
+        return Ok(StrategyOutput::default());
+    };
+    if x <= Int256::zero() || profit <= Int256::zero() || profit < expected_min_profit {
+        return Ok(StrategyOutput::default());
+    }
     deps.api.debug(
         format!(
-            "arbitrage from {} => {}, actual x: {}, estimate first swap output: {}, estimate second swap output: {}, estimate profit: {}",
-            src_pool.dex_name(),
-            dst_pool.dex_name(),
-            src_swap.amount,
-            first_swap_output,
-            second_swap_output,
-            second_swap_output - execute_amount,
+            "multi hop arbitrage via {}, x: {}, estimate profit: {}",
+            hops.iter()
+                .map(|hop| markets[hop.market].pool.dex_name())
+                .collect::<Vec<_>>()
+                .join(" => "),
+            x,
+            profit,
         )
         .as_str(),
     );
 
-    // actions, take usdt > btc arbitrage as example
-    // 1. do swap usdt to btc src pool
-    // 2. transfer the swapped btc amount to dst pool
-    // 3. swap the btc amount to usdt in dst pool
-    // 4. transfer usdt back to src pool
-    // TODO: Discuss and decide if initial denom should come from cosmos
-    let mut instructions = vec![];
-    instructions.extend(src_pool.compose_swap_fis(&src_swap)?);
-    instructions.push(astro_transfer(
-        sender.clone(),
-        &src_pool.denom_plane(),
-        &dst_pool.denom_plane(),
-        first_output_denom,
-        to_u128(first_swap_output),
-    ));
-    instructions.extend(dst_pool.compose_swap_fis(&dst_swap)?);
-    instructions.push(astro_transfer(
-        sender.clone(),
-        &dst_pool.denom_plane(),
-        &src_pool.denom_plane(),
-        second_output_denom,
-        to_u128(second_swap_output),
-    ));
-
-    Ok(to_json_binary(&StrategyOutput { instructions }).unwrap())
+    let instructions = compose_arbitrage(
+        &network,
+        &markets,
+        &hops,
+        &src_denom,
+        x,
+        expected_min_profit,
+        src_plane,
+        settle_plane,
+        slippage_bps,
+        query.load_balances(fis_input)?,
+        env.contract.address.to_string(),
+        acc_link.link.svm_addr,
+    )?;
+    Ok(StrategyOutput::new(instructions))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn swap(
-    _deps: Deps,
+    deps: Deps,
     env: Env,
     dex_name: String,
     src_denom: String,
     dst_denom: String,
     amount: Int128,
-    fis_input: &Vec<FISInput>,
-) -> StdResult<Binary> {
-    if src_denom != "usdt" && dst_denom != "usdt" {
-        return Err(StdError::generic_err(format!(
-            "Unsupported swap from {} to {}. Supported pairs: btc-usdt, eth-usdt, sol-usdt",
-            src_denom, dst_denom
-        )));
-    }
-
-    let pair = if src_denom == "usdt" {
-        format!("{}-{}", dst_denom, src_denom)
-    } else {
-        format!("{}-{}", src_denom, dst_denom)
-    };
-    let svm_link_input = fis_input
-        .get(0)
-        .ok_or(StdError::generic_err("account link on svm not found"))?;
-    let acc_link = from_json::<AccountLink>(svm_link_input.data.get(0).unwrap())?;
+    max_slippage_bps: Option<u16>,
+    output_to_cosmos: bool,
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
+    must_be_positive(amount)?;
+    let slippage_bps = slippage_bps(max_slippage_bps)?;
+    let network = Network::load(deps)?;
+    let pair = network.find_pair(&src_denom, &dst_denom)?;
+    let query = SwapQuery::new(&network)?;
+    let pair_input = query.pools.pair(&pair)?;
+    let acc_link = query.pools.svm_link.load(fis_input)?;
 
     let swap = &Swap {
         dex_name: dex_name.clone(),
@@ -417,49 +450,338 @@ pub fn swap(
         denom: src_denom,
         amount,
         sender_svm: acc_link.link.svm_addr.clone(),
+        slippage_bps,
     };
 
-    match str::to_lowercase(&dex_name).as_str() {
-        "svm raydium" => {
-            let pool = RaydiumPool::new(&pair)?;
-            let instructions = pool.compose_swap_fis(swap)?;
-            Ok(to_json_binary(&StrategyOutput { instructions })?)
-        }
+    // the pool state bounds the swap output
+    let pool = pair_input.pool(
+        &network,
+        parse_dex(&dex_name)?,
+        fis_input,
+        env.block.time.seconds(),
+    )?;
+    let plane = pool.denom_plane();
+    let balances = query.load_balances(fis_input)?;
+    let sender = env.contract.address.to_string();
+    let mut instructions = fund_plane(&network, &sender, plane, &swap.denom, amount, &balances)?;
+    instructions.extend(pool.compose_swap_fis(&network, swap)?);
+    if output_to_cosmos && plane != Plane::Cosmos {
+        // the actual output is only known on execution, at least the minimum is there
+        let (output_denom, min_output) = swap.min_output(pool.as_ref())?;
+        instructions.push(astro_transfer(
+            &network,
+            sender,
+            plane,
+            Plane::Cosmos,
+            output_denom,
+            to_u128(min_output)?,
+        )?);
+    }
+    Ok(StrategyOutput::new(instructions))
+}
 
-        "wasm astroport" => {
-            let pool = AstroportPool::new(&pair)?;
-            let instructions = pool.compose_swap_fis(swap)?;
-            Ok(to_json_binary(&StrategyOutput { instructions })?)
+/// Transfers moving `amount` (in cosmos decimals) of `alias` to `plane`, out of the
+/// `balances` of each plane (in their decimals), cosmos first, so a swap there can spend it.
+///
+/// Only whole units of `plane` are moved, e.g. 9 decimals eth on svm, as the swap spends no
+/// more.
+pub fn fund_plane(
+    network: &Network,
+    sender: &str,
+    plane: Plane,
+    alias: &str,
+    amount: Int128,
+    balances: &[(Plane, Uint128)],
+) -> NexusResult<Vec<FISInstruction>> {
+    let denoms = &network.denoms;
+    let in_cosmos =
+        |src_plane: Plane, amount: Uint128| denoms.convert(alias, src_plane, Plane::Cosmos, amount);
+    let unit = in_cosmos(plane, Uint128::one())?.max(Uint128::one());
+    let required = Uint128::try_from(amount)?
+        .checked_div(unit)?
+        .checked_mul(unit)?;
+
+    let mut available = Uint128::zero();
+    for (balance_plane, balance) in balances.iter().filter(|(p, _)| *p == plane) {
+        available = available.checked_add(in_cosmos(*balance_plane, *balance)?)?;
+    }
+    let mut missing = required.saturating_sub(available);
+    let mut instructions = vec![];
+    for (src_plane, balance) in balances.iter().filter(|(p, _)| *p != plane) {
+        if missing.is_zero() {
+            break;
+        }
+        let balance = in_cosmos(*src_plane, *balance)?;
+        let take = balance.checked_div(unit)?.checked_mul(unit)?.min(missing);
+        if take.is_zero() {
+            continue;
         }
+        instructions.push(astro_transfer(
+            network,
+            sender.to_string(),
+            *src_plane,
+            plane,
+            alias.to_string(),
+            take.u128(),
+        )?);
+        available = available.checked_add(take)?;
+        missing = missing.checked_sub(take)?;
+    }
+    if !missing.is_zero() {
+        return Err(NexusError::insufficient_balance(required, available));
+    }
+    Ok(instructions)
+}
 
-        "evm uniswap" => {
-            let pool = UniswapPool::new(&pair)?;
-            let instructions = pool.compose_swap_fis(swap)?;
-            Ok(to_json_binary(&StrategyOutput { instructions })?)
+// Swap split across the pools of every dex, from and back to cosmos
+pub fn best_swap(
+    deps: Deps,
+    env: Env,
+    src_denom: String,
+    dst_denom: String,
+    amount: Int128,
+    max_slippage_bps: Option<u16>,
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
+    must_be_positive(amount)?;
+    let slippage_bps = slippage_bps(max_slippage_bps)?;
+    let network = Network::load(deps)?;
+    let query = PoolQuery::new(&network)?;
+    let markets = route::load_markets(&network, &query, fis_input, env.block.time.seconds())?;
+    let acc_link = query.svm_link.load(fis_input)?;
+
+    let amount = Int256::from(amount.i128());
+    let splits = route::split_swap(&markets, &src_denom, &dst_denom, amount)?;
+    let output = splits
+        .iter()
+        .try_fold(Int256::zero(), |sum, split| sum.checked_add(split.output))?;
+    deps.api.debug(
+        format!(
+            "best swap {} {} to {}: {}, estimate output: {}",
+            amount,
+            src_denom,
+            dst_denom,
+            splits
+                .iter()
+                .map(|split| format!(
+                    "{} on {}",
+                    split.amount,
+                    markets[split.hop.market].pool.dex_name()
+                ))
+                .collect::<Vec<_>>()
+                .join(", "),
+            output,
+        )
+        .as_str(),
+    );
+
+    // the bound covers both fees and price impact
+    if let Some(bps) = max_slippage_bps {
+        let mut spot_output = Int256::zero();
+        for hop in route::direct_hops(&markets, &src_denom, &dst_denom) {
+            spot_output = spot_output.max(hop.spot_output(&markets, amount)?);
         }
+        let min_output = spot_output
+            .checked_mul(Int256::from(MAX_SLIPPAGE_BPS - bps))?
+            .checked_div(Int256::from(MAX_SLIPPAGE_BPS))?;
+        if output < min_output {
+            return Err(NexusError::slippage_exceeded(min_output, output));
+        }
+    }
 
-        _ => Err(StdError::generic_err(format!(
-            "Unsupported: {}. Supported: 'svm raydium', 'wasm astroport', 'evm uniswap'",
-            dex_name
-        ))),
+    let instructions = route::compose_split(
+        &network,
+        &markets,
+        &splits,
+        Plane::Cosmos,
+        env.contract.address.to_string(),
+        acc_link.link.svm_addr,
+        slippage_bps,
+    )?;
+    Ok(StrategyOutput::new(instructions))
+}
+
+// Deposit at the pool ratio, the amounts and lp tokens minted are estimated in the result
+#[allow(clippy::too_many_arguments)]
+pub fn add_liquidity(
+    deps: Deps,
+    env: Env,
+    dex_name: String,
+    pair: String,
+    base_amount: Int128,
+    quote_amount: Int128,
+    max_slippage_bps: Option<u16>,
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
+    must_be_positive(base_amount)?;
+    must_be_positive(quote_amount)?;
+    let slippage_bps = slippage_bps(max_slippage_bps)?;
+    let network = Network::load(deps)?;
+    let (x, y) = pair_denoms(&pair)?;
+    let pair = network.find_pair(x, y)?;
+    // the amounts follow the pair as given, e.g. usdt-btc quotes btc in usdt
+    let (base_amount, quote_amount) = if pair_denoms(&pair)?.0 == x {
+        (base_amount, quote_amount)
+    } else {
+        (quote_amount, base_amount)
+    };
+    let query = PoolQuery::new(&network)?;
+    let pool = query.pair(&pair)?.pool(
+        &network,
+        parse_dex(&dex_name)?,
+        fis_input,
+        env.block.time.seconds(),
+    )?;
+    let acc_link = query.svm_link.load(fis_input)?;
+
+    let deposit = Deposit {
+        dex_name,
+        pool_name: pair,
+        sender: env.contract.address.to_string(),
+        sender_svm: acc_link.link.svm_addr,
+        max_amount_a: quote_amount,
+        max_amount_b: base_amount,
+        slippage_bps,
+    };
+    let estimate = deposit.estimate(pool.as_ref())?;
+    Ok(StrategyOutput {
+        instructions: pool.compose_add_liquidity_fis(&network, &deposit)?,
+        result: to_json_string(&estimate)?,
+        ..Default::default()
+    })
+}
+
+// Redeem lp tokens at the pool ratio, the amounts returned are estimated in the result. Uniswap
+// positions aren't read, the estimate is the share of the in range liquidity, and their
+// withdrawals take no minimum amounts, so a max slippage is rejected for them
+pub fn remove_liquidity(
+    deps: Deps,
+    env: Env,
+    dex_name: String,
+    pair: String,
+    lp_amount: Int128,
+    max_slippage_bps: Option<u16>,
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
+    must_be_positive(lp_amount)?;
+    let slippage_bps = slippage_bps(max_slippage_bps)?;
+    let dex = parse_dex(&dex_name)?;
+    if dex == UNISWAP && max_slippage_bps.is_some() {
+        return Err(NexusError::invalid_input(
+            "uniswap withdrawals can't bound their amounts, leave max slippage unset",
+        ));
     }
+    let network = Network::load(deps)?;
+    let (x, y) = pair_denoms(&pair)?;
+    let pair = network.find_pair(x, y)?;
+    let query = PoolQuery::new(&network)?;
+    let pool = query
+        .pair(&pair)?
+        .pool(&network, dex, fis_input, env.block.time.seconds())?;
+    let acc_link = query.svm_link.load(fis_input)?;
+
+    let withdrawal = Withdrawal {
+        dex_name,
+        pool_name: pair,
+        sender: env.contract.address.to_string(),
+        sender_svm: acc_link.link.svm_addr,
+        lp_amount,
+        slippage_bps,
+    };
+    let estimate = withdrawal.estimate(pool.as_ref())?;
+    Ok(StrategyOutput {
+        instructions: pool.compose_remove_liquidity_fis(&network, &withdrawal)?,
+        result: to_json_string(&estimate)?,
+        ..Default::default()
+    })
+}
+
+// Read-only, the quote of each dex is returned in the result
+pub fn quote(
+    deps: Deps,
+    env: Env,
+    src_denom: String,
+    dst_denom: String,
+    amount: Int128,
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
+    must_be_positive(amount)?;
+    let network = Network::load(deps)?;
+    let query = PoolQuery::new(&network)?;
+    let markets = route::load_markets(&network, &query, fis_input, env.block.time.seconds())?;
+    let quotes = route::quote(
+        &network,
+        &markets,
+        &src_denom,
+        &dst_denom,
+        Int256::from(amount.i128()),
+    )?;
+    Ok(StrategyOutput {
+        result: to_json_string(&quotes)?,
+        ..Default::default()
+    })
 }
 
-#[entry_point]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    let action = from_json::<NexusAction>(msg.msg)?;
+pub fn handle(
+    deps: Deps,
+    env: Env,
+    action: NexusAction,
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
     match action {
         NexusAction::Arbitrage {
             pair,
             amount,
             min_profit,
-        } => arbitrage(deps, env, pair, amount, min_profit, &msg.fis_input),
+            src_denom,
+            src_plane,
+            settle_plane,
+            max_twap_deviation_bps,
+            max_slippage_bps,
+        } => arbitrage(
+            deps,
+            env,
+            pair,
+            amount,
+            min_profit,
+            src_denom,
+            src_plane,
+            settle_plane,
+            max_twap_deviation_bps,
+            max_slippage_bps,
+            fis_input,
+        ),
+
+        NexusAction::MultiHopArbitrage {
+            amount,
+            min_profit,
+            max_hops,
+            src_denom,
+            src_plane,
+            settle_plane,
+            max_twap_deviation_bps,
+            max_slippage_bps,
+        } => multi_hop_arbitrage(
+            deps,
+            env,
+            amount,
+            min_profit,
+            max_hops,
+            src_denom,
+            src_plane,
+            settle_plane,
+            max_twap_deviation_bps,
+            max_slippage_bps,
+            fis_input,
+        ),
 
         NexusAction::Swap {
             dex_name,
             src_denom,
             dst_denom,
             amount,
+            max_slippage_bps,
+            output_to_cosmos,
         } => swap(
             deps,
             env,
@@ -467,7 +789,62 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             src_denom,
             dst_denom,
             amount,
-            &msg.fis_input,
+            max_slippage_bps,
+            output_to_cosmos.unwrap_or_default(),
+            fis_input,
+        ),
+
+        NexusAction::BestSwap {
+            src_denom,
+            dst_denom,
+            amount,
+            max_slippage_bps,
+        } => best_swap(
+            deps,
+            env,
+            src_denom,
+            dst_denom,
+            amount,
+            max_slippage_bps,
+            fis_input,
+        ),
+
+        NexusAction::Quote {
+            src_denom,
+            dst_denom,
+            amount,
+        } => quote(deps, env, src_denom, dst_denom, amount, fis_input),
+
+        NexusAction::AddLiquidity {
+            dex_name,
+            pair,
+            base_amount,
+            quote_amount,
+            max_slippage_bps,
+        } => add_liquidity(
+            deps,
+            env,
+            dex_name,
+            pair,
+            base_amount,
+            quote_amount,
+            max_slippage_bps,
+            fis_input,
+        ),
+
+        NexusAction::RemoveLiquidity {
+            dex_name,
+            pair,
+            lp_amount,
+            max_slippage_bps,
+        } => remove_liquidity(
+            deps,
+            env,
+            dex_name,
+            pair,
+            lp_amount,
+            max_slippage_bps,
+            fis_input,
         ),
         // more actions goes here
     }
@@ -0,0 +1,150 @@
+use crate::svm::{raydium::PoolAccounts, whirlpool::WhirlpoolAccounts};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{from_json, Deps, Int256, Uint128};
+use nexus_sdk::{
+    astromesh::Plane,
+    denom::DenomRegistry,
+    error::{NexusError, NexusResult},
+    strategy::load_network,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Network the contract falls back to when instantiated without one
+pub const EMBEDDED_NETWORK: &str = include_str!("../networks/devnet.json");
+
+/// Addresses of one deployment, passed as `network` at instantiate, see `networks/devnet.json`.
+///
+/// The pools of each dex are the pair registry: a pair, named `base-quote` after its denom
+/// aliases, is tradable once any dex lists it.
+#[cw_serde]
+pub struct Network {
+    pub denoms: DenomRegistry,
+    pub astroport: AstroportNetwork,
+    pub raydium: RaydiumNetwork,
+    #[serde(default)]
+    pub whirlpool: WhirlpoolNetwork,
+    pub uniswap: UniswapNetwork,
+}
+
+#[cw_serde]
+pub struct AstroportNetwork {
+    /// pair contract of each pair
+    pub pools: BTreeMap<String, String>,
+}
+
+#[cw_serde]
+pub struct RaydiumNetwork {
+    pub cpmm_program_id: String,
+    pub pools: BTreeMap<String, PoolAccounts>,
+}
+
+/// Orca whirlpools, concentrated liquidity pools on svm
+#[cw_serde]
+#[derive(Default)]
+pub struct WhirlpoolNetwork {
+    pub program_id: String,
+    pub pools: BTreeMap<String, WhirlpoolAccounts>,
+}
+
+#[cw_serde]
+pub struct UniswapNetwork {
+    /// PoolManager the pool state is read from
+    pub pool_manager: String,
+    pub pool_action: String,
+    pub pools: BTreeMap<String, UniswapPoolConfig>,
+}
+
+/// Uniswap v4 pool key, currencies are aliases of `denoms`
+#[cw_serde]
+pub struct UniswapPoolConfig {
+    pub currency0: String,
+    pub currency1: String,
+    pub fee: u32,
+    pub tick_spacing: i32,
+}
+
+/// Base and quote denom aliases of a pair, e.g. `btc` and `usdt` of `btc-usdt`
+pub fn pair_denoms(pair: &str) -> NexusResult<(&str, &str)> {
+    pair.split_once('-')
+        .filter(|(base, quote)| !base.is_empty() && !quote.is_empty() && base != quote)
+        .ok_or_else(|| NexusError::invalid_input(format!("invalid pair format: {}", pair)))
+}
+
+impl Network {
+    /// Network stored at instantiate, or the embedded one
+    pub fn load(deps: Deps) -> NexusResult<Self> {
+        match load_network(deps.storage)? {
+            Some(network) => Ok(network),
+            None => Self::embedded(),
+        }
+    }
+
+    pub fn embedded() -> NexusResult<Self> {
+        Ok(from_json(EMBEDDED_NETWORK)?)
+    }
+
+    /// Pairs listed on any dex
+    pub fn pairs(&self) -> BTreeSet<&str> {
+        self.astroport
+            .pools
+            .keys()
+            .chain(self.raydium.pools.keys())
+            .chain(self.whirlpool.pools.keys())
+            .chain(self.uniswap.pools.keys())
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Listed pair of `x` and `y`, whichever is the base
+    pub fn find_pair(&self, x: &str, y: &str) -> NexusResult<String> {
+        let pairs = self.pairs();
+        [format!("{}-{}", x, y), format!("{}-{}", y, x)]
+            .into_iter()
+            .find(|pair| pairs.contains(pair.as_str()))
+            .ok_or_else(|| {
+                NexusError::UnsupportedPair(format!(
+                    "{}-{}. Supported pairs: {}",
+                    x,
+                    y,
+                    pairs.into_iter().collect::<Vec<_>>().join(", ")
+                ))
+            })
+    }
+
+    /// Scale of `alias` amounts from `plane` to cosmos decimals, e.g. 10^9 for eth with 18
+    /// decimals on cosmos but 9 on svm. One when `plane` has as many decimals or more.
+    pub fn decimal_multiplier(&self, alias: &str, plane: Plane) -> NexusResult<Int256> {
+        let unit = self
+            .denoms
+            .convert(alias, plane, Plane::Cosmos, Uint128::one())?;
+        Ok(Int256::from(unit.u128()).max(Int256::one()))
+    }
+
+    pub fn astroport_pool(&self, pair: &str) -> NexusResult<&String> {
+        self.astroport
+            .pools
+            .get(pair)
+            .ok_or_else(|| NexusError::UnsupportedPair(format!("astroport {}", pair)))
+    }
+
+    pub fn raydium_pool(&self, pair: &str) -> NexusResult<&PoolAccounts> {
+        self.raydium
+            .pools
+            .get(pair)
+            .ok_or_else(|| NexusError::UnsupportedPair(format!("raydium {}", pair)))
+    }
+
+    pub fn whirlpool_pool(&self, pair: &str) -> NexusResult<&WhirlpoolAccounts> {
+        self.whirlpool
+            .pools
+            .get(pair)
+            .ok_or_else(|| NexusError::UnsupportedPair(format!("whirlpool {}", pair)))
+    }
+
+    pub fn uniswap_pool(&self, pair: &str) -> NexusResult<&UniswapPoolConfig> {
+        self.uniswap
+            .pools
+            .get(pair)
+            .ok_or_else(|| NexusError::UnsupportedPair(format!("uniswap {}", pair)))
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{
+    astromesh::Pool,
+    evm::uniswap::{UniswapPool, UNISWAP},
+    network::Network,
+    svm::{
+        raydium::{RaydiumPool, RAYDIUM},
+        whirlpool::{WhirlpoolPool, WHIRLPOOL},
+    },
+    wasm::astroport::{AstroportPool, ASTROPORT},
+};
+use cosmwasm_std::{Coin, Uint128};
+use nexus_sdk::{
+    astromesh::{FISInput, Plane},
+    error::{NexusError, NexusResult},
+    input::Slot,
+    query::{Query, QueryInstruction},
+    svm::AccountLink,
+};
+
+pub const SVM_ACCOUNT_LINK_PATH: &str = "/flux/svm/v1beta1/account_link/cosmos/${wallet}";
+/// Balance of the swapped denom on one plane, in its decimals, the plane is appended
+pub const PLANE_BALANCE_PATH: &str =
+    "/flux/astromesh/v1beta1/balance/${wallet}/${src_denom}?plane=";
+
+// Query plans of the nexus actions, used by the schema generator and to decode fis_input
+// so instruction indexes can't drift apart when pairs or dexes are added
+
+/// Pools queried for one pair, as instruction indexes, none for dexes not listing it
+pub struct PairInput {
+    pub pair: String,
+    pub astroport: Option<usize>,
+    pub raydium: Option<usize>,
+    pub whirlpool: Option<usize>,
+    pub uniswap: Option<usize>,
+}
+
+impl PairInput {
+    /// Pools of every dex listing the pair, at `block_time` in unix seconds
+    pub fn pools(
+        &self,
+        network: &Network,
+        fis_input: &[FISInput],
+        block_time: u64,
+    ) -> NexusResult<Vec<Box<dyn Pool>>> {
+        [ASTROPORT, RAYDIUM, WHIRLPOOL, UNISWAP]
+            .into_iter()
+            .filter(|dex| self.instruction(dex).is_some())
+            .map(|dex| self.pool(network, dex, fis_input, block_time))
+            .collect()
+    }
+
+    /// Pool of `dex`, one of `ASTROPORT`, `RAYDIUM`, `WHIRLPOOL` or `UNISWAP`
+    pub fn pool(
+        &self,
+        network: &Network,
+        dex: &str,
+        fis_input: &[FISInput],
+        block_time: u64,
+    ) -> NexusResult<Box<dyn Pool>> {
+        let instruction = self
+            .instruction(dex)
+            .ok_or_else(|| NexusError::UnsupportedPair(format!("{} {}", dex, self.pair)))?;
+        Ok(match dex {
+            ASTROPORT => Box::new(AstroportPool::from_fis(
+                network,
+                &self.pair,
+                fis_input,
+                instruction,
+            )?),
+            RAYDIUM => Box::new(RaydiumPool::from_fis(
+                network,
+                &self.pair,
+                fis_input,
+                instruction,
+                block_time,
+            )?),
+            WHIRLPOOL => Box::new(WhirlpoolPool::from_fis(
+                network,
+                &self.pair,
+                fis_input,
+                instruction,
+            )?),
+            _ => Box::new(UniswapPool::from_fis(
+                network,
+                &self.pair,
+                fis_input,
+                instruction,
+            )?),
+        })
+    }
+
+    fn instruction(&self, dex: &str) -> Option<usize> {
+        match dex {
+            ASTROPORT => self.astroport,
+            RAYDIUM => self.raydium,
+            WHIRLPOOL => self.whirlpool,
+            UNISWAP => self.uniswap,
+            _ => None,
+        }
+    }
+}
+
+/// Astroport, raydium, whirlpool and uniswap pools of every listed pair, then the svm account link.
+///
+/// Shared by every action as swaps are priced, and bounded, with the pool state.
+pub struct PoolQuery {
+    pub query: Query,
+    pub pairs: Vec<PairInput>,
+    pub svm_link: Slot<AccountLink>,
+}
+
+impl PoolQuery {
+    pub fn new(network: &Network) -> NexusResult<Self> {
+        let mut query = Query::default();
+        let mut pairs = vec![];
+        for pair in network.pairs() {
+            pairs.push(PairInput {
+                pair: pair.to_string(),
+                astroport: if network.astroport.pools.contains_key(pair) {
+                    Some(query.push(AstroportPool::query(network, pair)?))
+                } else {
+                    None
+                },
+                raydium: if network.raydium.pools.contains_key(pair) {
+                    Some(query.push(RaydiumPool::query(network, pair)?))
+                } else {
+                    None
+                },
+                whirlpool: if network.whirlpool.pools.contains_key(pair) {
+                    Some(query.push(WhirlpoolPool::query(network, pair)?))
+                } else {
+                    None
+                },
+                uniswap: if network.uniswap.pools.contains_key(pair) {
+                    Some(query.push(UniswapPool::query(network, pair)?))
+                } else {
+                    None
+                },
+            });
+        }
+        let svm_link = Slot::new(
+            query.push(QueryInstruction::cosmos_query(SVM_ACCOUNT_LINK_PATH)),
+            0,
+            "svm account link",
+        );
+
+        Ok(PoolQuery {
+            query,
+            pairs,
+            svm_link,
+        })
+    }
+
+    pub fn pair(&self, pair: &str) -> NexusResult<&PairInput> {
+        self.pairs
+            .iter()
+            .find(|input| input.pair == pair)
+            .ok_or_else(|| NexusError::UnsupportedPair(pair.to_string()))
+    }
+}
+
+/// Pool query of a swap followed by the balance of its input denom on every plane, so the
+/// funds missing on the plane of the dex are moved there
+pub struct SwapQuery {
+    pub pools: PoolQuery,
+    pub balances: Vec<(Plane, Slot<Coin>)>,
+}
+
+impl SwapQuery {
+    pub fn new(network: &Network) -> NexusResult<Self> {
+        let mut pools = PoolQuery::new(network)?;
+        let balances = [Plane::Cosmos, Plane::Wasm, Plane::Evm, Plane::Svm]
+            .into_iter()
+            .map(|plane| {
+                let path = format!("{}{}", PLANE_BALANCE_PATH, plane);
+                let instruction = pools.query.push(QueryInstruction::cosmos_query(&path));
+                (plane, Slot::new(instruction, 0, "plane balance"))
+            })
+            .collect();
+        Ok(SwapQuery { pools, balances })
+    }
+
+    /// Balance of the swapped denom on each plane, in the plane's decimals
+    pub fn load_balances(&self, fis_input: &[FISInput]) -> NexusResult<Vec<(Plane, Uint128)>> {
+        self.balances
+            .iter()
+            .map(|(plane, slot)| Ok((*plane, slot.load(fis_input)?.amount)))
+            .collect()
+    }
+}
@@ -0,0 +1,570 @@
+use crate::{
+    astro_transfer,
+    astromesh::{to_int256, to_u128, to_uint256, Pool, Swap, MAX_SLIPPAGE_BPS},
+    network::Network,
+    query::PoolQuery,
+};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Decimal256, Int128, Int256, Isqrt, Uint256};
+use nexus_sdk::{
+    astromesh::{FISInput, FISInstruction, Plane},
+    error::{NexusError, NexusResult},
+};
+use std::cmp::{Ordering, Reverse};
+
+// Routing over the pool graph: denoms are the vertices and every pool is an edge that can be
+// traded both ways
+
+/// Denominator of `Pool::fee_rate`
+pub const FEE_DENOMINATOR: i128 = 1_000_000;
+
+/// Parts the amount of a split swap is allocated in
+pub const SPLIT_PARTS: u32 = 20;
+
+/// A pool and the pair it was queried for
+pub struct Market {
+    pub pair: String,
+    pub pool: Box<dyn Pool>,
+}
+
+/// Swap of `markets[market]`, a for b or b for a
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hop {
+    pub market: usize,
+    pub a_for_b: bool,
+}
+
+impl Hop {
+    fn input_denom(&self, markets: &[Market]) -> String {
+        let pool = &markets[self.market].pool;
+        if self.a_for_b {
+            pool.denom_a()
+        } else {
+            pool.denom_b()
+        }
+    }
+
+    fn output_denom(&self, markets: &[Market]) -> String {
+        let pool = &markets[self.market].pool;
+        if self.a_for_b {
+            pool.denom_b()
+        } else {
+            pool.denom_a()
+        }
+    }
+
+    // spot reserves as (input, output)
+    fn reserves(&self, markets: &[Market]) -> (Int256, Int256) {
+        let (a, b) = markets[self.market].pool.spot_reserves();
+        if self.a_for_b {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    }
+
+    /// Output of `x` at the current price, i.e. without fees and price impact
+    pub fn spot_output(&self, markets: &[Market], x: Int256) -> NexusResult<Int256> {
+        let (reserve_in, reserve_out) = self.reserves(markets);
+        Ok(x.checked_mul(reserve_out)?.checked_div(reserve_in)?)
+    }
+
+    fn swap_output(&self, markets: &[Market], x: Int256) -> Int256 {
+        markets[self.market].pool.swap_output(x, self.a_for_b).1
+    }
+}
+
+/// Share of a split swap routed through one market
+#[derive(Clone, Debug, PartialEq)]
+pub struct Split {
+    pub hop: Hop,
+    pub amount: Int256,
+    pub output: Int256,
+}
+
+/// Expected outcome of a swap on one market, amounts are in cosmos decimals
+#[cw_serde]
+pub struct Quote {
+    pub dex_name: String,
+    pub pair: String,
+    pub output: Int256,
+    /// output per input, in whole tokens
+    pub effective_price: Decimal256,
+    /// shortfall of the output against the spot price net of the fee
+    pub price_impact_bps: u16,
+    /// in input denom
+    pub fee: Int256,
+}
+
+/// Every pool of every pair of `query` at `block_time`, unpriced or drained pools are left out
+pub fn load_markets(
+    network: &Network,
+    query: &PoolQuery,
+    fis_input: &[FISInput],
+    block_time: u64,
+) -> NexusResult<Vec<Market>> {
+    let mut markets = vec![];
+    for input in query.pairs.iter() {
+        markets.extend(
+            input
+                .pools(network, fis_input, block_time)?
+                .into_iter()
+                .filter(|pool| !pool.a().is_zero() && !pool.b().is_zero())
+                .map(|pool| Market {
+                    pair: input.pair.clone(),
+                    pool,
+                }),
+        );
+    }
+    Ok(markets)
+}
+
+/// Cycles of 2 to `max_hops` swaps from `start` back to it.
+///
+/// A market is used at most once per cycle and no denom is visited twice, so a cycle never
+/// decomposes into shorter ones.
+pub fn find_cycles(markets: &[Market], start: &str, max_hops: usize) -> Vec<Vec<Hop>> {
+    let mut cycles = vec![];
+    let mut visited = vec![start.to_string()];
+    extend_cycles(markets, max_hops, &mut vec![], &mut visited, &mut cycles);
+    cycles
+}
+
+fn extend_cycles(
+    markets: &[Market],
+    max_hops: usize,
+    path: &mut Vec<Hop>,
+    visited: &mut Vec<String>,
+    cycles: &mut Vec<Vec<Hop>>,
+) {
+    let (start, denom) = (visited[0].clone(), visited[visited.len() - 1].clone());
+    for (market, Market { pool, .. }) in markets.iter().enumerate() {
+        if path.iter().any(|hop| hop.market == market) {
+            continue;
+        }
+        let hop = if pool.denom_a() == denom {
+            Hop {
+                market,
+                a_for_b: true,
+            }
+        } else if pool.denom_b() == denom {
+            Hop {
+                market,
+                a_for_b: false,
+            }
+        } else {
+            continue;
+        };
+
+        let next = hop.output_denom(markets);
+        path.push(hop);
+        if next == start {
+            if path.len() >= 2 {
+                cycles.push(path.clone());
+            }
+        } else if path.len() < max_hops && !visited.contains(&next) {
+            visited.push(next);
+            extend_cycles(markets, max_hops, path, visited, cycles);
+            visited.pop();
+        }
+        path.pop();
+    }
+}
+
+/// Output denom and amount of every swap when trading `x` along `hops`
+pub fn cycle_output(markets: &[Market], hops: &[Hop], x: Int256) -> Vec<(String, Int256)> {
+    let mut amount = x;
+    hops.iter()
+        .map(|hop| {
+            let (denom, output) = markets[hop.market].pool.swap_output(amount, hop.a_for_b);
+            amount = output;
+            (denom, output)
+        })
+        .collect()
+}
+
+/// Profit of trading `x` along the cycle, in the start denom
+pub fn cycle_profit(markets: &[Market], hops: &[Hop], x: Int256) -> Int256 {
+    match cycle_output(markets, hops, x).last() {
+        Some((_, output)) => *output - x,
+        None => Int256::zero(),
+    }
+}
+
+/// Best input of at most `max_input` along the cycle and its profit, see `optimize`
+pub fn optimize_cycle(
+    markets: &[Market],
+    hops: &[Hop],
+    max_input: Int256,
+) -> NexusResult<(Int256, Int256)> {
+    let legs: Vec<(&dyn Pool, bool)> = hops
+        .iter()
+        .map(|hop| (markets[hop.market].pool.as_ref(), hop.a_for_b))
+        .collect();
+    optimize(&legs, max_input)
+}
+
+/// Best input of at most `max_input` swapped along `legs`, pools and whether they swap a for b,
+/// and its profit. Zero when no input is profitable.
+///
+/// With its fee folded into the input reserve, a pool outputs `r_out * x / (r_in / (1 - fee) + x)`
+/// and the legs chain into one such pool, whose optimum `sqrt(r_in * r_out) - r_in` on the spot
+/// reserves starts the search. The bracket doubles from there, up to `max_input`, while the
+/// actual profit still grows, then a ternary search over the actual outputs, with their decimal
+/// scaling and rounding, finds the best integer input within it.
+///
+/// This assumes the profit is unimodal in the input. Constant product, stableswap and PCL pools
+/// output a concave amount of their input, as their marginal price only worsens along the
+/// curve, and so do concentrated liquidity pools across ticks. Chained concave outputs less
+/// the input are concave. Concentrated pools quote nothing past the ticks they are priced
+/// within, a drop after which the profit only falls, which keeps it unimodal. Rounding of the
+/// outputs can break ties off the exact optimum by a few units.
+pub fn optimize(legs: &[(&dyn Pool, bool)], max_input: Int256) -> NexusResult<(Int256, Int256)> {
+    let Some(((first, first_a_for_b), rest)) = legs.split_first() else {
+        return Ok((Int256::zero(), Int256::zero()));
+    };
+    // x in, y out of (r_in, r_out) then (s_in, s_out) is
+    // y = r_out * s_out / (r_out + s_in) * x / (r_in * s_in / (r_out + s_in) + x)
+    let (mut reserve_in, mut reserve_out) = fee_reserves(*first, *first_a_for_b)?;
+    for (pool, a_for_b) in rest {
+        let (next_in, next_out) = fee_reserves(*pool, *a_for_b)?;
+        let denominator = reserve_out.checked_add(next_in)?;
+        reserve_in = reserve_in.checked_mul(next_in)?.checked_div(denominator)?;
+        reserve_out = reserve_out
+            .checked_mul(next_out)?
+            .checked_div(denominator)?;
+    }
+    if reserve_out <= reserve_in {
+        return Ok((Int256::zero(), Int256::zero()));
+    }
+    let optimum = to_int256(Isqrt::isqrt(to_uint256(
+        reserve_in.checked_mul(reserve_out)?,
+    )))
+    .checked_sub(reserve_in)?;
+
+    let profit = |x: Int256| -> NexusResult<Int256> {
+        Ok(legs
+            .iter()
+            .fold(x, |amount, (pool, a_for_b)| {
+                pool.swap_output(amount, *a_for_b).1
+            })
+            .checked_sub(x)?)
+    };
+    // the best input is past `low` and, once the profit stops growing, before `high`
+    let (mut low, mut high) = (Int256::zero(), optimum.max(Int256::one()).min(max_input));
+    while high < max_input {
+        let next = high.saturating_mul(Int256::from(2)).min(max_input);
+        if profit(next)? <= profit(high)? {
+            high = next;
+            break;
+        }
+        (low, high) = (high, next);
+    }
+    let three = Int256::from(3);
+    while high - low > three {
+        let third = (high - low) / three;
+        let (left, right) = (low + third, high - third);
+        match profit(left)?.cmp(&profit(right)?) {
+            Ordering::Less => low = left,
+            Ordering::Greater => high = right,
+            Ordering::Equal => (low, high) = (left, right),
+        }
+    }
+    let (mut best, mut best_profit) = (low, profit(low)?);
+    while low < high {
+        low += Int256::one();
+        let low_profit = profit(low)?;
+        if low_profit > best_profit {
+            (best, best_profit) = (low, low_profit);
+        }
+    }
+    if best_profit <= Int256::zero() {
+        return Ok((Int256::zero(), Int256::zero()));
+    }
+    Ok((best, best_profit))
+}
+
+// tolerance of `swap`, at most its own, leaving a minimum output of at least `min_output`
+fn bounded_slippage_bps(
+    network: &Network,
+    pool: &dyn Pool,
+    swap: &Swap,
+    min_output: Int256,
+) -> NexusResult<u16> {
+    let (denom, estimate) = swap.estimate(pool)?;
+    // the swap bounds its output in whole units of the pool plane, rounded down
+    let unit = network.decimal_multiplier(&denom, pool.denom_plane())?;
+    let min_output = min_output
+        .checked_add(unit.checked_sub(Int256::one())?)?
+        .checked_div(unit)?
+        .checked_mul(unit)?;
+    if estimate < min_output {
+        return Err(NexusError::slippage_exceeded(min_output, estimate));
+    }
+    let bps = estimate
+        .checked_sub(min_output)?
+        .checked_mul(Int256::from(MAX_SLIPPAGE_BPS))?
+        .checked_div(estimate)?;
+    Ok(u16::try_from(to_u128(bps)?)
+        .unwrap_or(MAX_SLIPPAGE_BPS)
+        .min(swap.slippage_bps))
+}
+
+// spot reserves as (input, output), the input one grown by the fee
+fn fee_reserves(pool: &dyn Pool, a_for_b: bool) -> NexusResult<(Int256, Int256)> {
+    let (a, b) = pool.spot_reserves();
+    let (reserve_in, reserve_out) = if a_for_b { (a, b) } else { (b, a) };
+    let denominator = Int256::from(FEE_DENOMINATOR);
+    let reserve_in = reserve_in
+        .checked_mul(denominator)?
+        .checked_div(denominator.checked_sub(pool.fee_rate())?)?;
+    Ok((reserve_in, reserve_out))
+}
+
+/// Swaps of `x` along the cycle, moving every output to the plane of the next pool and the
+/// last one to `settle_plane`. The actual outputs are only known on execution, so each swap
+/// spends, and each transfer moves, the minimum output of the swap before.
+///
+/// The last swap reverts below `min_return`, e.g. `x` and the min profit, as the tolerances
+/// of the swaps before add up. Fails when its estimate doesn't reach it.
+#[allow(clippy::too_many_arguments)]
+pub fn compose_cycle(
+    network: &Network,
+    markets: &[Market],
+    hops: &[Hop],
+    x: Int256,
+    min_return: Int256,
+    settle_plane: Plane,
+    slippage_bps: u16,
+    sender: String,
+    sender_svm: String,
+) -> NexusResult<Vec<FISInstruction>> {
+    let mut amount = x;
+    let mut instructions = vec![];
+    for (i, hop) in hops.iter().enumerate() {
+        let Market { pair, pool } = &markets[hop.market];
+        let mut swap = Swap {
+            dex_name: pool.dex_name(),
+            pool_name: pair.clone(),
+            sender: sender.clone(),
+            denom: hop.input_denom(markets),
+            amount: Int128::try_from(amount)?,
+            sender_svm: sender_svm.clone(),
+            slippage_bps,
+        };
+        if i + 1 == hops.len() {
+            swap.slippage_bps = bounded_slippage_bps(network, pool.as_ref(), &swap, min_return)?;
+        }
+        instructions.extend(pool.compose_swap_fis(network, &swap)?);
+        let (output_denom, min_output) = swap.min_output(pool.as_ref())?;
+
+        let next_plane = match hops.get(i + 1) {
+            Some(next) => markets[next.market].pool.denom_plane(),
+            None => settle_plane,
+        };
+        if pool.denom_plane() != next_plane {
+            instructions.push(astro_transfer(
+                network,
+                sender.clone(),
+                pool.denom_plane(),
+                next_plane,
+                output_denom,
+                to_u128(min_output)?,
+            )?);
+        }
+        amount = min_output;
+    }
+    Ok(instructions)
+}
+
+/// Markets trading `src_denom` for `dst_denom`
+pub fn direct_hops(markets: &[Market], src_denom: &str, dst_denom: &str) -> Vec<Hop> {
+    markets
+        .iter()
+        .enumerate()
+        .filter_map(|(market, Market { pool, .. })| {
+            if pool.denom_a() == src_denom && pool.denom_b() == dst_denom {
+                Some(Hop {
+                    market,
+                    a_for_b: true,
+                })
+            } else if pool.denom_b() == src_denom && pool.denom_a() == dst_denom {
+                Some(Hop {
+                    market,
+                    a_for_b: false,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Splits `amount` of `src_denom` across the markets trading it for `dst_denom`.
+///
+/// Each of `SPLIT_PARTS` parts goes to the market with the highest marginal output, which is
+/// optimal up to the part size as pool outputs are concave in the input.
+pub fn split_swap(
+    markets: &[Market],
+    src_denom: &str,
+    dst_denom: &str,
+    amount: Int256,
+) -> NexusResult<Vec<Split>> {
+    let mut splits: Vec<Split> = direct_hops(markets, src_denom, dst_denom)
+        .into_iter()
+        .map(|hop| Split {
+            hop,
+            amount: Int256::zero(),
+            output: Int256::zero(),
+        })
+        .collect();
+    if splits.is_empty() {
+        return Err(NexusError::UnsupportedPair(format!(
+            "{}-{}",
+            src_denom, dst_denom
+        )));
+    }
+
+    let parts = Int256::from(SPLIT_PARTS);
+    let part = amount.checked_div(parts)?;
+    for i in 0..SPLIT_PARTS {
+        // the last part takes the remainder
+        let size = if i == SPLIT_PARTS - 1 {
+            amount - part * (parts - Int256::one())
+        } else {
+            part
+        };
+        if size.is_zero() {
+            continue;
+        }
+        let Some((best, output)) = splits
+            .iter()
+            .enumerate()
+            .map(|(i, split)| (i, split.hop.swap_output(markets, split.amount + size)))
+            .max_by_key(|(i, output)| *output - splits[*i].output)
+        else {
+            break;
+        };
+        splits[best].amount += size;
+        splits[best].output = output;
+    }
+
+    splits.retain(|split| !split.amount.is_zero());
+    Ok(splits)
+}
+
+/// Swaps of every split from funds on `home`, moving the input to the plane of the market and
+/// the output back to `home`
+pub fn compose_split(
+    network: &Network,
+    markets: &[Market],
+    splits: &[Split],
+    home: Plane,
+    sender: String,
+    sender_svm: String,
+    slippage_bps: u16,
+) -> NexusResult<Vec<FISInstruction>> {
+    let mut instructions = vec![];
+    for split in splits {
+        let Market { pair, pool } = &markets[split.hop.market];
+        let plane = pool.denom_plane();
+        let input_denom = split.hop.input_denom(markets);
+        if plane != home {
+            instructions.push(astro_transfer(
+                network,
+                sender.clone(),
+                home,
+                plane,
+                input_denom.clone(),
+                to_u128(split.amount)?,
+            )?);
+        }
+        let swap = Swap {
+            dex_name: pool.dex_name(),
+            pool_name: pair.clone(),
+            sender: sender.clone(),
+            denom: input_denom,
+            amount: Int128::try_from(split.amount)?,
+            sender_svm: sender_svm.clone(),
+            slippage_bps,
+        };
+        instructions.extend(pool.compose_swap_fis(network, &swap)?);
+        if plane != home {
+            // the actual output is only known on execution, at least the minimum is there
+            let (output_denom, min_output) = swap.min_output(pool.as_ref())?;
+            instructions.push(astro_transfer(
+                network,
+                sender.clone(),
+                plane,
+                home,
+                output_denom,
+                to_u128(min_output)?,
+            )?);
+        }
+    }
+    Ok(instructions)
+}
+
+/// Quote of `amount` on every market trading `src_denom` for `dst_denom`, best output first
+pub fn quote(
+    network: &Network,
+    markets: &[Market],
+    src_denom: &str,
+    dst_denom: &str,
+    amount: Int256,
+) -> NexusResult<Vec<Quote>> {
+    let hops = direct_hops(markets, src_denom, dst_denom);
+    if hops.is_empty() {
+        return Err(NexusError::UnsupportedPair(format!(
+            "{}-{}",
+            src_denom, dst_denom
+        )));
+    }
+    // scales the amounts to whole tokens, e.g. 8 decimals btc for 6 decimals usdt
+    let unit = |alias: &str| -> NexusResult<Uint256> {
+        let decimals = network.denoms.link(alias, Plane::Cosmos)?.decimals;
+        Ok(Uint256::from(10u32).checked_pow(u32::from(decimals))?)
+    };
+    let (src_unit, dst_unit) = (unit(src_denom)?, unit(dst_denom)?);
+    let denominator = Int256::from(FEE_DENOMINATOR);
+
+    let mut quotes = vec![];
+    for hop in hops {
+        let pool = &markets[hop.market].pool;
+        let output = hop.swap_output(markets, amount).max(Int256::zero());
+        let fee = amount
+            .checked_mul(pool.fee_rate())?
+            .checked_div(denominator)?;
+        let effective_price = Decimal256::checked_from_ratio(
+            to_uint256(output).checked_mul(src_unit)?,
+            to_uint256(amount).checked_mul(dst_unit)?,
+        )
+        .map_err(|e| NexusError::Math(e.to_string()))?;
+
+        let spot_output = hop
+            .spot_output(markets, amount)?
+            .checked_mul(denominator.checked_sub(pool.fee_rate())?)?
+            .checked_div(denominator)?;
+        let price_impact_bps = if spot_output > output {
+            spot_output
+                .checked_sub(output)?
+                .checked_mul(Int256::from(MAX_SLIPPAGE_BPS))?
+                .checked_div(spot_output)?
+                .min(Int256::from(MAX_SLIPPAGE_BPS))
+        } else {
+            Int256::zero()
+        };
+
+        quotes.push(Quote {
+            dex_name: pool.dex_name(),
+            pair: markets[hop.market].pair.clone(),
+            output,
+            effective_price,
+            price_impact_bps: u16::try_from(to_u128(price_impact_bps)?)
+                .map_err(|e| NexusError::Math(e.to_string()))?,
+            fee,
+        });
+    }
+    quotes.sort_by_key(|quote| Reverse(quote.output));
+    Ok(quotes)
+}
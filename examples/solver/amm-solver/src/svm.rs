@@ -1,26 +1,36 @@
-use cosmwasm_std::{from_json, Binary, StdError, Uint64};
-use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
+use crate::clmm::Tick;
+use nexus_sdk::{
+    error::{NexusError, NexusResult},
+    svm::Pubkey,
+};
 
 pub mod raydium {
-    use crate::astromesh::{FISInput, FISInstruction, Pool, Swap, ETH_DECIMAL_DIFF};
+    use crate::astromesh::{less_slippage, to_u128, to_uint256, Deposit, Pool, Swap, Withdrawal};
     use cosmwasm_schema::cw_serde;
-    use cosmwasm_std::{to_json_vec, Binary, Int128, Int256, StdError};
+    use cosmwasm_std::{to_json_vec, Binary, Decimal256, Int256, Uint128, Uint256, Uint64};
+    use nexus_sdk::{
+        astromesh::{FISInput, FISInstruction, Plane},
+        error::{NexusError, NexusResult},
+        input::Slot,
+        query::QueryInstruction,
+        svm::{
+            associated_token, get_associated_token_address, Account, InstructionAccountMeta,
+            InstructionMeta, MsgTransaction, Pubkey, TokenAccount, TransactionBuilder,
+            SPL_TOKEN_2022_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID,
+        },
+    };
     use tiny_keccak::{Hasher, Keccak};
 
-    use super::{
-        get_denom, Account, Instruction, InstructionAccount, MsgTransaction, PoolState, Pubkey,
-        TokenAccount,
-    };
+    use super::{AmmConfig, ObservationState, PoolState, PoolStatusBit};
+    use crate::network::{pair_denoms, Network};
 
     pub const RAYDIUM: &str = "raydium";
-    pub const SPL_TOKEN_2022: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
-    pub const CPMM_PROGRAM_ID: &str = "6W19gt519Ruyw3s4BiKtQXvxETzPbptjgfgB5gMgrfAf";
-    pub const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
-    pub const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
     pub const BPS: i128 = 1000000i128;
+    pub const MEMO_PROGRAM_ID: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+    /// Seconds the twap averages over, the observations span at least 25 minutes
+    pub const TWAP_WINDOW: u64 = 900;
 
-    #[derive(Clone)]
+    #[cw_serde]
     pub struct PoolAccounts {
         pub authority_account: String,
         pub amm_config_account: String,
@@ -32,52 +42,13 @@ pub mod raydium {
         pub observer_state: String,
     }
 
-    pub fn get_pool_accounts_by_name(pool_name: &String) -> Result<PoolAccounts, StdError> {
-        match pool_name.as_str() {
-            "btc-usdt" => Ok(PoolAccounts {
-                authority_account: "3NTS4CmziURYZJ1JywCaCF4urzVbhL6kxNLbpuLzaaR7".to_string(),
-                amm_config_account: "EHR3a7vLxBREzXic1rp7tyPPen6wy8VzdnYfKKRDXJG9".to_string(),
-                pool_state_account: "HvKMuSY1MWjbu9w8LRoEdaPcuEyYoQ92YP51QdsPEtNq".to_string(),
-                token0_mint: "5ouhhEqV1L9gj3qTg3nQhkYuAuw72suktwJ4PvGo32SP".to_string(),
-                token1_mint: "C3xXmrQWWnTmYABa8YTKrYU5jkonkTwz1qQCJbVX3mQh".to_string(),
-                token0_vault: "G8RqPzxyTooeMoHnG3JJGVMT42XTxPkmM7U5NJLUtnej".to_string(),
-                token1_vault: "8sLhiUaWXu95tzyekfzyvRohiWwu7G65MymJ52iotwLN".to_string(),
-                observer_state: "CxcLseCztNWp57K8NxjYSYuHhY6cdr7S2u6KH9pw9rLu".to_string(),
-            }),
-
-            "eth-usdt" => Ok(PoolAccounts {
-                authority_account: "3NTS4CmziURYZJ1JywCaCF4urzVbhL6kxNLbpuLzaaR7".to_string(),
-                amm_config_account: "EHR3a7vLxBREzXic1rp7tyPPen6wy8VzdnYfKKRDXJG9".to_string(),
-                pool_state_account: "8oECA26gMdnUUxsiWwhv2Q8ETvLxsJrmsFeJGmNcxAiT".to_string(),
-                token0_mint: "4SgGYkKAF4k3uAmkKaqMFnAuZkAhyzUuabRpHhssyW9B".to_string(),
-                token1_mint: "C3xXmrQWWnTmYABa8YTKrYU5jkonkTwz1qQCJbVX3mQh".to_string(),
-                token0_vault: "CjUnf3ayioRwYERhTVvmaiHfCf1cJr6vStBqUHwEWDbV".to_string(),
-                token1_vault: "Egq2Fqimn8ee6XQ5T8jciKBNBWVL86wUq6JsaVxdfHXu".to_string(),
-                observer_state: "YphK7jcwtsdSV1JotEV7yuuXByddZ1c7bbSgSy7XhZ2".to_string(),
-            }),
-
-            "sol-usdt" => Ok(PoolAccounts {
-                authority_account: "3NTS4CmziURYZJ1JywCaCF4urzVbhL6kxNLbpuLzaaR7".to_string(),
-                amm_config_account: "EHR3a7vLxBREzXic1rp7tyPPen6wy8VzdnYfKKRDXJG9".to_string(),
-                pool_state_account: "2hvtkbyM8ug4Mr64qvNrXYU7BLxtSU634kiSrs2AkgpE".to_string(),
-                token0_mint: "CPozhCGVaGAcPVkxERsUYat4b7NKT9QeAR9KjNH4JpDG".to_string(),
-                token1_mint: "C3xXmrQWWnTmYABa8YTKrYU5jkonkTwz1qQCJbVX3mQh".to_string(),
-                token0_vault: "HbtH63d2RQ2hYZdWXqTiquntddPqKBZwFqZ43kFb7ytZ".to_string(),
-                token1_vault: "4ok3rQY8yJKDG125N81FHazTvHTrhhpA3h3n9xkDi3f7".to_string(),
-                observer_state: "5fQB3U5eDo9k5Y4cAqChDTxuxsdU4ttfRZ1K36zjbVjq".to_string(),
-            }),
-            name => Err(StdError::generic_err(format!(
-                "raydium pair not found: {}",
-                name
-            ))),
-        }
-    }
-
+    #[allow(clippy::too_many_arguments)]
     pub fn swap_base_input(
+        program_id: String,
         sender: String,
         amount_in: u64,
         min_amount_out: u64,
-        sender_svm_account: String,
+        sender_svm_account: &Pubkey,
         authority_account: String,
         amm_config_account: String,
         pool_state_account: String,
@@ -85,188 +56,176 @@ pub mod raydium {
         output_token_account: String,
         input_vault: String,
         output_vault: String,
-        input_token_mint: String,
-        output_token_mint: String,
+        input_token_mint: &Pubkey,
+        output_token_mint: &Pubkey,
         observer_state: String,
-    ) -> MsgTransaction {
-        let accounts = vec![
-            sender_svm_account,
-            authority_account,
-            amm_config_account,
-            pool_state_account,
-            input_token_account,
-            output_token_account,
-            input_vault,
-            output_vault,
-            SPL_TOKEN_2022.to_string(),
-            input_token_mint,
-            output_token_mint,
-            observer_state,
-            CPMM_PROGRAM_ID.to_string(),
-            SYSTEM_PROGRAM_ID.to_string(),
-            ASSOCIATED_TOKEN_PROGRAM_ID.to_string(),
-        ];
+    ) -> NexusResult<MsgTransaction> {
+        let token_program = Pubkey::from_string(SPL_TOKEN_2022_PROGRAM_ID)?;
 
         // This instruction is idempotent, cost less fee when account exists
-        let create_output_ata_ix = Instruction {
-            program_index: vec![14],
-            accounts: vec![
-                InstructionAccount {
-                    id_index: 0,
-                    caller_index: 0,
-                    callee_index: 0,
-                    is_signer: true,
-                    is_writable: true,
-                },
-                InstructionAccount {
-                    id_index: 5,
-                    caller_index: 5,
-                    callee_index: 1,
-                    is_signer: false,
-                    is_writable: true,
-                },
-                InstructionAccount {
-                    id_index: 0,
-                    caller_index: 0,
-                    callee_index: 0,
-                    is_signer: false,
-                    is_writable: false,
-                },
-                InstructionAccount {
-                    id_index: 10,
-                    caller_index: 10,
-                    callee_index: 3,
-                    is_signer: false,
-                    is_writable: false,
-                },
-                InstructionAccount {
-                    id_index: 13,
-                    caller_index: 13,
-                    callee_index: 4,
-                    is_signer: false,
-                    is_writable: false,
-                },
-                InstructionAccount {
-                    id_index: 8,
-                    caller_index: 8,
-                    callee_index: 5,
-                    is_signer: false,
-                    is_writable: false,
-                },
-            ],
-            data: Binary::new(vec![1]), // 1 = CreateIdempotent instruction
-        };
+        let create_output_ata_ix = associated_token::create_idempotent(
+            sender_svm_account,
+            sender_svm_account,
+            output_token_mint,
+            &token_program,
+        )?;
 
         let mut data_bz: Vec<u8> = vec![143, 190, 90, 218, 196, 30, 51, 222]; // swap_base_input ix signature
         data_bz.extend(amount_in.to_le_bytes());
         data_bz.extend(min_amount_out.to_le_bytes());
 
-        let swap_ix = Instruction {
-            program_index: vec![12],
-            accounts: vec![
-                InstructionAccount {
-                    id_index: 0,
-                    caller_index: 0,
-                    callee_index: 0,
-                    is_signer: true,
-                    is_writable: true,
-                },
-                InstructionAccount {
-                    id_index: 1,
-                    caller_index: 1,
-                    callee_index: 1,
-                    is_signer: false,
-                    is_writable: false,
-                },
-                InstructionAccount {
-                    id_index: 2,
-                    caller_index: 2,
-                    callee_index: 2,
-                    is_signer: false,
-                    is_writable: false,
-                },
-                InstructionAccount {
-                    id_index: 3,
-                    caller_index: 3,
-                    callee_index: 3,
-                    is_signer: false,
-                    is_writable: true,
-                },
-                InstructionAccount {
-                    id_index: 4,
-                    caller_index: 4,
-                    callee_index: 4,
-                    is_signer: false,
-                    is_writable: true,
-                },
-                InstructionAccount {
-                    id_index: 5,
-                    caller_index: 5,
-                    callee_index: 5,
-                    is_signer: false,
-                    is_writable: true,
-                },
-                InstructionAccount {
-                    id_index: 6,
-                    caller_index: 6,
-                    callee_index: 6,
-                    is_signer: false,
-                    is_writable: true,
-                },
-                InstructionAccount {
-                    id_index: 7,
-                    caller_index: 7,
-                    callee_index: 7,
-                    is_signer: false,
-                    is_writable: true,
-                },
-                InstructionAccount {
-                    id_index: 8,
-                    caller_index: 8,
-                    callee_index: 8,
-                    is_signer: false,
-                    is_writable: false,
-                },
-                InstructionAccount {
-                    id_index: 8,
-                    caller_index: 8,
-                    callee_index: 8,
-                    is_signer: false,
-                    is_writable: false,
-                },
-                InstructionAccount {
-                    id_index: 9,
-                    caller_index: 9,
-                    callee_index: 10,
-                    is_signer: false,
-                    is_writable: false,
-                },
-                InstructionAccount {
-                    id_index: 10,
-                    caller_index: 10,
-                    callee_index: 11,
-                    is_signer: false,
-                    is_writable: false,
-                },
-                InstructionAccount {
-                    id_index: 11,
-                    caller_index: 11,
-                    callee_index: 12,
-                    is_signer: false,
-                    is_writable: true,
-                },
+        let swap_ix = InstructionMeta {
+            program_id,
+            account_meta: vec![
+                InstructionAccountMeta::new(sender_svm_account, true, true),
+                InstructionAccountMeta::new(authority_account, false, false),
+                InstructionAccountMeta::new(amm_config_account, false, false),
+                InstructionAccountMeta::new(pool_state_account, false, true),
+                InstructionAccountMeta::new(input_token_account, false, true),
+                InstructionAccountMeta::new(output_token_account, false, true),
+                InstructionAccountMeta::new(input_vault, false, true),
+                InstructionAccountMeta::new(output_vault, false, true),
+                // input and output token programs
+                InstructionAccountMeta::new(token_program, false, false),
+                InstructionAccountMeta::new(token_program, false, false),
+                InstructionAccountMeta::new(input_token_mint, false, false),
+                InstructionAccountMeta::new(output_token_mint, false, false),
+                InstructionAccountMeta::new(observer_state, false, true),
             ],
             data: Binary::from(data_bz),
         };
 
-        MsgTransaction {
-            signers: vec![sender],
-            accounts,
-            instructions: vec![create_output_ata_ix, swap_ix],
-            compute_budget: 10_000_000,
+        Ok(TransactionBuilder::new()
+            .add_instructions(vec![create_output_ata_ix, swap_ix])
+            .build(vec![sender], 10_000_000))
+    }
+
+    /// Token accounts of the sender for a deposit or withdrawal, pool tokens are token 2022
+    /// and the lp mint is a legacy spl token
+    pub struct LiquidityAccounts {
+        pub sender: Pubkey,
+        pub token_0: Pubkey,
+        pub token_1: Pubkey,
+        pub lp_token: Pubkey,
+        pub lp_mint: Pubkey,
+    }
+
+    impl LiquidityAccounts {
+        pub fn new(sender: Pubkey, accounts: &PoolAccounts, lp_mint: &str) -> NexusResult<Self> {
+            let token_program = Pubkey::from_string(SPL_TOKEN_2022_PROGRAM_ID)?;
+            let lp_mint = Pubkey::from_string(lp_mint)?;
+            Ok(LiquidityAccounts {
+                token_0: get_associated_token_address(
+                    &sender,
+                    &Pubkey::from_string(&accounts.token0_mint)?,
+                    &token_program,
+                )?,
+                token_1: get_associated_token_address(
+                    &sender,
+                    &Pubkey::from_string(&accounts.token1_mint)?,
+                    &token_program,
+                )?,
+                lp_token: get_associated_token_address(
+                    &sender,
+                    &lp_mint,
+                    &Pubkey::from_string(SPL_TOKEN_PROGRAM_ID)?,
+                )?,
+                sender,
+                lp_mint,
+            })
+        }
+
+        // accounts of the deposit and withdraw instructions, in order
+        fn metas(&self, accounts: &PoolAccounts) -> NexusResult<Vec<InstructionAccountMeta>> {
+            Ok(vec![
+                InstructionAccountMeta::new(self.sender, true, true),
+                InstructionAccountMeta::new(&accounts.authority_account, false, false),
+                InstructionAccountMeta::new(&accounts.pool_state_account, false, true),
+                InstructionAccountMeta::new(self.lp_token, false, true),
+                InstructionAccountMeta::new(self.token_0, false, true),
+                InstructionAccountMeta::new(self.token_1, false, true),
+                InstructionAccountMeta::new(&accounts.token0_vault, false, true),
+                InstructionAccountMeta::new(&accounts.token1_vault, false, true),
+                InstructionAccountMeta::new(SPL_TOKEN_PROGRAM_ID, false, false),
+                InstructionAccountMeta::new(SPL_TOKEN_2022_PROGRAM_ID, false, false),
+                InstructionAccountMeta::new(&accounts.token0_mint, false, false),
+                InstructionAccountMeta::new(&accounts.token1_mint, false, false),
+                InstructionAccountMeta::new(self.lp_mint, false, true),
+            ])
         }
     }
 
+    /// Deposits at most `maximum_token_amounts` for `lp_token_amount` lp tokens
+    pub fn deposit_ix(
+        program_id: String,
+        sender: String,
+        lp_token_amount: u64,
+        maximum_token_amounts: [u64; 2],
+        accounts: &PoolAccounts,
+        owner: &LiquidityAccounts,
+    ) -> NexusResult<MsgTransaction> {
+        // This instruction is idempotent, cost less fee when account exists
+        let create_lp_ata_ix = associated_token::create_idempotent(
+            &owner.sender,
+            &owner.sender,
+            &owner.lp_mint,
+            &Pubkey::from_string(SPL_TOKEN_PROGRAM_ID)?,
+        )?;
+
+        let mut data_bz: Vec<u8> = vec![242, 35, 198, 137, 82, 225, 242, 182]; // deposit ix signature
+        data_bz.extend(lp_token_amount.to_le_bytes());
+        data_bz.extend(maximum_token_amounts[0].to_le_bytes());
+        data_bz.extend(maximum_token_amounts[1].to_le_bytes());
+
+        let deposit_ix = InstructionMeta {
+            program_id,
+            account_meta: owner.metas(accounts)?,
+            data: Binary::from(data_bz),
+        };
+
+        Ok(TransactionBuilder::new()
+            .add_instructions(vec![create_lp_ata_ix, deposit_ix])
+            .build(vec![sender], 10_000_000))
+    }
+
+    /// Burns `lp_token_amount` lp tokens for at least `minimum_token_amounts`
+    pub fn withdraw_ix(
+        program_id: String,
+        sender: String,
+        lp_token_amount: u64,
+        minimum_token_amounts: [u64; 2],
+        accounts: &PoolAccounts,
+        owner: &LiquidityAccounts,
+    ) -> NexusResult<MsgTransaction> {
+        let token_program = Pubkey::from_string(SPL_TOKEN_2022_PROGRAM_ID)?;
+        let mut ixs = vec![];
+        for mint in [&accounts.token0_mint, &accounts.token1_mint] {
+            ixs.push(associated_token::create_idempotent(
+                &owner.sender,
+                &owner.sender,
+                &Pubkey::from_string(mint)?,
+                &token_program,
+            )?);
+        }
+
+        let mut data_bz: Vec<u8> = vec![183, 18, 70, 156, 148, 109, 161, 34]; // withdraw ix signature
+        data_bz.extend(lp_token_amount.to_le_bytes());
+        data_bz.extend(minimum_token_amounts[0].to_le_bytes());
+        data_bz.extend(minimum_token_amounts[1].to_le_bytes());
+
+        let mut account_meta = owner.metas(accounts)?;
+        account_meta.push(InstructionAccountMeta::new(MEMO_PROGRAM_ID, false, false));
+        ixs.push(InstructionMeta {
+            program_id,
+            account_meta,
+            data: Binary::from(data_bz),
+        });
+
+        Ok(TransactionBuilder::new()
+            .add_instructions(ixs)
+            .build(vec![sender], 10_000_000))
+    }
+
     pub fn keccak256(input: &[u8]) -> [u8; 32] {
         let mut hash = Keccak::v256();
         hash.update(input);
@@ -279,62 +238,217 @@ pub mod raydium {
     #[derive(Default)]
     pub struct RaydiumPool {
         pub dex_name: String,
-        pub denom_plane: String,
+        pub denom_plane: Plane,
         pub a: Int256,
         pub b: Int256,
         pub fee_rate: Int256,
         pub denom_a: String,
         pub denom_b: String,
+        // a and b amounts are scaled by these from svm to cosmos decimals
+        pub decimal_multiplier_a: Int256,
+        pub decimal_multiplier_b: Int256,
+        pub lp_mint: String,
+        pub lp_supply: Int256,
+        /// see `PoolStatusBit`
+        pub status: u8,
+        // swaps are accepted strictly after open_time, both are unix timestamps
+        pub open_time: u64,
+        pub block_time: u64,
+        // price of b in a in cosmos decimals over the last `TWAP_WINDOW` seconds
+        pub twap: Option<Decimal256>,
     }
 
     impl RaydiumPool {
-        pub fn new(pair: &str) -> Result<RaydiumPool, StdError> {
-            // Fetch pool accounts using the provided pair name
-            let pool_accounts = get_pool_accounts_by_name(&pair.to_string())?;
+        pub fn new(network: &Network, pair: &str) -> NexusResult<RaydiumPool> {
+            network.raydium_pool(pair)?;
+            // a is the quote denom, as for the other pools
+            let (denom_b, denom_a) = pair_denoms(pair)?;
 
             // Create and return the RaydiumPool struct with amounts set to zero and denominations extracted from pair
             Ok(RaydiumPool {
                 dex_name: RAYDIUM.to_string(),
-                denom_plane: "SVM".to_string(),
+                denom_plane: Plane::Svm,
                 a: Int256::zero(),
                 b: Int256::zero(),
                 fee_rate: Int256::from(1000i128),
-                denom_a: pool_accounts.token0_mint,
-                denom_b: pool_accounts.token1_mint,
+                decimal_multiplier_a: network.decimal_multiplier(denom_a, Plane::Svm)?,
+                decimal_multiplier_b: network.decimal_multiplier(denom_b, Plane::Svm)?,
+                denom_a: denom_a.to_string(),
+                denom_b: denom_b.to_string(),
+                lp_mint: String::new(),
+                lp_supply: Int256::zero(),
+                status: 0,
+                open_time: 0,
+                block_time: 0,
+                twap: None,
             })
         }
 
-        pub fn from_fis(input: &FISInput) -> Result<Self, StdError> {
-            let token_0_vault_account = Account::from_json_bytes(
-                input
-                    .data
-                    .first()
-                    .ok_or(StdError::generic_err("expected account 0"))?,
-            )?;
-            let token_1_vault_account = Account::from_json_bytes(
-                input
-                    .data
-                    .get(1)
-                    .ok_or(StdError::generic_err("expected account 1"))?,
-            )?;
-            let pool_state = Account::from_json_bytes(
-                input
-                    .data
-                    .get(2)
-                    .ok_or(StdError::generic_err("expected account 2"))?,
-            )?;
+        pub fn can_swap(&self) -> bool {
+            PoolStatusBit::Swap.is_enabled(self.status) && self.block_time > self.open_time
+        }
+
+        fn ensure_enabled(&self, bit: PoolStatusBit) -> NexusResult<()> {
+            let pair = format!("{}-{}", self.denom_b, self.denom_a);
+            if !bit.is_enabled(self.status) {
+                return Err(NexusError::invalid_input(format!(
+                    "{:?} is disabled on raydium pool {}",
+                    bit, pair
+                )));
+            }
+            if matches!(bit, PoolStatusBit::Swap) && self.block_time <= self.open_time {
+                return Err(NexusError::invalid_input(format!(
+                    "raydium pool {} opens at {}",
+                    pair, self.open_time
+                )));
+            }
+            Ok(())
+        }
 
-            let mut token_0_info = TokenAccount::unpack(token_0_vault_account.data.as_slice())?;
-            let mut token_1_info = TokenAccount::unpack(token_1_vault_account.data.as_slice())?;
+        // the deposit or withdrawal amounts, in cosmos decimals, as svm amounts of token 0 and 1
+        fn token_amounts(
+            &self,
+            network: &Network,
+            accounts: &PoolAccounts,
+            amount_a: Int256,
+            amount_b: Int256,
+        ) -> NexusResult<[u64; 2]> {
+            let svm_amount = |alias: &str, amount: Int256| -> NexusResult<u64> {
+                let amount = network.denoms.convert(
+                    alias,
+                    Plane::Cosmos,
+                    Plane::Svm,
+                    Uint128::new(to_u128(amount)?),
+                )?;
+                Ok(Uint64::try_from(amount)?.u64())
+            };
+            let a = svm_amount(&self.denom_a, amount_a)?;
+            let b = svm_amount(&self.denom_b, amount_b)?;
+            if network.denoms.address(&self.denom_a, Plane::Svm)? == accounts.token0_mint {
+                Ok([a, b])
+            } else {
+                Ok([b, a])
+            }
+        }
+
+        /// Queries the `[token 0 vault, token 1 vault, pool state, amm config, observation
+        /// state]` accounts read by `from_fis`
+        pub fn query(network: &Network, pair: &str) -> NexusResult<QueryInstruction> {
+            let pool = network.raydium_pool(pair)?;
+            let accounts = [
+                &pool.token0_vault,
+                &pool.token1_vault,
+                &pool.pool_state_account,
+                &pool.amm_config_account,
+                &pool.observer_state,
+            ]
+            .iter()
+            .map(|account| Ok(Binary::from(Pubkey::from_string(account)?.0)))
+            .collect::<NexusResult<Vec<_>>>()?;
+            Ok(QueryInstruction::vm_query(
+                Plane::Svm,
+                Binary::default(),
+                accounts,
+            ))
+        }
+
+        /// Parses the `[token 0 vault, token 1 vault, pool state, amm config, observation state]`
+        /// accounts of `pair` at `fis_input[instruction]`, `block_time` decides whether the pool
+        /// is open
+        pub fn from_fis(
+            network: &Network,
+            pair: &str,
+            fis_input: &[FISInput],
+            instruction: usize,
+            block_time: u64,
+        ) -> NexusResult<Self> {
+            let pool = Self::new(network, pair)?;
+            let accounts = network.raydium_pool(pair)?;
+            let token_0_vault_account =
+                Slot::<Account>::new(instruction, 0, "raydium token 0 vault").load(fis_input)?;
+            let token_1_vault_account =
+                Slot::<Account>::new(instruction, 1, "raydium token 1 vault").load(fis_input)?;
+            let pool_state =
+                Slot::<Account>::new(instruction, 2, "raydium pool state").load(fis_input)?;
+            let amm_config_slot = Slot::<Account>::new(instruction, 3, "raydium amm config");
+            let amm_config = amm_config_slot.load(fis_input)?;
+            let observation_slot =
+                Slot::<Account>::new(instruction, 4, "raydium observation state");
+            let observation = observation_slot.load(fis_input)?;
+
+            let token_0_info = TokenAccount::unpack(token_0_vault_account.data.as_slice())?;
+            let token_1_info = TokenAccount::unpack(token_1_vault_account.data.as_slice())?;
             let mut pool_state_info = PoolState::unpack(pool_state.data.as_slice())?;
+            let amm_config_info = AmmConfig::unpack(amm_config.data.as_slice())?;
+            let observation_info = ObservationState::unpack(observation.data.as_slice())?;
+            if pool_state_info.amm_config.to_string() != accounts.amm_config_account {
+                return Err(NexusError::InvalidFisInput {
+                    slot: amm_config_slot.to_string(),
+                    reason: format!("not the amm config of pool {}", pair),
+                });
+            }
+            if observation_info.pool_id.to_string() != accounts.pool_state_account {
+                return Err(NexusError::InvalidFisInput {
+                    slot: observation_slot.to_string(),
+                    reason: format!("not the observation state of pool {}", pair),
+                });
+            }
 
-            // let (mut protocol_fees_token_0, mut protocol_fees_token_1, mut fund_fees_token_0, mut fund_fees_token_1)
-            // TODO: more constraint as validate basic
             let (mut a, mut b) = (token_0_info.amount, token_1_info.amount);
-            // we always swap from usdt so let it be the first
-            if token_0_info.mint.to_string() != get_denom("usdt") {
+            let mint_a = network.denoms.address(&pool.denom_a, Plane::Svm)?;
+            let mint_b = network.denoms.address(&pool.denom_b, Plane::Svm)?;
+            let mints = (token_0_info.mint.to_string(), token_1_info.mint.to_string());
+            if mints != (mint_a.to_string(), mint_b.to_string())
+                && mints != (mint_b.to_string(), mint_a.to_string())
+            {
+                return Err(NexusError::InvalidFisInput {
+                    slot: Slot::<Account>::new(instruction, 0, "raydium token 0 vault").to_string(),
+                    reason: format!("vault mints don't match pair {}", pair),
+                });
+            }
+            // the multipliers assume the decimals of the registry
+            for (mint, decimals) in [
+                (&mints.0, pool_state_info.mint_0_decimals),
+                (&mints.1, pool_state_info.mint_1_decimals),
+            ] {
+                let alias = if mint == mint_a {
+                    &pool.denom_a
+                } else {
+                    &pool.denom_b
+                };
+                let expected = network.denoms.link(alias, Plane::Svm)?.decimals;
+                if decimals != expected {
+                    return Err(NexusError::InvalidFisInput {
+                        slot: Slot::<Account>::new(instruction, 2, "raydium pool state")
+                            .to_string(),
+                        reason: format!(
+                            "{} has {} decimals, expected {}",
+                            mint, decimals, expected
+                        ),
+                    });
+                }
+            }
+            // token 0 is priced in token 1, b in a is the price of token 0 when b is token 0
+            let twap = observation_info
+                .twap_x32(TWAP_WINDOW)
+                .map(|prices| {
+                    let price = if mints.0 == mint_a {
+                        prices[1]
+                    } else {
+                        prices[0]
+                    };
+                    Decimal256::checked_from_ratio(
+                        Uint256::from(price).checked_mul(to_uint256(pool.decimal_multiplier_a))?,
+                        Uint256::from(1u128 << 32)
+                            .checked_mul(to_uint256(pool.decimal_multiplier_b))?,
+                    )
+                    .map_err(|e| NexusError::Math(e.to_string()))
+                })
+                .transpose()?;
+
+            // the quote denom is a
+            if mints.0 != mint_a {
                 (a, b) = (b, a);
-                (token_0_info, token_1_info) = (token_1_info, token_0_info);
                 (
                     pool_state_info.protocol_fees_token_0,
                     pool_state_info.protocol_fees_token_1,
@@ -351,24 +465,39 @@ pub mod raydium {
                 );
             }
 
-            a = a - (pool_state_info.protocol_fees_token_0 + pool_state_info.fund_fees_token_0);
-            b = b - (pool_state_info.protocol_fees_token_1 + pool_state_info.fund_fees_token_1);
-
-            // hack: ETH, it's 18 decimals on cosmos/wasm/evm => need to convert the eth amount from 9 decimals to 18 decimals
-            // hardcode for now, should use denom link later on
-            let mut decimal_multiplier = Int256::one();
-            if &token_1_info.mint.to_string() == &get_denom("eth") {
-                decimal_multiplier = Int256::from_i128(1_000_000_000i128);
-            }
+            // the vaults also hold the accrued fees, which aren't tradable
+            let less_fees = |vault: u64, protocol_fees: u64, fund_fees: u64| {
+                protocol_fees
+                    .checked_add(fund_fees)
+                    .and_then(|fees| vault.checked_sub(fees))
+                    .ok_or_else(|| NexusError::InvalidFisInput {
+                        slot: Slot::<Account>::new(instruction, 2, "raydium pool state")
+                            .to_string(),
+                        reason: format!("fees exceed the vault amount {}", vault),
+                    })
+            };
+            a = less_fees(
+                a,
+                pool_state_info.protocol_fees_token_0,
+                pool_state_info.fund_fees_token_0,
+            )?;
+            b = less_fees(
+                b,
+                pool_state_info.protocol_fees_token_1,
+                pool_state_info.fund_fees_token_1,
+            )?;
 
             Ok(Self {
-                dex_name: RAYDIUM.to_string(),
-                denom_plane: "SVM".to_string(),
-                a: Int256::from_i128(a as i128),
-                b: Int256::from_i128(b as i128) * decimal_multiplier,
-                fee_rate: Int256::from(1000i128),
-                denom_a: token_0_info.mint.to_string(),
-                denom_b: token_1_info.mint.to_string(),
+                a: Int256::from_i128(a as i128) * pool.decimal_multiplier_a,
+                b: Int256::from_i128(b as i128) * pool.decimal_multiplier_b,
+                lp_mint: pool_state_info.lp_mint.to_string(),
+                lp_supply: Int256::from(pool_state_info.lp_supply),
+                fee_rate: Int256::from(amm_config_info.trade_fee_rate),
+                status: pool_state_info.status,
+                open_time: pool_state_info.open_time,
+                block_time,
+                twap,
+                ..pool
             })
         }
     }
@@ -378,8 +507,8 @@ pub mod raydium {
             self.dex_name.clone()
         }
 
-        fn denom_plane(&self) -> String {
-            self.denom_plane.clone()
+        fn denom_plane(&self) -> Plane {
+            self.denom_plane
         }
 
         fn a(&self) -> Int256 {
@@ -390,46 +519,79 @@ pub mod raydium {
             self.b
         }
 
+        fn denom_a(&self) -> String {
+            self.denom_a.clone()
+        }
+
+        fn denom_b(&self) -> String {
+            self.denom_b.clone()
+        }
+
+        fn fee_rate(&self) -> Int256 {
+            self.fee_rate
+        }
+
+        fn twap(&self) -> Option<Decimal256> {
+            self.twap
+        }
+
         fn swap_output(&self, x: Int256, a_for_b: bool) -> (String, Int256) {
             let bps = Int256::from_i128(BPS);
-            let mut x = x * (bps - self.fee_rate) / bps;
-            let a = self.a;
-            let b = self.b;
-
-            if a_for_b {
-                let denom = self.denom_b.clone();
-                let mut output_amount = (b * x) / (a + x);
-
-                // rounding down for ETH so that it could be transferred out
-                if &self.denom_b == &get_denom("eth") {
-                    let decimal_diff = Int256::from_i128(ETH_DECIMAL_DIFF as i128);
-                    output_amount = (output_amount / decimal_diff) * decimal_diff;
-                }
-                (denom, output_amount)
+            let (multiplier_a, multiplier_b) =
+                (self.decimal_multiplier_a, self.decimal_multiplier_b);
+            let (denom, reserve_in, reserve_out, multiplier_in, multiplier_out) = if a_for_b {
+                (
+                    self.denom_b.clone(),
+                    self.a,
+                    self.b,
+                    multiplier_a,
+                    multiplier_b,
+                )
             } else {
-                // rounding down for ETH so that it match the transferred in amount
-                if &self.denom_b == &get_denom("eth") {
-                    let decimal_diff = Int256::from_i128(ETH_DECIMAL_DIFF as i128);
-                    x = (x / decimal_diff) * decimal_diff;
-                }
+                (
+                    self.denom_a.clone(),
+                    self.b,
+                    self.a,
+                    multiplier_b,
+                    multiplier_a,
+                )
+            };
 
-                (self.denom_a.clone(), (a * x) / (b + x))
+            if !self.can_swap() {
+                return (denom, Int256::zero());
             }
+
+            // as swap_base_input, in svm units: the trade fee is rounded up and the output
+            // down, so the amounts match what is transferred in and out
+            let x = x / multiplier_in;
+            let trade_fee = (x * self.fee_rate + bps - Int256::one()) / bps;
+            let x = x - trade_fee;
+            let (reserve_in, reserve_out) =
+                (reserve_in / multiplier_in, reserve_out / multiplier_out);
+            let output_amount = (reserve_out * x) / (reserve_in + x);
+            (denom, output_amount * multiplier_out)
         }
 
-        fn compose_swap_fis(&self, swap: &Swap) -> Result<Vec<FISInstruction>, StdError> {
-            let accounts = get_pool_accounts_by_name(&swap.pool_name)?;
+        fn compose_swap_fis(
+            &self,
+            network: &Network,
+            swap: &Swap,
+        ) -> NexusResult<Vec<FISInstruction>> {
+            self.ensure_enabled(PoolStatusBit::Swap)?;
+            let accounts = network.raydium_pool(&swap.pool_name)?.clone();
             let sender_svm_account = Pubkey::from_string(&swap.sender_svm)
-                .map_err(|e| StdError::generic_err(format!("parse svm address err: {}", e)))?;
-            let input_denom = get_denom(&swap.denom);
-            let mut amount = swap.amount;
-            if input_denom == get_denom("eth") {
-                amount = amount / Int128::from(ETH_DECIMAL_DIFF as i128)
-            }
+                .map_err(|e| NexusError::invalid_input(format!("parse svm address err: {}", e)))?;
+            let input_denom = network.denoms.address(&swap.denom, Plane::Svm)?.to_string();
+            let amount = network.denoms.convert(
+                &swap.denom,
+                Plane::Cosmos,
+                Plane::Svm,
+                Uint128::try_from(swap.amount)?,
+            )?;
 
             let (mut input_vault, mut output_vault) =
                 (accounts.token0_vault, accounts.token1_vault);
-            if &input_denom == &accounts.token1_mint {
+            if input_denom == accounts.token1_mint {
                 (input_vault, output_vault) = (output_vault, input_vault);
             }
 
@@ -441,26 +603,30 @@ pub mod raydium {
 
             let input_denom_pk = Pubkey::from_string(&input_denom)?;
             let output_denom_pk = Pubkey::from_string(&output_denom)?;
-            let token_program = Pubkey::from_string(&SPL_TOKEN_2022.to_string())?;
-            let ata_program = Pubkey::from_string(&ASSOCIATED_TOKEN_PROGRAM_ID.to_string())?;
-
-            let (input_token_account, _) = Pubkey::find_program_address(
-                &[&sender_svm_account.0, &token_program.0, &input_denom_pk.0],
-                &ata_program,
-            )
-            .unwrap();
-            let (output_token_account, _) = Pubkey::find_program_address(
-                &[&sender_svm_account.0, &token_program.0, &output_denom_pk.0],
-                &ata_program,
-            )
-            .unwrap();
+            let token_program = Pubkey::from_string(SPL_TOKEN_2022_PROGRAM_ID)?;
+
+            let input_token_account =
+                get_associated_token_address(&sender_svm_account, &input_denom_pk, &token_program)?;
+            let output_token_account = get_associated_token_address(
+                &sender_svm_account,
+                &output_denom_pk,
+                &token_program,
+            )?;
+
+            let (output_alias, min_output) = swap.min_output(self)?;
+            let min_amount_out = network.denoms.convert(
+                &output_alias,
+                Plane::Cosmos,
+                Plane::Svm,
+                Uint128::new(to_u128(min_output)?),
+            )?;
 
             let msg = swap_base_input(
+                network.raydium.cpmm_program_id.clone(),
                 swap.sender.clone(),
-                amount.i128() as u64,
-                0,
-                sender_svm_account.to_string(),
-                // sender_svm_account.to_string(),
+                Uint64::try_from(amount)?.u64(),
+                Uint64::try_from(min_amount_out)?.u64(),
+                &sender_svm_account,
                 accounts.authority_account,
                 accounts.amm_config_account,
                 accounts.pool_state_account,
@@ -468,242 +634,900 @@ pub mod raydium {
                 output_token_account.to_string(),
                 input_vault,
                 output_vault,
-                input_denom,
-                output_denom,
+                &input_denom_pk,
+                &output_denom_pk,
                 accounts.observer_state,
-            );
-            Ok(vec![FISInstruction {
-                plane: "SVM".to_string(),
-                action: "VM_INVOKE".to_string(),
-                address: "".to_string(),
-                msg: to_json_vec(&msg)?,
-            }])
+            )?;
+            Ok(vec![FISInstruction::vm_invoke(
+                Plane::Svm,
+                to_json_vec(&msg)?,
+            )])
         }
-    }
-}
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct MsgTransaction {
-    /// Sender is the address of the actor that signed the message
-    pub signers: Vec<String>,
-    /// Accounts are the cosmos addresses that sign this message
-    pub accounts: Vec<String>,
-    /// Instructions are the instructions for the transaction
-    pub instructions: Vec<Instruction>,
-    /// ComputeBudget is the budget for computation
-    pub compute_budget: u64,
-}
+        fn lp_supply(&self) -> Int256 {
+            self.lp_supply
+        }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Instruction {
-    /// ProgramIndex is a list of program indices
-    pub program_index: Vec<u32>,
-    /// Accounts are the accounts involved in the instruction
-    pub accounts: Vec<InstructionAccount>,
-    /// Data is the data for the instruction
-    pub data: Binary,
-}
+        fn compose_add_liquidity_fis(
+            &self,
+            network: &Network,
+            deposit: &Deposit,
+        ) -> NexusResult<Vec<FISInstruction>> {
+            self.ensure_enabled(PoolStatusBit::Deposit)?;
+            let accounts = network.raydium_pool(&deposit.pool_name)?;
+            let sender_svm_account = Pubkey::from_string(&deposit.sender_svm)
+                .map_err(|e| NexusError::invalid_input(format!("parse svm address err: {}", e)))?;
+            let owner = LiquidityAccounts::new(sender_svm_account, accounts, &self.lp_mint)?;
+            // fewer lp tokens than the estimate so the amounts cover a moved price
+            let estimate = deposit.estimate(self)?;
+            let lp_amount = less_slippage(estimate.lp_amount, deposit.slippage_bps)?;
+            let maximum_token_amounts =
+                self.token_amounts(network, accounts, estimate.amount_a, estimate.amount_b)?;
+
+            let msg = deposit_ix(
+                network.raydium.cpmm_program_id.clone(),
+                deposit.sender.clone(),
+                Uint64::try_from(Uint128::new(to_u128(lp_amount)?))?.u64(),
+                maximum_token_amounts,
+                accounts,
+                &owner,
+            )?;
+            Ok(vec![FISInstruction::vm_invoke(
+                Plane::Svm,
+                to_json_vec(&msg)?,
+            )])
+        }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Account {
-    pub pubkey: Binary,
-    pub owner: Binary,
-    pub lamports: Uint64, // JSON cdc returns string (with quotes), standard u64 can't be parsed
-    pub data: Binary,
-    pub executable: bool,
-    pub rent_epoch: Uint64,
-}
+        fn compose_remove_liquidity_fis(
+            &self,
+            network: &Network,
+            withdrawal: &Withdrawal,
+        ) -> NexusResult<Vec<FISInstruction>> {
+            self.ensure_enabled(PoolStatusBit::Withdraw)?;
+            let accounts = network.raydium_pool(&withdrawal.pool_name)?;
+            let sender_svm_account = Pubkey::from_string(&withdrawal.sender_svm)
+                .map_err(|e| NexusError::invalid_input(format!("parse svm address err: {}", e)))?;
+            let owner = LiquidityAccounts::new(sender_svm_account, accounts, &self.lp_mint)?;
+            let estimate = withdrawal.estimate(self)?;
+            let minimum_token_amounts = self.token_amounts(
+                network,
+                accounts,
+                less_slippage(estimate.amount_a, withdrawal.slippage_bps)?,
+                less_slippage(estimate.amount_b, withdrawal.slippage_bps)?,
+            )?;
 
-impl Account {
-    pub fn from_json_bytes(bz: &[u8]) -> Result<Self, StdError> {
-        from_json(bz)
+            let msg = withdraw_ix(
+                network.raydium.cpmm_program_id.clone(),
+                withdrawal.sender.clone(),
+                Uint64::try_from(Uint128::new(to_u128(estimate.lp_amount)?))?.u64(),
+                minimum_token_amounts,
+                accounts,
+                &owner,
+            )?;
+            Ok(vec![FISInstruction::vm_invoke(
+                Plane::Svm,
+                to_json_vec(&msg)?,
+            )])
+        }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct InstructionAccount {
-    pub id_index: u32,
-    pub caller_index: u32,
-    pub callee_index: u32,
-    pub is_signer: bool,
-    pub is_writable: bool,
-}
+pub mod whirlpool {
+    use super::{TickArray, Whirlpool, TICK_ARRAY_SIZE};
+    use crate::{
+        astromesh::{to_int256, to_u128, to_uint256, Deposit, Pool, Swap, Withdrawal},
+        clmm::{self, ConcentratedPool, SwapResult},
+        network::{pair_denoms, Network},
+        svm::raydium::MEMO_PROGRAM_ID,
+    };
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{to_json_vec, Binary, Int256, Uint128, Uint256, Uint64};
+    use nexus_sdk::{
+        astromesh::{FISInput, FISInstruction, Plane},
+        error::{NexusError, NexusResult},
+        input::Slot,
+        query::QueryInstruction,
+        svm::{
+            associated_token, get_associated_token_address, Account, InstructionAccountMeta,
+            InstructionMeta, Pubkey, TransactionBuilder, SPL_TOKEN_2022_PROGRAM_ID,
+        },
+    };
 
-const PDA_MARKER: &[u8; 21] = b"ProgramDerivedAddress";
+    pub const WHIRLPOOL: &str = "whirlpool";
+    // Q64.64 sqrt price bounds of the program, at ticks -443636 and 443636
+    pub const MIN_SQRT_PRICE_X64: u128 = 4295048016;
+    pub const MAX_SQRT_PRICE_X64: u128 = 79226673515401279992447579055;
+    const SWAP_V2_DISCRIMINATOR: [u8; 8] = [43, 4, 237, 11, 26, 201, 30, 98];
 
-pub struct Hash(pub(crate) [u8; 32]);
+    /// Whirlpool of a pair and the tick arrays queried along with it
+    #[cw_serde]
+    pub struct WhirlpoolAccounts {
+        pub whirlpool: String,
+        /// start tick indexes of the arrays around the traded price, swaps are priced within
+        /// the ones queried
+        pub tick_arrays: Vec<i32>,
+    }
 
-#[derive(Clone, Default)]
-pub struct Hasher {
-    hasher: Sha256,
-}
+    /// Q64.64 sqrt price as the Q64.96 one of `clmm`
+    pub fn sqrt_price_x96(sqrt_price_x64: u128) -> Uint256 {
+        Uint256::from(sqrt_price_x64) << 32
+    }
 
-impl Hasher {
-    pub fn hash(&mut self, val: &[u8]) {
-        self.hasher.update(val);
+    /// Start tick index of the tick array holding `tick`
+    pub fn tick_array_start(tick: i32, tick_spacing: i32) -> i32 {
+        let span = TICK_ARRAY_SIZE * tick_spacing;
+        tick.div_euclid(span) * span
     }
-    pub fn hashv(&mut self, vals: &[&[u8]]) {
-        for val in vals {
-            self.hash(val);
-        }
+
+    /// Start tick indexes of the 3 tick arrays a swap from `tick` crosses at most, b to a swaps
+    /// start from the next tick as the program does
+    pub fn swap_tick_arrays(tick: i32, tick_spacing: i32, a_to_b: bool) -> [i32; 3] {
+        let span = TICK_ARRAY_SIZE * tick_spacing;
+        let (start, step) = if a_to_b {
+            (tick_array_start(tick, tick_spacing), -span)
+        } else {
+            (tick_array_start(tick + tick_spacing, tick_spacing), span)
+        };
+        [start, start + step, start + 2 * step]
     }
-    pub fn result(self) -> Hash {
-        Hash(self.hasher.finalize().into())
+
+    pub fn tick_array_address(
+        program_id: &Pubkey,
+        whirlpool: &Pubkey,
+        start_tick_index: i32,
+    ) -> NexusResult<Pubkey> {
+        let start = start_tick_index.to_string();
+        program_address(&[b"tick_array", &whirlpool.0, start.as_bytes()], program_id)
     }
-}
 
-pub fn bytes_are_curve_point<T: AsRef<[u8]>>(_bytes: T) -> bool {
-    curve25519_dalek::edwards::CompressedEdwardsY::from_slice(_bytes.as_ref())
-        .unwrap()
-        .decompress()
-        .is_some()
-}
+    pub fn oracle_address(program_id: &Pubkey, whirlpool: &Pubkey) -> NexusResult<Pubkey> {
+        program_address(&[b"oracle", &whirlpool.0], program_id)
+    }
 
-#[derive(Debug)]
-pub struct Pubkey(pub [u8; 32]);
+    fn program_address(seeds: &[&[u8]], program_id: &Pubkey) -> NexusResult<Pubkey> {
+        Pubkey::find_program_address(seeds, program_id)
+            .map(|(address, _)| address)
+            .ok_or_else(|| NexusError::invalid_input("no program address for the seeds"))
+    }
 
-pub enum PubkeyError {
-    MaxSeedLengthExceeded,
-    InvalidSeeds,
-    IllegalOwner,
-}
+    fn mint(network: &Network, alias: &str) -> NexusResult<Pubkey> {
+        Ok(Pubkey::from_string(
+            network.denoms.address(alias, Plane::Svm)?,
+        )?)
+    }
 
-impl Pubkey {
-    pub fn to_string(&self) -> String {
-        bs58::encode(self.0).into_string()
+    #[derive(Debug)]
+    pub struct WhirlpoolPool {
+        pub dex_name: String,
+        pub denom_plane: Plane,
+        // virtual reserves of the in range liquidity
+        pub a: Int256,
+        pub b: Int256,
+        // in millionths
+        pub fee_rate: Int256,
+        pub denom_a: String,
+        pub denom_b: String,
+        // whether denom a is token a of the whirlpool, the one with the lower mint
+        pub a_is_token_a: bool,
+        // a and b amounts are scaled by these from svm to cosmos decimals
+        pub decimal_multiplier_a: Int256,
+        pub decimal_multiplier_b: Int256,
+        pub token_vault_a: String,
+        pub token_vault_b: String,
+        // pool state to simulate swaps with, unpriced until read with `from_fis`
+        pub state: Option<ConcentratedPool>,
+        // lowest and highest sqrt prices swaps reach on the queried tick arrays
+        pub sqrt_price_range: (Uint256, Uint256),
     }
 
-    pub fn from_slice(bz: &[u8]) -> Result<Self, StdError> {
-        if bz.len() != 32 {
-            return Err(StdError::generic_err(format!(
-                "pubkey must be 32 bytes: {}",
-                bz.len()
-            )));
+    impl WhirlpoolPool {
+        pub fn new(network: &Network, pair: &str) -> NexusResult<Self> {
+            network.whirlpool_pool(pair)?;
+            // a is the quote denom, as for the other pools
+            let (denom_b, denom_a) = pair_denoms(pair)?;
+            Ok(Self {
+                dex_name: WHIRLPOOL.to_string(),
+                denom_plane: Plane::Svm,
+                a: Int256::zero(),
+                b: Int256::zero(),
+                fee_rate: Int256::zero(),
+                denom_a: denom_a.to_string(),
+                denom_b: denom_b.to_string(),
+                a_is_token_a: mint(network, denom_a)? < mint(network, denom_b)?,
+                decimal_multiplier_a: network.decimal_multiplier(denom_a, Plane::Svm)?,
+                decimal_multiplier_b: network.decimal_multiplier(denom_b, Plane::Svm)?,
+                token_vault_a: String::new(),
+                token_vault_b: String::new(),
+                state: None,
+                sqrt_price_range: (Uint256::zero(), Uint256::zero()),
+            })
         }
 
-        let mut pubkey: [u8; 32] = [0; 32];
-        pubkey.copy_from_slice(bz);
-        Ok(Self(pubkey))
-    }
-
-    pub fn from_string(s: &String) -> Result<Self, StdError> {
-        let bz = bs58::decode(s.as_str())
-            .into_vec()
-            .map_err(|e| StdError::generic_err(e.to_string()))?;
-        Pubkey::from_slice(bz.as_slice())
-            .map_err(|e| StdError::generic_err(format!("pubkey from string: {}: {}", s, e)))
-    }
+        /// Queries the `[whirlpool, tick arrays...]` accounts read by `from_fis`
+        pub fn query(network: &Network, pair: &str) -> NexusResult<QueryInstruction> {
+            let accounts = network.whirlpool_pool(pair)?;
+            let program_id = Pubkey::from_string(&network.whirlpool.program_id)?;
+            let whirlpool = Pubkey::from_string(&accounts.whirlpool)?;
+            let mut keys = vec![Binary::from(whirlpool.0)];
+            for start in accounts.tick_arrays.iter() {
+                keys.push(Binary::from(
+                    tick_array_address(&program_id, &whirlpool, *start)?.0,
+                ));
+            }
+            Ok(QueryInstruction::vm_query(
+                Plane::Svm,
+                Binary::default(),
+                keys,
+            ))
+        }
 
-    pub fn find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> Option<(Pubkey, u8)> {
-        let mut bump_seed = [u8::MAX];
-        for _ in 0..u8::MAX {
+        /// Parses the `[whirlpool, tick arrays...]` accounts of `pair` at
+        /// `fis_input[instruction]`, tick arrays that don't exist yet are left out
+        pub fn from_fis(
+            network: &Network,
+            pair: &str,
+            fis_input: &[FISInput],
+            instruction: usize,
+        ) -> NexusResult<Self> {
+            let accounts = network.whirlpool_pool(pair)?;
+            let mut pool = Self::new(network, pair)?;
+            let whirlpool_slot = Slot::<Account>::new(instruction, 0, "whirlpool");
+            let whirlpool = Whirlpool::unpack(whirlpool_slot.load(fis_input)?.data.as_slice())?;
+            let (token_a, token_b) = if pool.a_is_token_a {
+                (&pool.denom_a, &pool.denom_b)
+            } else {
+                (&pool.denom_b, &pool.denom_a)
+            };
+            if (whirlpool.token_mint_a, whirlpool.token_mint_b)
+                != (mint(network, token_a)?, mint(network, token_b)?)
             {
-                let mut seeds_with_bump = seeds.to_vec();
-                seeds_with_bump.push(&bump_seed);
-                match Self::create_program_address(&seeds_with_bump, program_id) {
-                    Ok(address) => return Some((address, bump_seed[0])),
-                    Err(PubkeyError::InvalidSeeds) => (),
-                    _ => break,
+                return Err(NexusError::InvalidFisInput {
+                    slot: whirlpool_slot.to_string(),
+                    reason: format!("token mints don't match pair {}", pair),
+                });
+            }
+
+            let tick_spacing = i32::from(whirlpool.tick_spacing);
+            let mut ticks = vec![];
+            let mut starts = vec![];
+            for (i, start) in accounts.tick_arrays.iter().enumerate() {
+                let slot = Slot::<Account>::new(instruction, i + 1, "whirlpool tick array");
+                let Some(account) = slot.may_load(fis_input)? else {
+                    continue;
+                };
+                let array = TickArray::unpack(account.data.as_slice())?;
+                if array.whirlpool.to_string() != accounts.whirlpool
+                    || array.start_tick_index != *start
+                {
+                    return Err(NexusError::InvalidFisInput {
+                        slot: slot.to_string(),
+                        reason: format!("not the tick array {} of whirlpool {}", start, pair),
+                    });
                 }
+                ticks.extend(array.ticks(tick_spacing));
+                starts.push(*start);
             }
-            bump_seed[0] -= 1;
+            ticks.sort_by_key(|tick| tick.index);
+
+            let state = ConcentratedPool {
+                sqrt_price_x96: sqrt_price_x96(whirlpool.sqrt_price),
+                tick: whirlpool.tick_current_index,
+                liquidity: whirlpool.liquidity,
+                tick_spacing,
+                ticks,
+            };
+            // swaps stop within the queried arrays of the 3 the program is passed
+            let reach = |a_to_b: bool| -> NexusResult<Uint256> {
+                let mut limit = state.sqrt_price_x96;
+                for start in swap_tick_arrays(state.tick, tick_spacing, a_to_b) {
+                    if !starts.contains(&start) {
+                        break;
+                    }
+                    let tick = if a_to_b {
+                        start
+                    } else {
+                        start + (TICK_ARRAY_SIZE - 1) * tick_spacing
+                    };
+                    limit = clmm::sqrt_price_at_tick(tick)?;
+                }
+                Ok(limit.clamp(
+                    sqrt_price_x96(MIN_SQRT_PRICE_X64),
+                    sqrt_price_x96(MAX_SQRT_PRICE_X64),
+                ))
+            };
+            pool.sqrt_price_range = (
+                reach(true)?.min(state.sqrt_price_x96),
+                reach(false)?.max(state.sqrt_price_x96),
+            );
+
+            let (reserve_a, reserve_b) = state.virtual_reserves()?;
+            let (a, b) = if pool.a_is_token_a {
+                (reserve_a, reserve_b)
+            } else {
+                (reserve_b, reserve_a)
+            };
+            pool.a = to_int256(a).checked_mul(pool.decimal_multiplier_a)?;
+            pool.b = to_int256(b).checked_mul(pool.decimal_multiplier_b)?;
+            pool.fee_rate = Int256::from(whirlpool.fee_rate);
+            pool.token_vault_a = whirlpool.token_vault_a.to_string();
+            pool.token_vault_b = whirlpool.token_vault_b.to_string();
+            pool.state = Some(state);
+            Ok(pool)
         }
-        None
-    }
 
-    pub fn create_program_address(
-        seeds: &[&[u8]],
-        program_id: &Pubkey,
-    ) -> Result<Pubkey, PubkeyError> {
-        if seeds.len() > 255 {
-            return Err(PubkeyError::MaxSeedLengthExceeded);
+        /// Output of swapping `x`, zero for unpriced pools and swaps leaving the queried ticks
+        pub fn simulate_swap(&self, x: Int256, a_for_b: bool) -> NexusResult<Int256> {
+            let multiplier_out = if a_for_b {
+                self.decimal_multiplier_b
+            } else {
+                self.decimal_multiplier_a
+            };
+            match self.simulate(x, a_for_b)? {
+                Some(result) => Ok(to_int256(result.amount_out).checked_mul(multiplier_out)?),
+                None => Ok(Int256::zero()),
+            }
         }
 
-        for seed in seeds.iter() {
-            if seed.len() > 32 {
-                return Err(PubkeyError::MaxSeedLengthExceeded);
+        fn simulate(&self, x: Int256, a_for_b: bool) -> NexusResult<Option<SwapResult>> {
+            let Some(state) = &self.state else {
+                return Ok(None);
+            };
+            if x <= Int256::zero() {
+                return Ok(None);
             }
+            let a_to_b = a_for_b == self.a_is_token_a;
+            let multiplier_in = if a_for_b {
+                self.decimal_multiplier_a
+            } else {
+                self.decimal_multiplier_b
+            };
+            let amount_in = to_uint256(x.checked_div(multiplier_in)?);
+            let (lower, upper) = self.sqrt_price_range;
+            let fee = u32::try_from(to_u128(self.fee_rate)?)
+                .map_err(|_| NexusError::invalid_input(format!("fee rate {}", self.fee_rate)))?;
+            let result = state.swap_exact_in_to(
+                a_to_b,
+                amount_in,
+                fee,
+                if a_to_b { lower } else { upper },
+            )?;
+            // the program fails once it runs out of tick arrays
+            if result.amount_in < amount_in {
+                return Ok(None);
+            }
+            Ok(Some(result))
         }
+    }
 
-        let mut hasher = Hasher::default();
-        for seed in seeds.iter() {
-            hasher.hash(seed);
+    impl Pool for WhirlpoolPool {
+        fn dex_name(&self) -> String {
+            self.dex_name.clone()
         }
-        hasher.hashv(&[program_id.0.as_slice(), PDA_MARKER]);
-        let hash = hasher.result();
 
-        if bytes_are_curve_point(hash.0) {
-            return Err(PubkeyError::InvalidSeeds);
+        fn denom_plane(&self) -> Plane {
+            self.denom_plane
         }
 
-        Ok(Pubkey::from_slice(hash.0.as_slice()).unwrap())
-    }
-}
+        fn a(&self) -> Int256 {
+            self.a
+        }
 
-// Simplified version of token account
-#[derive(Debug)]
-pub struct TokenAccount {
-    pub mint: Pubkey,
-    pub owner: Pubkey,
-    pub amount: u64,
-}
+        fn b(&self) -> Int256 {
+            self.b
+        }
 
-impl TokenAccount {
-    pub fn unpack(bz: &[u8]) -> Result<TokenAccount, StdError> {
-        if bz.len() < 72 {
-            return Err(StdError::generic_err("token account size must >= 72 bytes"));
+        fn denom_a(&self) -> String {
+            self.denom_a.clone()
         }
 
-        Ok(TokenAccount {
-            mint: Pubkey::from_slice(&bz[0..32])?,
-            owner: Pubkey::from_slice(&bz[32..64])?,
-            amount: u64::from_le_bytes(bz[64..72].try_into().unwrap()), // we know for sure it's 8 bytes => unwrap() is safe
-        })
+        fn denom_b(&self) -> String {
+            self.denom_b.clone()
+        }
+
+        fn fee_rate(&self) -> Int256 {
+            self.fee_rate
+        }
+
+        fn swap_output(&self, x: Int256, a_for_b: bool) -> (String, Int256) {
+            let denom = if a_for_b {
+                self.denom_b.clone()
+            } else {
+                self.denom_a.clone()
+            };
+            // a failed simulation quotes nothing so the pool is never picked
+            (denom, self.simulate_swap(x, a_for_b).unwrap_or_default())
+        }
+
+        fn compose_swap_fis(
+            &self,
+            network: &Network,
+            swap: &Swap,
+        ) -> NexusResult<Vec<FISInstruction>> {
+            let (output_alias, min_output) = swap.min_output(self)?;
+            let Some(state) = &self.state else {
+                return Err(NexusError::invalid_input(format!(
+                    "whirlpool {} is not priced",
+                    swap.pool_name
+                )));
+            };
+            let a_to_b = swap.a_for_b(self)? == self.a_is_token_a;
+            let accounts = network.whirlpool_pool(&swap.pool_name)?;
+            let program_id = Pubkey::from_string(&network.whirlpool.program_id)?;
+            let whirlpool = Pubkey::from_string(&accounts.whirlpool)?;
+            let sender_svm_account = Pubkey::from_string(&swap.sender_svm)
+                .map_err(|e| NexusError::invalid_input(format!("parse svm address err: {}", e)))?;
+            let token_program = Pubkey::from_string(SPL_TOKEN_2022_PROGRAM_ID)?;
+
+            let (alias_a, alias_b) = if self.a_is_token_a {
+                (&self.denom_a, &self.denom_b)
+            } else {
+                (&self.denom_b, &self.denom_a)
+            };
+            let (mint_a, mint_b) = (mint(network, alias_a)?, mint(network, alias_b)?);
+            let owner_account_a =
+                get_associated_token_address(&sender_svm_account, &mint_a, &token_program)?;
+            let owner_account_b =
+                get_associated_token_address(&sender_svm_account, &mint_b, &token_program)?;
+            let svm_amount = |alias: &str, amount: Uint128| -> NexusResult<u64> {
+                let amount = network
+                    .denoms
+                    .convert(alias, Plane::Cosmos, Plane::Svm, amount)?;
+                Ok(Uint64::try_from(amount)?.u64())
+            };
+            let amount = svm_amount(&swap.denom, Uint128::try_from(swap.amount)?)?;
+            let min_amount_out = svm_amount(&output_alias, Uint128::new(to_u128(min_output)?))?;
+
+            // This instruction is idempotent, cost less fee when account exists
+            let output_mint = if a_to_b { &mint_b } else { &mint_a };
+            let create_output_ata_ix = associated_token::create_idempotent(
+                &sender_svm_account,
+                &sender_svm_account,
+                output_mint,
+                &token_program,
+            )?;
+
+            // exact input, bounded by the minimum output rather than the price
+            let sqrt_price_limit = if a_to_b {
+                MIN_SQRT_PRICE_X64
+            } else {
+                MAX_SQRT_PRICE_X64
+            };
+            let mut data = SWAP_V2_DISCRIMINATOR.to_vec();
+            data.extend(amount.to_le_bytes());
+            data.extend(min_amount_out.to_le_bytes());
+            data.extend(sqrt_price_limit.to_le_bytes());
+            // amount is input, a to b, no remaining accounts info
+            data.extend([1, u8::from(a_to_b), 0]);
+
+            let mut account_meta = vec![
+                InstructionAccountMeta::new(token_program, false, false),
+                InstructionAccountMeta::new(token_program, false, false),
+                InstructionAccountMeta::new(MEMO_PROGRAM_ID, false, false),
+                InstructionAccountMeta::new(sender_svm_account, true, false),
+                InstructionAccountMeta::new(whirlpool, false, true),
+                InstructionAccountMeta::new(mint_a, false, false),
+                InstructionAccountMeta::new(mint_b, false, false),
+                InstructionAccountMeta::new(owner_account_a, false, true),
+                InstructionAccountMeta::new(&self.token_vault_a, false, true),
+                InstructionAccountMeta::new(owner_account_b, false, true),
+                InstructionAccountMeta::new(&self.token_vault_b, false, true),
+            ];
+            for start in swap_tick_arrays(state.tick, state.tick_spacing, a_to_b) {
+                account_meta.push(InstructionAccountMeta::new(
+                    tick_array_address(&program_id, &whirlpool, start)?,
+                    false,
+                    true,
+                ));
+            }
+            account_meta.push(InstructionAccountMeta::new(
+                oracle_address(&program_id, &whirlpool)?,
+                false,
+                true,
+            ));
+            let swap_ix = InstructionMeta {
+                program_id: program_id.to_string(),
+                account_meta,
+                data: Binary::from(data),
+            };
+
+            let msg = TransactionBuilder::new()
+                .add_instructions(vec![create_output_ata_ix, swap_ix])
+                .build(vec![swap.sender.clone()], 10_000_000);
+            Ok(vec![FISInstruction::vm_invoke(
+                Plane::Svm,
+                to_json_vec(&msg)?,
+            )])
+        }
+
+        fn lp_supply(&self) -> Int256 {
+            self.state
+                .as_ref()
+                .map_or(Int256::zero(), |state| Int256::from(state.liquidity))
+        }
+
+        // positions are nfts with their own tick range, not lp tokens of the pool
+        fn compose_add_liquidity_fis(
+            &self,
+            _network: &Network,
+            deposit: &Deposit,
+        ) -> NexusResult<Vec<FISInstruction>> {
+            Err(NexusError::invalid_input(format!(
+                "whirlpool {} positions are not supported",
+                deposit.pool_name
+            )))
+        }
+
+        fn compose_remove_liquidity_fis(
+            &self,
+            _network: &Network,
+            withdrawal: &Withdrawal,
+        ) -> NexusResult<Vec<FISInstruction>> {
+            Err(NexusError::invalid_input(format!(
+                "whirlpool {} positions are not supported",
+                withdrawal.pool_name
+            )))
+        }
     }
 }
 
-pub fn get_denom(denom: &str) -> String {
-    match denom {
-        "btc" => "5ouhhEqV1L9gj3qTg3nQhkYuAuw72suktwJ4PvGo32SP".to_string(),
-        "eth" => "4SgGYkKAF4k3uAmkKaqMFnAuZkAhyzUuabRpHhssyW9B".to_string(),
-        "sol" => "CPozhCGVaGAcPVkxERsUYat4b7NKT9QeAR9KjNH4JpDG".to_string(),
-        "usdt" => "C3xXmrQWWnTmYABa8YTKrYU5jkonkTwz1qQCJbVX3mQh".to_string(),
-        _ => denom.to_string(),
+/// Status bits of a Raydium CPMM pool, a set bit disables the operation
+#[derive(Clone, Copy, Debug)]
+pub enum PoolStatusBit {
+    Deposit = 0,
+    Withdraw = 1,
+    Swap = 2,
+}
+
+impl PoolStatusBit {
+    pub fn is_enabled(self, status: u8) -> bool {
+        status & (1 << self as u8) == 0
     }
 }
 
-// Simplified version of poolstate
+/// Raydium CPMM `PoolState` account
 #[derive(Debug)]
 pub struct PoolState {
-    // additional information goes here
+    pub amm_config: Pubkey,
+    pub pool_creator: Pubkey,
+    pub token_0_vault: Pubkey,
+    pub token_1_vault: Pubkey,
+    pub lp_mint: Pubkey,
+    pub token_0_mint: Pubkey,
+    pub token_1_mint: Pubkey,
+    pub token_0_program: Pubkey,
+    pub token_1_program: Pubkey,
+    pub observation_key: Pubkey,
+    pub auth_bump: u8,
+    /// see `PoolStatusBit`
+    pub status: u8,
+    pub lp_mint_decimals: u8,
+    pub mint_0_decimals: u8,
+    pub mint_1_decimals: u8,
+    pub lp_supply: u64,
     pub protocol_fees_token_0: u64,
     pub protocol_fees_token_1: u64,
     pub fund_fees_token_0: u64,
     pub fund_fees_token_1: u64,
+    /// unix timestamp, swaps are accepted strictly after it
+    pub open_time: u64,
+    pub recent_epoch: u64,
 }
 
 impl PoolState {
-    pub const LEN: usize = 8 + 10 * 32 + 1 * 5 + 8 * 6 + 8 * 32;
+    pub const DISCRIMINATOR: [u8; 8] = [247, 237, 227, 245, 215, 195, 222, 70];
+    pub const LEN: usize = 8 + 10 * 32 + 5 + 8 * 7 + 8 * 31;
+
+    pub fn unpack(bz: &[u8]) -> NexusResult<PoolState> {
+        let mut r = AccountReader::new(bz, "raydium pool state", Self::LEN, Self::DISCRIMINATOR)?;
+        Ok(PoolState {
+            amm_config: r.pubkey()?,
+            pool_creator: r.pubkey()?,
+            token_0_vault: r.pubkey()?,
+            token_1_vault: r.pubkey()?,
+            lp_mint: r.pubkey()?,
+            token_0_mint: r.pubkey()?,
+            token_1_mint: r.pubkey()?,
+            token_0_program: r.pubkey()?,
+            token_1_program: r.pubkey()?,
+            observation_key: r.pubkey()?,
+            auth_bump: r.u8(),
+            status: r.u8(),
+            lp_mint_decimals: r.u8(),
+            mint_0_decimals: r.u8(),
+            mint_1_decimals: r.u8(),
+            lp_supply: r.u64(),
+            protocol_fees_token_0: r.u64(),
+            protocol_fees_token_1: r.u64(),
+            fund_fees_token_0: r.u64(),
+            fund_fees_token_1: r.u64(),
+            open_time: r.u64(),
+            recent_epoch: r.u64(),
+        })
+    }
+
+    pub fn is_enabled(&self, bit: PoolStatusBit) -> bool {
+        bit.is_enabled(self.status)
+    }
+}
+
+/// Raydium CPMM `AmmConfig` account, fee rates are in millionths and the protocol and fund
+/// rates are shares of the trade fee
+#[derive(Debug)]
+pub struct AmmConfig {
+    pub bump: u8,
+    pub disable_create_pool: bool,
+    pub index: u16,
+    pub trade_fee_rate: u64,
+    pub protocol_fee_rate: u64,
+    pub fund_fee_rate: u64,
+    pub create_pool_fee: u64,
+    pub protocol_owner: Pubkey,
+    pub fund_owner: Pubkey,
+}
+
+impl AmmConfig {
+    pub const DISCRIMINATOR: [u8; 8] = [218, 244, 33, 104, 203, 203, 43, 111];
+    pub const LEN: usize = 8 + 1 + 1 + 2 + 8 * 4 + 32 * 2 + 8 * 16;
+
+    pub fn unpack(bz: &[u8]) -> NexusResult<AmmConfig> {
+        let mut r = AccountReader::new(bz, "raydium amm config", Self::LEN, Self::DISCRIMINATOR)?;
+        Ok(AmmConfig {
+            bump: r.u8(),
+            disable_create_pool: r.u8() != 0,
+            index: u16::from_le_bytes(r.take()),
+            trade_fee_rate: r.u64(),
+            protocol_fee_rate: r.u64(),
+            fund_fee_rate: r.u64(),
+            create_pool_fee: r.u64(),
+            protocol_owner: r.pubkey()?,
+            fund_owner: r.pubkey()?,
+        })
+    }
+}
+
+/// Observations kept by a Raydium CPMM pool
+pub const OBSERVATION_NUM: usize = 100;
+
+/// Prices of a Raydium CPMM pool summed over every second up to `block_timestamp`, the price of
+/// token 0 is in token 1 and the other way round, in svm units as Q32 fixed point numbers
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Observation {
+    pub block_timestamp: u64,
+    pub cumulative_token_0_price_x32: u128,
+    pub cumulative_token_1_price_x32: u128,
+}
+
+/// Raydium CPMM `ObservationState` account, a ring buffer the pool writes before a swap at most
+/// every 15 seconds, `observation_index` is the latest
+#[derive(Debug)]
+pub struct ObservationState {
+    pub initialized: bool,
+    pub observation_index: u16,
+    pub pool_id: Pubkey,
+    pub observations: Vec<Observation>,
+}
+
+impl ObservationState {
+    pub const DISCRIMINATOR: [u8; 8] = [122, 174, 197, 53, 129, 9, 165, 132];
+    pub const LEN: usize = 8 + 1 + 2 + 32 + (8 + 16 * 2) * OBSERVATION_NUM + 8 * 4;
+
+    pub fn unpack(bz: &[u8]) -> NexusResult<ObservationState> {
+        let mut r = AccountReader::new(
+            bz,
+            "raydium observation state",
+            Self::LEN,
+            Self::DISCRIMINATOR,
+        )?;
+        Ok(ObservationState {
+            initialized: r.u8() != 0,
+            observation_index: u16::from_le_bytes(r.take()),
+            pool_id: r.pubkey()?,
+            observations: (0..OBSERVATION_NUM)
+                .map(|_| Observation {
+                    block_timestamp: r.u64(),
+                    cumulative_token_0_price_x32: r.u128(),
+                    cumulative_token_1_price_x32: r.u128(),
+                })
+                .collect(),
+        })
+    }
+
+    /// Average prices of token 0 and token 1 over the `window` seconds up to the latest
+    /// observation, or as far back as the buffer goes. None until two observations are apart.
+    pub fn twap_x32(&self, window: u64) -> Option<[u128; 2]> {
+        let index = usize::from(self.observation_index);
+        let latest = self.observations.get(index).filter(|_| self.initialized)?;
+        let mut oldest = latest;
+        for i in 1..OBSERVATION_NUM {
+            let previous = &self.observations[(index + OBSERVATION_NUM - i) % OBSERVATION_NUM];
+            // unwritten slots, or back to the latest after wrapping around
+            if previous.block_timestamp == 0 || previous.block_timestamp >= oldest.block_timestamp {
+                break;
+            }
+            oldest = previous;
+            if latest.block_timestamp - oldest.block_timestamp >= window {
+                break;
+            }
+        }
+        let elapsed = u128::from(latest.block_timestamp - oldest.block_timestamp);
+        if elapsed == 0 {
+            return None;
+        }
+        // the sums wrap around on overflow
+        Some([
+            latest
+                .cumulative_token_0_price_x32
+                .wrapping_sub(oldest.cumulative_token_0_price_x32)
+                / elapsed,
+            latest
+                .cumulative_token_1_price_x32
+                .wrapping_sub(oldest.cumulative_token_1_price_x32)
+                / elapsed,
+        ])
+    }
+}
+
+/// Orca `Whirlpool` account, the sqrt price is the Q64.64 square root of the price of token a
+/// in token b and the fee rate is in millionths
+#[derive(Debug)]
+pub struct Whirlpool {
+    pub whirlpools_config: Pubkey,
+    pub tick_spacing: u16,
+    pub fee_rate: u16,
+    pub protocol_fee_rate: u16,
+    pub liquidity: u128,
+    pub sqrt_price: u128,
+    pub tick_current_index: i32,
+    pub token_mint_a: Pubkey,
+    pub token_vault_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_vault_b: Pubkey,
+}
+
+impl Whirlpool {
+    pub const DISCRIMINATOR: [u8; 8] = [63, 149, 209, 12, 225, 128, 99, 9];
+    pub const LEN: usize =
+        8 + 32 + 1 + 2 * 4 + 16 * 2 + 4 + 8 * 2 + (32 * 2 + 16) * 2 + 8 + 128 * 3;
+
+    pub fn unpack(bz: &[u8]) -> NexusResult<Whirlpool> {
+        let mut r = AccountReader::new(bz, "whirlpool", Self::LEN, Self::DISCRIMINATOR)?;
+        let whirlpools_config = r.pubkey()?;
+        // bump
+        r.u8();
+        let tick_spacing = r.u16();
+        // tick spacing seed
+        r.take::<2>();
+        let fee_rate = r.u16();
+        let protocol_fee_rate = r.u16();
+        let liquidity = r.u128();
+        let sqrt_price = r.u128();
+        let tick_current_index = r.i32();
+        // protocol fees owed
+        r.u64();
+        r.u64();
+        let token_mint_a = r.pubkey()?;
+        let token_vault_a = r.pubkey()?;
+        // fee growth
+        r.u128();
+        Ok(Whirlpool {
+            whirlpools_config,
+            tick_spacing,
+            fee_rate,
+            protocol_fee_rate,
+            liquidity,
+            sqrt_price,
+            tick_current_index,
+            token_mint_a,
+            token_vault_a,
+            token_mint_b: r.pubkey()?,
+            token_vault_b: r.pubkey()?,
+        })
+    }
+}
+
+/// Ticks of a whirlpool tick array
+pub const TICK_ARRAY_SIZE: i32 = 88;
+
+/// Orca `TickArray` account, `TICK_ARRAY_SIZE` ticks one tick spacing apart from
+/// `start_tick_index`
+#[derive(Debug)]
+pub struct TickArray {
+    pub start_tick_index: i32,
+    /// liquidity net of each tick, none for the uninitialized ones
+    pub liquidity_net: Vec<Option<i128>>,
+    pub whirlpool: Pubkey,
+}
+
+impl TickArray {
+    pub const DISCRIMINATOR: [u8; 8] = [69, 97, 189, 190, 110, 7, 66, 187];
+    // a tick is its initialized flag, liquidity net and gross, 2 fee and 3 reward growths
+    pub const LEN: usize = 8 + 4 + (1 + 16 * 7) * TICK_ARRAY_SIZE as usize + 32;
+
+    pub fn unpack(bz: &[u8]) -> NexusResult<TickArray> {
+        let mut r = AccountReader::new(bz, "whirlpool tick array", Self::LEN, Self::DISCRIMINATOR)?;
+        let start_tick_index = r.i32();
+        let liquidity_net = (0..TICK_ARRAY_SIZE)
+            .map(|_| {
+                let initialized = r.u8() != 0;
+                let liquidity_net = i128::from_le_bytes(r.take());
+                r.take::<{ 16 * 6 }>();
+                initialized.then_some(liquidity_net)
+            })
+            .collect();
+        Ok(TickArray {
+            start_tick_index,
+            liquidity_net,
+            whirlpool: r.pubkey()?,
+        })
+    }
+
+    /// Initialized ticks, for pools with `tick_spacing`
+    pub fn ticks(&self, tick_spacing: i32) -> Vec<Tick> {
+        (0..TICK_ARRAY_SIZE)
+            .zip(self.liquidity_net.iter())
+            .filter_map(|(i, liquidity_net)| {
+                liquidity_net.map(|liquidity_net| Tick {
+                    index: self.start_tick_index + i * tick_spacing,
+                    liquidity_net,
+                })
+            })
+            .collect()
+    }
+}
+
+// Little endian reader of an anchor account, the length and discriminator are checked upfront
+struct AccountReader<'a> {
+    bz: &'a [u8],
+    offset: usize,
+}
 
-    pub fn unpack(bz: &[u8]) -> Result<PoolState, StdError> {
-        if bz.len() != Self::LEN {
-            return Err(StdError::generic_err(format!(
-                "pool state account must size must >= {} bytes, current len: {}",
-                Self::LEN,
+impl<'a> AccountReader<'a> {
+    fn new(bz: &'a [u8], name: &str, len: usize, discriminator: [u8; 8]) -> NexusResult<Self> {
+        let invalid = |reason: String| NexusError::InvalidFisInput {
+            slot: name.to_string(),
+            reason,
+        };
+        if bz.len() != len {
+            return Err(invalid(format!(
+                "account must be {} bytes, got {}",
+                len,
                 bz.len()
             )));
         }
+        if bz[..8] != discriminator {
+            return Err(invalid("account discriminator mismatch".to_string()));
+        }
+        Ok(AccountReader { bz, offset: 8 })
+    }
+
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let mut word = [0u8; N];
+        word.copy_from_slice(&self.bz[self.offset..self.offset + N]);
+        self.offset += N;
+        word
+    }
 
-        let protocol_fees_token_0 = u64::from_le_bytes(bz[341..349].try_into().unwrap());
-        let protocol_fees_token_1 = u64::from_le_bytes(bz[349..357].try_into().unwrap());
-        let fund_fees_token_0 = u64::from_le_bytes(bz[357..365].try_into().unwrap());
-        let fund_fees_token_1 = u64::from_le_bytes(bz[365..373].try_into().unwrap());
+    fn u8(&mut self) -> u8 {
+        self.take::<1>()[0]
+    }
 
-        Ok(PoolState {
-            protocol_fees_token_0,
-            protocol_fees_token_1,
-            fund_fees_token_0,
-            fund_fees_token_1,
-        })
+    fn u16(&mut self) -> u16 {
+        u16::from_le_bytes(self.take())
+    }
+
+    fn i32(&mut self) -> i32 {
+        i32::from_le_bytes(self.take())
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.take())
+    }
+
+    fn u128(&mut self) -> u128 {
+        u128::from_le_bytes(self.take())
+    }
+
+    fn pubkey(&mut self) -> NexusResult<Pubkey> {
+        Ok(Pubkey::from_slice(&self.take::<32>())?)
     }
 }
@@ -1,31 +1,39 @@
 #[cfg(test)]
 mod tests {
     use crate::{
-        astromesh::Pool,
+        astromesh::{
+            less_slippage, near_twap, to_int256, to_uint256, Deposit, NexusAction, Pool, Swap,
+            Withdrawal, DEFAULT_SLIPPAGE_BPS,
+        },
         calculate_pools_output,
+        clmm::{self, ConcentratedPool, Tick},
+        curve::Invariant,
+        evm::uniswap::{parse_pool_info, PoolInfo, UniswapPool},
+        network::Network,
+        query::{PoolQuery, SwapQuery},
+        route::{self, Market},
         svm::{
-            raydium::{self, keccak256, ASSOCIATED_TOKEN_PROGRAM_ID, RAYDIUM, SPL_TOKEN_2022},
-            Pubkey, TokenAccount,
+            raydium::{self, keccak256, RAYDIUM},
+            whirlpool::{self, WhirlpoolAccounts, WhirlpoolPool, WHIRLPOOL},
+            AmmConfig, ObservationState, PoolState, PoolStatusBit, TickArray, Whirlpool,
+            OBSERVATION_NUM, TICK_ARRAY_SIZE,
         },
         wasm::astroport::{self, ASTROPORT},
     };
-    use cosmwasm_std::{Binary, Int256};
-
-    #[test]
-    fn test_parse_token_account() {
-        // {"Mint":"AarDASauqWwFsuG9r62pYCH3m9CFtvvQucUs2iLg18AW","Owner":"GonQpn9zzCF2rD521AiYg1RFpC4aFEzJ8RwC9XDi54L6","Amount":399000000,"Delegate":null,"State":1,"IsNative":null,"DelegatedAmount":0,"CloseAuthority":null}
-        let account_data = Binary::from_base64("jmT9mZ6EZ9zYFBt+eBm190VZG6DWEc7ey8OWylSvrTPq214B6Yq6zIE78NDn5DRCobUH5USgLNzQMQKlCAQt78BByBcAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAgcAAAA=").unwrap();
-        let acc = TokenAccount::unpack(account_data.as_slice()).unwrap();
-        assert_eq!(
-            acc.mint.to_string(),
-            "AarDASauqWwFsuG9r62pYCH3m9CFtvvQucUs2iLg18AW".to_string()
-        );
-        assert_eq!(
-            acc.owner.to_string(),
-            "GonQpn9zzCF2rD521AiYg1RFpC4aFEzJ8RwC9XDi54L6".to_string()
-        );
-        assert_eq!(acc.amount, 399000000);
-    }
+    use cosmwasm_std::{
+        from_json,
+        testing::{message_info, mock_dependencies, mock_env},
+        Addr, Binary, Coin, Decimal, Decimal256, Int128, Int256, Uint128, Uint256, Uint64,
+    };
+    use nexus_sdk::{
+        astromesh::{FISInput, FisAction, MsgAstroTransfer, Plane},
+        error::NexusError,
+        strategy::InstantiateMsg,
+        svm::{
+            get_associated_token_address, Account, MsgTransaction, Pubkey,
+            SPL_TOKEN_2022_PROGRAM_ID,
+        },
+    };
 
     #[test]
     fn test_calculate_svm_address() {
@@ -38,31 +46,22 @@ mod tests {
             "DRK5Bi2NwkGRPsqHJSyy6rhUo3uQ8YHtt1xUWbu7Bnsx".to_string()
         );
 
-        let token_program = Pubkey::from_string(&SPL_TOKEN_2022.to_string()).unwrap();
+        let token_program = Pubkey::from_string(SPL_TOKEN_2022_PROGRAM_ID).unwrap();
         let output_denom_pk =
-            Pubkey::from_string(&"ErDYXZUZ9rpSSvdWvrsQwgh6K4BQeoY2CPyv1FeD1S9r".to_string())
-                .unwrap();
+            Pubkey::from_string("ErDYXZUZ9rpSSvdWvrsQwgh6K4BQeoY2CPyv1FeD1S9r").unwrap();
         let input_denom_pk =
-            Pubkey::from_string(&"ENyus6yS21v95sreLKcVEA5Wjcyh8jg6w4jBFHzJaPox".to_string())
-                .unwrap();
-        let ata_program = Pubkey::from_string(&ASSOCIATED_TOKEN_PROGRAM_ID.to_string()).unwrap();
-
-        let (output_token_account, _) = Pubkey::find_program_address(
-            &[&sender_svm_account.0, &token_program.0, &output_denom_pk.0],
-            &ata_program,
-        )
-        .unwrap();
+            Pubkey::from_string("ENyus6yS21v95sreLKcVEA5Wjcyh8jg6w4jBFHzJaPox").unwrap();
 
-        let (input_token_account, _) = Pubkey::find_program_address(
-            &[&sender_svm_account.0, &token_program.0, &input_denom_pk.0],
-            &ata_program,
-        )
-        .unwrap();
+        let output_token_account =
+            get_associated_token_address(&sender_svm_account, &output_denom_pk, &token_program)
+                .unwrap();
+        let input_token_account =
+            get_associated_token_address(&sender_svm_account, &input_denom_pk, &token_program)
+                .unwrap();
 
         println!(
             "input ata: {}, output ata: {}",
-            input_token_account.to_string(),
-            output_token_account.to_string()
+            input_token_account, output_token_account
         );
 
         assert_eq!(
@@ -75,30 +74,1846 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_swap_base_input_transaction() {
+        let sender_svm_account =
+            Pubkey::from_string("DRK5Bi2NwkGRPsqHJSyy6rhUo3uQ8YHtt1xUWbu7Bnsx").unwrap();
+        let input_mint =
+            Pubkey::from_string("ENyus6yS21v95sreLKcVEA5Wjcyh8jg6w4jBFHzJaPox").unwrap();
+        let output_mint =
+            Pubkey::from_string("ErDYXZUZ9rpSSvdWvrsQwgh6K4BQeoY2CPyv1FeD1S9r").unwrap();
+        let network = Network::embedded().unwrap();
+        let accounts = network.raydium_pool("btc-usdt").unwrap().clone();
+
+        let tx = raydium::swap_base_input(
+            network.raydium.cpmm_program_id.clone(),
+            "lux1jcltmuhplrdcwp7stlr4hlhlhgd4htqhu86cqx".to_string(),
+            1000,
+            0,
+            &sender_svm_account,
+            accounts.authority_account,
+            accounts.amm_config_account,
+            accounts.pool_state_account,
+            "CytVtp6RTC9uDpf2wSqwk2WiByXXdUwmH7Hp3WiKUDMa".to_string(),
+            "C2xipnf5somAHFMmSFYqkfwmMHAr21uqLDLS1MxkdW3L".to_string(),
+            accounts.token0_vault,
+            accounts.token1_vault,
+            &input_mint,
+            &output_mint,
+            accounts.observer_state,
+        )
+        .unwrap();
+
+        assert_eq!(tx.instructions.len(), 2);
+        // create idempotent ata ix
+        let ata_ix = &tx.instructions[0];
+        assert_eq!(ata_ix.data.as_slice(), &[1]);
+        assert_eq!(ata_ix.accounts[0].id_index, ata_ix.accounts[2].id_index);
+        // swap ix uses same token program for input and output
+        let swap_ix = &tx.instructions[1];
+        assert_eq!(swap_ix.accounts.len(), 13);
+        assert_eq!(swap_ix.accounts[8].id_index, swap_ix.accounts[9].id_index);
+        assert_eq!(swap_ix.accounts[9].callee_index, 8);
+        assert!(swap_ix.accounts[0].is_signer && swap_ix.accounts[0].is_writable);
+        assert_eq!(swap_ix.data.len(), 8 + 8 + 8);
+    }
+
     #[test]
     fn test_arbitrage_profit() {
         let input_amount = Int256::from(4990212513i128);
         let raydium_pool: Box<dyn Pool> = Box::new(raydium::RaydiumPool {
             dex_name: ASTROPORT.to_string(),
-            denom_plane: "COSMOS".to_string(),
+            denom_plane: Plane::Cosmos,
             a: 10000000000i128.into(),
             b: 10000000000i128.into(),
             fee_rate: Int256::from_i128(10000),
             denom_a: "".to_string(),
             denom_b: "".to_string(),
+            decimal_multiplier_a: Int256::one(),
+            decimal_multiplier_b: Int256::one(),
+            lp_mint: String::new(),
+            lp_supply: Int256::zero(),
+            status: 0,
+            open_time: 0,
+            block_time: 1,
+            twap: None,
         });
         let astroport_pool: Box<dyn Pool> = Box::new(astroport::AstroportPool {
             dex_name: RAYDIUM.to_string(),
-            denom_plane: "SVM".to_string(),
+            denom_plane: Plane::Svm,
             a: 139304175643i128.into(),
             b: 201000000i128.into(),
             fee_rate: Int256::from_i128(1000),
             denom_a: "".to_string(),
             denom_b: "".to_string(),
+            lp_supply: Int256::zero(),
+            curve: None,
         });
 
         let (_, _, _, second_swap_output) =
-            calculate_pools_output(&raydium_pool, &astroport_pool, input_amount);
+            calculate_pools_output(raydium_pool.as_ref(), astroport_pool.as_ref(), input_amount);
         assert!(second_swap_output - input_amount > Int256::zero());
     }
+
+    #[test]
+    fn test_arbitrage_query_layout() {
+        let network = Network::embedded().unwrap();
+        let query = PoolQuery::new(&network).unwrap();
+        assert_eq!(
+            query.query.instructions.len(),
+            network.pairs().len() * 3 + 1
+        );
+
+        let eth = query.pair("eth-usdt").unwrap();
+        let astroport = &query.query.instructions[eth.astroport.unwrap()];
+        assert_eq!(astroport.plane, Plane::Wasm);
+        assert_eq!(astroport.input[0].as_slice(), br#"{"pool":{}}"#);
+        assert_eq!(astroport.input.len(), 3);
+        let raydium = &query.query.instructions[eth.raydium.unwrap()];
+        assert_eq!(raydium.plane, Plane::Svm);
+        assert_eq!(raydium.input.len(), 5);
+        let uniswap = &query.query.instructions[eth.uniswap.unwrap()];
+        assert_eq!(uniswap.plane, Plane::Evm);
+        assert_eq!(
+            uniswap.address.as_slice(),
+            hex::decode(&network.uniswap.pool_manager).unwrap()
+        );
+        // slot0 and liquidity of the same pool state
+        let [slot0, liquidity] = uniswap.input.as_slice() else {
+            panic!("uniswap must query 2 storage slots");
+        };
+        assert_eq!(
+            Uint256::from_be_bytes(liquidity.as_slice().try_into().unwrap())
+                - Uint256::from_be_bytes(slot0.as_slice().try_into().unwrap()),
+            Uint256::from(3u8)
+        );
+
+        let link = &query.query.instructions[query.svm_link.instruction];
+        assert_eq!(link.action, FisAction::CosmosQuery);
+        assert_eq!(
+            query.pair("atom-usdt").err(),
+            Some(NexusError::UnsupportedPair("atom-usdt".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_swap_errors() {
+        let deps = mock_dependencies();
+        let swap = |dex_name: &str, amount: i128, max_slippage_bps: Option<u16>| {
+            crate::swap(
+                deps.as_ref(),
+                mock_env(),
+                dex_name.to_string(),
+                "usdt".to_string(),
+                "btc".to_string(),
+                Int128::new(amount),
+                max_slippage_bps,
+                false,
+                &[],
+            )
+            .unwrap_err()
+        };
+
+        assert_eq!(swap("wasm astroport", 0, None).code(), 2);
+        assert_eq!(swap("wasm astroport", 100, None).code(), 3);
+        // rejected before the input is read
+        assert_eq!(
+            swap("wasm astroport", 100, Some(10_001)),
+            NexusError::invalid_input("max slippage must be at most 10000 bps")
+        );
+    }
+
+    #[test]
+    fn test_arbitrage_src_denom() {
+        // the balances of the start denom are queried, it has no default
+        let action = r#"{"arbitrage":{"pair":"btc-usdt","amount":"100"}}"#;
+        assert!(from_json::<NexusAction>(action.as_bytes()).is_err());
+
+        let deps = mock_dependencies();
+        let err = crate::arbitrage(
+            deps.as_ref(),
+            mock_env(),
+            "btc-usdt".to_string(),
+            Int128::new(100),
+            None,
+            "atom".to_string(),
+            None,
+            None,
+            None,
+            None,
+            &[],
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            NexusError::UnsupportedPair("btc-usdt does not trade atom".to_string())
+        );
+    }
+
+    #[test]
+    fn test_liquidity_errors() {
+        let deps = mock_dependencies();
+        let add = |max_slippage_bps: Option<u16>| {
+            crate::add_liquidity(
+                deps.as_ref(),
+                mock_env(),
+                "wasm astroport".to_string(),
+                "btc-usdt".to_string(),
+                Int128::new(100),
+                Int128::new(100),
+                max_slippage_bps,
+                &[],
+            )
+            .unwrap_err()
+        };
+        let remove = |dex_name: &str, max_slippage_bps: Option<u16>| {
+            crate::remove_liquidity(
+                deps.as_ref(),
+                mock_env(),
+                dex_name.to_string(),
+                "btc-usdt".to_string(),
+                Int128::new(100),
+                max_slippage_bps,
+                &[],
+            )
+            .unwrap_err()
+        };
+
+        let too_loose = NexusError::invalid_input("max slippage must be at most 10000 bps");
+        assert_eq!(add(Some(10_001)), too_loose);
+        assert_eq!(remove("wasm astroport", Some(10_001)), too_loose);
+        // uniswap withdrawals have no minimum amounts to bound
+        assert_eq!(
+            remove("evm uniswap", Some(100)),
+            NexusError::invalid_input(
+                "uniswap withdrawals can't bound their amounts, leave max slippage unset"
+            )
+        );
+        assert_eq!(remove("evm uniswap", None).code(), 3);
+    }
+
+    #[test]
+    fn test_instantiate_network() {
+        let mut deps = mock_dependencies();
+        assert!(Network::load(deps.as_ref())
+            .unwrap()
+            .raydium_pool("sol-usdt")
+            .is_ok());
+
+        let mut network = Network::embedded().unwrap();
+        network.raydium.pools.remove("sol-usdt");
+        crate::instantiate(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&Addr::unchecked("owner"), &[]),
+            InstantiateMsg {
+                owner: None,
+                network: Some(network),
+                config: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            Network::load(deps.as_ref())
+                .unwrap()
+                .raydium_pool("sol-usdt"),
+            Err(NexusError::UnsupportedPair("raydium sol-usdt".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_astro_transfer_decimals() {
+        let network = Network::embedded().unwrap();
+        let transfer = |src_plane, dst_plane, denom: &str| {
+            let ix = crate::astro_transfer(
+                &network,
+                "lux1".to_string(),
+                src_plane,
+                dst_plane,
+                denom.to_string(),
+                1_234_567_891_234,
+            )
+            .unwrap();
+            from_json::<MsgAstroTransfer>(ix.msg()).unwrap().coin
+        };
+
+        // eth has 9 decimals on svm, dust is dropped
+        assert_eq!(
+            transfer(Plane::Wasm, Plane::Svm, "eth"),
+            Coin::new(1_234_000_000_000u128, "eth")
+        );
+        let mint = network.denoms.address("eth", Plane::Svm).unwrap();
+        assert_eq!(
+            transfer(Plane::Svm, Plane::Wasm, "eth"),
+            Coin::new(1_234u128, format!("astro/{}", mint))
+        );
+        assert_eq!(
+            transfer(Plane::Wasm, Plane::Svm, "btc"),
+            Coin::new(1_234_567_891_234u128, "btc")
+        );
+    }
+
+    fn test_ticks() -> Vec<Tick> {
+        // [-120, 120] with 1e18 and [-600, 600] with 5e17 of liquidity
+        [
+            (-600, 500_000_000_000_000_000),
+            (-120, 1_000_000_000_000_000_000),
+        ]
+        .iter()
+        .flat_map(|(index, net)| {
+            [
+                Tick {
+                    index: *index,
+                    liquidity_net: *net,
+                },
+                Tick {
+                    index: -index,
+                    liquidity_net: -net,
+                },
+            ]
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_clmm_sqrt_price_at_tick() {
+        let q96 = Uint256::one() << 96;
+        assert_eq!(clmm::sqrt_price_at_tick(0).unwrap(), q96);
+        assert_eq!(
+            clmm::sqrt_price_at_tick(1).unwrap(),
+            Uint256::from_u128(79232123823359799118286999568)
+        );
+        assert_eq!(
+            clmm::sqrt_price_at_tick(-1).unwrap(),
+            Uint256::from_u128(79224201403219477170569942574)
+        );
+        assert_eq!(
+            clmm::sqrt_price_at_tick(clmm::MIN_TICK).unwrap(),
+            clmm::MIN_SQRT_PRICE
+        );
+        assert_eq!(
+            clmm::sqrt_price_at_tick(clmm::MAX_TICK).unwrap(),
+            clmm::MAX_SQRT_PRICE
+        );
+        assert_eq!(
+            clmm::MAX_SQRT_PRICE.to_string(),
+            "1461446703485210103287273052203988822378723970342"
+        );
+        assert!(clmm::sqrt_price_at_tick(clmm::MAX_TICK + 1).is_err());
+    }
+
+    #[test]
+    fn test_clmm_swap_crosses_ticks() {
+        let pool = ConcentratedPool {
+            sqrt_price_x96: Uint256::one() << 96,
+            tick: 0,
+            liquidity: 1_500_000_000_000_000_000,
+            tick_spacing: 60,
+            ticks: test_ticks(),
+        };
+
+        // crosses -120, leaving the wide position only
+        let result = pool
+            .swap_exact_in(true, Uint256::from_u128(20_000_000_000_000_000), 3000)
+            .unwrap();
+        assert_eq!(result.amount_out, Uint256::from_u128(19526846291051687));
+        assert_eq!(
+            result.sqrt_price_x96,
+            Uint256::from_u128(77081854394402073945010799962)
+        );
+        assert_eq!(result.tick, -121);
+        assert_eq!(result.liquidity, 500_000_000_000_000_000);
+
+        // drains all liquidity, the rest of the input is not consumed
+        let result = pool
+            .swap_exact_in(true, Uint256::from_u128(100_000_000_000_000_000_000), 3000)
+            .unwrap();
+        assert_eq!(result.amount_in, Uint256::from_u128(21308152915521108));
+        assert_eq!(result.amount_out, Uint256::from_u128(20758243200078246));
+        assert_eq!(result.liquidity, 0);
+
+        assert_eq!(clmm::swap_fee(3000, 0, true), 3000);
+        // 0.1% protocol fee on one for zero swaps only
+        assert_eq!(clmm::swap_fee(3000, 1000 << 12, true), 3000);
+        assert_eq!(clmm::swap_fee(3000, 1000 << 12, false), 3997);
+    }
+
+    #[test]
+    fn test_uniswap_swap_output() {
+        let network = Network::embedded().unwrap();
+        let unpriced = UniswapPool::new(&network, "btc-usdt").unwrap();
+        assert_eq!(
+            unpriced.swap_output(Int256::from(1000), true),
+            ("btc".to_string(), Int256::zero())
+        );
+
+        let info = PoolInfo {
+            sqrt_price_x96: Uint256::one() << 96,
+            tick: Int256::zero(),
+            protocol_fee: 0,
+            lp_fee: 3000,
+        };
+        let pool = unpriced
+            .with_state(&info, 1_500_000_000_000_000_000, test_ticks())
+            .unwrap();
+        assert_eq!(pool.a(), Int256::from(1_500_000_000_000_000_000i128));
+        // btc is currency0, usdt to btc is one for zero
+        assert_eq!(
+            pool.swap_output(Int256::from(1_000_000_000_000_000i128), true),
+            ("btc".to_string(), Int256::from(996337767497203i128))
+        );
+        assert_eq!(
+            pool.swap_output(Int256::from(20_000_000_000_000_000i128), false),
+            ("usdt".to_string(), Int256::from(19526846291051687i128))
+        );
+    }
+
+    #[test]
+    fn test_uniswap_from_fis() {
+        let network = Network::embedded().unwrap();
+        // lp fee 3000, protocol fee 0, tick 0, sqrt price 2^96
+        let mut slot0 = [0u8; 32];
+        slot0[3..6].copy_from_slice(&3000u32.to_be_bytes()[1..]);
+        slot0[12..].copy_from_slice(&(Uint256::one() << 96).to_be_bytes()[12..]);
+        let liquidity = Uint256::from(1_500_000_000_000_000_000u128).to_be_bytes();
+        let fis_input = |slot0: &[u8]| {
+            vec![FISInput {
+                data: vec![Binary::from(slot0), Binary::from(liquidity)],
+            }]
+        };
+
+        let pool = UniswapPool::from_fis(&network, "eth-usdt", &fis_input(&slot0), 0).unwrap();
+        assert_eq!(pool.a(), Int256::from(1_500_000_000_000_000_000i128));
+        assert_eq!(pool.b(), Int256::from(1_500_000_000_000_000_000i128));
+        // eth is currency1, eth to usdt is one for zero
+        assert_eq!(
+            pool.swap_output(Int256::from(1_000_000_000_000_000i128), false),
+            ("usdt".to_string(), Int256::from(996337767497203i128))
+        );
+        // swaps are priced up to the next tick spacing only, the price is at its lower end
+        let (lower, upper) = pool.sqrt_price_range.unwrap();
+        assert_eq!(
+            (lower, upper),
+            (Uint256::one() << 96, clmm::sqrt_price_at_tick(60).unwrap())
+        );
+        assert_eq!(
+            pool.swap_output(Int256::from(10_000_000_000_000_000i128), false)
+                .1,
+            Int256::zero()
+        );
+        assert_eq!(
+            pool.swap_output(Int256::from(1_000_000_000_000_000i128), true)
+                .1,
+            Int256::zero()
+        );
+
+        let unpriced =
+            UniswapPool::from_fis(&network, "eth-usdt", &fis_input(&[0; 32]), 0).unwrap();
+        assert!(unpriced.a().is_zero() && unpriced.state.is_none());
+
+        let err = UniswapPool::from_fis(&network, "eth-usdt", &fis_input(&[1; 33]), 0).unwrap_err();
+        assert!(matches!(err, NexusError::InvalidFisInput { .. }));
+    }
+
+    #[test]
+    fn test_parse_pool_info() {
+        // lp fee 500, protocol fee 0, tick -200000, sqrt price 2^96 / 22000
+        let sqrt_price_x96 = (Uint256::one() << 96) / Uint256::from(22_000u32);
+        let mut slot0 = [0u8; 32];
+        slot0[3..6].copy_from_slice(&500u32.to_be_bytes()[1..]);
+        slot0[9..12].copy_from_slice(&(-200_000i32).to_be_bytes()[1..]);
+        slot0[12..].copy_from_slice(&sqrt_price_x96.to_be_bytes()[12..]);
+        let info = parse_pool_info(&slot0).unwrap();
+        assert_eq!(
+            (
+                info.sqrt_price_x96,
+                info.tick,
+                info.protocol_fee,
+                info.lp_fee
+            ),
+            (sqrt_price_x96, Int256::from(-200_000), 0, 500)
+        );
+
+        // the highest and lowest int24
+        for tick in [8_388_607i32, -8_388_608] {
+            slot0[9..12].copy_from_slice(&tick.to_be_bytes()[1..]);
+            assert_eq!(parse_pool_info(&slot0).unwrap().tick, Int256::from(tick));
+        }
+    }
+
+    #[test]
+    fn test_pair_registry() {
+        let mut network = Network::embedded().unwrap();
+        // eth quoted in btc, on astroport only
+        let contract = network.astroport_pool("eth-usdt").unwrap().clone();
+        network
+            .astroport
+            .pools
+            .insert("eth-btc".to_string(), contract);
+        assert_eq!(network.find_pair("btc", "eth").unwrap(), "eth-btc");
+        assert_eq!(network.find_pair("eth", "btc").unwrap(), "eth-btc");
+        assert_eq!(
+            network.find_pair("btc", "sol").unwrap_err().code(),
+            NexusError::UnsupportedPair("".to_string()).code()
+        );
+
+        let query = PoolQuery::new(&network).unwrap();
+        assert_eq!(query.query.instructions.len(), 3 * 3 + 1 + 1);
+        let input = query.pair("eth-btc").unwrap();
+        assert!(input.astroport.is_some() && input.raydium.is_none() && input.uniswap.is_none());
+
+        let pool_response = |assets: [(&str, u128); 2]| {
+            let response = astroport::PoolResponse {
+                assets: assets
+                    .iter()
+                    .map(|(denom, amount)| astroport::Asset {
+                        info: astroport::AssetInfo::NativeToken {
+                            denom: denom.to_string(),
+                        },
+                        amount: (*amount).into(),
+                    })
+                    .collect(),
+                total_share: 0u128.into(),
+            };
+            // at the instruction of the pair
+            let mut fis_input = vec![FISInput { data: vec![] }; input.astroport.unwrap()];
+            fis_input.push(FISInput {
+                data: vec![
+                    Binary::from(cosmwasm_std::to_json_vec(&response).unwrap()),
+                    Binary::from(br#"{"pair_type":{"xyk":{}}}"#),
+                    Binary::from(br#"{"params":null}"#),
+                ],
+            });
+            fis_input
+        };
+        // the quote denom btc is a whatever the asset order
+        let fis_input = pool_response([("eth", 20_000), ("btc", 1_000)]);
+        let pools = input.pools(&network, &fis_input, 0).unwrap();
+        assert_eq!(pools.len(), 1);
+        assert_eq!(
+            (
+                pools[0].denom_a(),
+                pools[0].a(),
+                pools[0].denom_b(),
+                pools[0].b()
+            ),
+            (
+                "btc".to_string(),
+                Int256::from(1_000),
+                "eth".to_string(),
+                Int256::from(20_000)
+            )
+        );
+        assert_eq!(
+            input.pool(&network, RAYDIUM, &fis_input, 0).err(),
+            Some(NexusError::UnsupportedPair("raydium eth-btc".to_string()))
+        );
+        let err = input
+            .pools(
+                &network,
+                &pool_response([("eth", 20_000), ("usdt", 1_000)]),
+                0,
+            )
+            .err()
+            .unwrap();
+        assert!(matches!(err, NexusError::InvalidFisInput { .. }));
+    }
+
+    fn market(pair: &str, plane: Plane, a: i128, b: i128) -> Market {
+        let (denom_b, denom_a) = pair.split_once('-').unwrap();
+        let pool: Box<dyn Pool> = match plane {
+            Plane::Svm => Box::new(raydium::RaydiumPool {
+                dex_name: RAYDIUM.to_string(),
+                denom_plane: plane,
+                a: a.into(),
+                b: b.into(),
+                fee_rate: Int256::from(3000),
+                denom_a: denom_a.to_string(),
+                denom_b: denom_b.to_string(),
+                decimal_multiplier_a: Int256::one(),
+                decimal_multiplier_b: Int256::one(),
+                lp_mint: String::new(),
+                lp_supply: a.into(),
+                status: 0,
+                open_time: 0,
+                block_time: 1,
+                twap: None,
+            }),
+            _ => Box::new(astroport::AstroportPool {
+                dex_name: ASTROPORT.to_string(),
+                denom_plane: plane,
+                a: a.into(),
+                b: b.into(),
+                fee_rate: Int256::from(3000),
+                denom_a: denom_a.to_string(),
+                denom_b: denom_b.to_string(),
+                lp_supply: a.into(),
+                curve: None,
+            }),
+        };
+        Market {
+            pair: pair.to_string(),
+            pool,
+        }
+    }
+
+    #[test]
+    fn test_multi_hop_cycles() {
+        // 1 btc = 1 usdt = 1 eth, but 1 btc = 1.1 eth on the cross pair
+        let markets = vec![
+            market(
+                "btc-usdt",
+                Plane::Cosmos,
+                1_000_000_000_000,
+                1_000_000_000_000,
+            ),
+            market(
+                "btc-eth",
+                Plane::Cosmos,
+                1_100_000_000_000,
+                1_000_000_000_000,
+            ),
+            market("eth-usdt", Plane::Svm, 1_000_000_000_000, 1_000_000_000_000),
+        ];
+        assert!(route::find_cycles(&markets, "usdt", 2).is_empty());
+        let cycles = route::find_cycles(&markets, "usdt", 3);
+        assert_eq!(cycles.len(), 2);
+
+        let [forward, backward] = [&cycles[0], &cycles[1]].map(|hops| {
+            let (x, profit) = route::optimize_cycle(&markets, hops, Int256::MAX).unwrap();
+            (hops, x, profit)
+        });
+        // usdt => btc => eth => usdt
+        assert_eq!(
+            forward.0.iter().map(|hop| hop.market).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        assert!(forward.2 > Int256::zero());
+        let one_percent = forward.1 / Int256::from(100);
+        for x in [forward.1 - one_percent, forward.1 + one_percent] {
+            assert!(route::cycle_profit(&markets, forward.0, x) < forward.2);
+        }
+        assert_eq!(backward.1, Int256::zero());
+
+        // the profit is still increasing at the cap, up to rounding
+        let cap = Int256::from(1_000_000_000);
+        let (x, _) = route::optimize_cycle(&markets, forward.0, cap).unwrap();
+        assert!(x <= cap && x > cap * Int256::from(99) / Int256::from(100));
+
+        let mut network = Network::embedded().unwrap();
+        let btc_usdt = network.astroport_pool("btc-usdt").unwrap().clone();
+        network
+            .astroport
+            .pools
+            .insert("btc-eth".to_string(), btc_usdt);
+        let instructions = route::compose_cycle(
+            &network,
+            &markets,
+            forward.0,
+            forward.1,
+            forward.1,
+            Plane::Wasm,
+            DEFAULT_SLIPPAGE_BPS,
+            "lux1jcltmuhplrdcwp7stlr4hlhlhgd4htqhu86cqx".to_string(),
+            "DRK5Bi2NwkGRPsqHJSyy6rhUo3uQ8YHtt1xUWbu7Bnsx".to_string(),
+        )
+        .unwrap();
+        // 2 wasm swaps, eth to svm, svm swap, usdt back to cosmos
+        assert_eq!(
+            instructions
+                .iter()
+                .map(|ix| (ix.plane(), ix.action()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Plane::Wasm, FisAction::VmInvoke),
+                (Plane::Wasm, FisAction::VmInvoke),
+                (Plane::Cosmos, FisAction::CosmosInvoke),
+                (Plane::Svm, FisAction::VmInvoke),
+                (Plane::Cosmos, FisAction::CosmosInvoke),
+            ]
+        );
+        // each swap spends the minimum output of the one before, so does the transfer
+        let mut amount = forward.1;
+        for hop in &forward.0[..2] {
+            let (_, output) = markets[hop.market].pool.swap_output(amount, hop.a_for_b);
+            amount = less_slippage(output, DEFAULT_SLIPPAGE_BPS).unwrap();
+        }
+        let transfer = from_json::<MsgAstroTransfer>(instructions[2].msg()).unwrap();
+        // whole svm units, eth has 9 decimals there
+        let convert = |src: Plane, dst: Plane, amount: Uint128| {
+            network.denoms.convert("eth", src, dst, amount).unwrap()
+        };
+        let svm_amount = convert(
+            Plane::Cosmos,
+            Plane::Svm,
+            Uint128::try_from(amount).unwrap(),
+        );
+        assert_eq!(
+            transfer.coin.amount,
+            convert(Plane::Svm, Plane::Wasm, svm_amount)
+        );
+    }
+
+    #[test]
+    fn test_cycle_min_return() {
+        let network = Network::embedded().unwrap();
+        // btc is 10% dearer on raydium
+        let markets = vec![
+            market(
+                "btc-usdt",
+                Plane::Cosmos,
+                1_000_000_000_000,
+                1_000_000_000_000,
+            ),
+            market("btc-usdt", Plane::Svm, 1_100_000_000_000, 1_000_000_000_000),
+        ];
+        let hops = [
+            route::Hop {
+                market: 0,
+                a_for_b: true,
+            },
+            route::Hop {
+                market: 1,
+                a_for_b: false,
+            },
+        ];
+        let x = Int256::from(1_000_000_000);
+        let compose = |min_return: Int256| {
+            route::compose_cycle(
+                &network,
+                &markets,
+                &hops,
+                x,
+                min_return,
+                Plane::Svm,
+                500,
+                "lux1jcltmuhplrdcwp7stlr4hlhlhgd4htqhu86cqx".to_string(),
+                "DRK5Bi2NwkGRPsqHJSyy6rhUo3uQ8YHtt1xUWbu7Bnsx".to_string(),
+            )
+        };
+
+        // 5% off each estimate chains to less than x
+        let mut chained = x;
+        for hop in &hops {
+            let (_, output) = markets[hop.market].pool.swap_output(chained, hop.a_for_b);
+            chained = less_slippage(output, 500).unwrap();
+        }
+        assert!(chained < x);
+
+        // the raydium swap still returns x and a 1% profit
+        let min_return = x * Int256::from(101) / Int256::from(100);
+        let instructions = compose(min_return).unwrap();
+        let tx = from_json::<MsgTransaction>(instructions.last().unwrap().msg()).unwrap();
+        let data = tx.instructions[1].data.as_slice();
+        let min_output = Int256::from(u64::from_le_bytes(data[16..24].try_into().unwrap()));
+        assert!(min_output >= min_return && min_output > chained);
+
+        // past the estimate of the last swap
+        let err = compose(x * Int256::from(11) / Int256::from(10)).unwrap_err();
+        assert!(matches!(err, NexusError::SlippageExceeded { .. }));
+    }
+
+    #[test]
+    fn test_best_swap_split() {
+        let markets = vec![
+            market(
+                "btc-usdt",
+                Plane::Cosmos,
+                1_000_000_000_000,
+                1_000_000_000_000,
+            ),
+            market(
+                "eth-usdt",
+                Plane::Cosmos,
+                1_000_000_000_000,
+                1_000_000_000_000,
+            ),
+            market("btc-usdt", Plane::Svm, 1_000_000_000_000, 1_000_000_000_000),
+        ];
+        let amount = Int256::from(100_000_000_000i128);
+        let splits = route::split_swap(&markets, "usdt", "btc", amount).unwrap();
+        // same pools, same share
+        assert_eq!(
+            splits
+                .iter()
+                .map(|split| (split.hop.market, split.amount))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, Int256::from(50_000_000_000i128)),
+                (2, Int256::from(50_000_000_000i128)),
+            ]
+        );
+        let (_, single) = markets[0].pool.swap_output(amount, true);
+        assert!(splits[0].output + splits[1].output > single);
+        assert_eq!(
+            route::split_swap(&markets, "btc", "eth", amount).unwrap_err(),
+            NexusError::UnsupportedPair("btc-eth".to_string())
+        );
+
+        let instructions = route::compose_split(
+            &Network::embedded().unwrap(),
+            &markets,
+            &splits,
+            Plane::Cosmos,
+            "lux1jcltmuhplrdcwp7stlr4hlhlhgd4htqhu86cqx".to_string(),
+            "DRK5Bi2NwkGRPsqHJSyy6rhUo3uQ8YHtt1xUWbu7Bnsx".to_string(),
+            DEFAULT_SLIPPAGE_BPS,
+        )
+        .unwrap();
+        // usdt to svm and btc back for the raydium share only
+        assert_eq!(
+            instructions
+                .iter()
+                .map(|ix| (ix.plane(), ix.action()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Plane::Wasm, FisAction::VmInvoke),
+                (Plane::Cosmos, FisAction::CosmosInvoke),
+                (Plane::Svm, FisAction::VmInvoke),
+                (Plane::Cosmos, FisAction::CosmosInvoke),
+            ]
+        );
+        // the minimum output is moved back, the estimate may not be filled
+        let back = from_json::<MsgAstroTransfer>(instructions[3].msg()).unwrap();
+        assert_eq!(
+            Int256::from(back.coin.amount.u128()),
+            splits[1].output * Int256::from(99) / Int256::from(100)
+        );
+    }
+
+    #[test]
+    fn test_optimize_matches_brute_force() {
+        for decimal_multiplier in [1, 1000] {
+            let astroport = market(
+                "btc-usdt",
+                Plane::Cosmos,
+                1_000_000,
+                1_000_000 * decimal_multiplier,
+            );
+            // raydium rounds b to its svm decimals
+            let raydium = raydium::RaydiumPool {
+                dex_name: RAYDIUM.to_string(),
+                denom_plane: Plane::Svm,
+                a: Int256::from(1_100_000),
+                b: Int256::from(1_000_000 * decimal_multiplier),
+                fee_rate: Int256::from(1000),
+                denom_a: "usdt".to_string(),
+                denom_b: "btc".to_string(),
+                decimal_multiplier_a: Int256::one(),
+                decimal_multiplier_b: Int256::from(decimal_multiplier),
+                lp_mint: String::new(),
+                lp_supply: Int256::zero(),
+                status: 0,
+                open_time: 0,
+                block_time: 1,
+                twap: None,
+            };
+            let legs: [(&dyn Pool, bool); 2] = [(astroport.pool.as_ref(), true), (&raydium, false)];
+            let profit = |x: i128| {
+                let (_, _, _, output) = calculate_pools_output(legs[0].0, legs[1].0, x.into());
+                output - Int256::from(x)
+            };
+            let best = (0..=100_000).map(profit).max().unwrap();
+
+            let (x, expected) = route::optimize(&legs, Int256::MAX).unwrap();
+            assert!(best > Int256::zero());
+            assert_eq!(expected, best);
+            assert_eq!(profit(x.to_string().parse().unwrap()), best);
+            // no profit the other way
+            assert_eq!(
+                route::optimize(&[(&raydium, true), (legs[0].0, false)], Int256::MAX).unwrap(),
+                (Int256::zero(), Int256::zero())
+            );
+        }
+
+        // uniswap liquidity deepens 100 times a tick spacing above the price, far past the
+        // optimum on its spot reserves, btc is 10% dearer on astroport
+        let network = Network::embedded().unwrap();
+        let info = PoolInfo {
+            sqrt_price_x96: Uint256::one() << 96,
+            tick: Int256::zero(),
+            protocol_fee: 0,
+            lp_fee: 3000,
+        };
+        let liquidity = 1_000_000_000u128;
+        let uniswap = UniswapPool::new(&network, "btc-usdt")
+            .unwrap()
+            .with_state(
+                &info,
+                liquidity,
+                vec![Tick {
+                    index: 60,
+                    liquidity_net: 100 * liquidity as i128,
+                }],
+            )
+            .unwrap();
+        let astroport = market(
+            "btc-usdt",
+            Plane::Cosmos,
+            1_100_000_000_000,
+            1_000_000_000_000,
+        );
+        let legs: [(&dyn Pool, bool); 2] = [(&uniswap, true), (astroport.pool.as_ref(), false)];
+        let profit = |x: Int256| {
+            legs.iter().fold(x, |amount, (pool, a_for_b)| {
+                pool.swap_output(amount, *a_for_b).1
+            }) - x
+        };
+        let (x, best) = route::optimize(&legs, Int256::MAX).unwrap();
+        assert!(x > Int256::from(liquidity));
+        assert_eq!(profit(x), best);
+        for other in [
+            x * Int256::from(99) / Int256::from(100),
+            x * Int256::from(101) / Int256::from(100),
+        ] {
+            assert!(profit(other) <= best);
+        }
+        // bounded by the max input
+        let max_input = Int256::from(liquidity);
+        let (x, _) = route::optimize(&legs, max_input).unwrap();
+        assert!(x <= max_input && x > max_input * Int256::from(99) / Int256::from(100));
+    }
+
+    #[test]
+    fn test_quote() {
+        let network = Network::embedded().unwrap();
+        let markets = vec![
+            market("btc-usdt", Plane::Svm, 100_000_000_000, 100_000_000_000),
+            market(
+                "eth-usdt",
+                Plane::Cosmos,
+                1_000_000_000_000,
+                1_000_000_000_000,
+            ),
+            market(
+                "btc-usdt",
+                Plane::Cosmos,
+                1_000_000_000_000,
+                1_000_000_000_000,
+            ),
+        ];
+        let amount = Int256::from(1_000_000_000i128);
+        let quotes = route::quote(&network, &markets, "usdt", "btc", amount).unwrap();
+        // deepest pool first
+        assert_eq!(
+            quotes
+                .iter()
+                .map(|quote| quote.dex_name.as_str())
+                .collect::<Vec<_>>(),
+            vec![ASTROPORT, RAYDIUM]
+        );
+        let (_, output) = markets[2].pool.swap_output(amount, true);
+        assert_eq!(quotes[0].output, output);
+        assert_eq!(quotes[0].fee, Int256::from(3_000_000));
+        // usdt has 6 decimals and btc 8
+        assert_eq!(
+            quotes[0].effective_price,
+            Decimal256::from_ratio(
+                to_uint256(output) * Uint256::from(1_000_000u32),
+                Uint256::from(100_000_000_000_000_000u128)
+            )
+        );
+        // 0.1% and 1% of the pool move the price by ~0.1% and ~1%
+        assert_eq!(quotes[0].price_impact_bps, 9);
+        assert_eq!(quotes[1].price_impact_bps, 98);
+
+        assert_eq!(
+            route::quote(&network, &markets, "btc", "eth", amount).unwrap_err(),
+            NexusError::UnsupportedPair("btc-eth".to_string())
+        );
+    }
+
+    fn deposit(pool_name: &str, max_amount_a: i128, max_amount_b: i128) -> Deposit {
+        Deposit {
+            dex_name: "".to_string(),
+            pool_name: pool_name.to_string(),
+            sender: "lux1jcltmuhplrdcwp7stlr4hlhlhgd4htqhu86cqx".to_string(),
+            sender_svm: "DRK5Bi2NwkGRPsqHJSyy6rhUo3uQ8YHtt1xUWbu7Bnsx".to_string(),
+            max_amount_a: Int128::new(max_amount_a),
+            max_amount_b: Int128::new(max_amount_b),
+            slippage_bps: DEFAULT_SLIPPAGE_BPS,
+        }
+    }
+
+    fn withdrawal(pool_name: &str, lp_amount: i128) -> Withdrawal {
+        Withdrawal {
+            dex_name: "".to_string(),
+            pool_name: pool_name.to_string(),
+            sender: "lux1jcltmuhplrdcwp7stlr4hlhlhgd4htqhu86cqx".to_string(),
+            sender_svm: "DRK5Bi2NwkGRPsqHJSyy6rhUo3uQ8YHtt1xUWbu7Bnsx".to_string(),
+            lp_amount: Int128::new(lp_amount),
+            slippage_bps: DEFAULT_SLIPPAGE_BPS,
+        }
+    }
+
+    #[test]
+    fn test_astroport_liquidity() {
+        let network = Network::embedded().unwrap();
+        // 1 btc for 0.5 usdt, 1000000000 lp tokens
+        let pool = market("btc-usdt", Plane::Cosmos, 1_000_000_000, 2_000_000_000).pool;
+
+        // btc is the scarcer side at the pool ratio
+        let deposit = deposit("btc-usdt", 100_000_000, 100_000_000);
+        let estimate = deposit.estimate(pool.as_ref()).unwrap();
+        assert_eq!(
+            (estimate.amount_a, estimate.amount_b, estimate.lp_amount),
+            (
+                Int256::from(50_000_000),
+                Int256::from(100_000_000),
+                Int256::from(50_000_000)
+            )
+        );
+        let ix = &pool.compose_add_liquidity_fis(&network, &deposit).unwrap()[0];
+        let msg = String::from_utf8(ix.msg().to_vec()).unwrap();
+        assert!(msg.contains(r#""min_lp_to_receive":"49500000""#));
+        assert!(msg.contains(r#""slippage_tolerance":"0.01""#));
+        // funds are sorted by denom
+        assert!(msg.contains(
+            r#""funds":[{"denom":"btc","amount":"100000000"},{"denom":"usdt","amount":"50000000"}]"#
+        ));
+
+        let withdrawal = withdrawal("btc-usdt", 50_000_000);
+        let estimate = withdrawal.estimate(pool.as_ref()).unwrap();
+        assert_eq!(
+            (estimate.amount_a, estimate.amount_b),
+            (Int256::from(50_000_000), Int256::from(100_000_000))
+        );
+        let ix = &pool
+            .compose_remove_liquidity_fis(&network, &withdrawal)
+            .unwrap()[0];
+        let msg = String::from_utf8(ix.msg().to_vec()).unwrap();
+        let lp_denom = astroport::lp_denom(network.astroport_pool("btc-usdt").unwrap());
+        assert!(msg.contains(&format!(
+            r#""funds":[{{"denom":"{}","amount":"50000000"}}]"#,
+            lp_denom
+        )));
+        assert!(msg.contains(r#""amount":"49500000""#) && msg.contains(r#""amount":"99000000""#));
+
+        assert_eq!(
+            self::withdrawal("btc-usdt", 1_000_000_001)
+                .estimate(pool.as_ref())
+                .unwrap_err()
+                .code(),
+            2
+        );
+        let empty = market("btc-usdt", Plane::Cosmos, 0, 0).pool;
+        assert_eq!(deposit.estimate(empty.as_ref()).unwrap_err().code(), 2);
+    }
+
+    #[test]
+    fn test_raydium_uniswap_liquidity() {
+        let network = Network::embedded().unwrap();
+        let raydium = raydium::RaydiumPool {
+            a: Int256::from(1_000_000_000),
+            b: Int256::from(2_000_000_000),
+            lp_supply: Int256::from(1_000_000_000),
+            lp_mint: "ErDYXZUZ9rpSSvdWvrsQwgh6K4BQeoY2CPyv1FeD1S9r".to_string(),
+            ..raydium::RaydiumPool::new(&network, "btc-usdt").unwrap()
+        };
+        let u64_at = |data: &[u8], offset: usize| {
+            u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+        };
+
+        // btc is token 0
+        let ix = &raydium
+            .compose_add_liquidity_fis(&network, &deposit("btc-usdt", 100_000_000, 100_000_000))
+            .unwrap()[0];
+        let tx = from_json::<MsgTransaction>(ix.msg()).unwrap();
+        let deposit_ix = &tx.instructions[1];
+        assert_eq!(deposit_ix.accounts.len(), 13);
+        let data = deposit_ix.data.as_slice();
+        assert_eq!(
+            [u64_at(data, 8), u64_at(data, 16), u64_at(data, 24)],
+            [49_500_000, 100_000_000, 50_000_000]
+        );
+
+        let ix = &raydium
+            .compose_remove_liquidity_fis(&network, &withdrawal("btc-usdt", 50_000_000))
+            .unwrap()[0];
+        let tx = from_json::<MsgTransaction>(ix.msg()).unwrap();
+        // token accounts are created first, the withdraw ix also takes the memo program
+        let withdraw_ix = &tx.instructions[2];
+        assert_eq!(withdraw_ix.accounts.len(), 14);
+        let data = withdraw_ix.data.as_slice();
+        assert_eq!(
+            [u64_at(data, 8), u64_at(data, 16), u64_at(data, 24)],
+            [50_000_000, 99_000_000, 49_500_000]
+        );
+
+        // the full range liquidity of the amounts never needs more than them
+        let (tick_lower, tick_upper) = clmm::full_range_ticks(60);
+        assert_eq!((tick_lower, tick_upper), (-887220, 887220));
+        let (lower, upper) = (
+            clmm::sqrt_price_at_tick(tick_lower).unwrap(),
+            clmm::sqrt_price_at_tick(tick_upper).unwrap(),
+        );
+        let sqrt_price = Uint256::one() << 96;
+        let (amount0, amount1) = (Uint256::from(1_000_000u32), Uint256::from(2_000_000u32));
+        let liquidity =
+            clmm::liquidity_for_amounts(sqrt_price, lower, upper, amount0, amount1).unwrap();
+        assert_eq!(liquidity, 1_000_000);
+        assert!(clmm::amount0_delta(sqrt_price, upper, liquidity, true).unwrap() <= amount0);
+        assert!(clmm::amount1_delta(lower, sqrt_price, liquidity, true).unwrap() <= amount1);
+
+        let info = PoolInfo {
+            sqrt_price_x96: sqrt_price,
+            tick: Int256::zero(),
+            protocol_fee: 0,
+            lp_fee: 3000,
+        };
+        let uniswap = UniswapPool::new(&network, "btc-usdt")
+            .unwrap()
+            .with_state(&info, 1_000_000_000_000, vec![])
+            .unwrap();
+        assert_eq!(uniswap.lp_supply(), Int256::from(1_000_000_000_000i128));
+        // approve both denoms, then modify the position
+        let deposit = deposit("btc-usdt", 1_000_000, 1_000_000);
+        assert_eq!(
+            uniswap
+                .compose_add_liquidity_fis(&network, &deposit)
+                .unwrap()
+                .len(),
+            3
+        );
+        let unpriced = UniswapPool::new(&network, "btc-usdt").unwrap();
+        assert_eq!(
+            unpriced
+                .compose_add_liquidity_fis(&network, &deposit)
+                .unwrap_err()
+                .code(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_swap_slippage_limits() {
+        let network = Network::embedded().unwrap();
+        let swap = Swap {
+            dex_name: "".to_string(),
+            pool_name: "btc-usdt".to_string(),
+            sender: "lux1jcltmuhplrdcwp7stlr4hlhlhgd4htqhu86cqx".to_string(),
+            denom: "usdt".to_string(),
+            amount: Int128::new(1_000_000_000),
+            sender_svm: "DRK5Bi2NwkGRPsqHJSyy6rhUo3uQ8YHtt1xUWbu7Bnsx".to_string(),
+            slippage_bps: 100,
+        };
+
+        // min output is the estimate less 1%
+        let raydium = market("btc-usdt", Plane::Svm, 1_000_000_000_000, 1_000_000_000_000).pool;
+        let (_, estimate) = raydium.swap_output(Int256::from(1_000_000_000), true);
+        let min_output = estimate * Int256::from(99) / Int256::from(100);
+        assert_eq!(
+            swap.min_output(raydium.as_ref()).unwrap(),
+            ("btc".to_string(), min_output)
+        );
+        let ix = &raydium.compose_swap_fis(&network, &swap).unwrap()[0];
+        let tx = from_json::<MsgTransaction>(ix.msg()).unwrap();
+        let data = tx.instructions[1].data.as_slice();
+        assert_eq!(
+            Int256::from(u64::from_le_bytes(data[16..24].try_into().unwrap())),
+            min_output
+        );
+
+        // astroport reverts below offer / belief_price less max_spread
+        let astroport = market(
+            "btc-usdt",
+            Plane::Cosmos,
+            1_000_000_000_000,
+            1_000_000_000_000,
+        )
+        .pool;
+        let ix = &astroport.compose_swap_fis(&network, &swap).unwrap()[0];
+        let msg = String::from_utf8(ix.msg().to_vec()).unwrap();
+        let (_, estimate) = swap.estimate(astroport.as_ref()).unwrap();
+        let belief_price = Decimal::from_ratio(
+            1_000_000_000u128,
+            estimate.to_string().parse::<u128>().unwrap(),
+        );
+        assert!(msg.contains(&format!(r#""belief_price":"{}""#, belief_price)));
+        assert!(msg.contains(r#""max_spread":"0.01""#));
+
+        // uniswap stops 1% past the estimated price, usdt is currency1 so the price rises
+        let info = PoolInfo {
+            sqrt_price_x96: Uint256::one() << 96,
+            tick: Int256::zero(),
+            protocol_fee: 0,
+            lp_fee: 3000,
+        };
+        let uniswap = UniswapPool::new(&network, "btc-usdt")
+            .unwrap()
+            .with_state(&info, 1_500_000_000_000_000_000, test_ticks())
+            .unwrap();
+        let exact = Swap {
+            slippage_bps: 0,
+            ..swap.clone()
+        };
+        let estimated = uniswap.sqrt_price_limit(&exact).unwrap();
+        assert!(estimated > info.sqrt_price_x96);
+        let limit = uniswap.sqrt_price_limit(&swap).unwrap();
+        // sqrt(1 / 0.99) ~ 1.005
+        assert_eq!(
+            limit * Uint256::from(1000u32) / estimated,
+            Uint256::from(1005u32)
+        );
+        // a price moved right below the limit fills part of the swap, short of the minimum
+        let sqrt_price_x96 = limit - (Uint256::one() << 64);
+        let moved = clmm::ConcentratedPool {
+            sqrt_price_x96,
+            tick: (0..200)
+                .take_while(|tick| clmm::sqrt_price_at_tick(*tick).unwrap() <= sqrt_price_x96)
+                .last()
+                .unwrap(),
+            ..uniswap.state.clone().unwrap()
+        };
+        let amount_in = Uint256::from(1_000_000_000u128);
+        let filled = moved
+            .swap_exact_in_to(false, amount_in, 3000, limit)
+            .unwrap();
+        assert_eq!(filled.sqrt_price_x96, limit);
+        assert!(!filled.amount_in.is_zero() && filled.amount_in < amount_in);
+        let (_, min_output) = swap.min_output(&uniswap).unwrap();
+        assert!(to_int256(filled.amount_out) < min_output);
+
+        let unpriced = UniswapPool::new(&network, "btc-usdt").unwrap();
+        assert_eq!(
+            unpriced
+                .compose_swap_fis(&network, &swap)
+                .unwrap_err()
+                .code(),
+            2
+        );
+        let too_loose = Swap {
+            slippage_bps: 10_001,
+            ..swap
+        };
+        assert_eq!(
+            too_loose.min_output(raydium.as_ref()).unwrap_err().code(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_raydium_pool_state() {
+        let network = Network::embedded().unwrap();
+        let accounts = network.raydium_pool("btc-usdt").unwrap();
+        let key = |account: &str| Pubkey::from_string(account).unwrap().0.to_vec();
+        let account = |data: Vec<u8>| {
+            Binary::from(
+                cosmwasm_std::to_json_vec(&Account {
+                    pubkey: Binary::default(),
+                    owner: Binary::default(),
+                    lamports: Uint64::zero(),
+                    data: Binary::from(data),
+                    executable: false,
+                    rent_epoch: Uint64::zero(),
+                })
+                .unwrap(),
+            )
+        };
+        let vault = |mint: &str, amount: u64| {
+            let mut data = vec![0u8; 165];
+            data[..32].copy_from_slice(&key(mint));
+            data[64..72].copy_from_slice(&amount.to_le_bytes());
+            data[108] = 1;
+            account(data)
+        };
+        let pool_state = |amm_config: &str, status: u8, open_time: u64| {
+            let mut data = PoolState::DISCRIMINATOR.to_vec();
+            data.extend(key(amm_config));
+            data.extend([0u8; 32 * 3]);
+            data.extend(key("ErDYXZUZ9rpSSvdWvrsQwgh6K4BQeoY2CPyv1FeD1S9r"));
+            data.extend(key(&accounts.token0_mint));
+            data.extend(key(&accounts.token1_mint));
+            data.extend([0u8; 32 * 3]);
+            // auth bump, status, lp, btc and usdt decimals
+            data.extend([255, status, 8, 8, 6]);
+            // lp supply, btc protocol fees, 3 other fee counters and open time
+            for word in [1_000_000u64, 10, 0, 0, 0, open_time] {
+                data.extend(word.to_le_bytes());
+            }
+            data.resize(PoolState::LEN, 0);
+            account(data)
+        };
+        // bump, disable create pool, index and the trade fee rate
+        let mut amm_config = AmmConfig::DISCRIMINATOR.to_vec();
+        amm_config.extend([254, 0, 0, 0]);
+        amm_config.extend(2500u64.to_le_bytes());
+        amm_config.resize(AmmConfig::LEN, 0);
+        // latest observation at the index, the other timestamps and cumulative token 0 prices
+        // are written from the previous slot backwards
+        let observation = |pool_id: &str, index: u16, history: &[(u64, u128)]| {
+            let mut observations = [(0u64, 0u128); OBSERVATION_NUM];
+            for (i, entry) in history.iter().enumerate() {
+                observations[(usize::from(index) + OBSERVATION_NUM - i) % OBSERVATION_NUM] = *entry;
+            }
+            let mut data = ObservationState::DISCRIMINATOR.to_vec();
+            data.push(u8::from(!history.is_empty()));
+            data.extend(index.to_le_bytes());
+            data.extend(key(pool_id));
+            for (timestamp, cumulative_price) in observations {
+                data.extend(timestamp.to_le_bytes());
+                data.extend(cumulative_price.to_le_bytes());
+                data.extend(0u128.to_le_bytes());
+            }
+            data.resize(ObservationState::LEN, 0);
+            account(data)
+        };
+        let fis_input = |pool_state: Binary, amm_config: &[u8]| {
+            vec![FISInput {
+                data: vec![
+                    vault(&accounts.token0_mint, 1_000_010),
+                    vault(&accounts.token1_mint, 2_000_000_000),
+                    pool_state,
+                    account(amm_config.to_vec()),
+                    observation(&accounts.pool_state_account, 0, &[]),
+                ],
+            }]
+        };
+
+        // the trade fee of the amm config, reserves net of the protocol fees
+        let input = fis_input(
+            pool_state(&accounts.amm_config_account, 0, 100),
+            &amm_config,
+        );
+        let pool = raydium::RaydiumPool::from_fis(&network, "btc-usdt", &input, 0, 101).unwrap();
+        assert_eq!(
+            (pool.fee_rate, pool.a, pool.b, pool.lp_supply),
+            (
+                Int256::from(2500),
+                Int256::from(2_000_000_000),
+                Int256::from(1_000_000),
+                Int256::from(1_000_000)
+            )
+        );
+        // 2500 fee rounded up, then 997_500 * 1_000_000 / 2_000_997_500
+        assert_eq!(
+            pool.swap_output(Int256::from(1_000_000), true),
+            ("btc".to_string(), Int256::from(498))
+        );
+
+        // more fees accrued than the vault holds
+        let mut input = fis_input(
+            pool_state(&accounts.amm_config_account, 0, 100),
+            &amm_config,
+        );
+        input[0].data[0] = vault(&accounts.token0_mint, 9);
+        let err = raydium::RaydiumPool::from_fis(&network, "btc-usdt", &input, 0, 101).unwrap_err();
+        assert!(matches!(err, NexusError::InvalidFisInput { .. }));
+
+        // not open yet, or swaps disabled
+        let swap = Swap {
+            dex_name: RAYDIUM.to_string(),
+            pool_name: "btc-usdt".to_string(),
+            sender: "lux1jcltmuhplrdcwp7stlr4hlhlhgd4htqhu86cqx".to_string(),
+            denom: "usdt".to_string(),
+            amount: Int128::new(1_000_000),
+            sender_svm: "DRK5Bi2NwkGRPsqHJSyy6rhUo3uQ8YHtt1xUWbu7Bnsx".to_string(),
+            slippage_bps: 100,
+        };
+        assert!(pool.compose_swap_fis(&network, &swap).is_ok());
+        for (status, block_time) in [(0, 100), (1 << PoolStatusBit::Swap as u8, 101)] {
+            let input = fis_input(
+                pool_state(&accounts.amm_config_account, status, 100),
+                &amm_config,
+            );
+            let pool = raydium::RaydiumPool::from_fis(&network, "btc-usdt", &input, 0, block_time)
+                .unwrap();
+            assert_eq!(
+                pool.swap_output(Int256::from(1_000_000), true).1,
+                Int256::zero()
+            );
+            assert_eq!(
+                pool.compose_swap_fis(&network, &swap).unwrap_err().code(),
+                2
+            );
+        }
+
+        // another pool's config or a wrong account type
+        let input = fis_input(
+            pool_state(&accounts.pool_state_account, 0, 100),
+            &amm_config,
+        );
+        let err = raydium::RaydiumPool::from_fis(&network, "btc-usdt", &input, 0, 101).unwrap_err();
+        assert!(matches!(err, NexusError::InvalidFisInput { .. }));
+        amm_config[0] = 0;
+        let input = fis_input(
+            pool_state(&accounts.amm_config_account, 0, 100),
+            &amm_config,
+        );
+        let err = raydium::RaydiumPool::from_fis(&network, "btc-usdt", &input, 0, 101).unwrap_err();
+        assert!(matches!(err, NexusError::InvalidFisInput { .. }));
+        amm_config[0] = AmmConfig::DISCRIMINATOR[0];
+
+        // no observations yet, no twap to check against
+        let mut input = fis_input(
+            pool_state(&accounts.amm_config_account, 0, 100),
+            &amm_config,
+        );
+        let pool = raydium::RaydiumPool::from_fis(&network, "btc-usdt", &input, 0, 2000).unwrap();
+        assert_eq!(pool.twap(), None);
+        assert!(near_twap(&pool, 0).unwrap());
+
+        // 2000 usdt per btc for the last 900 seconds across the end of the ring, 1000 before
+        let x32 = 1u128 << 32;
+        input[0].data[4] = observation(
+            &accounts.pool_state_account,
+            0,
+            &[
+                (1900, 1000 * x32 * 900 + 2000 * x32 * 900),
+                (1000, 1000 * x32 * 900),
+                (100, 0),
+            ],
+        );
+        let pool = raydium::RaydiumPool::from_fis(&network, "btc-usdt", &input, 0, 2000).unwrap();
+        assert_eq!(pool.twap(), Some(Decimal256::from_ratio(2000u128, 1u128)));
+        assert_eq!(pool.twap(), Some(pool.spot_price().unwrap()));
+        assert!(near_twap(&pool, 0).unwrap());
+
+        // the spot price doubled against the average
+        input[0].data[4] = observation(
+            &accounts.pool_state_account,
+            7,
+            &[(1900, 1000 * x32 * 900), (1000, 0)],
+        );
+        let pool = raydium::RaydiumPool::from_fis(&network, "btc-usdt", &input, 0, 2000).unwrap();
+        assert_eq!(pool.twap(), Some(Decimal256::from_ratio(1000u128, 1u128)));
+        assert!(!near_twap(&pool, 9_999).unwrap());
+        assert!(near_twap(&pool, 10_000).unwrap());
+
+        // another pool's observations
+        input[0].data[4] = observation(&accounts.amm_config_account, 0, &[]);
+        let err =
+            raydium::RaydiumPool::from_fis(&network, "btc-usdt", &input, 0, 2000).unwrap_err();
+        assert!(matches!(err, NexusError::InvalidFisInput { .. }));
+    }
+
+    #[test]
+    fn test_whirlpool_pool() {
+        let mut network = Network::embedded().unwrap();
+        let program_id = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+        let whirlpool_id = Pubkey([7; 32]);
+        let btc = Pubkey::from_string(network.denoms.address("btc", Plane::Svm).unwrap()).unwrap();
+        let usdt =
+            Pubkey::from_string(network.denoms.address("usdt", Plane::Svm).unwrap()).unwrap();
+        // 2^(32000 / 10000 * log2(1.0001)) ~ 600 usdt units per btc unit, priced in token b
+        let btc_is_a = btc < usdt;
+        let sign = if btc_is_a { 1 } else { -1 };
+        let (tick, spacing, liquidity) = (64_000 * sign, 64, 1_000_000_000_000u128);
+        let initialized = Tick {
+            index: 60_032 * sign,
+            liquidity_net: 500_000_000_000 * i128::from(sign),
+        };
+        let span = TICK_ARRAY_SIZE * spacing;
+        let start = whirlpool::tick_array_start(tick, spacing);
+        network.whirlpool.program_id = program_id.to_string();
+        network.whirlpool.pools.insert(
+            "btc-usdt".to_string(),
+            WhirlpoolAccounts {
+                whirlpool: whirlpool_id.to_string(),
+                tick_arrays: vec![start - 2 * span, start - span, start, start + span],
+            },
+        );
+
+        let account = |data: Vec<u8>| {
+            Binary::from(
+                cosmwasm_std::to_json_vec(&Account {
+                    pubkey: Binary::default(),
+                    owner: Binary::default(),
+                    lamports: Uint64::zero(),
+                    data: Binary::from(data),
+                    executable: false,
+                    rent_epoch: Uint64::zero(),
+                })
+                .unwrap(),
+            )
+        };
+        let sqrt_price_x64 = clmm::sqrt_price_at_tick(tick).unwrap() >> 32;
+        let (mint_a, mint_b) = if btc_is_a { (btc, usdt) } else { (usdt, btc) };
+        let mut data = Whirlpool::DISCRIMINATOR.to_vec();
+        data.extend([0u8; 32 + 1]);
+        data.extend((spacing as u16).to_le_bytes());
+        data.extend((spacing as u16).to_le_bytes());
+        // 0.3% fee, protocol fee rate
+        data.extend(3000u16.to_le_bytes());
+        data.extend(300u16.to_le_bytes());
+        data.extend(liquidity.to_le_bytes());
+        data.extend(
+            Uint128::try_from(sqrt_price_x64)
+                .unwrap()
+                .u128()
+                .to_le_bytes(),
+        );
+        data.extend(tick.to_le_bytes());
+        data.extend([0u8; 16]);
+        for (mint, vault) in [(mint_a, [1u8; 32]), (mint_b, [2u8; 32])] {
+            data.extend(mint.0);
+            data.extend(vault);
+            data.extend([0u8; 16]);
+        }
+        data.resize(Whirlpool::LEN, 0);
+        let whirlpool_account = account(data);
+        let tick_array = |start_tick_index: i32| {
+            let mut data = TickArray::DISCRIMINATOR.to_vec();
+            data.extend(start_tick_index.to_le_bytes());
+            for i in 0..TICK_ARRAY_SIZE {
+                let tick = if start_tick_index + i * spacing == initialized.index {
+                    Some(initialized.liquidity_net)
+                } else {
+                    None
+                };
+                data.push(u8::from(tick.is_some()));
+                data.extend(tick.unwrap_or_default().to_le_bytes());
+                data.extend([0u8; 16 * 6]);
+            }
+            data.extend(whirlpool_id.0);
+            account(data)
+        };
+        // the outermost array below the price isn't initialized
+        let null = Binary::from(b"null".to_vec());
+        let input = vec![FISInput {
+            data: vec![
+                whirlpool_account.clone(),
+                null.clone(),
+                tick_array(start - span),
+                tick_array(start),
+                tick_array(start + span),
+            ],
+        }];
+
+        let query = PoolQuery::new(&network).unwrap();
+        let pair = query.pair("btc-usdt").unwrap();
+        let instruction = pair.whirlpool.unwrap();
+        assert_eq!(query.query.instructions[instruction].input.len(), 5);
+        let pool: Box<dyn Pool> =
+            Box::new(WhirlpoolPool::from_fis(&network, "btc-usdt", &input, 0).unwrap());
+        assert_eq!(pool.fee_rate(), Int256::from(3000));
+        assert_eq!(pool.lp_supply(), Int256::from(liquidity));
+        assert_eq!(
+            pool.spot_price()
+                .unwrap()
+                .to_string()
+                .split_once('.')
+                .unwrap()
+                .0,
+            "601"
+        );
+
+        // selling 90 btc crosses the initialized tick, as on the pool without array bounds
+        let state = ConcentratedPool {
+            sqrt_price_x96: whirlpool::sqrt_price_x96(
+                Uint128::try_from(sqrt_price_x64).unwrap().u128(),
+            ),
+            tick,
+            liquidity,
+            tick_spacing: spacing,
+            ticks: vec![initialized.clone()],
+        };
+        for (denom, amount) in [("btc", 9_000_000_000u128), ("usdt", 1_000_000_000)] {
+            let a_to_b = (denom == "btc") == btc_is_a;
+            let expected = state
+                .swap_exact_in(a_to_b, Uint256::from(amount), 3000)
+                .unwrap();
+            assert_eq!(expected.amount_in, Uint256::from(amount));
+            assert_eq!(
+                pool.swap_output(Int256::from(amount), denom == "usdt").1,
+                Int256::try_from(expected.amount_out).unwrap()
+            );
+        }
+        let crossed = state
+            .swap_exact_in(btc_is_a, Uint256::from(9_000_000_000u128), 3000)
+            .unwrap();
+        assert!((crossed.tick - initialized.index) * sign < 0);
+        // 1000 btc runs past the queried arrays
+        assert_eq!(
+            pool.swap_output(Int256::from(100_000_000_000u128), false).1,
+            Int256::zero()
+        );
+
+        // swap_v2 over the 3 arrays from the current tick and the oracle, after the output ata
+        let swap = Swap {
+            dex_name: WHIRLPOOL.to_string(),
+            pool_name: "btc-usdt".to_string(),
+            sender: "lux1jcltmuhplrdcwp7stlr4hlhlhgd4htqhu86cqx".to_string(),
+            denom: "btc".to_string(),
+            amount: Int128::new(9_000_000_000),
+            sender_svm: "DRK5Bi2NwkGRPsqHJSyy6rhUo3uQ8YHtt1xUWbu7Bnsx".to_string(),
+            slippage_bps: 100,
+        };
+        let (_, min_output) = swap.min_output(pool.as_ref()).unwrap();
+        let ix = &pool.compose_swap_fis(&network, &swap).unwrap()[0];
+        let tx = from_json::<MsgTransaction>(ix.msg()).unwrap();
+        assert_eq!(tx.instructions.len(), 2);
+        let swap_ix = &tx.instructions[1];
+        assert_eq!(tx.accounts[swap_ix.program_index[0] as usize], program_id);
+        let keys: Vec<&str> = swap_ix
+            .accounts
+            .iter()
+            .map(|account| tx.accounts[account.id_index as usize].as_str())
+            .collect();
+        let program = Pubkey::from_string(program_id).unwrap();
+        let mut expected_keys = whirlpool::swap_tick_arrays(tick, spacing, btc_is_a)
+            .map(|start| {
+                whirlpool::tick_array_address(&program, &whirlpool_id, start)
+                    .unwrap()
+                    .to_string()
+            })
+            .to_vec();
+        expected_keys.push(
+            whirlpool::oracle_address(&program, &whirlpool_id)
+                .unwrap()
+                .to_string(),
+        );
+        assert_eq!(keys.len(), 15);
+        assert_eq!(keys[4], whirlpool_id.to_string());
+        assert_eq!(keys[11..], expected_keys);
+        assert!(swap_ix.accounts[3].is_signer);
+        let data = swap_ix.data.as_slice();
+        assert_eq!(data[8..16], 9_000_000_000u64.to_le_bytes());
+        assert_eq!(
+            Int256::from(u64::from_le_bytes(data[16..24].try_into().unwrap())),
+            min_output
+        );
+        assert_eq!(data[40..], [1, u8::from(btc_is_a), 0]);
+        assert_eq!(
+            pool.compose_add_liquidity_fis(&network, &deposit("btc-usdt", 1, 1))
+                .unwrap_err()
+                .code(),
+            2
+        );
+
+        // an array of another start index
+        let mut wrong = input.clone();
+        wrong[0].data.swap(2, 3);
+        let err = WhirlpoolPool::from_fis(&network, "btc-usdt", &wrong, 0).unwrap_err();
+        assert!(matches!(err, NexusError::InvalidFisInput { .. }));
+    }
+
+    #[test]
+    fn test_astroport_curves() {
+        let network = Network::embedded().unwrap();
+        let decimal = |x: &str| x.parse::<Decimal256>().unwrap();
+        let curve_pool = |curve: astroport::CurvePool, a: i128, b: i128| astroport::AstroportPool {
+            a: a.into(),
+            b: b.into(),
+            curve: Some(curve),
+            ..astroport::AstroportPool::new(&network, "btc-usdt").unwrap()
+        };
+
+        // 1m usdt and 1m btc, priced one to one by the stableswap
+        let (a, b) = (1_000_000_000_000i128, 100_000_000_000_000i128);
+        let stable = astroport::CurvePool::new(
+            Invariant::Stable {
+                amp: decimal("100"),
+            },
+            (6, 8),
+            Decimal256::one(),
+            None,
+            (a.into(), b.into()),
+        )
+        .unwrap();
+        assert!(stable.d.abs_diff(decimal("2000000")) <= decimal("0.000001"));
+        let stable = curve_pool(stable, a, b);
+        let xyk = astroport::AstroportPool {
+            curve: None,
+            ..stable.clone()
+        };
+        // Astroport's compute_d and compute_y give 99900.110864758 btc for a tenth of the pool,
+        // less the 0.1% fee, far less impact than on xyk
+        let (denom, output) = stable.swap_output(Int256::from(100_000_000_000i128), true);
+        assert_eq!(denom, "btc");
+        assert_eq!(output, Int256::from(9_980_021_075_389i128));
+        assert!(output > xyk.swap_output(Int256::from(100_000_000_000i128), true).1);
+        // deeper than the balances at the same price
+        let (spot_a, spot_b) = stable.spot_reserves();
+        assert!(spot_a > Int256::from(a) * Int256::from(10));
+        assert!((spot_b / Int256::from(100) - spot_a).abs() < spot_a / Int256::from(1000));
+
+        // PCL pair listing btc first at 60k usdt, balanced once scaled
+        let response = |json: String| Binary::from(json.into_bytes());
+        let fis_input = vec![FISInput {
+            data: vec![
+                response(format!(
+                    r#"{{"assets":[{{"info":{{"native_token":{{"denom":"btc"}}}},"amount":"{}"}},{{"info":{{"native_token":{{"denom":"usdt"}}}},"amount":"{}"}}],"total_share":"1000"}}"#,
+                    100_000_000_000u128, 60_000_000_000_000u128
+                )),
+                response(r#"{"pair_type":{"custom":"concentrated"}}"#.to_string()),
+                response(format!(
+                    r#"{{"params":"{}"}}"#,
+                    Binary::from(
+                        cosmwasm_std::to_json_vec(&astroport::ConcentratedPoolConfig {
+                            amp: decimal("10"),
+                            gamma: decimal("0.000145"),
+                            mid_fee: decimal("0.0026"),
+                            out_fee: decimal("0.0045"),
+                            fee_gamma: decimal("0.00023"),
+                            price_scale: Decimal256::from_ratio(1u128, 60_000u128),
+                        })
+                        .unwrap()
+                    )
+                )),
+            ],
+        }];
+        let pcl = astroport::AstroportPool::from_fis(&network, "btc-usdt", &fis_input, 0).unwrap();
+        let curve = pcl.curve.clone().unwrap();
+        assert!(curve.price_scale.abs_diff(decimal("60000")) < decimal("0.000001"));
+        // the fee of a balanced pool is the mid fee
+        assert_eq!(pcl.fee_rate, Int256::from(2600));
+        // 60k usdt buy a btc less the fee and the impact
+        let (_, output) = pcl.swap_output(Int256::from(60_000_000_000i128), true);
+        assert!(output < Int256::from(99_740_000) && output > Int256::from(99_000_000));
+        let (_, back) = pcl.swap_output(output, false);
+        assert!(back < Int256::from(60_000_000_000i128));
+
+        let mut unknown = fis_input.clone();
+        unknown[0].data[1] = response(r#"{"pair_type":{"custom":"other"}}"#.to_string());
+        let err =
+            astroport::AstroportPool::from_fis(&network, "btc-usdt", &unknown, 0).unwrap_err();
+        assert!(matches!(err, NexusError::InvalidFisInput { .. }));
+    }
+
+    #[test]
+    fn test_swap_funds_plane() {
+        let network = Network::embedded().unwrap();
+        let query = SwapQuery::new(&network).unwrap();
+        assert_eq!(
+            query.pools.query.instructions.len(),
+            PoolQuery::new(&network).unwrap().query.instructions.len() + 4
+        );
+        let (plane, slot) = &query.balances[3];
+        assert_eq!(*plane, Plane::Svm);
+        assert_eq!(
+            query.pools.query.instructions[slot.instruction].input[0].as_slice(),
+            b"/flux/astromesh/v1beta1/balance/${wallet}/${src_denom}?plane=SVM"
+        );
+
+        let sender = "lux1jcltmuhplrdcwp7stlr4hlhlhgd4htqhu86cqx";
+        let transfers = |amount: i128, balances: &[(Plane, u128)]| {
+            let balances: Vec<_> = balances
+                .iter()
+                .map(|(plane, amount)| (*plane, Uint128::new(*amount)))
+                .collect();
+            crate::fund_plane(
+                &network,
+                sender,
+                Plane::Svm,
+                "eth",
+                Int128::new(amount),
+                &balances,
+            )
+            .map(|ixs| {
+                ixs.iter()
+                    .map(|ix| {
+                        let msg = from_json::<MsgAstroTransfer>(ix.msg()).unwrap();
+                        (msg.src_plane, msg.coin.amount.u128())
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        // 3 eth on svm (9 decimals) from 1 already there, cosmos then evm (18 decimals)
+        let eth = 1_000_000_000_000_000_000i128;
+        assert_eq!(
+            transfers(
+                3 * eth + 1,
+                &[
+                    (Plane::Cosmos, eth as u128),
+                    (Plane::Wasm, 0),
+                    (Plane::Evm, 5 * eth as u128),
+                    (Plane::Svm, 1_000_000_000),
+                ]
+            )
+            .unwrap(),
+            vec![(Plane::Cosmos, eth as u128), (Plane::Evm, eth as u128)]
+        );
+        // nothing to move
+        assert_eq!(
+            transfers(eth, &[(Plane::Cosmos, 0), (Plane::Svm, 2_000_000_000)]).unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            transfers(3 * eth, &[(Plane::Cosmos, eth as u128), (Plane::Svm, 0)])
+                .unwrap_err()
+                .code(),
+            8
+        );
+    }
+
+    #[test]
+    fn test_arbitrage_from_base() {
+        let network = Network::embedded().unwrap();
+        // btc is dearer on wasm, btc => usdt there and back on svm
+        let markets = vec![
+            market("btc-usdt", Plane::Svm, 1_000_000_000_000, 1_000_000_000_000),
+            market(
+                "btc-usdt",
+                Plane::Wasm,
+                1_100_000_000_000,
+                1_000_000_000_000,
+            ),
+        ];
+        let hops = [
+            route::Hop {
+                market: 1,
+                a_for_b: false,
+            },
+            route::Hop {
+                market: 0,
+                a_for_b: true,
+            },
+        ];
+        let (x, profit) =
+            route::optimize_cycle(&markets, &hops, Int256::from(100_000_000_000i128)).unwrap();
+        assert!(x > Int256::zero() && profit > Int256::zero());
+
+        let compose = |src_plane: Option<Plane>, balances: &[(Plane, u128)]| {
+            let balances = balances
+                .iter()
+                .map(|(plane, amount)| (*plane, Uint128::new(*amount)))
+                .collect();
+            crate::compose_arbitrage(
+                &network,
+                &markets,
+                &hops,
+                "btc",
+                x,
+                Int256::zero(),
+                src_plane,
+                Some(Plane::Cosmos),
+                DEFAULT_SLIPPAGE_BPS,
+                balances,
+                "lux1jcltmuhplrdcwp7stlr4hlhlhgd4htqhu86cqx".to_string(),
+                "DRK5Bi2NwkGRPsqHJSyy6rhUo3uQ8YHtt1xUWbu7Bnsx".to_string(),
+            )
+            .unwrap()
+            .iter()
+            .map(|ix| match ix.action() {
+                FisAction::CosmosInvoke => {
+                    let msg = from_json::<MsgAstroTransfer>(ix.msg()).unwrap();
+                    (msg.src_plane, msg.dst_plane)
+                }
+                _ => (ix.plane(), ix.plane()),
+            })
+            .collect::<Vec<_>>()
+        };
+
+        // funded from cosmos, profit settled back there
+        let all_on_cosmos = Uint128::try_from(x).unwrap().u128();
+        assert_eq!(
+            compose(None, &[(Plane::Cosmos, all_on_cosmos), (Plane::Evm, 0)]),
+            vec![
+                (Plane::Cosmos, Plane::Wasm),
+                (Plane::Wasm, Plane::Wasm),
+                (Plane::Wasm, Plane::Svm),
+                (Plane::Svm, Plane::Svm),
+                (Plane::Svm, Plane::Cosmos),
+            ]
+        );
+        // the source plane is drawn from before cosmos
+        let transfers = compose(
+            Some(Plane::Evm),
+            &[(Plane::Cosmos, all_on_cosmos), (Plane::Evm, u128::MAX)],
+        );
+        assert_eq!(transfers[0].0, Plane::Evm);
+        assert_eq!(transfers.len(), 5);
+    }
 }
@@ -4,15 +4,29 @@ use serde::{Deserialize, Serialize};
 pub mod astroport {
     use super::MsgExecuteContract;
     use crate::{
-        astromesh::{FISInput, Pool, Swap},
-        FISInstruction,
+        astromesh::{
+            less_slippage, to_int256, to_u128, to_uint256, Deposit, Pool, Swap, Withdrawal,
+            MAX_SLIPPAGE_BPS,
+        },
+        curve::{div, Invariant},
+        network::{pair_denoms, Network},
     };
     use cosmwasm_schema::cw_serde;
-    use cosmwasm_std::{from_json, to_json_vec, Addr, Coin, Decimal, Int256, StdError, Uint128};
-    use std::str::FromStr;
+    use cosmwasm_std::{
+        from_json, to_json_vec, Addr, Binary, Coin, Decimal, Decimal256, Int256, Uint128, Uint256,
+    };
+    use nexus_sdk::{
+        astromesh::{FISInput, FISInstruction, Plane},
+        error::{NexusError, NexusResult},
+        input::Slot,
+        query::QueryInstruction,
+    };
+    use serde::{Deserialize, Serialize};
 
     pub const ASTROPORT: &str = "astroport";
     pub const BPS: i128 = 1000000i128;
+    /// Custom pair type of the PCL pairs
+    pub const CONCENTRATED: &str = "concentrated";
 
     // TODO: Get these from astroport library
     #[cw_serde]
@@ -32,6 +46,46 @@ pub mod astroport {
         pub total_share: Uint128,
     }
 
+    #[cw_serde]
+    pub enum PairType {
+        /// XYK pair type
+        Xyk {},
+        /// Stable pair type
+        Stable {},
+        /// Custom pair type
+        Custom(String),
+    }
+
+    // The pair and config responses differ across pair types and versions, only the fields
+    // read are kept
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub struct PairInfo {
+        pub pair_type: PairType,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub struct ConfigResponse {
+        /// Parameters of the pair type, none for xyk pairs
+        pub params: Option<Binary>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub struct StablePoolConfig {
+        pub amp: Decimal256,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub struct ConcentratedPoolConfig {
+        pub amp: Decimal256,
+        pub gamma: Decimal256,
+        pub mid_fee: Decimal256,
+        pub out_fee: Decimal256,
+        pub fee_gamma: Decimal256,
+        /// price of the second asset in the first one
+        pub price_scale: Decimal256,
+    }
+
     #[cw_serde]
     pub struct Asset {
         /// Information about an asset stored in a [`AssetInfo`] struct
@@ -49,103 +103,380 @@ pub mod astroport {
             max_spread: Option<Decimal>,
             to: Option<String>,
         },
+        ProvideLiquidity {
+            /// The assets available in the pool
+            assets: Vec<Asset>,
+            /// The slippage tolerance that allows liquidity provision only if the price in the pool doesn't move too much
+            slippage_tolerance: Option<Decimal>,
+            /// Determines whether the LP tokens minted for the user is auto_staked in the Generator contract
+            auto_stake: Option<bool>,
+            /// The receiver of LP tokens
+            receiver: Option<String>,
+            /// The minimum amount of LP tokens to receive
+            min_lp_to_receive: Option<Uint128>,
+        },
+        WithdrawLiquidity {
+            /// Empty for a withdrawal at the pool ratio
+            assets: Vec<Asset>,
+            /// The minimum amounts of the assets to receive
+            min_assets_to_receive: Option<Vec<Asset>>,
+        },
+    }
+
+    /// Tokenfactory denom of the lp token of a pair contract
+    pub fn lp_denom(contract: &str) -> String {
+        format!("factory/{}/astroport/share", contract)
+    }
+
+    // native asset and coin of `amount` of `alias`, sent along the message
+    fn native_asset(network: &Network, alias: &str, amount: Int256) -> NexusResult<(Asset, Coin)> {
+        let denom = network.denoms.address(alias, Plane::Wasm)?.to_string();
+        let amount = Uint128::new(to_u128(amount)?);
+        Ok((
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: denom.clone(),
+                },
+                amount,
+            },
+            Coin { denom, amount },
+        ))
     }
 
+    /// Pair contract and denom aliases, a is the quote denom as for the other pools
     pub struct PoolMeta {
         pub contract: String,
         pub denom_a: String,
         pub denom_b: String,
     }
 
-    pub fn get_pool_meta_by_name(pool_name: &String) -> Result<PoolMeta, StdError> {
-        let contract = match pool_name.as_str() {
-            "btc-usdt" => {
-                "lux1nc5tatafv6eyq7llkr2gv50ff9e22mnf70qgjlv737ktmt4eswrqhywrts".to_string()
-            }
-            "eth-usdt" => {
-                "lux1aakfpghcanxtc45gpqlx8j3rq0zcpyf49qmhm9mdjrfx036h4z5sdltq0m".to_string()
-            }
-            "sol-usdt" => {
-                "lux18v47nqmhvejx3vc498pantg8vr435xa0rt6x0m6kzhp6yuqmcp8s3z45es".to_string()
-            }
-            _ => {
-                return Err(StdError::generic_err(format!(
-                    "astroport pair not found: {}",
-                    pool_name
-                )))
-            }
-        };
-
-        // Split the pair to extract denom_a and denom_b
-        let denoms: Vec<&str> = pool_name.split('-').collect();
-        if denoms.len() != 2 {
-            return Err(StdError::generic_err(format!(
-                "invalid pair format: {}",
-                pool_name
-            )));
-        }
+    pub fn get_pool_meta_by_name(network: &Network, pool_name: &str) -> NexusResult<PoolMeta> {
+        let contract = network.astroport_pool(pool_name)?.clone();
+        let (denom_b, denom_a) = pair_denoms(pool_name)?;
 
         Ok(PoolMeta {
             contract,
-            denom_a: denoms[0].to_string(),
-            denom_b: denoms[1].to_string(),
+            denom_a: denom_a.to_string(),
+            denom_b: denom_b.to_string(),
         })
     }
 
+    /// Fee of PCL pairs, from `mid_fee` for balanced pools to `out_fee` for imbalanced ones
+    #[cw_serde]
+    pub struct DynamicFee {
+        pub mid_fee: Decimal256,
+        pub out_fee: Decimal256,
+        pub fee_gamma: Decimal256,
+    }
+
+    impl DynamicFee {
+        /// Rate of the pool at scaled balances `xs`
+        pub fn rate(&self, xs: [Decimal256; 2]) -> NexusResult<Decimal256> {
+            // f = fee_gamma / (fee_gamma + 1 - 4 x0 x1 / (x0 + x1)^2)
+            let sum = xs[0].checked_add(xs[1])?;
+            let k = div(
+                xs[0]
+                    .checked_mul(xs[1])?
+                    .checked_mul(Decimal256::from_ratio(4u128, 1u128))?,
+                sum.checked_mul(sum)?,
+            )?;
+            let f = div(
+                self.fee_gamma,
+                self.fee_gamma
+                    .checked_add(Decimal256::one())?
+                    .saturating_sub(k),
+            )?;
+            Ok(self.mid_fee.checked_mul(f)?.checked_add(
+                self.out_fee
+                    .checked_mul(Decimal256::one().saturating_sub(f))?,
+            )?)
+        }
+    }
+
+    /// Stableswap or PCL state of a pair beyond its balances, a is the quote denom
+    #[cw_serde]
+    pub struct CurvePool {
+        pub invariant: Invariant,
+        pub decimals_a: u8,
+        pub decimals_b: u8,
+        /// price of b in a the balance of b is scaled by, one on stableswap pairs
+        pub price_scale: Decimal256,
+        /// D of the balances
+        pub d: Decimal256,
+        /// the fee rate of the pool applies when unset
+        pub fee: Option<DynamicFee>,
+    }
+
+    impl CurvePool {
+        pub fn new(
+            invariant: Invariant,
+            (decimals_a, decimals_b): (u8, u8),
+            price_scale: Decimal256,
+            fee: Option<DynamicFee>,
+            (a, b): (Int256, Int256),
+        ) -> NexusResult<Self> {
+            let mut pool = CurvePool {
+                invariant,
+                decimals_a,
+                decimals_b,
+                price_scale,
+                d: Decimal256::zero(),
+                fee,
+            };
+            pool.d = pool.invariant.d(pool.balances(a, b)?)?;
+            Ok(pool)
+        }
+
+        // a and b in whole tokens, b scaled by the price
+        fn balances(&self, a: Int256, b: Int256) -> NexusResult<[Decimal256; 2]> {
+            Ok([
+                tokens(a, self.decimals_a)?,
+                tokens(b, self.decimals_b)?.checked_mul(self.price_scale)?,
+            ])
+        }
+
+        /// Output of `x` on balances `a` and `b`, `fee_rate` in millionths
+        pub fn swap_output(
+            &self,
+            (a, b): (Int256, Int256),
+            x: Int256,
+            a_for_b: bool,
+            fee_rate: Int256,
+        ) -> NexusResult<Int256> {
+            let xs = self.balances(a, b)?;
+            if x <= Int256::zero() || xs[0].is_zero() || xs[1].is_zero() {
+                return Ok(Int256::zero());
+            }
+            let decimals = [self.decimals_a, self.decimals_b];
+            let scales = [Decimal256::one(), self.price_scale];
+            let (i, o) = if a_for_b { (0, 1) } else { (1, 0) };
+
+            let input = xs[i].checked_add(tokens(x, decimals[i])?.checked_mul(scales[i])?)?;
+            let remaining = self.invariant.y(input, self.d)?;
+            let fee = match &self.fee {
+                Some(fee) => fee.rate([input, remaining])?,
+                None => Decimal256::from_ratio(to_uint256(fee_rate), Uint256::from(BPS as u128)),
+            };
+            let output = div(xs[o].saturating_sub(remaining), scales[o])?
+                .checked_mul(Decimal256::one().saturating_sub(fee))?;
+            amount(output, decimals[o])
+        }
+
+        /// Reserves of a constant product pool with the price and depth of the curve at
+        /// balances `a` and `b`.
+        ///
+        /// A constant product pool of price `p` is `2p / |dp/dx|` deep on the input side, the
+        /// slope is taken over a swap of a thousandth of a.
+        pub fn spot_reserves(&self, a: Int256, b: Int256) -> NexusResult<(Int256, Int256)> {
+            let xs = self.balances(a, b)?;
+            let price = self.invariant.price(xs, self.d)?;
+            let dx = xs[0] / Decimal256::from_ratio(1000u128, 1u128);
+            let x = xs[0].checked_add(dx)?;
+            let next_price = self
+                .invariant
+                .price([x, self.invariant.y(x, self.d)?], self.d)?;
+            if next_price >= price {
+                return Err(NexusError::Math("curve price must decrease".to_string()));
+            }
+            let reserve_a = div(
+                price
+                    .checked_mul(dx)?
+                    .checked_mul(Decimal256::from_ratio(2u128, 1u128))?,
+                price - next_price,
+            )?;
+            let reserve_b = div(reserve_a.checked_mul(price)?, self.price_scale)?;
+            Ok((
+                amount(reserve_a, self.decimals_a)?,
+                amount(reserve_b, self.decimals_b)?,
+            ))
+        }
+    }
+
+    // whole tokens of an amount in `decimals`
+    fn tokens(amount: Int256, decimals: u8) -> NexusResult<Decimal256> {
+        Decimal256::checked_from_ratio(to_uint256(amount), unit(decimals)?)
+            .map_err(|e| NexusError::Math(e.to_string()))
+    }
+
+    // amount in `decimals` of whole tokens, rounded down
+    fn amount(tokens: Decimal256, decimals: u8) -> NexusResult<Int256> {
+        let amount = tokens.checked_mul(Decimal256::from_ratio(unit(decimals)?, 1u128))?;
+        Ok(to_int256(amount.to_uint_floor()))
+    }
+
+    fn unit(decimals: u8) -> NexusResult<Uint256> {
+        Ok(Uint256::from(10u32).checked_pow(u32::from(decimals))?)
+    }
+
     #[cw_serde]
     #[derive(Default)]
     pub struct AstroportPool {
         pub dex_name: String,
-        pub denom_plane: String,
+        pub denom_plane: Plane,
         pub a: Int256,
         pub b: Int256,
         pub fee_rate: Int256,
         pub denom_a: String,
         pub denom_b: String,
+        pub lp_supply: Int256,
+        /// none on xyk pairs
+        pub curve: Option<CurvePool>,
     }
 
     impl AstroportPool {
-        pub fn new(pair: &str) -> Result<Self, StdError> {
+        pub fn new(network: &Network, pair: &str) -> NexusResult<Self> {
             // Create and return the AstroportPool struct with amounts set to zero and denominations empty
-            let pool_meta = get_pool_meta_by_name(&pair.to_string())?;
+            let pool_meta = get_pool_meta_by_name(network, pair)?;
             Ok(AstroportPool {
                 dex_name: ASTROPORT.to_string(),
-                denom_plane: "COSMOS".to_string(),
+                denom_plane: Plane::Cosmos,
                 a: Int256::zero(),
                 b: Int256::zero(),
                 fee_rate: Int256::from(1000i128),
                 denom_a: pool_meta.denom_a,
                 denom_b: pool_meta.denom_b,
+                lp_supply: Int256::zero(),
+                curve: None,
             })
         }
 
-        pub fn from_fis(input: &FISInput) -> Result<Self, StdError> {
-            let pool_info = from_json::<PoolResponse>(input.data.first().unwrap())?;
-            let asset_0 = pool_info.assets.first().unwrap();
-            let asset_1 = pool_info.assets.get(1).unwrap();
-            let mut asset_0_denom = match &asset_0.info {
-                AssetInfo::Token { contract_addr } => contract_addr.to_string(),
-                AssetInfo::NativeToken { denom } => denom.clone(),
+        /// Queries the `[pool, pair, config]` of the pool contract read by `from_fis`
+        pub fn query(network: &Network, pair: &str) -> NexusResult<QueryInstruction> {
+            let pool = get_pool_meta_by_name(network, pair)?;
+            let (_, contract) = bech32::decode(&pool.contract).map_err(|e| {
+                NexusError::invalid_input(format!("invalid pool contract {}: {}", pool.contract, e))
+            })?;
+            Ok(QueryInstruction::vm_query(
+                Plane::Wasm,
+                contract,
+                vec![
+                    Binary::from(br#"{"pool":{}}"#),
+                    Binary::from(br#"{"pair":{}}"#),
+                    Binary::from(br#"{"config":{}}"#),
+                ],
+            ))
+        }
+
+        /// Parses the `[pool, pair, config]` query results of `pair` at `fis_input[instruction]`,
+        /// the pair type decides the invariant
+        pub fn from_fis(
+            network: &Network,
+            pair: &str,
+            fis_input: &[FISInput],
+            instruction: usize,
+        ) -> NexusResult<Self> {
+            let slot = Slot::<PoolResponse>::new(instruction, 0, "astroport pool");
+            let pool_info = slot.load(fis_input)?;
+            let [asset_0, asset_1] = pool_info.assets.as_slice() else {
+                return Err(NexusError::InvalidFisInput {
+                    slot: slot.to_string(),
+                    reason: format!("must have 2 assets, got {}", pool_info.assets.len()),
+                });
             };
-            let mut asset_1_denom = match &asset_1.info {
-                AssetInfo::Token { contract_addr } => contract_addr.to_string(),
-                AssetInfo::NativeToken { denom } => denom.clone(),
+            let alias = |asset: &Asset| {
+                let denom = match &asset.info {
+                    AssetInfo::Token { contract_addr } => contract_addr.to_string(),
+                    AssetInfo::NativeToken { denom } => denom.clone(),
+                };
+                network.denoms.find(Plane::Wasm, &denom).unwrap_or_default()
             };
 
-            let (mut a, mut b) = (asset_0.amount, asset_1.amount);
-            if asset_0_denom != "usdt" {
-                (a, b) = (b, a);
-                (asset_0_denom, asset_1_denom) = (asset_1_denom, asset_0_denom);
-            }
+            let pool = get_pool_meta_by_name(network, pair)?;
+            // PCL prices the second asset in the first one
+            let (a, b, b_second) = match (alias(asset_0), alias(asset_1)) {
+                (denom_0, denom_1) if denom_0 == pool.denom_a && denom_1 == pool.denom_b => {
+                    (asset_0.amount, asset_1.amount, true)
+                }
+                (denom_0, denom_1) if denom_0 == pool.denom_b && denom_1 == pool.denom_a => {
+                    (asset_1.amount, asset_0.amount, false)
+                }
+                _ => {
+                    return Err(NexusError::InvalidFisInput {
+                        slot: slot.to_string(),
+                        reason: format!("assets don't match pair {}", pair),
+                    })
+                }
+            };
+
+            let (a, b) = (Int256::from(a.u128()), Int256::from(b.u128()));
+            let pair_slot = Slot::<PairInfo>::new(instruction, 1, "astroport pair");
+            let pair_info = pair_slot.load(fis_input)?;
+            let config_slot = Slot::<ConfigResponse>::new(instruction, 2, "astroport config");
+            let params = config_slot.load(fis_input)?.params;
+            let invalid_params = |reason: String| NexusError::InvalidFisInput {
+                slot: config_slot.to_string(),
+                reason,
+            };
+            let decimals = (
+                network.denoms.link(&pool.denom_a, Plane::Wasm)?.decimals,
+                network.denoms.link(&pool.denom_b, Plane::Wasm)?.decimals,
+            );
+
+            let mut fee_rate = Int256::from(10000i128);
+            let curve = match pair_info.pair_type {
+                PairType::Xyk {} => None,
+                PairType::Stable {} => {
+                    let params = params.ok_or_else(|| invalid_params("no params".to_string()))?;
+                    let config = from_json::<StablePoolConfig>(&params)
+                        .map_err(|e| invalid_params(e.to_string()))?;
+                    Some(CurvePool::new(
+                        Invariant::Stable { amp: config.amp },
+                        decimals,
+                        Decimal256::one(),
+                        None,
+                        (a, b),
+                    )?)
+                }
+                PairType::Custom(pair_type) if pair_type == CONCENTRATED => {
+                    let params = params.ok_or_else(|| invalid_params("no params".to_string()))?;
+                    let config = from_json::<ConcentratedPoolConfig>(&params)
+                        .map_err(|e| invalid_params(e.to_string()))?;
+                    let price_scale = if b_second {
+                        config.price_scale
+                    } else {
+                        div(Decimal256::one(), config.price_scale)?
+                    };
+                    let fee = DynamicFee {
+                        mid_fee: config.mid_fee,
+                        out_fee: config.out_fee,
+                        fee_gamma: config.fee_gamma,
+                    };
+                    let curve = CurvePool::new(
+                        Invariant::Crypto {
+                            amp: config.amp,
+                            gamma: config.gamma,
+                        },
+                        decimals,
+                        price_scale,
+                        Some(fee.clone()),
+                        (a, b),
+                    )?;
+                    // the fee at the current balances, swaps pay it at the balances after
+                    let rate = fee.rate(curve.balances(a, b)?)?;
+                    fee_rate = to_int256(
+                        rate.checked_mul(Decimal256::from_ratio(BPS as u128, 1u128))?
+                            .to_uint_floor(),
+                    );
+                    Some(curve)
+                }
+                PairType::Custom(pair_type) => {
+                    return Err(NexusError::InvalidFisInput {
+                        slot: pair_slot.to_string(),
+                        reason: format!("unsupported pair type {}", pair_type),
+                    })
+                }
+            };
 
             Ok(Self {
                 dex_name: ASTROPORT.to_string(),
-                denom_plane: "COSMOS".to_string(),
-                a: Int256::from(a.u128()),
-                b: Int256::from(b.u128()),
-                fee_rate: Int256::from(10000i128),
-                denom_a: asset_0_denom,
-                denom_b: asset_1_denom,
+                denom_plane: Plane::Cosmos,
+                a,
+                b,
+                fee_rate,
+                denom_a: pool.denom_a,
+                denom_b: pool.denom_b,
+                lp_supply: Int256::from(pool_info.total_share.u128()),
+                curve,
             })
         }
     }
@@ -155,8 +486,8 @@ pub mod astroport {
             self.dex_name.clone()
         }
 
-        fn denom_plane(&self) -> String {
-            self.denom_plane.clone()
+        fn denom_plane(&self) -> Plane {
+            self.denom_plane
         }
 
         fn a(&self) -> Int256 {
@@ -167,8 +498,40 @@ pub mod astroport {
             self.b
         }
 
+        fn denom_a(&self) -> String {
+            self.denom_a.clone()
+        }
+
+        fn denom_b(&self) -> String {
+            self.denom_b.clone()
+        }
+
+        fn fee_rate(&self) -> Int256 {
+            self.fee_rate
+        }
+
+        fn spot_reserves(&self) -> (Int256, Int256) {
+            match &self.curve {
+                Some(curve) => curve
+                    .spot_reserves(self.a, self.b)
+                    .unwrap_or((self.a, self.b)),
+                None => (self.a, self.b),
+            }
+        }
+
         fn swap_output(&self, x: Int256, a_for_b: bool) -> (String, Int256) {
             let bps = Int256::from_i128(BPS);
+            if let Some(curve) = &self.curve {
+                let denom = if a_for_b {
+                    self.denom_b.clone()
+                } else {
+                    self.denom_a.clone()
+                };
+                let output = curve
+                    .swap_output((self.a, self.b), x, a_for_b, self.fee_rate)
+                    .unwrap_or_default();
+                return (denom, output);
+            }
             if a_for_b {
                 (
                     self.denom_b.clone(),
@@ -182,8 +545,24 @@ pub mod astroport {
             }
         }
 
-        fn compose_swap_fis(&self, swap: &Swap) -> Result<Vec<FISInstruction>, StdError> {
-            let pool = get_pool_meta_by_name(&swap.pool_name)?;
+        fn compose_swap_fis(
+            &self,
+            network: &Network,
+            swap: &Swap,
+        ) -> NexusResult<Vec<FISInstruction>> {
+            let pool = get_pool_meta_by_name(network, &swap.pool_name)?;
+            // astroport reverts when the return is more than max_spread below
+            // offer / belief_price, i.e. the estimate
+            swap.min_output(self)?;
+            let (_, estimate) = swap.estimate(self)?;
+            let belief_price =
+                Decimal::checked_from_ratio(Uint128::try_from(swap.amount)?, to_u128(estimate)?)
+                    .map_err(|e| NexusError::Math(e.to_string()))?;
+            let max_spread = Decimal::from_ratio(swap.slippage_bps, MAX_SLIPPAGE_BPS);
+            let denom = network
+                .denoms
+                .address(&swap.denom, Plane::Wasm)?
+                .to_string();
 
             let msg = MsgExecuteContract::new(
                 swap.sender.clone(),
@@ -191,27 +570,99 @@ pub mod astroport {
                 AstroportMsg::Swap {
                     offer_asset: Asset {
                         info: AssetInfo::NativeToken {
-                            denom: swap.denom.clone(),
+                            denom: denom.clone(),
                         },
                         amount: Uint128::new(swap.amount.i128() as u128),
                     },
                     ask_asset_info: None,
-                    belief_price: None,
-                    max_spread: Some(Decimal::from_str("0.5").unwrap()),
+                    belief_price: Some(belief_price),
+                    max_spread: Some(max_spread),
                     to: Some(swap.sender.clone()),
                 },
                 vec![Coin {
                     amount: Uint128::new(swap.amount.i128() as u128),
-                    denom: swap.denom.clone(),
+                    denom,
                 }],
             );
 
-            Ok(vec![FISInstruction {
-                plane: "WASM".to_string(),
-                action: "VM_INVOKE".to_string(),
-                address: "".to_string(),
-                msg: to_json_vec(&msg)?,
-            }])
+            Ok(vec![FISInstruction::vm_invoke(
+                Plane::Wasm,
+                to_json_vec(&msg)?,
+            )])
+        }
+
+        fn lp_supply(&self) -> Int256 {
+            self.lp_supply
+        }
+
+        fn compose_add_liquidity_fis(
+            &self,
+            network: &Network,
+            deposit: &Deposit,
+        ) -> NexusResult<Vec<FISInstruction>> {
+            let pool = get_pool_meta_by_name(network, &deposit.pool_name)?;
+            let estimate = deposit.estimate(self)?;
+            let min_lp = less_slippage(estimate.lp_amount, deposit.slippage_bps)?;
+            let (asset_a, coin_a) = native_asset(network, &estimate.denom_a, estimate.amount_a)?;
+            let (asset_b, coin_b) = native_asset(network, &estimate.denom_b, estimate.amount_b)?;
+            let mut funds = vec![coin_a, coin_b];
+            funds.sort_by(|x, y| x.denom.cmp(&y.denom));
+
+            let msg = MsgExecuteContract::new(
+                deposit.sender.clone(),
+                pool.contract,
+                AstroportMsg::ProvideLiquidity {
+                    assets: vec![asset_a, asset_b],
+                    slippage_tolerance: Some(Decimal::from_ratio(
+                        deposit.slippage_bps,
+                        MAX_SLIPPAGE_BPS,
+                    )),
+                    auto_stake: Some(false),
+                    receiver: Some(deposit.sender.clone()),
+                    min_lp_to_receive: Some(Uint128::new(to_u128(min_lp)?)),
+                },
+                funds,
+            );
+            Ok(vec![FISInstruction::vm_invoke(
+                Plane::Wasm,
+                to_json_vec(&msg)?,
+            )])
+        }
+
+        fn compose_remove_liquidity_fis(
+            &self,
+            network: &Network,
+            withdrawal: &Withdrawal,
+        ) -> NexusResult<Vec<FISInstruction>> {
+            let pool = get_pool_meta_by_name(network, &withdrawal.pool_name)?;
+            let estimate = withdrawal.estimate(self)?;
+            let (min_a, _) = native_asset(
+                network,
+                &estimate.denom_a,
+                less_slippage(estimate.amount_a, withdrawal.slippage_bps)?,
+            )?;
+            let (min_b, _) = native_asset(
+                network,
+                &estimate.denom_b,
+                less_slippage(estimate.amount_b, withdrawal.slippage_bps)?,
+            )?;
+
+            let msg = MsgExecuteContract::new(
+                withdrawal.sender.clone(),
+                pool.contract.clone(),
+                AstroportMsg::WithdrawLiquidity {
+                    assets: vec![],
+                    min_assets_to_receive: Some(vec![min_a, min_b]),
+                },
+                vec![Coin {
+                    denom: lp_denom(&pool.contract),
+                    amount: Uint128::new(to_u128(estimate.lp_amount)?),
+                }],
+            );
+            Ok(vec![FISInstruction::vm_invoke(
+                Plane::Wasm,
+                to_json_vec(&msg)?,
+            )])
         }
     }
 }
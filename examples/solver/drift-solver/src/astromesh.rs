@@ -1,49 +1,5 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Binary, Coin, Int128, Uint64};
-use serde::{Deserialize, Serialize};
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct MsgAstroTransfer {
-    #[serde(rename = "@type")]
-    pub ty: String,
-    pub sender: String,
-    pub receiver: String,
-    pub src_plane: String,
-    pub dst_plane: String,
-    pub coin: Coin,
-}
-
-impl MsgAstroTransfer {
-    pub fn new(
-        sender: String,
-        receiver: String,
-        src_plane: String,
-        dst_plane: String,
-        coin: Coin,
-    ) -> Self {
-        MsgAstroTransfer {
-            ty: "/flux.astromesh.v1beta1.MsgAstroTransfer".to_string(),
-            sender,
-            receiver,
-            src_plane,
-            dst_plane,
-            coin,
-        }
-    }
-}
-
-#[cw_serde]
-pub struct FISInput {
-    pub data: Vec<Binary>,
-}
-
-#[cw_serde]
-pub struct FISInstruction {
-    pub plane: String,
-    pub action: String,
-    pub address: String,
-    pub msg: Vec<u8>,
-}
+use cosmwasm_std::{Int128, Uint64};
 
 #[cw_serde]
 pub enum NexusAction {
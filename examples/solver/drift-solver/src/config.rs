@@ -0,0 +1,26 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Deps;
+use nexus_sdk::{error::NexusResult, strategy::load_config};
+
+/// Order limits, passed as `config` at instantiate and replaced with `UpdateConfig`
+#[cw_serde]
+pub struct Config {
+    pub max_leverage: u64,
+    /// in blocks, drift caps the auction duration at 255
+    pub min_auction_duration: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_leverage: 20,
+            min_auction_duration: 10,
+        }
+    }
+}
+
+impl Config {
+    pub fn load(deps: Deps) -> NexusResult<Self> {
+        Ok(load_config(deps.storage)?)
+    }
+}
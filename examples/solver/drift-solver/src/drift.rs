@@ -1,44 +1,51 @@
-use cosmwasm_std::{Binary, Deps, StdError, StdResult};
+use cosmwasm_std::Binary;
+
+use crate::network::Network;
 
-use crate::svm::{
-    InstructionAccountMeta, InstructionMeta, Pubkey, ASSOCIATED_TOKEN_PROGRAM_ID, MINT,
-    SPL_TOKEN2022_PROGRAM_ID, SYSTEM_PROGRAM_ID, SYS_VAR_RENT_ID,
-};
 use borsh::{BorshDeserialize, BorshSerialize};
+use nexus_sdk::{
+    error::{NexusError, NexusResult},
+    svm::{
+        get_associated_token_address, InstructionAccountMeta, InstructionMeta, Pubkey,
+        SPL_TOKEN_2022_PROGRAM_ID, SYSTEM_PROGRAM_ID, SYS_VAR_RENT_ID,
+    },
+};
 
-pub const DRIFT_PROGRAM_ID: &str = "FLR3mfYrMZUnhqEadNJVwjUhjX8ky9vE9qTtDmkK4vwC";
-pub const ORACLE_BTC: &str = "3HRnxmtHQrHkooPdFZn5ZQbPTKGvBSyoTi4VVkkoT6u6";
-pub const ORACLE_ETH: &str = "2S8JS8K4E7EYnXaoVABFWG3wkxKKaVWEVKZ8GiyinBuS";
-pub const ORACLE_SOL: &str = "362SGYeXLRddaacjbyRuXPc1iewF1FrZpRpkyw72LHAM";
-pub const DRIFT_STATE: &str = "HYEM9xMiSVsGzwEVRhX3WHH9CB2sFeHnWhyZUR4KVr8c";
-pub const DRIFT_DEFAULT_PERCISION: u64 = 1000_000;
-
-pub const ALL_MARKETS: &[&str] = &[
-    "GbMqWisskNfP9ZY53cy8eZNK16sg89FKCo4yzpRhFZ2",
-    "EUCAzwBhsNnK9BRK6SW4aYbn9eT4foMVHXjpyUP9WuH4",
-    "7WrZxBiKCMGuzLCW2VwKK7sQjhTZLbDe5sKfJsEcARpF",
-    "E4DJDZwcSWzujRLjoWQXqq4KQVuzbvBiHSR35BPbK7BX",
-    "2GKUdmaBJNjfCucDT14HrsWchVrm3yvj4QY2jjnUEg3v",
-];
+pub const DRIFT_DEFAULT_PERCISION: u64 = 1_000_000;
 
 pub const DISCRIMINATOR_OFFSET: usize = 8;
 pub const PERP_MARKET_DISCRIMINATOR: &[u8] = &[10, 223, 12, 44, 107, 245, 55, 247];
+pub const USER_DISCRIMINATOR: &[u8] = &[159, 117, 95, 227, 239, 151, 58, 236];
+
+/// Checks the anchor discriminator of the `name` account data
+pub fn check_discriminator(name: &str, data: &[u8], discriminator: &[u8]) -> NexusResult<()> {
+    if !data.starts_with(discriminator) {
+        return Err(NexusError::InvalidFisInput {
+            slot: name.to_string(),
+            reason: format!("account data must begin with {:?}", discriminator),
+        });
+    }
+    Ok(())
+}
 
-pub fn create_initialize_user_ixs(sender_svm: String) -> StdResult<Vec<InstructionMeta>> {
+pub fn create_initialize_user_ixs(
+    network: &Network,
+    sender_svm: String,
+) -> NexusResult<Vec<InstructionMeta>> {
     let sender_pubkey = Pubkey::from_string(&sender_svm)?;
-    let drift_program_id = Pubkey::from_string(&DRIFT_PROGRAM_ID.to_string())?;
+    let drift_program_id = Pubkey::from_string(&network.program_id)?;
     let subacc_index = 0u16.to_le_bytes();
     let (user, _) = Pubkey::find_program_address(
         &["user".as_bytes(), sender_pubkey.0.as_slice(), &subacc_index],
         &drift_program_id,
     )
-    .ok_or_else(|| StdError::generic_err("failed to find user PDA"))?;
+    .ok_or_else(|| NexusError::NotFound("user PDA".to_string()))?;
 
     let (userstats, _) = Pubkey::find_program_address(
         &["user_stats".as_bytes(), sender_pubkey.0.as_slice()],
         &drift_program_id,
     )
-    .ok_or_else(|| StdError::generic_err("failed to find userstats PDA"))?;
+    .ok_or_else(|| NexusError::NotFound("userstats PDA".to_string()))?;
 
     // deps.api.debug(&format!("user: {}, userstats: {}", user.to_string(), userstats.to_string()));
 
@@ -51,7 +58,7 @@ pub fn create_initialize_user_ixs(sender_svm: String) -> StdResult<Vec<Instructi
     .concat();
     Ok(vec![
         InstructionMeta {
-            program_id: DRIFT_PROGRAM_ID.to_string(),
+            program_id: network.program_id.clone(),
             account_meta: vec![
                 InstructionAccountMeta {
                     pubkey: userstats.to_string(),
@@ -59,7 +66,7 @@ pub fn create_initialize_user_ixs(sender_svm: String) -> StdResult<Vec<Instructi
                     is_writable: true,
                 },
                 InstructionAccountMeta {
-                    pubkey: DRIFT_STATE.to_string(),
+                    pubkey: network.state.clone(),
                     is_signer: false,
                     is_writable: true,
                 },
@@ -87,7 +94,7 @@ pub fn create_initialize_user_ixs(sender_svm: String) -> StdResult<Vec<Instructi
             data: Binary::new(initialize_user_stat_data.to_vec()),
         },
         InstructionMeta {
-            program_id: DRIFT_PROGRAM_ID.to_string(),
+            program_id: network.program_id.clone(),
             account_meta: vec![
                 InstructionAccountMeta {
                     pubkey: user.to_string(),
@@ -100,7 +107,7 @@ pub fn create_initialize_user_ixs(sender_svm: String) -> StdResult<Vec<Instructi
                     is_writable: true,
                 },
                 InstructionAccountMeta {
-                    pubkey: DRIFT_STATE.to_string(),
+                    pubkey: network.state.clone(),
                     is_signer: false,
                     is_writable: true,
                 },
@@ -131,57 +138,48 @@ pub fn create_initialize_user_ixs(sender_svm: String) -> StdResult<Vec<Instructi
 }
 
 pub fn create_deposit_usdt_ix(
-    _deps: Deps,
+    network: &Network,
     sender_svm: String,
     amount: u64,
-) -> StdResult<Vec<InstructionMeta>> {
+) -> NexusResult<Vec<InstructionMeta>> {
     let sender_pubkey = Pubkey::from_string(&sender_svm)?;
-    let spl_token2022_pubkey = Pubkey::from_string(&SPL_TOKEN2022_PROGRAM_ID.to_string())?;
-    let mint = Pubkey::from_string(&MINT.to_string())?;
-    let drift_program_id = Pubkey::from_string(&DRIFT_PROGRAM_ID.to_string())?;
+    let spl_token2022_pubkey = Pubkey::from_string(SPL_TOKEN_2022_PROGRAM_ID)?;
+    let mint = Pubkey::from_string(&network.usdt_mint)?;
+    let drift_program_id = Pubkey::from_string(&network.program_id)?;
     let subacc_index = 0u16.to_le_bytes();
     let (user, _) = Pubkey::find_program_address(
         &["user".as_bytes(), sender_pubkey.0.as_slice(), &subacc_index],
         &drift_program_id,
     )
-    .ok_or_else(|| StdError::generic_err("failed to find user PDA"))?;
+    .ok_or_else(|| NexusError::NotFound("user PDA".to_string()))?;
 
     let (user_stats, _) = Pubkey::find_program_address(
         &["user_stats".as_bytes(), sender_pubkey.0.as_slice()],
         &drift_program_id,
     )
-    .ok_or_else(|| StdError::generic_err("failed to find userstats PDA"))?;
+    .ok_or_else(|| NexusError::NotFound("userstats PDA".to_string()))?;
 
     let market_index = 0u16;
     let (spot_market_vault, _) = Pubkey::find_program_address(
         &[
             "spot_market_vault".as_bytes(),
-            &market_index.to_le_bytes().as_slice(),
+            market_index.to_le_bytes().as_slice(),
         ],
         &drift_program_id,
     )
-    .ok_or_else(|| StdError::generic_err("failed to find spot market vault PDA"))?;
+    .ok_or_else(|| NexusError::NotFound("spot market vault PDA".to_string()))?;
 
     let (spot_market, _) = Pubkey::find_program_address(
         &[
             "spot_market".as_bytes(),
-            &market_index.to_le_bytes().as_slice(),
+            market_index.to_le_bytes().as_slice(),
         ],
         &drift_program_id,
     )
-    .ok_or_else(|| StdError::generic_err("failed to find spot market PDA"))?;
+    .ok_or_else(|| NexusError::NotFound("spot market PDA".to_string()))?;
 
-    let associated_token_program_id =
-        Pubkey::from_string(&ASSOCIATED_TOKEN_PROGRAM_ID.to_string())?;
-    let (user_token_account, _) = Pubkey::find_program_address(
-        &[
-            sender_pubkey.0.as_slice(),
-            spl_token2022_pubkey.0.as_slice(),
-            mint.0.as_slice(),
-        ],
-        &associated_token_program_id,
-    )
-    .ok_or_else(|| StdError::generic_err("failed to find user token account PDA"))?;
+    let user_token_account =
+        get_associated_token_address(&sender_pubkey, &mint, &spl_token2022_pubkey)?;
 
     let deposit_data = &[
         [242, 35, 198, 137, 82, 225, 242, 182].as_slice(),
@@ -192,10 +190,10 @@ pub fn create_deposit_usdt_ix(
     .concat();
 
     Ok(vec![InstructionMeta {
-        program_id: DRIFT_PROGRAM_ID.to_string(),
+        program_id: network.program_id.clone(),
         account_meta: vec![
             InstructionAccountMeta {
-                pubkey: DRIFT_STATE.to_string(),
+                pubkey: network.state.clone(),
                 is_signer: false,
                 is_writable: true,
             },
@@ -225,7 +223,7 @@ pub fn create_deposit_usdt_ix(
                 is_writable: true,
             },
             InstructionAccountMeta {
-                pubkey: SPL_TOKEN2022_PROGRAM_ID.to_string(),
+                pubkey: SPL_TOKEN_2022_PROGRAM_ID.to_string(),
                 is_signer: false,
                 is_writable: false,
             },
@@ -239,8 +237,9 @@ pub fn create_deposit_usdt_ix(
     }])
 }
 
-fn get_all_oracles_and_markets() -> Vec<InstructionAccountMeta> {
-    let mut all_oracles: Vec<InstructionAccountMeta> = [ORACLE_BTC, ORACLE_ETH, ORACLE_SOL]
+fn get_all_oracles_and_markets(network: &Network) -> Vec<InstructionAccountMeta> {
+    let mut all_oracles: Vec<InstructionAccountMeta> = network
+        .oracles
         .iter()
         .map(|oracle_id| InstructionAccountMeta {
             pubkey: oracle_id.to_string(),
@@ -249,7 +248,8 @@ fn get_all_oracles_and_markets() -> Vec<InstructionAccountMeta> {
         })
         .collect();
 
-    let all_markets: Vec<InstructionAccountMeta> = ALL_MARKETS
+    let all_markets: Vec<InstructionAccountMeta> = network
+        .markets
         .iter()
         .map(|id| InstructionAccountMeta {
             pubkey: id.to_string(),
@@ -263,24 +263,21 @@ fn get_all_oracles_and_markets() -> Vec<InstructionAccountMeta> {
 }
 
 pub fn create_place_order_ix(
+    network: &Network,
     sender_svm: String,
     order_params: OrderParams,
-) -> StdResult<Vec<InstructionMeta>> {
+) -> NexusResult<Vec<InstructionMeta>> {
     let sender_pubkey = Pubkey::from_string(&sender_svm)?;
-    let drift_program_id = Pubkey::from_string(&DRIFT_PROGRAM_ID.to_string())?;
+    let drift_program_id = Pubkey::from_string(&network.program_id)?;
     let subacc_index = 0u16.to_le_bytes();
     let (user, _) = Pubkey::find_program_address(
         &["user".as_bytes(), sender_pubkey.0.as_slice(), &subacc_index],
         &drift_program_id,
     )
-    .ok_or_else(|| StdError::generic_err("failed to find user PDA"))?;
+    .ok_or_else(|| NexusError::NotFound("user PDA".to_string()))?;
 
-    let order_param_bz = borsh::to_vec(&order_params).or_else(|e| {
-        Err(StdError::generic_err(format!(
-            "serialize order param err: {}",
-            e.to_string()
-        )))
-    })?;
+    let order_param_bz = borsh::to_vec(&order_params)
+        .map_err(|e| NexusError::invalid_input(format!("serialize order param err: {}", e)))?;
 
     let place_order_data = &[
         [69, 161, 93, 202, 120, 126, 76, 185].as_slice(),
@@ -290,7 +287,7 @@ pub fn create_place_order_ix(
 
     let mut account_meta = vec![
         InstructionAccountMeta {
-            pubkey: DRIFT_STATE.to_string(),
+            pubkey: network.state.clone(),
             is_signer: false,
             is_writable: true,
         },
@@ -308,56 +305,53 @@ pub fn create_place_order_ix(
 
     // TODO: we should include only user related oracle/markets in this instruction
     // this add some more filtering logic so I skipped it for now
-    let all_oracles_markets = get_all_oracles_and_markets();
+    let all_oracles_markets = get_all_oracles_and_markets(network);
     account_meta.extend(all_oracles_markets);
 
     Ok(vec![InstructionMeta {
-        program_id: DRIFT_PROGRAM_ID.to_string(),
+        program_id: network.program_id.clone(),
         account_meta,
         data: Binary::new(place_order_data.to_vec()),
     }])
 }
 
 pub fn create_fill_order_jit_ixs(
+    network: &Network,
     sender_svm: String,
     order_params: OrderParams,
     taker_svm: String,
     taker_order_id: u32,
-) -> StdResult<Vec<InstructionMeta>> {
+) -> NexusResult<Vec<InstructionMeta>> {
     let sender_pubkey = Pubkey::from_string(&sender_svm)?;
     let taker_pubkey = Pubkey::from_string(&taker_svm)?;
-    let drift_program_id = Pubkey::from_string(&DRIFT_PROGRAM_ID.to_string())?;
+    let drift_program_id = Pubkey::from_string(&network.program_id)?;
     let subaccount_id = &[0, 0];
     let (user, _) = Pubkey::find_program_address(
         &["user".as_bytes(), sender_pubkey.0.as_slice(), subaccount_id],
         &drift_program_id,
     )
-    .ok_or_else(|| StdError::generic_err("failed to find user PDA"))?;
+    .ok_or_else(|| NexusError::NotFound("user PDA".to_string()))?;
 
     let (user_stats, _) = Pubkey::find_program_address(
         &["user_stats".as_bytes(), sender_pubkey.0.as_slice()],
         &drift_program_id,
     )
-    .ok_or_else(|| StdError::generic_err("failed to find userstats PDA"))?;
+    .ok_or_else(|| NexusError::NotFound("userstats PDA".to_string()))?;
 
     let (taker_user, _) = Pubkey::find_program_address(
         &["user".as_bytes(), taker_pubkey.0.as_slice(), subaccount_id],
         &drift_program_id,
     )
-    .ok_or_else(|| StdError::generic_err("failed to find taker user PDA"))?;
+    .ok_or_else(|| NexusError::NotFound("taker user PDA".to_string()))?;
 
     let (taker_user_stats, _) = Pubkey::find_program_address(
         &["user_stats".as_bytes(), taker_pubkey.0.as_slice()],
         &drift_program_id,
     )
-    .ok_or_else(|| StdError::generic_err("failed to find taker userstats PDA"))?;
+    .ok_or_else(|| NexusError::NotFound("taker userstats PDA".to_string()))?;
 
-    let order_param_bz = borsh::to_vec(&order_params).or_else(|e| {
-        Err(StdError::generic_err(format!(
-            "serialize order param err: {}",
-            e.to_string()
-        )))
-    })?;
+    let order_param_bz = borsh::to_vec(&order_params)
+        .map_err(|e| NexusError::invalid_input(format!("serialize order param err: {}", e)))?;
 
     let place_and_make_data = &[
         [149, 117, 11, 237, 47, 95, 89, 237].as_slice(),
@@ -368,7 +362,7 @@ pub fn create_fill_order_jit_ixs(
 
     let mut account_meta = vec![
         InstructionAccountMeta {
-            pubkey: DRIFT_STATE.to_string(),
+            pubkey: network.state.clone(),
             is_signer: false,
             is_writable: true,
         },
@@ -399,49 +393,51 @@ pub fn create_fill_order_jit_ixs(
         },
     ];
 
-    let all_oracles_markets = get_all_oracles_and_markets();
+    let all_oracles_markets = get_all_oracles_and_markets(network);
     account_meta.extend(all_oracles_markets);
 
     Ok(vec![InstructionMeta {
-        program_id: DRIFT_PROGRAM_ID.to_string(),
+        program_id: network.program_id.clone(),
         account_meta,
         data: Binary::new(place_and_make_data.to_vec()),
     }])
 }
 
+#[allow(dead_code)]
 pub fn create_fill_order_vamm_ix(
+    network: &Network,
     sender_svm: String,
     taker_svm: String,
     taker_order_id: u32,
-) -> StdResult<Vec<InstructionMeta>> {
+) -> NexusResult<Vec<InstructionMeta>> {
     let sender_pubkey = Pubkey::from_string(&sender_svm)?;
     let taker_pubkey = Pubkey::from_string(&taker_svm)?;
-    let drift_program_id = Pubkey::from_string(&DRIFT_PROGRAM_ID.to_string())?;
+    let drift_program_id = Pubkey::from_string(&network.program_id)?;
     let subacc_index = &0u16.to_le_bytes();
 
     let (filler, _) = Pubkey::find_program_address(
         &["user".as_bytes(), sender_pubkey.0.as_slice(), subacc_index], // TODO: Verify here when we actually implement it
         &drift_program_id,
     )
-    .ok_or_else(|| StdError::generic_err("failed to find filler PDA"))?;
+    .ok_or_else(|| NexusError::NotFound("filler PDA".to_string()))?;
 
     let (filler_stats, _) = Pubkey::find_program_address(
         &["user_stats".as_bytes(), sender_pubkey.0.as_slice()],
         &drift_program_id,
     )
-    .ok_or_else(|| StdError::generic_err("failed to find fillerstats PDA"))?;
+    .ok_or_else(|| NexusError::NotFound("fillerstats PDA".to_string()))?;
 
     let (taker, _) = Pubkey::find_program_address(
         &["user".as_bytes(), taker_pubkey.0.as_slice(), subacc_index],
         &drift_program_id,
     )
-    .ok_or_else(|| StdError::generic_err("failed to find taker PDA"))?;
+    .ok_or_else(|| NexusError::NotFound("taker PDA".to_string()))?;
 
     let (taker_stats, _) = Pubkey::find_program_address(
         &["user_stats".as_bytes(), taker_pubkey.0.as_slice()],
         &drift_program_id,
     )
-    .ok_or_else(|| StdError::generic_err("failed to find takerstats PDA"))?;
+    .ok_or_else(|| NexusError::NotFound("takerstats PDA".to_string()))?;
 
     let fill_data = &[
         &[13, 188, 248, 103, 134, 217, 106, 240],
@@ -453,7 +449,7 @@ pub fn create_fill_order_vamm_ix(
 
     let mut account_meta = vec![
         InstructionAccountMeta {
-            pubkey: DRIFT_STATE.to_string(),
+            pubkey: network.state.clone(),
             is_signer: false,
             is_writable: true,
         },
@@ -484,11 +480,11 @@ pub fn create_fill_order_vamm_ix(
         },
     ];
 
-    let all_oracles_markets = get_all_oracles_and_markets();
+    let all_oracles_markets = get_all_oracles_and_markets(network);
     account_meta.extend(all_oracles_markets);
 
     let instruction_meta = InstructionMeta {
-        program_id: DRIFT_PROGRAM_ID.to_string(),
+        program_id: network.program_id.clone(),
         account_meta,
         data: Binary::new(fill_data.to_vec()),
     };
@@ -718,6 +714,19 @@ pub struct PerpPosition {
     pub per_lp_base: i8,
 }
 
+impl User {
+    /// Decodes the `name` user account data, discriminator included
+    pub fn unpack(name: &str, data: &[u8]) -> NexusResult<Self> {
+        check_discriminator(name, data, USER_DISCRIMINATOR)?;
+        borsh::from_slice::<User>(&data[DISCRIMINATOR_OFFSET..]).map_err(|e| {
+            NexusError::InvalidFisInput {
+                slot: name.to_string(),
+                reason: e.to_string(),
+            }
+        })
+    }
+}
+
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Eq, Debug, Default)]
 pub struct User {
     /// The owner/authority of the account
@@ -786,7 +795,7 @@ pub struct User {
     pub padding: [u8; 12],
 }
 
-pub fn oracle_price_from_perp_market(market_bz: &Binary) -> StdResult<i64> {
+pub fn oracle_price_from_perp_market(market_bz: &Binary) -> NexusResult<i64> {
     const AMM_OFFSET: usize = 32;
     const HISTORICAL_PRICE_OFFSET: usize = 32;
 
@@ -796,8 +805,10 @@ pub fn oracle_price_from_perp_market(market_bz: &Binary) -> StdResult<i64> {
             DISCRIMINATOR_OFFSET + AMM_OFFSET + HISTORICAL_PRICE_OFFSET
                 ..DISCRIMINATOR_OFFSET + AMM_OFFSET + HISTORICAL_PRICE_OFFSET + 8,
         )
-        .ok_or_else(|| {
-            StdError::generic_err("read oracle price failed: must have valid data within range")
+        .and_then(|bz| <[u8; 8]>::try_from(bz).ok())
+        .ok_or_else(|| NexusError::InvalidFisInput {
+            slot: "drift perp market".to_string(),
+            reason: "oracle price out of range".to_string(),
         })?;
-    Ok(i64::from_le_bytes(price_bz.try_into().unwrap()))
+    Ok(i64::from_le_bytes(price_bz))
 }
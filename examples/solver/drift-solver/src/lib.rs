@@ -1,66 +1,52 @@
-use astromesh::{FISInput, FISInstruction, MsgAstroTransfer, NexusAction};
-use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{
-    entry_point, from_json, to_json_binary, to_json_vec, Binary, Coin, Deps, DepsMut, Env, Int128, MessageInfo, Response, StdError, StdResult, Uint128, Uint64
-};
+use astromesh::NexusAction;
+use config::Config;
+use cosmwasm_std::{to_json_vec, Coin, Deps, Env, Int128, Uint128, Uint64};
 use drift::{
-    create_deposit_usdt_ix, create_fill_order_jit_ixs,
+    check_discriminator, create_deposit_usdt_ix, create_fill_order_jit_ixs,
     create_initialize_user_ixs, create_place_order_ix, oracle_price_from_perp_market, MarketType,
     OrderParams, OrderStatus, OrderTriggerCondition, OrderType, PositionDirection, PostOnlyParam,
     User, DRIFT_DEFAULT_PERCISION, PERP_MARKET_DISCRIMINATOR,
 };
-use std::{collections::HashMap, vec::Vec};
-use svm::{Account, AccountLink, Pubkey, TransactionBuilder};
+use network::Network;
+use nexus_sdk::{
+    astromesh::{FISInput, FISInstruction, MsgAstroTransfer, Plane, StrategyOutput},
+    error::{NexusError, NexusResult},
+    input::Slot,
+    nexus_strategy,
+    svm::{Account, AccountLink, Pubkey, TransactionBuilder},
+};
+use std::collections::HashMap;
 mod astromesh;
+mod config;
 mod drift;
-mod svm;
+mod network;
 mod test;
 
-#[cw_serde]
-pub struct InstantiateMsg {}
+nexus_strategy!(NexusAction, handle, network = Network, config = Config);
 
-#[cw_serde]
-pub enum ExecuteMsg {}
+// fis input layout of `place_perp_market_order`:
+// [[svm account link], [user (null until initialized), perp market 0, perp market 1, perp market 2]]
+const PLACE_ORDER_SVM_LINK: Slot<AccountLink> = Slot::new(0, 0, "svm account link");
+const PLACE_ORDER_USER: Slot<Account> = Slot::new(1, 0, "drift user");
 
-#[entry_point]
-pub fn instantiate(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "instantiate"))
+fn perp_market_slot(market_index: u16) -> Slot<Account> {
+    Slot::new(1, market_index as usize + 1, "drift perp market")
 }
 
-#[entry_point]
-pub fn execute(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: ExecuteMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "execute"))
-}
-
-#[cw_serde]
-pub struct QueryMsg {
-    msg: Binary,
-    fis_input: Vec<FISInput>,
-}
+// fis input layout of `fill_perp_market_order`:
+// [[svm account link], [user (null until initialized), taker user]]
+const FILL_ORDER_SVM_LINK: Slot<AccountLink> = Slot::new(0, 0, "svm account link");
+const FILL_ORDER_USER: Slot<Account> = Slot::new(1, 0, "drift user");
+const FILL_ORDER_TAKER_USER: Slot<Account> = Slot::new(1, 1, "drift taker user");
 
-#[cw_serde]
-pub struct StrategyOutput {
-    instructions: Vec<FISInstruction>,
-}
-
-pub fn get_all_market_indexes(drift_program_id: Pubkey) -> StdResult<HashMap<String, u16>> {
+pub fn get_all_market_indexes(drift_program_id: Pubkey) -> NexusResult<HashMap<String, u16>> {
     let mut market_indexes = HashMap::new();
     for idx in 0u16..4 {
         let (market, _) = Pubkey::find_program_address(
             &["perp_market".as_bytes(), idx.to_le_bytes().as_ref()],
             &drift_program_id,
         )
-        .ok_or_else(|| StdError::generic_err("failed to find market PDA"))?;
+        .ok_or_else(|| NexusError::NotFound("market PDA".to_string()))?;
         market_indexes.insert(market.to_string(), idx);
     }
     Ok(market_indexes)
@@ -73,30 +59,26 @@ pub fn is_in_auction_time(height: u64, order_creation_slot: u64, auction_period:
     false
 }
 
-pub fn astro_transfer(cosmos_addr: String, amount: u64) -> Vec<FISInstruction> {
+pub fn astro_transfer(cosmos_addr: String, amount: u64) -> NexusResult<Vec<FISInstruction>> {
     let mut instructions = vec![];
 
     let msg = MsgAstroTransfer::new(
         cosmos_addr.clone(),
         cosmos_addr.clone(),
-        "COSMOS".to_string(),
-        "SVM".to_string(),
+        Plane::Cosmos,
+        Plane::Svm,
         Coin {
             denom: "usdt".to_string(),
             amount: amount.into(),
         },
     );
 
-    instructions.push(FISInstruction {
-        plane: "COSMOS".to_string(),
-        action: "COSMOS_INVOKE".to_string(),
-        address: "".to_string(),
-        msg: to_json_vec(&msg).unwrap(),
-    });
+    instructions.push(FISInstruction::cosmos_invoke(to_json_vec(&msg)?));
 
-    instructions
+    Ok(instructions)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn place_perp_market_order(
     deps: Deps,
     env: Env,
@@ -107,101 +89,91 @@ pub fn place_perp_market_order(
     direction: String,
     // fis[0]: cosmos: acc link
     // fis[1]: svm: accounts [user, market 0, market 1, market 2]
-    fis_input: &Vec<FISInput>,
-) -> StdResult<Binary> {
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
     let mut instructions = vec![];
     // validate msg inputs
-    let market_index: u16 = match market.as_str() {
-        "btc-usdt" => 0,
-        "eth-usdt" => 1,
-        "sol-usdt" => 2,
-        unknown_market => {
-            return Err(StdError::generic_err(format!(
-                "market '{}' is not supported",
-                unknown_market
-            )))
-        }
-    };
+    let network = Network::load(deps)?;
+    let config = Config::load(deps)?;
+    let market_index = network.perp_market_index(&market)?;
 
     let leverage = leverage.u64();
-    if leverage < 1 || leverage > 20 {
-        return Err(StdError::generic_err(format!(
-            "leverage must be integer in range 1..20. Actual: {}",
-            leverage,
+    if !(1..=config.max_leverage).contains(&leverage) {
+        return Err(NexusError::invalid_input(format!(
+            "leverage must be integer in range 1..{}. Actual: {}",
+            config.max_leverage, leverage,
         )));
     }
 
     let auction_duration = auction_duration.u64();
-    if auction_duration < 10 || auction_duration > 255 {
-        return Err(StdError::generic_err(format!(
-            "auction_duration must be integer in range 10..255. Actual: {}",
-            leverage,
+    if !(config.min_auction_duration..=255).contains(&auction_duration) {
+        return Err(NexusError::invalid_input(format!(
+            "auction_duration must be integer in range {}..255. Actual: {}",
+            config.min_auction_duration, auction_duration,
         )));
     }
 
     if direction != "long" && direction != "short" {
-        return Err(StdError::generic_err(
+        return Err(NexusError::invalid_input(
             "direction must be either 'long' or 'short'",
         ));
     }
 
+    let quote_asset_amount = u64::try_from(usdt_amount.i128())
+        .ok()
+        .filter(|amount| *amount > 0)
+        .ok_or_else(|| {
+            NexusError::invalid_input(format!(
+                "usdt_amount must be a positive u64. Actual: {}",
+                usdt_amount
+            ))
+        })?;
+
     // parse + validate fis query
-    let fis = &fis_input[0];
-    let acc_link = from_json::<AccountLink>(fis.data.first().unwrap())?;
+    let acc_link = PLACE_ORDER_SVM_LINK.load(fis_input)?;
     let svm_addr = acc_link.link.svm_addr;
-    let user_info_bz = fis_input[1]
-        .data
-        .get(0)
-        .ok_or_else(|| StdError::generic_err("user info must exist"))?;
-
-    let market_bz = fis_input[1]
-        .data
-        .get((market_index as usize) + 1)
-        .ok_or_else(|| StdError::generic_err("requested market must exist"))?;
-    deps.api.debug(format!("market bz: {}", market_bz).as_str());
-
-    let market_account = from_json::<Account>(market_bz)?;
-    if !market_account.data[..8].starts_with(PERP_MARKET_DISCRIMINATOR) {
-        return Err(StdError::generic_err(format!(
-            "market account data must begin with {:?}",
-            PERP_MARKET_DISCRIMINATOR
-        )));
-    }
+    let user_info = PLACE_ORDER_USER.may_load(fis_input)?;
+
+    let market_slot = perp_market_slot(market_index);
+    deps.api
+        .debug(format!("market bz: {}", market_slot.raw(fis_input)?).as_str());
+
+    let market_account = market_slot.load(fis_input)?;
+    check_discriminator(
+        market_slot.name,
+        &market_account.data,
+        PERP_MARKET_DISCRIMINATOR,
+    )?;
 
     // compose instructions
     // 1. create accounts if not exist
     let mut tx = TransactionBuilder::new();
     let mut user_order_id = 1;
-    if user_info_bz.eq(&"null".as_bytes()) {
-        let init_account_ixs = create_initialize_user_ixs(svm_addr.clone())?;
-        tx.add_instructions(init_account_ixs);
-    } else {
+    if let Some(user_info) = user_info {
         // if user exists, get next user id from its info
-        let user_info = from_json::<Account>(user_info_bz)?;
-        const USER_DISCRIMINATOR: &[u8] = &[159, 117, 95, 227, 239, 151, 58, 236];
-        let user_data = user_info.data;
-        if !user_data[..8].starts_with(USER_DISCRIMINATOR) {
-            return Err(StdError::generic_err(format!(
-                "invalid user discriminator, expected: {:?}",
-                USER_DISCRIMINATOR
-            )));
-        }
-        let user_info =
-            borsh::from_slice::<User>(&user_data[8..]).expect("must be parsed as drift::User");
+        let user_info = User::unpack(PLACE_ORDER_USER.name, &user_info.data)?;
         user_order_id = user_info.next_order_id as u8; // TODO: Inspect this order_id to see why it's u32 in user_info struct
+    } else {
+        let init_account_ixs = create_initialize_user_ixs(&network, svm_addr.clone())?;
+        tx.add_instructions(init_account_ixs);
     };
 
     // 2. deposit usdt
-    let quote_asset_amount = usdt_amount.i128() as u64;
     let cosmos_addr = env.contract.address.to_string();
-    let astro_transfer_ix = astro_transfer(cosmos_addr.clone(), quote_asset_amount);
+    let astro_transfer_ix = astro_transfer(cosmos_addr.clone(), quote_asset_amount)?;
     instructions.extend(astro_transfer_ix);
 
-    let deposit_ixs = create_deposit_usdt_ix(deps, svm_addr.clone(), quote_asset_amount)?;
+    let deposit_ixs = create_deposit_usdt_ix(&network, svm_addr.clone(), quote_asset_amount)?;
     tx.add_instructions(deposit_ixs);
 
     // 3. place order
     let market_price = oracle_price_from_perp_market(&market_account.data)?;
+    if market_price <= 0 {
+        return Err(NexusError::InvalidFisInput {
+            slot: market_slot.name.to_string(),
+            reason: format!("oracle price must be positive. Actual: {}", market_price),
+        });
+    }
     let order_direction: PositionDirection;
     let start_price: i64;
     let end_price: i64;
@@ -215,13 +187,16 @@ pub fn place_perp_market_order(
     let expire_time = env.block.time.seconds() as i64 + 120;
 
     // base_asset_amount = usdt_amount * leverage / price
+    let base_asset_amount =
+        u128::from(quote_asset_amount) * u128::from(leverage) * u128::from(DRIFT_DEFAULT_PERCISION)
+            / market_price as u128;
     let order_params = OrderParams {
         order_type: OrderType::Market,
         market_type: MarketType::Perp,
         direction: order_direction,
         user_order_id,
-        base_asset_amount: quote_asset_amount * (leverage as u64) * DRIFT_DEFAULT_PERCISION
-            / (market_price as u64),
+        base_asset_amount: u64::try_from(base_asset_amount)
+            .map_err(|_| NexusError::Math(format!("base asset amount {}", base_asset_amount)))?,
         price: market_price as u64, // oralce price
         market_index,
         reduce_only: false,
@@ -232,25 +207,20 @@ pub fn place_perp_market_order(
         trigger_condition: OrderTriggerCondition::Above,
         oracle_price_offset: Some(0),
         auction_duration: Some(auction_duration as u8),
-        auction_start_price: Some(start_price.try_into().unwrap()),
-        auction_end_price: Some(end_price.try_into().unwrap()),
+        auction_start_price: Some(start_price),
+        auction_end_price: Some(end_price),
     };
 
-    let place_order_ixs = create_place_order_ix(svm_addr.clone(), order_params)?;
+    let place_order_ixs = create_place_order_ix(&network, svm_addr.clone(), order_params)?;
     let compute_budget = 5_000_000u64;
     tx.add_instructions(place_order_ixs);
 
-    let msg = tx.build(vec![cosmos_addr], compute_budget.into());
+    let msg = tx.build(vec![cosmos_addr], compute_budget);
     deps.api.debug(&format!("msg {:?}", msg));
 
-    instructions.push(FISInstruction {
-        plane: "SVM".to_string(),
-        action: "VM_INVOKE".to_string(),
-        address: "".to_string(),
-        msg: to_json_vec(&msg)?,
-    });
+    instructions.push(FISInstruction::vm_invoke(Plane::Svm, to_json_vec(&msg)?));
 
-    Ok(to_json_binary(&StrategyOutput { instructions })?)
+    Ok(StrategyOutput::new(instructions))
 }
 
 pub fn fill_perp_market_order(
@@ -259,47 +229,30 @@ pub fn fill_perp_market_order(
     taker_svm: String,
     taker_order_id: Uint64,
     quantity: Uint64,
-    // fis[0]: cosmos: acc link
-    // fis[1]: svm: accounts [maker_user, taker_user]
-    fis_input: &Vec<FISInput>,
-) -> StdResult<Binary> {
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
+    let network = Network::load(deps)?;
     let sender = env.contract.address.to_string();
     let taker_order_id = taker_order_id.u64() as u32;
 
-    let sender_svm_link = from_json::<AccountLink>(fis_input.get(0).unwrap().data.get(0).unwrap())?; // sender svm
+    let sender_svm_link = FILL_ORDER_SVM_LINK.load(fis_input)?; // sender svm
     let svm_addr = sender_svm_link.link.svm_addr;
-    let taker_info_bz = fis_input.get(1).unwrap().data.get(1).unwrap();
-    if taker_info_bz.eq(&"null".as_bytes()) {
-        return Err(StdError::generic_err("taker subaccount is not initialized"));
-    }
+    let taker_info = FILL_ORDER_TAKER_USER
+        .may_load(fis_input)?
+        .filter(|taker_info| !taker_info.lamports.is_zero())
+        .ok_or_else(|| NexusError::NotFound("taker subaccount".to_string()))?;
+    let taker_info = User::unpack(FILL_ORDER_TAKER_USER.name, &taker_info.data)?;
 
-    let taker_info = from_json::<Account>(taker_info_bz)?;
-    if taker_info.lamports.is_zero() {
-        return Err(StdError::generic_err("taker subaccount is not initialized"));
-    }
-
-    let taker_info_bz = taker_info.data;
-    const USER_DISCRIMINATOR: &[u8] = &[159, 117, 95, 227, 239, 151, 58, 236];
-    if !taker_info_bz[..8].starts_with(USER_DISCRIMINATOR) {
-        return Err(StdError::generic_err(format!(
-            "invalid user discriminator, expected: {:?}",
-            USER_DISCRIMINATOR
-        )));
-    }
-
-    let sender_info = fis_input.get(1).unwrap().data.get(0).unwrap();
+    let sender_info = FILL_ORDER_USER.may_load(fis_input)?;
     let mut fis_instructions = vec![];
     let mut tx_builder = TransactionBuilder::new();
 
     // if subaccount is not created, create it
-    if sender_info.starts_with("null".as_bytes()) {
-        let initialize_ixs = create_initialize_user_ixs(svm_addr.clone())?;
+    if sender_info.is_none() {
+        let initialize_ixs = create_initialize_user_ixs(&network, svm_addr.clone())?;
         tx_builder.add_instructions(initialize_ixs);
     }
 
-    let taker_info =
-        borsh::from_slice::<User>(&taker_info_bz[8..]).expect("must be parsed as drift::User");
-
     let order_ids = taker_info
         .orders
         .iter()
@@ -311,29 +264,30 @@ pub fn fill_perp_market_order(
         .orders
         .iter()
         .find(|x| x.order_id == taker_order_id)
-        .expect(
-            format!(
-                "taker order id {} must exist. Existing orders: {:?}",
+        .ok_or_else(|| {
+            NexusError::NotFound(format!(
+                "taker order id {}, existing orders: {:?}",
                 taker_order_id, order_ids
-            )
-            .as_str(),
-        );
+            ))
+        })?;
 
     // if not in auction time => do nothing
     if !is_in_auction_time(env.block.height, order.slot, order.auction_duration) {
-        return to_json_binary(&StrategyOutput {
-            instructions: vec![],
-        });
+        return Ok(StrategyOutput::default());
     }
 
-    let mut fillable_quantity = quantity.u64();
     // try to fill all fillable amount to improve UX
-    if fillable_quantity > order.base_asset_amount - order.base_asset_amount_filled {
-        fillable_quantity = order.base_asset_amount - order.base_asset_amount_filled
-    }
-
-    let usdt_to_deposit = fillable_quantity * order.price / DRIFT_DEFAULT_PERCISION;
-    let deposit_ixs = create_deposit_usdt_ix(deps, svm_addr.clone(), usdt_to_deposit)?;
+    let unfilled_quantity = order
+        .base_asset_amount
+        .checked_sub(order.base_asset_amount_filled)
+        .ok_or_else(|| NexusError::Math("order filled more than its amount".to_string()))?;
+    let fillable_quantity = quantity.u64().min(unfilled_quantity);
+
+    let usdt_to_deposit = fillable_quantity
+        .checked_mul(order.price)
+        .ok_or_else(|| NexusError::Math("usdt to deposit overflows".to_string()))?
+        / DRIFT_DEFAULT_PERCISION;
+    let deposit_ixs = create_deposit_usdt_ix(&network, svm_addr.clone(), usdt_to_deposit)?;
     tx_builder.add_instructions(deposit_ixs);
 
     let direction = match order.direction {
@@ -362,7 +316,7 @@ pub fn fill_perp_market_order(
     };
 
     let fill_jit_ixs =
-        create_fill_order_jit_ixs(svm_addr, order_params, taker_svm, taker_order_id)?;
+        create_fill_order_jit_ixs(&network, svm_addr, order_params, taker_svm, taker_order_id)?;
 
     tx_builder.add_instructions(fill_jit_ixs);
     tx_builder.build(vec![sender.clone()], 10_000_000);
@@ -371,39 +325,32 @@ pub fn fill_perp_market_order(
     let cosmos_transfer = MsgAstroTransfer::new(
         sender.clone(),
         sender,
-        "COSMOS".to_string(),
-        "SVM".to_string(),
+        Plane::Cosmos,
+        Plane::Svm,
         Coin {
             denom: "usdt".to_string(),
             amount: Uint128::from(usdt_to_deposit),
         },
     );
 
-    fis_instructions.push(FISInstruction {
-        plane: "COSMOS".to_string(),
-        action: "COSMOS_INVOKE".to_string(),
-        address: "".to_string(),
-        msg: to_json_vec(&cosmos_transfer)?,
-    });
+    fis_instructions.push(FISInstruction::cosmos_invoke(to_json_vec(
+        &cosmos_transfer,
+    )?));
 
     // do drift instructions
     let msg = tx_builder.build(vec![env.contract.address.to_string()], 10_000_000);
-    let instruction = FISInstruction {
-        plane: "SVM".to_string(),
-        action: "VM_INVOKE".to_string(),
-        address: "".to_string(),
-        msg: to_json_vec(&msg)?,
-    };
+    let instruction = FISInstruction::vm_invoke(Plane::Svm, to_json_vec(&msg)?);
     fis_instructions.push(instruction);
 
-    return to_json_binary(&StrategyOutput {
-        instructions: fis_instructions,
-    });
+    Ok(StrategyOutput::new(fis_instructions))
 }
 
-#[entry_point]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    let action = from_json::<NexusAction>(msg.msg)?;
+pub fn handle(
+    deps: Deps,
+    env: Env,
+    action: NexusAction,
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
     match action {
         NexusAction::PlacePerpMarketOrder {
             direction,
@@ -419,7 +366,7 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             leverage,
             auction_duration,
             direction,
-            &msg.fis_input,
+            fis_input,
         ),
         NexusAction::FillPerpMarketOrder {
             taker_svm_address,
@@ -431,7 +378,7 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             taker_svm_address,
             taker_order_id,
             quantity,
-            &msg.fis_input,
+            fis_input,
         ),
     }
 }
@@ -0,0 +1,45 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{from_json, Deps};
+use nexus_sdk::{
+    error::{NexusError, NexusResult},
+    strategy::load_network,
+};
+use std::collections::BTreeMap;
+
+/// Network the contract falls back to when instantiated without one
+pub const EMBEDDED_NETWORK: &str = include_str!("../networks/devnet.json");
+
+/// Drift deployment, passed as `network` at instantiate, see `networks/devnet.json`
+#[cw_serde]
+pub struct Network {
+    pub program_id: String,
+    pub state: String,
+    /// spl mint of the usdt spot market, deposited as collateral
+    pub usdt_mint: String,
+    /// oracles and markets passed as remaining accounts of order instructions
+    pub oracles: Vec<String>,
+    pub markets: Vec<String>,
+    /// perp market index of each market name, e.g. `btc-usdt`
+    pub perp_market_indexes: BTreeMap<String, u16>,
+}
+
+impl Network {
+    /// Network stored at instantiate, or the embedded one
+    pub fn load(deps: Deps) -> NexusResult<Self> {
+        match load_network(deps.storage)? {
+            Some(network) => Ok(network),
+            None => Self::embedded(),
+        }
+    }
+
+    pub fn embedded() -> NexusResult<Self> {
+        Ok(from_json(EMBEDDED_NETWORK)?)
+    }
+
+    pub fn perp_market_index(&self, market: &str) -> NexusResult<u16> {
+        self.perp_market_indexes
+            .get(market)
+            .copied()
+            .ok_or_else(|| NexusError::UnsupportedPair(market.to_string()))
+    }
+}
@@ -1,43 +1,8 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Binary, Coin, DenomMetadata, Uint128, Uint64};
+use cosmwasm_std::{DenomMetadata, Uint128, Uint64};
 use rlp::RlpStream;
-use sha2::{Digest, Sha256};
 use tiny_keccak::{Hasher, Keccak};
 
-pub const PLANE_COSMOS: &str = "COSMOS";
-
-pub const ACTION_COSMOS_INVOKE: &str = "COSMOS_INVOKE";
-
-#[cw_serde]
-pub struct MsgAstroTransfer {
-    #[serde(rename = "@type")]
-    pub ty: String,
-    pub sender: String,
-    pub receiver: String,
-    pub src_plane: String,
-    pub dst_plane: String,
-    pub coin: Coin,
-}
-
-impl MsgAstroTransfer {
-    pub fn new(
-        sender: String,
-        receiver: String,
-        src_plane: String,
-        dst_plane: String,
-        coin: Coin,
-    ) -> Self {
-        MsgAstroTransfer {
-            ty: "/flux.astromesh.v1beta1.MsgAstroTransfer".to_string(),
-            sender,
-            receiver,
-            src_plane,
-            dst_plane,
-            coin,
-        }
-    }
-}
-
 #[cw_serde]
 pub struct InitialMint {
     pub address: String,
@@ -71,19 +36,6 @@ impl MsgCreateBankDenom {
     }
 }
 
-#[cw_serde]
-pub struct FISInput {
-    pub data: Vec<Binary>,
-}
-
-#[cw_serde]
-pub struct FISInstruction {
-    pub plane: String,
-    pub action: String,
-    pub address: String,
-    pub msg: Vec<u8>,
-}
-
 #[cw_serde]
 pub enum NexusAction {
     CreateToken {
@@ -125,13 +77,6 @@ pub struct AccountResponse {
     pub account: Account,
 }
 
-#[cw_serde]
-pub struct QueryDenomLinkResponse {
-    pub dst_addr: String,
-    pub src_decimals: i32,
-    pub dst_decimals: i32,
-}
-
 pub fn keccak256(input: &[u8]) -> [u8; 32] {
     let mut hash = Keccak::v256();
     hash.update(input);
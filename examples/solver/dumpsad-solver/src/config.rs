@@ -0,0 +1,27 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Deps, Uint128};
+use nexus_sdk::{error::NexusResult, strategy::load_config};
+
+/// Curve parameters, passed as `config` at instantiate and replaced with `UpdateConfig`
+#[cw_serde]
+pub struct Config {
+    /// denom memes are bought and sold with
+    pub quote_denom: String,
+    /// market cap in quote denom at which the curve graduates to a dex pool
+    pub market_cap_to_graduate: Uint128,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            quote_denom: "sol".to_string(),
+            market_cap_to_graduate: Uint128::new(400_000_000_000),
+        }
+    }
+}
+
+impl Config {
+    pub fn load(deps: Deps) -> NexusResult<Self> {
+        Ok(load_config(deps.storage)?)
+    }
+}
@@ -1,4 +1,5 @@
 use cosmwasm_std::Uint128;
+use nexus_sdk::error::NexusResult;
 
 // curve y = a*1000_000_000 - (b*1000_000_000) / (30 * 1_000_000_000 + x)
 // both SOL and meme has 9 decimals
@@ -24,41 +25,51 @@ impl BondingCurve {
         }
     }
 
-    pub fn price(&self) -> Uint128 {
+    pub fn price(&self) -> NexusResult<Uint128> {
         // Price: (30 + x)^2 / b
-        let tmp = Uint128::new(30) * BondingCurve::PRECISION_MULTIPLIER + self.x;
-        (tmp * tmp) / self.b
+        let tmp = self.offset_x()?;
+        Ok(tmp.checked_mul(tmp)?.checked_div(self.b)?)
+    }
+
+    // 30 + x
+    fn offset_x(&self) -> NexusResult<Uint128> {
+        Ok((Uint128::new(30) * BondingCurve::PRECISION_MULTIPLIER).checked_add(self.x)?)
     }
 
     // dY = delta Y, dX = delta X
-    pub fn buy(&mut self, dx: Uint128) -> Uint128 {
+    pub fn buy(&mut self, dx: Uint128) -> NexusResult<Uint128> {
         // y = a - b / (30 + x) (recall: y is the minted amount for user, counting from 0, not 10^9)
         // newY = a - b / (30 + newX)
         // newX = x + dx
         // newY = y + dy => dy = newY - y
-        let new_x = Uint128::new(30) * BondingCurve::PRECISION_MULTIPLIER + self.x + dx;
-        let new_y = self.a - (self.b * BondingCurve::PRECISION_MULTIPLIER) / new_x;
-        let dy = new_y - self.y;
+        let new_x = self.offset_x()?.checked_add(dx)?;
+        let new_y = self
+            .a
+            .checked_sub(self.b.checked_mul(BondingCurve::PRECISION_MULTIPLIER)? / new_x)?;
+        let dy = new_y.checked_sub(self.y)?;
 
         // Update state
         self.x += dx;
         self.y = new_y;
 
-        dy
+        Ok(dy)
     }
 
-    pub fn sell(&mut self, dy: Uint128) -> Uint128 {
+    pub fn sell(&mut self, dy: Uint128) -> NexusResult<Uint128> {
         // y = a - b / (30 + x) (recall: y is the minted amount for user, counting from 0, not 10^9)
         // newY = a - b / (30 + newX)
         // newY = y - dy
         // newX = x - dx => dx = x - newX
-        let new_y = self.y - dy;
-        let new_x = (self.b * BondingCurve::PRECISION_MULTIPLIER) / (self.a - new_y);
-        let dx = Uint128::new(30) * BondingCurve::PRECISION_MULTIPLIER + self.x - new_x;
+        let new_y = self.y.checked_sub(dy)?;
+        let new_x = self
+            .b
+            .checked_mul(BondingCurve::PRECISION_MULTIPLIER)?
+            .checked_div(self.a.checked_sub(new_y)?)?;
+        let dx = self.offset_x()?.checked_sub(new_x)?;
         // Update state
-        self.x -= dx;
+        self.x = self.x.checked_sub(dx)?;
         self.y = new_y;
 
-        dx
+        Ok(dx)
     }
 }
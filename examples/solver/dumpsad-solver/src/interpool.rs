@@ -55,6 +55,7 @@ pub struct MsgUpdatePool {
 }
 
 impl MsgUpdatePool {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         sender: String,
         pool_id: String,
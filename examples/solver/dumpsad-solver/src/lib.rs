@@ -1,33 +1,35 @@
 use astromesh::{
-    denom_address, keccak256, AccountResponse, FISInput, FISInstruction, InitialMint,
-    MsgAstroTransfer, MsgCreateBankDenom, NexusAction, ACTION_COSMOS_INVOKE, PLANE_COSMOS,
+    denom_address, keccak256, AccountResponse, InitialMint, MsgCreateBankDenom, NexusAction,
 };
 use bech32::{Bech32, Hrp};
+use config::Config;
 use core::str;
-use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    entry_point, from_json, to_json_binary, to_json_string, to_json_vec, Binary, Coin,
-    DenomMetadata, DenomUnit, Deps, DepsMut, Env, HexBinary, MessageInfo, Response, StdError,
-    StdResult, Uint128,
+    from_json, to_json_binary, to_json_string, to_json_vec, Coin, DenomMetadata, DenomUnit, Deps,
+    Env, HexBinary, Uint128,
 };
 use curve::BondingCurve;
 use events::{CreateTokenEvent, GraduateEvent, TradeTokenEvent};
 use interpool::{
     CommissionConfig, DumpsadPoolState, MsgCreatePool, MsgUpdatePool, QueryPoolResponse,
 };
-use std::vec::Vec;
-use svm::{AccountLink, Pubkey};
+use nexus_sdk::{
+    astromesh::{FISInput, FISInstruction, MsgAstroTransfer, Plane, StrategyEvent, StrategyOutput},
+    error::{NexusError, NexusResult},
+    input::Slot,
+    nexus_strategy,
+    svm::Pubkey,
+};
+use std::str::FromStr;
 mod astromesh;
+mod config;
 mod curve;
 mod events;
 mod interpool;
-mod svm;
 mod test;
 
 const PERCENTAGE_BPS: u128 = 10_000;
-const DEFAULT_QUOTE_DENOM: &str = "sol";
 const INITIAL_AMOUNT: &Uint128 = &Uint128::new(1_000_000_000_000_000_000);
-const MARKET_CAP_TO_GRADUATE: &Uint128 = &Uint128::new(400_000_000_000);
 const POOL_AUTHORITY: &[u8] = &[
     111, 10, 197, 241, 216, 79, 240, 92, 96, 219, 139, 173, 223, 107, 146, 221, 199, 188, 78, 138,
     204, 94, 40, 161, 156, 98, 22, 62, 231, 66, 234, 135,
@@ -37,51 +39,49 @@ const MINT_AUTHORITY: &[u8] = &[
     185, 169, 178, 17, 51, 230, 69, 149, 173, 160, 138,
 ];
 
-#[cw_serde]
-pub struct InstantiateMsg {}
-
-#[cw_serde]
-pub enum ExecuteMsg {}
+nexus_strategy!(NexusAction, handle, config = Config);
 
-#[entry_point]
-pub fn instantiate(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "instantiate"))
-}
+// fis input layout of `create_token`
+const CREATOR_ACCOUNT: Slot<AccountResponse> = Slot::new(0, 0, "creator account");
 
-#[entry_point]
-pub fn execute(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: ExecuteMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "execute"))
-}
+// fis input layout of `trade`, both buy and sell
+const TRADE_POOL: Slot<QueryPoolResponse> = Slot::new(0, 0, "dumpsad pool");
 
-#[cw_serde]
-pub struct QueryMsg {
-    msg: Binary,
-    fis_input: Vec<FISInput>,
+fn lux_address(bz: &[u8]) -> NexusResult<String> {
+    let hrp = Hrp::parse("lux").map_err(NexusError::invalid_input)?;
+    bech32::encode::<Bech32>(hrp, bz).map_err(NexusError::invalid_input)
 }
 
-#[cw_serde]
-pub struct StrategyEvent {
-    pub topic: String,
-    pub data: Binary,
+fn find_program_address(seeds: &[&[u8]], program_id: &[u8]) -> NexusResult<Pubkey> {
+    let program_id = Pubkey::from_slice(program_id)?;
+    Pubkey::find_program_address(seeds, &program_id)
+        .map(|(address, _)| address)
+        .ok_or_else(|| NexusError::NotFound(format!("program address of {}", program_id)))
 }
 
-#[cw_serde]
-pub struct StrategyOutput {
-    instructions: Vec<FISInstruction>,
-    events: Vec<StrategyEvent>,
-    result: String,
+// worst accepted output for `amount` at `price` (scaled by the curve precision) given slippage in bps
+fn worst_amount(
+    amount: Uint128,
+    slippage: Uint128,
+    price_num: Uint128,
+    price_den: Uint128,
+) -> NexusResult<Uint128> {
+    let remaining_bps = Uint128::new(PERCENTAGE_BPS)
+        .checked_sub(slippage)
+        .map_err(|_| {
+            NexusError::invalid_input(format!(
+                "slippage {} exceeds {} bps",
+                slippage, PERCENTAGE_BPS
+            ))
+        })?;
+    Ok(amount
+        .checked_mul(price_num)?
+        .checked_mul(remaining_bps)?
+        .checked_div(price_den)?
+        .checked_div(Uint128::new(PERCENTAGE_BPS))?)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_create_token(
     _deps: Deps,
     env: Env,
@@ -92,14 +92,13 @@ fn handle_create_token(
     target_vm: String,
     solver_id: String,
     cron_id: String,
-    fis_input: &Vec<FISInput>,
-) -> StdResult<StrategyOutput> {
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
     let creator = env.contract.address.to_string();
     let target_vm = target_vm.to_uppercase();
 
-    let acc_info = from_json::<AccountResponse>(fis_input.get(0).unwrap().data.get(0).unwrap())?;
-    let (_, creator_bz) =
-        bech32::decode(&creator).map_err(|e| StdError::generic_err(e.to_string()))?;
+    let acc_info = CREATOR_ACCOUNT.load(fis_input)?;
+    let (_, creator_bz) = bech32::decode(&creator).map_err(NexusError::invalid_input)?;
     let pool_id_seed = &[
         "pool".as_bytes(),
         creator_bz.as_slice(),
@@ -109,27 +108,21 @@ fn handle_create_token(
     ]
     .concat();
 
-    let pool_id = &keccak256(&pool_id_seed)[12..];
+    let pool_id = &keccak256(pool_id_seed)[12..];
     // TODO: Check cosmwasm std Addr, it needs callback/FFI
-    let pool_address = bech32::encode::<Bech32>(Hrp::parse("lux").unwrap(), pool_id)
-        .map_err(|e| StdError::generic_err(e.to_string()))?;
-    let (pool_svm_address, _) =
-        Pubkey::find_program_address(&[pool_id], &Pubkey::from_slice(POOL_AUTHORITY).unwrap())
-            .unwrap();
+    let pool_address = lux_address(pool_id)?;
+    let pool_svm_address = find_program_address(&[pool_id], POOL_AUTHORITY)?;
 
     let denom_base = format!("astromesh/{}/{}", creator.clone(), symbol);
     let denom_symbol = symbol;
-    let vm_denom_addr = match target_vm.as_str() {
-        "SVM" => {
+    let target_plane = Plane::from_str(&target_vm)?;
+    let vm_denom_addr = match target_plane {
+        Plane::Svm => {
             let denom_address = denom_address(pool_id, 0u64);
-            let (svm_denom, _) = Pubkey::find_program_address(
-                &[denom_address.as_slice()],
-                &Pubkey::from_slice(MINT_AUTHORITY).unwrap(),
-            )
-            .unwrap();
+            let svm_denom = find_program_address(&[denom_address.as_slice()], MINT_AUTHORITY)?;
             &svm_denom.to_string()
         }
-        "EVM" => {
+        Plane::Evm => {
             let denom_address = denom_address(pool_id, 0u64);
             &HexBinary::from(denom_address).to_string()
         }
@@ -175,7 +168,7 @@ fn handle_create_token(
         "".to_string(), // only do initial mint, cannot mint more
         vec![InitialMint {
             address: pool_address.clone(),
-            amount: INITIAL_AMOUNT.clone(),
+            amount: *INITIAL_AMOUNT,
         }],
     );
 
@@ -191,40 +184,22 @@ fn handle_create_token(
     );
 
     let mut instructions = vec![
-        FISInstruction {
-            plane: PLANE_COSMOS.to_string(),
-            action: ACTION_COSMOS_INVOKE.to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&create_pool_msg)?,
-        },
-        FISInstruction {
-            plane: PLANE_COSMOS.to_string(),
-            action: ACTION_COSMOS_INVOKE.to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&update_pool_msg)?,
-        },
-        FISInstruction {
-            plane: PLANE_COSMOS.to_string(),
-            action: ACTION_COSMOS_INVOKE.to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&create_denom_msg)?,
-        },
+        FISInstruction::cosmos_invoke(to_json_vec(&create_pool_msg)?),
+        FISInstruction::cosmos_invoke(to_json_vec(&update_pool_msg)?),
+        FISInstruction::cosmos_invoke(to_json_vec(&create_denom_msg)?),
     ];
 
-    if target_vm.as_str() == "EVM" || target_vm.as_str() == "SVM" {
+    if target_plane == Plane::Evm || target_plane == Plane::Svm {
         let transfer_target_plane = MsgAstroTransfer::new(
             pool_address.clone(),
             pool_address.clone(),
-            PLANE_COSMOS.to_string(),
-            target_vm.clone(),
+            Plane::Cosmos,
+            target_plane,
             Coin::new(Uint128::zero(), denom_base.clone()),
         );
-        instructions.push(FISInstruction {
-            plane: PLANE_COSMOS.to_string(),
-            action: ACTION_COSMOS_INVOKE.to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&transfer_target_plane)?,
-        });
+        instructions.push(FISInstruction::cosmos_invoke(to_json_vec(
+            &transfer_target_plane,
+        )?));
     }
 
     Ok(StrategyOutput {
@@ -250,99 +225,105 @@ fn handle_create_token(
 // for now, brute-force to find target coins in pool
 // better if use map / kv query
 fn get_pool_sol_meme_amounts(
-    pool_inventory: &Vec<Coin>,
+    pool_inventory: &[Coin],
+    quote_denom: &str,
     meme_denom: &String,
-) -> StdResult<(Uint128, Uint128)> {
+) -> NexusResult<(Uint128, Uint128)> {
     let sol_coin = pool_inventory
-        .into_iter()
-        .find(|c| c.denom == DEFAULT_QUOTE_DENOM)
+        .iter()
+        .find(|c| c.denom == quote_denom)
         .map(|c| c.amount)
-        .or(Some(Uint128::zero()))
-        .unwrap();
+        .unwrap_or(Uint128::zero());
 
     let meme_coin = pool_inventory
-        .into_iter()
+        .iter()
         .find(|c| &c.denom == meme_denom)
-        .ok_or_else(|| StdError::generic_err(format!("denom {} not found", meme_denom)))?
+        .ok_or_else(|| NexusError::NotFound(format!("denom {}", meme_denom)))?
         .amount;
     Ok((sol_coin, meme_coin))
 }
 
+fn must_be_positive(amount: Uint128) -> NexusResult<()> {
+    if amount.is_zero() {
+        return Err(NexusError::invalid_input("amount must be positive"));
+    }
+    Ok(())
+}
+
+fn must_not_be_graduated(meme_amount: Uint128) -> NexusResult<()> {
+    if meme_amount.is_zero() {
+        return Err(NexusError::invalid_input(
+            "cannot trade, the curve is graduated",
+        ));
+    }
+    Ok(())
+}
+
 fn handle_buy(
-    _deps: Deps,
+    deps: Deps,
     env: Env,
     meme_denom: String,
     amount: Uint128,
     slippage: Uint128,
-    fis_input: &Vec<FISInput>,
-) -> StdResult<StrategyOutput> {
-    assert!(amount.gt(&Uint128::zero()), "amount must be positive");
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
+    must_be_positive(amount)?;
 
     let trader = env.contract.address.clone();
-    let pool_res = from_json::<QueryPoolResponse>(fis_input.get(0).unwrap().data.get(0).unwrap())?;
-    let (sol_amount, meme_amount) =
-        get_pool_sol_meme_amounts(&pool_res.pool.inventory_snapshot, &meme_denom)?;
-    assert!(
-        !meme_amount.is_zero(),
-        "cannot trade, the curve is graduated"
-    );
+    let config = Config::load(deps)?;
+    let pool_res = TRADE_POOL.load(fis_input)?;
+    let (sol_amount, meme_amount) = get_pool_sol_meme_amounts(
+        &pool_res.pool.inventory_snapshot,
+        &config.quote_denom,
+        &meme_denom,
+    )?;
+    must_not_be_graduated(meme_amount)?;
 
     // calculate the delta Y
-    let mut curve = BondingCurve::default(sol_amount, INITIAL_AMOUNT - meme_amount);
-    let current_price = curve.price();
-    let worst_amount = amount
-        * BondingCurve::PRECISION_MULTIPLIER
-        * Uint128::new(PERCENTAGE_BPS - slippage.u128())
-        / current_price
-        / Uint128::new(PERCENTAGE_BPS);
-
-    let received_amount = curve.buy(amount);
-    assert!(received_amount.gt(&Uint128::zero()), "cannot buy 0 amount");
-    assert!(
-        !received_amount.lt(&worst_amount),
-        "slippage exceeds. worst amount: {}, actual amount: {}",
-        worst_amount,
-        received_amount
-    );
-    let post_price = curve.price();
+    let mut curve = BondingCurve::default(sol_amount, INITIAL_AMOUNT.checked_sub(meme_amount)?);
+    let current_price = curve.price()?;
+    let worst_amount = worst_amount(
+        amount,
+        slippage,
+        BondingCurve::PRECISION_MULTIPLIER,
+        current_price,
+    )?;
+
+    let received_amount = curve.buy(amount)?;
+    if received_amount.is_zero() {
+        return Err(NexusError::invalid_input("cannot buy 0 amount"));
+    }
+    if received_amount < worst_amount {
+        return Err(NexusError::slippage_exceeded(worst_amount, received_amount));
+    }
+    let post_price = curve.price()?;
     let pool_id_bz = HexBinary::from_hex(&pool_res.pool.pool_id)?;
-    let pool_address = bech32::encode::<Bech32>(Hrp::parse("lux").unwrap(), pool_id_bz.as_slice())
-        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    let pool_address = lux_address(pool_id_bz.as_slice())?;
 
     // send quote to vault
-    let trader_send_quote = FISInstruction {
-        plane: PLANE_COSMOS.to_string(),
-        action: ACTION_COSMOS_INVOKE.to_string(),
-        address: "".to_string(),
-        msg: to_json_vec(&MsgAstroTransfer::new(
-            trader.to_string(),
-            pool_address.clone(),
-            PLANE_COSMOS.to_string(),
-            PLANE_COSMOS.to_string(),
-            Coin {
-                denom: DEFAULT_QUOTE_DENOM.to_string(),
-                amount,
-            },
-        ))?,
-    };
+    let trader_send_quote = FISInstruction::cosmos_invoke(to_json_vec(&MsgAstroTransfer::new(
+        trader.to_string(),
+        pool_address.clone(),
+        Plane::Cosmos,
+        Plane::Cosmos,
+        Coin {
+            denom: config.quote_denom.clone(),
+            amount,
+        },
+    ))?);
 
     // send meme to trader
     let received_coin = Coin {
         denom: meme_denom.clone(),
         amount: received_amount,
     };
-    let pool_send_meme = FISInstruction {
-        plane: PLANE_COSMOS.to_string(),
-        action: ACTION_COSMOS_INVOKE.to_string(),
-        address: "".to_string(),
-        msg: to_json_vec(&MsgAstroTransfer::new(
-            pool_address.clone(),
-            trader.to_string(),
-            PLANE_COSMOS.to_string(),
-            PLANE_COSMOS.to_string(),
-            received_coin.clone(),
-        ))?,
-    };
+    let pool_send_meme = FISInstruction::cosmos_invoke(to_json_vec(&MsgAstroTransfer::new(
+        pool_address.clone(),
+        trader.to_string(),
+        Plane::Cosmos,
+        Plane::Cosmos,
+        received_coin.clone(),
+    ))?);
 
     let mut instructions = vec![trader_send_quote, pool_send_meme];
     let mut events = vec![StrategyEvent {
@@ -357,8 +338,9 @@ fn handle_buy(
         })?,
     }];
 
-    let is_graduate = (post_price * INITIAL_AMOUNT / BondingCurve::PRECISION_MULTIPLIER)
-        .ge(MARKET_CAP_TO_GRADUATE);
+    let is_graduate = (post_price.checked_mul(*INITIAL_AMOUNT)?
+        / BondingCurve::PRECISION_MULTIPLIER)
+        .ge(&config.market_cap_to_graduate);
     if is_graduate {
         let update_pool_msg = MsgUpdatePool::new(
             pool_address.clone(),
@@ -371,14 +353,15 @@ fn handle_buy(
             "".to_string(),
         );
 
-        instructions.push(FISInstruction {
-            plane: PLANE_COSMOS.to_string(),
-            action: ACTION_COSMOS_INVOKE.to_string(),
-            address: "".to_string(),
-            msg: to_json_vec(&update_pool_msg)?,
-        });
+        instructions.push(FISInstruction::cosmos_invoke(to_json_vec(
+            &update_pool_msg,
+        )?));
 
-        let pool_state = from_json::<DumpsadPoolState>(pool_res.pool.input_blob.unwrap())?;
+        let input_blob = pool_res
+            .pool
+            .input_blob
+            .ok_or_else(|| NexusError::NotFound("dumpsad pool input blob".to_string()))?;
+        let pool_state = from_json::<DumpsadPoolState>(input_blob)?;
         events.push(StrategyEvent {
             topic: "graduate".to_string(),
             data: to_json_binary(&GraduateEvent {
@@ -403,83 +386,74 @@ fn handle_buy(
 }
 
 fn handle_sell(
-    _deps: Deps,
+    deps: Deps,
     env: Env,
     meme_denom: String,
     amount: Uint128,
     slippage: Uint128,
-    fis_input: &Vec<FISInput>,
-) -> StdResult<StrategyOutput> {
-    assert!(amount.gt(&Uint128::zero()), "amount must be positive");
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
+    must_be_positive(amount)?;
 
     // Load quote and meme amounts from input
     let trader = env.contract.address.clone();
-    let pool_res = from_json::<QueryPoolResponse>(fis_input.get(0).unwrap().data.get(0).unwrap())?;
-    let (sol_amount, meme_amount) =
-        get_pool_sol_meme_amounts(&pool_res.pool.inventory_snapshot, &meme_denom)?;
-    assert!(
-        !meme_amount.is_zero(),
-        "cannot trade, the curve is graduated"
-    );
+    let config = Config::load(deps)?;
+    let pool_res = TRADE_POOL.load(fis_input)?;
+    let (sol_amount, meme_amount) = get_pool_sol_meme_amounts(
+        &pool_res.pool.inventory_snapshot,
+        &config.quote_denom,
+        &meme_denom,
+    )?;
+    must_not_be_graduated(meme_amount)?;
 
     // Initialize bonding curve
-    let mut curve = BondingCurve::default(sol_amount, INITIAL_AMOUNT - meme_amount);
+    let mut curve = BondingCurve::default(sol_amount, INITIAL_AMOUNT.checked_sub(meme_amount)?);
 
     // Calculate receive amount and verify slippage
-    let current_price = curve.price();
-    let received_amount = curve.sell(amount);
-    let worst_amount = amount * current_price * Uint128::new(PERCENTAGE_BPS - slippage.u128())
-        / Uint128::new(PERCENTAGE_BPS)
-        / BondingCurve::PRECISION_MULTIPLIER;
-    assert!(
-        received_amount.gt(&Uint128::zero()),
-        "receive zero sol, try larger meme amount"
-    );
-    assert!(
-        !received_amount.lt(&worst_amount),
-        "slippage exceeds. worst amount: {}, actual amount: {}",
-        worst_amount,
-        received_amount
-    );
-    let post_price = curve.price();
+    let current_price = curve.price()?;
+    let received_amount = curve.sell(amount)?;
+    let worst_amount = worst_amount(
+        amount,
+        slippage,
+        current_price,
+        BondingCurve::PRECISION_MULTIPLIER,
+    )?;
+    if received_amount.is_zero() {
+        return Err(NexusError::invalid_input(
+            "receive zero sol, try larger meme amount",
+        ));
+    }
+    if received_amount < worst_amount {
+        return Err(NexusError::slippage_exceeded(worst_amount, received_amount));
+    }
+    let post_price = curve.price()?;
 
     let pool_id_bz = HexBinary::from_hex(&pool_res.pool.pool_id)?;
-    let pool_address = bech32::encode::<Bech32>(Hrp::parse("lux").unwrap(), pool_id_bz.as_slice())
-        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    let pool_address = lux_address(pool_id_bz.as_slice())?;
 
     // Transfer instructions
-    let trader_send_meme = FISInstruction {
-        plane: PLANE_COSMOS.to_string(),
-        action: ACTION_COSMOS_INVOKE.to_string(),
-        address: "".to_string(),
-        msg: to_json_vec(&MsgAstroTransfer::new(
-            trader.to_string(),
-            pool_address.clone(),
-            PLANE_COSMOS.to_string(),
-            PLANE_COSMOS.to_string(),
-            Coin {
-                denom: meme_denom.clone(),
-                amount,
-            },
-        ))?,
-    };
+    let trader_send_meme = FISInstruction::cosmos_invoke(to_json_vec(&MsgAstroTransfer::new(
+        trader.to_string(),
+        pool_address.clone(),
+        Plane::Cosmos,
+        Plane::Cosmos,
+        Coin {
+            denom: meme_denom.clone(),
+            amount,
+        },
+    ))?);
 
     let received_coin = Coin {
-        denom: DEFAULT_QUOTE_DENOM.to_string(),
+        denom: config.quote_denom.clone(),
         amount: received_amount,
     };
-    let pool_send_quote = FISInstruction {
-        plane: PLANE_COSMOS.to_string(),
-        action: ACTION_COSMOS_INVOKE.to_string(),
-        address: "".to_string(),
-        msg: to_json_vec(&MsgAstroTransfer::new(
-            pool_address,
-            trader.to_string(),
-            PLANE_COSMOS.to_string(),
-            PLANE_COSMOS.to_string(),
-            received_coin.clone(),
-        ))?,
-    };
+    let pool_send_quote = FISInstruction::cosmos_invoke(to_json_vec(&MsgAstroTransfer::new(
+        pool_address,
+        trader.to_string(),
+        Plane::Cosmos,
+        Plane::Cosmos,
+        received_coin.clone(),
+    ))?);
 
     Ok(StrategyOutput {
         instructions: vec![trader_send_meme, pool_send_quote],
@@ -505,25 +479,24 @@ fn handle_trade(
     denom: String,
     amount: Uint128,
     slippage: Uint128,
-    fis_input: &Vec<FISInput>,
-) -> StdResult<StrategyOutput> {
-    if action.as_str() != "buy" && action.as_str() != "sell" {
-        return Err(StdError::generic_err(
-            "incorrect action. accepted [buy, sell]",
-        ));
-    }
-
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
     match action.as_str() {
         "buy" => handle_buy(deps, env, denom, amount, slippage, fis_input),
         "sell" => handle_sell(deps, env, denom, amount, slippage, fis_input),
-        _ => unreachable!(),
+        _ => Err(NexusError::invalid_input(
+            "incorrect action. accepted [buy, sell]",
+        )),
     }
 }
 
-#[entry_point]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    let nexus_action: NexusAction = from_json(&msg.msg)?;
-    let output = match nexus_action {
+pub fn handle(
+    deps: Deps,
+    env: Env,
+    nexus_action: NexusAction,
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
+    match nexus_action {
         NexusAction::CreateToken {
             name,
             symbol,
@@ -542,15 +515,13 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             target_vm,
             solver_id,
             cron_id,
-            &msg.fis_input,
+            fis_input,
         ),
         NexusAction::Trade {
             action,
             denom,
             amount,
             slippage,
-        } => handle_trade(deps, env, action, denom, amount, slippage, &msg.fis_input),
-    }?;
-
-    Ok(to_json_binary(&output).unwrap())
+        } => handle_trade(deps, env, action, denom, amount, slippage, fis_input),
+    }
 }
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod tests {
-    use cosmwasm_std::HexBinary;
+    use crate::curve::BondingCurve;
+    use cosmwasm_std::{HexBinary, Uint128};
+    use nexus_sdk::error::NexusError;
     use rlp::RlpStream;
 
     #[test]
@@ -16,4 +18,15 @@ mod tests {
             "d694a1f9834843d6303f8762389f4713fc3ac74cf32080"
         );
     }
+
+    #[test]
+    fn test_curve_sell_more_than_minted() {
+        let mut curve = BondingCurve::default(Uint128::zero(), Uint128::new(1_000));
+        let err = curve.sell(Uint128::new(1_001)).unwrap_err();
+        assert!(matches!(err, NexusError::Math(_)));
+
+        let received = curve.buy(Uint128::new(1_000_000_000)).unwrap();
+        assert!(!received.is_zero());
+        assert_eq!(curve.x, Uint128::new(1_000_000_000));
+    }
 }
@@ -13,6 +13,11 @@ pub enum NexusAction {
     },
     ClaimAllRewards {},
     ClaimRewardsAndRedelegate {},
+    Redelegate {
+        amount: Uint128,
+        src_validator_address: String,
+        new_validator_address: String,
+    },
 }
 
 #[cw_serde]
@@ -1,10 +1,11 @@
-use cosmwasm_std::{to_json_string, Binary, StdError};
+use cosmwasm_std::{to_json_string, Binary};
+use nexus_sdk::astromesh::{FisAction, Plane};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct QueryInstruction {
-    pub plane: String,
-    pub action: String,
+    pub plane: Plane,
+    pub action: FisAction,
     pub address: Binary,
     pub input: Vec<Binary>,
 }
@@ -47,8 +48,8 @@ fn main() {
         msg_fields: vec!["amount".to_string(), "validator_name".to_string()],
         query: Query {
             instructions: vec![QueryInstruction {
-                plane: "COSMOS".to_string(),
-                action: "COSMOS_QUERY".to_string(),
+                plane: Plane::Cosmos,
+                action: FisAction::CosmosQuery,
                 address: Binary::new(vec![]),
                 input: vec![Binary::from(
                     "/cosmos/staking/v1beta1/validators".as_bytes(),
@@ -64,16 +65,16 @@ fn main() {
         query: Query {
             instructions: vec![
                 QueryInstruction {
-                    plane: "COSMOS".to_string(),
-                    action: "COSMOS_QUERY".to_string(),
+                    plane: Plane::Cosmos,
+                    action: FisAction::CosmosQuery,
                     address: Binary::new(vec![]),
                     input: vec![Binary::from(
                         "/cosmos/staking/v1beta1/delegations/${wallet}".as_bytes(),
                     )],
                 },
                 QueryInstruction {
-                    plane: "COSMOS".to_string(),
-                    action: "COSMOS_QUERY".to_string(),
+                    plane: Plane::Cosmos,
+                    action: FisAction::CosmosQuery,
                     address: Binary::new(vec![]),
                     input: vec![Binary::from(
                         "/cosmos/staking/v1beta1/validators".as_bytes(),
@@ -88,8 +89,8 @@ fn main() {
         msg_fields: vec![],
         query: Query {
             instructions: vec![QueryInstruction {
-                plane: "COSMOS".to_string(),
-                action: "COSMOS_QUERY".to_string(),
+                plane: Plane::Cosmos,
+                action: FisAction::CosmosQuery,
                 address: Binary::new(vec![]),
                 input: vec![Binary::from(
                     "/cosmos/distribution/v1beta1/delegators/${wallet}/rewards".as_bytes(),
@@ -103,8 +104,8 @@ fn main() {
         msg_fields: vec![],
         query: Query {
             instructions: vec![QueryInstruction {
-                plane: "COSMOS".to_string(),
-                action: "COSMOS_QUERY".to_string(),
+                plane: Plane::Cosmos,
+                action: FisAction::CosmosQuery,
                 address: Binary::new(vec![]),
                 input: vec![Binary::from(
                     "/cosmos/distribution/v1beta1/delegators/${wallet}/rewards".as_bytes(),
@@ -123,16 +124,16 @@ fn main() {
         query: Query {
             instructions: vec![
                 QueryInstruction {
-                    plane: "COSMOS".to_string(),
-                    action: "COSMOS_QUERY".to_string(),
+                    plane: Plane::Cosmos,
+                    action: FisAction::CosmosQuery,
                     address: Binary::new(vec![]),
                     input: vec![Binary::from(
                         "/cosmos/distribution/v1beta1/delegators/${wallet}/rewards".as_bytes(),
                     )],
                 },
                 QueryInstruction {
-                    plane: "COSMOS".to_string(),
-                    action: "COSMOS_QUERY".to_string(),
+                    plane: Plane::Cosmos,
+                    action: FisAction::CosmosQuery,
                     address: Binary::new(vec![]),
                     input: vec![Binary::from(
                         "/cosmos/staking/v1beta1/validators".as_bytes(),
@@ -150,6 +151,7 @@ fn main() {
             undelegate: undelegate_prompt,
             claim_all_rewards: claim_all_rewards_prompt,
             claim_rewards_and_redelegate: claim_rewards_and_redelegate_prompt,
+            redelegate: redelegate_prompt,
         },
     };
 
@@ -3,109 +3,58 @@ use astromesh::{
     DelegationResponse, MsgBeginRedelegate, MsgDelegate, MsgUndelegate, MsgWithdrawDelegatorReward,
     NexusAction, ValidatorResponse,
 };
-use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    entry_point, from_json, to_json_binary, to_json_vec, Binary, Coin,
-    DelegationTotalRewardsResponse, DelegatorReward, Deps, DepsMut, Env, MessageInfo, Response,
-    StdError, StdResult, Uint128,
+    to_json_vec, Coin, DelegationTotalRewardsResponse, DelegatorReward, Deps, Env, Uint128,
+};
+use nexus_sdk::{
+    astromesh::{FISInput, FISInstruction, StrategyOutput},
+    error::{NexusError, NexusResult},
+    input::Slot,
+    nexus_strategy,
 };
-use std::vec::Vec;
-
-#[cw_serde]
-pub struct FisInput {
-    data: Vec<Binary>,
-}
-
-#[cw_serde]
-pub struct FISInstruction {
-    pub plane: String,
-    pub action: String,
-    pub address: String,
-    pub msg: Vec<u8>,
-}
-
-#[cw_serde]
-pub struct InstantiateMsg {}
-
-#[cw_serde]
-pub enum ExecuteMsg {}
-
-#[cw_serde]
-pub struct QueryMsg {
-    msg: Binary,
-    fis_input: Vec<FisInput>,
-}
-
-#[cw_serde]
-pub struct StrategyOutput {
-    instructions: Vec<FISInstruction>,
-}
 
-#[entry_point]
-pub fn instantiate(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "instantiate"))
-}
+nexus_strategy!(NexusAction, handle);
 
-#[entry_point]
-pub fn execute(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: ExecuteMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "execute"))
-}
+// fis input layout per action:
+// Delegate: [0] validators
+// Undelegate: [0] delegations, [1] validators
+// ClaimAllRewards, ClaimRewardsAndRedelegate: [0] rewards
+const DELEGATE_VALIDATORS: Slot<ValidatorResponse> = Slot::new(0, 0, "validators");
+const UNDELEGATE_DELEGATIONS: Slot<DelegationResponse> = Slot::new(0, 0, "delegations");
+const UNDELEGATE_VALIDATORS: Slot<ValidatorResponse> = Slot::new(1, 0, "validators");
+const REWARDS: Slot<DelegationTotalRewardsResponse> = Slot::new(0, 0, "rewards");
 
 pub fn ix_delegate(
     _deps: Deps,
     amount: Uint128,
     validator_address: String,
     delegator_address: String,
-) -> FISInstruction {
+) -> NexusResult<FISInstruction> {
     let stake_reward = MsgDelegate {
         ty: "/cosmos.staking.v1beta1.MsgDelegate".to_string(),
         delegator_address: delegator_address.clone(),
         validator_address: validator_address.clone(),
         amount: Coin {
             denom: "lux".to_string(),
-            amount: amount.into(),
+            amount,
         },
     };
 
-    let instruction = FISInstruction {
-        plane: "COSMOS".to_string(),
-        action: "COSMOS_INVOKE".to_string(),
-        address: "".to_string(),
-        msg: to_json_vec(&stake_reward).unwrap(),
-    };
-
-    instruction
+    Ok(FISInstruction::cosmos_invoke(to_json_vec(&stake_reward)?))
 }
 
 pub fn ix_withdraw_delegator_reward(
     _deps: Deps,
     validator_address: String,
     delegator_address: String,
-) -> FISInstruction {
+) -> NexusResult<FISInstruction> {
     let claim_reward = MsgWithdrawDelegatorReward {
         ty: "/cosmos.distribution.v1beta1.MsgWithdrawDelegatorReward".to_string(),
         delegator_address: delegator_address.clone(),
         validator_address: validator_address.to_string(),
     };
 
-    let instruction = FISInstruction {
-        plane: "COSMOS".to_string(),
-        action: "COSMOS_INVOKE".to_string(),
-        address: "".to_string(),
-        msg: to_json_vec(&claim_reward).unwrap(),
-    };
-
-    instruction
+    Ok(FISInstruction::cosmos_invoke(to_json_vec(&claim_reward)?))
 }
 
 pub fn ix_undelegate(
@@ -113,53 +62,39 @@ pub fn ix_undelegate(
     delegator_address: String,
     validator_address: String,
     amount: Uint128,
-) -> FISInstruction {
+) -> NexusResult<FISInstruction> {
     let undelegate = MsgUndelegate {
         ty: "/cosmos.staking.v1beta1.MsgUndelegate".to_string(),
         delegator_address: delegator_address.clone(),
         validator_address: validator_address.clone(),
         amount: Coin {
             denom: "lux".to_string(),
-            amount: amount.into(),
+            amount,
         },
     };
 
-    let instruction = FISInstruction {
-        plane: "COSMOS".to_string(),
-        action: "COSMOS_INVOKE".to_string(),
-        address: "".to_string(),
-        msg: to_json_vec(&undelegate).unwrap(),
-    };
-
-    instruction
+    Ok(FISInstruction::cosmos_invoke(to_json_vec(&undelegate)?))
 }
 
-pub fn get_rewards(_deps: Deps, fis_input: &Vec<FisInput>) -> StdResult<Vec<DelegatorReward>> {
-    let fis = &fis_input[0];
-    let rewards_response =
-        from_json::<DelegationTotalRewardsResponse>(fis.data.get(0).unwrap()).unwrap();
+pub fn get_rewards(_deps: Deps, fis_input: &[FISInput]) -> NexusResult<Vec<DelegatorReward>> {
+    let rewards_response = REWARDS.load(fis_input)?;
 
     let rewards = rewards_response.rewards;
-    if rewards.len() == 0 {
-        return Err(StdError::generic_err(
-            format!("No rewards to claim").as_str(),
-        ));
+    if rewards.is_empty() {
+        return Err(NexusError::NotFound("rewards to claim".to_string()));
     }
 
     Ok(rewards)
 }
 
 pub fn get_validator_by_name(
-    fis_input: &Vec<FisInput>,
+    fis_input: &[FISInput],
     validator_name: String,
-) -> StdResult<String> {
-    let fis = &fis_input[1];
-    let validators_response = from_json::<ValidatorResponse>(fis.data.get(0).unwrap()).unwrap();
-
-    if validators_response.validators.len() == 0 {
-        return Err(StdError::generic_err(
-            format!("No validators found").as_str(),
-        ));
+) -> NexusResult<String> {
+    let validators_response = UNDELEGATE_VALIDATORS.load(fis_input)?;
+
+    if validators_response.validators.is_empty() {
+        return Err(NexusError::NotFound("validators".to_string()));
     }
 
     for idx in 0..validators_response.validators.len() {
@@ -168,9 +103,10 @@ pub fn get_validator_by_name(
         }
     }
 
-    Err(StdError::generic_err(
-        format!("Validator {} not found", validator_name).as_str(),
-    ))
+    Err(NexusError::NotFound(format!(
+        "validator {}",
+        validator_name
+    )))
 }
 
 pub fn delegate(
@@ -178,17 +114,14 @@ pub fn delegate(
     env: Env,
     amount: Uint128,
     validator_name: String,
-    fis_input: &Vec<FisInput>,
-) -> StdResult<Binary> {
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
     let delegator_address = env.contract.address.to_string();
 
-    let fis = &fis_input[0];
-    let validators_response = from_json::<ValidatorResponse>(fis.data.get(0).unwrap()).unwrap();
+    let validators_response = DELEGATE_VALIDATORS.load(fis_input)?;
 
-    if validators_response.validators.len() == 0 {
-        return Err(StdError::generic_err(
-            format!("No validators found").as_str(),
-        ));
+    if validators_response.validators.is_empty() {
+        return Err(NexusError::NotFound("validators".to_string()));
     }
 
     let mut validator_address: String = "".to_string();
@@ -198,17 +131,15 @@ pub fn delegate(
         }
     }
 
-    if validator_address == "" {
-        return Err(StdError::generic_err(
-            format!("Validator {} not found", validator_name).as_str(),
-        ));
+    if validator_address.is_empty() {
+        return Err(NexusError::NotFound(format!(
+            "validator {}",
+            validator_name
+        )));
     }
 
-    let instruction = ix_delegate(deps, amount, validator_address, delegator_address);
-    Ok(to_json_binary(&StrategyOutput {
-        instructions: vec![instruction],
-    })
-    .unwrap())
+    let instruction = ix_delegate(deps, amount, validator_address, delegator_address)?;
+    Ok(StrategyOutput::new(vec![instruction]))
 }
 
 pub fn undelegate(
@@ -216,31 +147,32 @@ pub fn undelegate(
     env: Env,
     amount: Uint128,
     validator_name: String,
-    fis_input: &Vec<FisInput>,
-) -> StdResult<Binary> {
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
     let delegator_address = env.contract.address.to_string();
     let mut instructions = vec![];
 
     let validator_address = get_validator_by_name(fis_input, validator_name.clone())?;
-    let fis = &fis_input[0];
-    let delegates_response = from_json::<DelegationResponse>(fis.data.get(0).unwrap()).unwrap();
+    let delegates_response = UNDELEGATE_DELEGATIONS.load(fis_input)?;
 
-    if delegates_response.delegation_responses.len() == 0 {
-        return Err(StdError::generic_err(format!("No delegate found").as_str()));
+    if delegates_response.delegation_responses.is_empty() {
+        return Err(NexusError::NotFound("delegations".to_string()));
     }
 
     for idx in 0..delegates_response.delegation_responses.len() {
-        if delegates_response.delegation_responses[idx].delegation.validator_address != validator_address {
+        if delegates_response.delegation_responses[idx]
+            .delegation
+            .validator_address
+            != validator_address
+        {
             continue;
         }
 
         let delegate = &delegates_response.delegation_responses[idx];
         let balance = delegate.balance.amount;
-        
+
         if balance < amount {
-            return Err(StdError::generic_err(
-                format!("Insufficient balance to undelegate").as_str(),
-            ));
+            return Err(NexusError::insufficient_balance(amount, balance));
         }
 
         instructions.push(ix_undelegate(
@@ -248,69 +180,72 @@ pub fn undelegate(
             delegator_address.clone(),
             validator_address.clone(),
             amount,
-        ));
+        )?);
 
-        return Ok(to_json_binary(&StrategyOutput { instructions }).unwrap());
+        return Ok(StrategyOutput::new(instructions));
     }
 
-    Err(StdError::generic_err(
-        format!("Validator {} not found", validator_name).as_str(),
-    ))
+    Err(NexusError::NotFound(format!(
+        "validator {}",
+        validator_name
+    )))
 }
 
-pub fn claim_all_rewards(deps: Deps, env: Env, fis_input: &Vec<FisInput>) -> StdResult<Binary> {
+pub fn claim_all_rewards(
+    deps: Deps,
+    env: Env,
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
     let delegator_address = env.contract.address.to_string();
     let mut instructions = vec![];
 
-    let rewards = get_rewards(deps, &fis_input)?;
+    let rewards = get_rewards(deps, fis_input)?;
 
-    for idx in 0..rewards.len() {
-        let validator_address = rewards[idx].validator_address.clone();
+    for reward in rewards {
+        let validator_address = reward.validator_address;
 
         instructions.push(ix_withdraw_delegator_reward(
             deps,
             validator_address,
             delegator_address.clone(),
-        ));
+        )?);
     }
 
-    Ok(to_json_binary(&StrategyOutput { instructions }).unwrap())
+    Ok(StrategyOutput::new(instructions))
 }
 
 pub fn claim_rewards_and_redelegate(
     deps: Deps,
     env: Env,
-    fis_input: &Vec<FisInput>,
-) -> StdResult<Binary> {
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
     let delegator_address = env.contract.address.to_string();
     let mut instructions = vec![];
 
-    let rewards = get_rewards(deps, &fis_input)?;
+    let rewards = get_rewards(deps, fis_input)?;
 
-    for idx in 0..rewards.len() {
-        let validator_address = rewards[idx].validator_address.clone();
-        let reward = &rewards[idx].reward[0];
-        let reward_amount_uint256 = reward.amount.to_uint_floor();
-        let reward_amount = reward_amount_uint256
-            .to_string()
-            .parse::<Uint128>()
-            .unwrap();
+    for delegator_reward in rewards {
+        let validator_address = delegator_reward.validator_address;
+        let Some(reward) = delegator_reward.reward.first() else {
+            continue;
+        };
+        let reward_amount = Uint128::try_from(reward.amount.to_uint_floor())?;
 
         instructions.push(ix_withdraw_delegator_reward(
             deps,
             validator_address.clone(),
             delegator_address.clone(),
-        ));
+        )?);
 
         instructions.push(ix_delegate(
             deps,
             reward_amount,
             validator_address.clone(),
             delegator_address.clone(),
-        ));
+        )?);
     }
 
-    Ok(to_json_binary(&StrategyOutput { instructions }).unwrap())
+    Ok(StrategyOutput::new(instructions))
 }
 
 pub fn redelegate(
@@ -319,7 +254,7 @@ pub fn redelegate(
     amount: Uint128,
     src_validator_address: String,
     new_validator_address: String,
-) -> StdResult<Binary> {
+) -> NexusResult<StrategyOutput> {
     let delegator = env.contract.address.to_string();
     let mut instructions = vec![];
 
@@ -330,35 +265,44 @@ pub fn redelegate(
         validator_dst_address: new_validator_address.clone(),
         amount: Coin {
             denom: "lux".to_string(),
-            amount: amount.into(),
+            amount,
         },
     };
 
-    instructions.push(FISInstruction {
-        plane: "COSMOS".to_string(),
-        action: "COSMOS_INVOKE".to_string(),
-        address: "".to_string(),
-        msg: to_json_vec(&redelegate).unwrap(),
-    });
+    instructions.push(FISInstruction::cosmos_invoke(to_json_vec(&redelegate)?));
 
-    Ok(to_json_binary(&StrategyOutput { instructions }).unwrap())
+    Ok(StrategyOutput::new(instructions))
 }
 
-#[entry_point]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    let action = from_json::<NexusAction>(msg.msg)?;
+pub fn handle(
+    deps: Deps,
+    env: Env,
+    action: NexusAction,
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
     match action {
         NexusAction::Delegate {
             amount,
             validator_name,
-        } => delegate(deps, env, amount, validator_name, &msg.fis_input),
+        } => delegate(deps, env, amount, validator_name, fis_input),
         NexusAction::Undelegate {
             amount,
             validator_name,
-        } => undelegate(deps, env, amount, validator_name, &msg.fis_input),
-        NexusAction::ClaimAllRewards {} => claim_all_rewards(deps, env, &msg.fis_input),
+        } => undelegate(deps, env, amount, validator_name, fis_input),
+        NexusAction::ClaimAllRewards {} => claim_all_rewards(deps, env, fis_input),
         NexusAction::ClaimRewardsAndRedelegate {} => {
-            claim_rewards_and_redelegate(deps, env, &msg.fis_input)
+            claim_rewards_and_redelegate(deps, env, fis_input)
         }
+        NexusAction::Redelegate {
+            amount,
+            src_validator_address,
+            new_validator_address,
+        } => redelegate(
+            deps,
+            env,
+            amount,
+            src_validator_address,
+            new_validator_address,
+        ),
     }
 }
@@ -1,5 +1,8 @@
 use cosmwasm_schema::write_api;
-use wasmvm_counter::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use nexus_sdk::{
+    astromesh::QueryMsg,
+    strategy::{ExecuteMsg, InstantiateMsg},
+};
 
 fn main() {
     write_api! {
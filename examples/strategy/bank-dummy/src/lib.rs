@@ -1,106 +1,54 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{
-    entry_point, from_json, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo,
-    Response, StdResult, Uint256,
+use cosmwasm_std::{to_json_binary, Coin, Deps, Env, Uint128};
+use nexus_sdk::{
+    astromesh::{FISInput, FISInstruction, MsgSend, StrategyOutput},
+    error::{NexusError, NexusResult},
+    input::Slots,
+    nexus_strategy,
 };
-use std::{str::FromStr, vec::Vec};
 
-#[cw_serde]
-pub struct InstantiateMsg {}
-
-#[cw_serde]
-pub enum ExecuteMsg {}
-
-#[cw_serde]
-pub struct QueryMsg {
-    msg: Binary,
-    fis_input: Vec<FisInput>
-}
-
-#[cw_serde]
-pub struct FisInput {
-    data: Vec<Binary>
-}
+// fis input layout: [0] balance of each receiver
+const BALANCES: Slots<Coin> = Slots::new(0, "balances");
 
 #[cw_serde]
 pub struct Fund {
     receivers: Vec<String>,
 }
 
-#[cw_serde]
-pub struct FISInstruction {
-    plane: String,
-    action: String,
-    address: String,
-    msg: Vec<u8>,
-}
+nexus_strategy!(Fund, handle);
 
-#[cw_serde]
-pub struct StrategyOutput {
-    instructions: Vec<FISInstruction>,
-}
-
-#[cw_serde]
-pub struct MsgSend {
-    from_address: String,
-    to_address: String,
-    amount: Vec<BankAmount>,
-}
-
-#[cw_serde]
-pub struct BankAmount {
-    denom: String,
-    amount: String,
-}
-
-#[entry_point]
-pub fn instantiate(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "instantiate"))
-}
-
-#[entry_point]
-pub fn execute(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    _msg: ExecuteMsg,
-) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "execute"))
-}
-
-#[entry_point]
-pub fn query(_deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn handle(
+    _deps: Deps,
+    env: Env,
+    command: Fund,
+    fis_input: &[FISInput],
+) -> NexusResult<StrategyOutput> {
     // parse command, we can store it as proto bytes, encrypted binary
-    let command = from_json::<Fund>(msg.msg)?;
     let mut instructions = vec![];
-    let balances_input = msg.fis_input[0].clone();
+    let balances = BALANCES.load(fis_input)?;
+    if balances.len() > command.receivers.len() {
+        return Err(NexusError::invalid_input(format!(
+            "{} balances for {} receivers",
+            balances.len(),
+            command.receivers.len()
+        )));
+    }
 
-    for i in 0..balances_input.data.len() {
-        let fis_input = from_json::<BankAmount>(balances_input.data.get(i).unwrap())?;
-        let balance = Uint256::from_str(fis_input.amount.as_str()).unwrap();
-        if balance % Uint256::from_u128(2u128) == Uint256::one() {
-            instructions.push(FISInstruction {
-                plane: "COSMOS".to_string(),
-                action: "COSMOS_BANK_SEND".to_string(),
-                address: "".to_string(),
-                msg: to_json_binary(&MsgSend {
-                    from_address: env.contract.address.clone().into_string(),
-                    to_address: command.receivers[i].clone(),
-                    amount: vec![BankAmount {
-                        denom: fis_input.denom.to_string(),
-                        amount: "1".to_string(),
+    for (receiver, balance) in command.receivers.into_iter().zip(balances) {
+        if balance.amount % Uint128::new(2) == Uint128::one() {
+            instructions.push(FISInstruction::cosmos_bank_send(
+                to_json_binary(&MsgSend::new(
+                    env.contract.address.clone().into_string(),
+                    receiver,
+                    vec![Coin {
+                        denom: balance.denom,
+                        amount: Uint128::one(),
                     }],
-                })
-                .unwrap()
+                ))?
                 .to_vec(),
-            })
+            ))
         }
     }
 
-    Ok(to_json_binary(&StrategyOutput { instructions }).unwrap())
+    Ok(StrategyOutput::new(instructions))
 }
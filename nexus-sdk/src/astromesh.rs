@@ -0,0 +1,219 @@
+use cosmwasm_schema::{cw_serde, schema_for, QueryResponses};
+use cosmwasm_std::{Binary, Coin, StdError};
+use schemars::schema::RootSchema;
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+#[cw_serde]
+#[derive(Copy, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Plane {
+    #[default]
+    #[serde(rename = "COSMOS")]
+    Cosmos,
+    #[serde(rename = "WASM")]
+    Wasm,
+    #[serde(rename = "EVM")]
+    Evm,
+    #[serde(rename = "SVM")]
+    Svm,
+}
+
+impl Plane {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Plane::Cosmos => "COSMOS",
+            Plane::Wasm => "WASM",
+            Plane::Evm => "EVM",
+            Plane::Svm => "SVM",
+        }
+    }
+}
+
+impl fmt::Display for Plane {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Plane {
+    type Err = StdError;
+
+    // case insensitive so user facing inputs like "svm" are accepted too
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "COSMOS" => Ok(Plane::Cosmos),
+            "WASM" => Ok(Plane::Wasm),
+            "EVM" => Ok(Plane::Evm),
+            "SVM" => Ok(Plane::Svm),
+            _ => Err(StdError::generic_err(format!("unknown plane: {}", s))),
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Copy, Eq, Hash)]
+pub enum FisAction {
+    #[serde(rename = "COSMOS_INVOKE")]
+    CosmosInvoke,
+    #[serde(rename = "COSMOS_BANK_SEND")]
+    CosmosBankSend,
+    #[serde(rename = "COSMOS_ASTROMESH_TRANSFER")]
+    CosmosAstromeshTransfer,
+    #[serde(rename = "VM_INVOKE")]
+    VmInvoke,
+    #[serde(rename = "COSMOS_QUERY")]
+    CosmosQuery,
+    #[serde(rename = "VM_QUERY")]
+    VmQuery,
+}
+
+/// Query results injected by the chain, one entry per query instruction of the prompt
+#[cw_serde]
+pub struct FISInput {
+    pub data: Vec<Binary>,
+}
+
+/// Message the chain sends to the strategy `query` entry point
+#[cw_serde]
+pub struct QueryMsg {
+    pub msg: Binary,
+    pub fis_input: Vec<FISInput>,
+}
+
+// lets bots feed the query message to `write_api!`, every strategy query answers a StrategyOutput
+impl QueryResponses for QueryMsg {
+    fn response_schemas_impl() -> BTreeMap<String, RootSchema> {
+        BTreeMap::from([("query".to_string(), schema_for!(StrategyOutput))])
+    }
+}
+
+/// Instruction executed by the chain on behalf of the strategy.
+///
+/// Built through the constructors only, so the plane always matches the action.
+#[cw_serde]
+pub struct FISInstruction {
+    plane: Plane,
+    action: FisAction,
+    address: String,
+    msg: Vec<u8>,
+}
+
+impl FISInstruction {
+    fn new(plane: Plane, action: FisAction, msg: impl Into<Vec<u8>>) -> Self {
+        FISInstruction {
+            plane,
+            action,
+            address: "".to_string(),
+            msg: msg.into(),
+        }
+    }
+
+    pub fn cosmos_invoke(msg: impl Into<Vec<u8>>) -> Self {
+        Self::new(Plane::Cosmos, FisAction::CosmosInvoke, msg)
+    }
+
+    pub fn cosmos_bank_send(msg: impl Into<Vec<u8>>) -> Self {
+        Self::new(Plane::Cosmos, FisAction::CosmosBankSend, msg)
+    }
+
+    pub fn cosmos_astromesh_transfer(msg: impl Into<Vec<u8>>) -> Self {
+        Self::new(Plane::Cosmos, FisAction::CosmosAstromeshTransfer, msg)
+    }
+
+    /// Invokes a contract/program on `plane`, `Plane::Cosmos` falls back to a cosmos invoke
+    pub fn vm_invoke(plane: Plane, msg: impl Into<Vec<u8>>) -> Self {
+        match plane {
+            Plane::Cosmos => Self::cosmos_invoke(msg),
+            _ => Self::new(plane, FisAction::VmInvoke, msg),
+        }
+    }
+
+    pub fn plane(&self) -> Plane {
+        self.plane
+    }
+
+    pub fn action(&self) -> FisAction {
+        self.action
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn msg(&self) -> &[u8] {
+        &self.msg
+    }
+}
+
+#[cw_serde]
+pub struct StrategyEvent {
+    pub topic: String,
+    pub data: Binary,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct StrategyOutput {
+    pub instructions: Vec<FISInstruction>,
+    // events and result are optional on the wire, strategies without them keep the old output
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<StrategyEvent>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub result: String,
+}
+
+impl StrategyOutput {
+    pub fn new(instructions: Vec<FISInstruction>) -> Self {
+        StrategyOutput {
+            instructions,
+            ..Default::default()
+        }
+    }
+}
+
+#[cw_serde]
+pub struct MsgAstroTransfer {
+    #[serde(rename = "@type")]
+    pub ty: String,
+    pub sender: String,
+    pub receiver: String,
+    pub src_plane: Plane,
+    pub dst_plane: Plane,
+    pub coin: Coin,
+}
+
+impl MsgAstroTransfer {
+    pub fn new(
+        sender: String,
+        receiver: String,
+        src_plane: Plane,
+        dst_plane: Plane,
+        coin: Coin,
+    ) -> Self {
+        MsgAstroTransfer {
+            ty: "/flux.astromesh.v1beta1.MsgAstroTransfer".to_string(),
+            sender,
+            receiver,
+            src_plane,
+            dst_plane,
+            coin,
+        }
+    }
+}
+
+/// Bank send payload used with the COSMOS_BANK_SEND action
+#[cw_serde]
+pub struct MsgSend {
+    pub from_address: String,
+    pub to_address: String,
+    pub amount: Vec<Coin>,
+}
+
+impl MsgSend {
+    pub fn new(from_address: String, to_address: String, amount: Vec<Coin>) -> Self {
+        MsgSend {
+            from_address,
+            to_address,
+            amount,
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{
+    astromesh::{MsgAstroTransfer, Plane},
+    error::{NexusError, NexusResult},
+};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Coin, Uint128};
+use std::collections::BTreeMap;
+
+/// Representation of a denom on one plane
+#[cw_serde]
+pub struct DenomLink {
+    /// bank denom on cosmos/wasm, spl mint on svm, erc20 address (hex without 0x) on evm
+    pub denom: String,
+    pub decimals: u8,
+}
+
+/// A denom across planes, wasm uses the cosmos bank denom when unset
+#[cw_serde]
+pub struct Denom {
+    pub cosmos: DenomLink,
+    pub wasm: Option<DenomLink>,
+    pub evm: Option<DenomLink>,
+    pub svm: Option<DenomLink>,
+}
+
+impl Denom {
+    pub fn link(&self, plane: Plane) -> Option<&DenomLink> {
+        match plane {
+            Plane::Cosmos => Some(&self.cosmos),
+            Plane::Wasm => Some(self.wasm.as_ref().unwrap_or(&self.cosmos)),
+            Plane::Evm => self.evm.as_ref(),
+            Plane::Svm => self.svm.as_ref(),
+        }
+    }
+}
+
+/// Denoms by alias, e.g. `eth`, with their representation and decimals on each plane
+#[cw_serde]
+#[derive(Default)]
+pub struct DenomRegistry(pub BTreeMap<String, Denom>);
+
+impl DenomRegistry {
+    pub fn get(&self, alias: &str) -> NexusResult<&Denom> {
+        self.0
+            .get(alias)
+            .ok_or_else(|| NexusError::NotFound(format!("denom {}", alias)))
+    }
+
+    pub fn link(&self, alias: &str, plane: Plane) -> NexusResult<&DenomLink> {
+        self.get(alias)?
+            .link(plane)
+            .ok_or_else(|| NexusError::NotFound(format!("{} denom {}", plane, alias)))
+    }
+
+    /// Representation of `alias` on `plane`, e.g. the spl mint of `eth`
+    pub fn address(&self, alias: &str, plane: Plane) -> NexusResult<&str> {
+        Ok(self.link(alias, plane)?.denom.as_str())
+    }
+
+    /// Alias of a denom given its representation on `plane`, e.g. of an erc20 address
+    pub fn find(&self, plane: Plane, denom: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(_, d)| d.link(plane).is_some_and(|link| link.denom == denom))
+            .map(|(alias, _)| alias.as_str())
+    }
+
+    /// Scales `amount` from `src_plane` to `dst_plane` decimals, rounding down
+    pub fn convert(
+        &self,
+        alias: &str,
+        src_plane: Plane,
+        dst_plane: Plane,
+        amount: Uint128,
+    ) -> NexusResult<Uint128> {
+        let src = self.link(alias, src_plane)?.decimals;
+        let dst = self.link(alias, dst_plane)?.decimals;
+        if dst >= src {
+            Ok(amount.checked_mul(pow10(dst - src)?)?)
+        } else {
+            Ok(amount.checked_div(pow10(src - dst)?)?)
+        }
+    }
+
+    /// Coin moving `amount` (in `src_plane` decimals) of `alias` out of `src_plane`.
+    ///
+    /// The amount is rounded down to what `dst_plane` can represent so no dust is left
+    /// behind by the conversion, e.g. 18 decimals eth to 9 decimals on svm.
+    pub fn transfer_coin(
+        &self,
+        alias: &str,
+        src_plane: Plane,
+        dst_plane: Plane,
+        amount: Uint128,
+    ) -> NexusResult<Coin> {
+        let src = self.link(alias, src_plane)?;
+        let dst = self.link(alias, dst_plane)?;
+        let amount = if dst.decimals < src.decimals {
+            let unit = pow10(src.decimals - dst.decimals)?;
+            amount.checked_div(unit)?.checked_mul(unit)?
+        } else {
+            amount
+        };
+        // vm planes escrow their tokens under the astro/ prefix on cosmos
+        let denom = match src_plane {
+            Plane::Evm | Plane::Svm => format!("astro/{}", src.denom),
+            Plane::Cosmos | Plane::Wasm => src.denom.clone(),
+        };
+        Ok(Coin { denom, amount })
+    }
+
+    /// `MsgAstroTransfer` of `amount` (in `src_plane` decimals), see `transfer_coin`
+    pub fn astro_transfer(
+        &self,
+        sender: String,
+        receiver: String,
+        src_plane: Plane,
+        dst_plane: Plane,
+        alias: &str,
+        amount: Uint128,
+    ) -> NexusResult<MsgAstroTransfer> {
+        let coin = self.transfer_coin(alias, src_plane, dst_plane, amount)?;
+        Ok(MsgAstroTransfer::new(
+            sender, receiver, src_plane, dst_plane, coin,
+        ))
+    }
+}
+
+fn pow10(exp: u8) -> NexusResult<Uint128> {
+    Ok(Uint128::new(10).checked_pow(u32::from(exp))?)
+}
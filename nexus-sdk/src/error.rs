@@ -0,0 +1,116 @@
+use cosmwasm_std::{
+    ConversionOverflowError, DivideByZeroError, DivisionError, OverflowError, StdError,
+};
+use thiserror::Error;
+
+pub type NexusResult<T> = Result<T, NexusError>;
+
+/// Error returned by strategy handlers.
+///
+/// Converted to `StdError` at the entry point with its stable `code` as prefix,
+/// e.g. `[7] slippage exceeded: ...`, so clients can match on failures.
+#[derive(Error, Debug, PartialEq)]
+pub enum NexusError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("missing fis input {0}")]
+    MissingFisInput(String),
+
+    #[error("invalid fis input {slot}: {reason}")]
+    InvalidFisInput { slot: String, reason: String },
+
+    #[error("unsupported pair: {0}")]
+    UnsupportedPair(String),
+
+    #[error("unsupported dex: {0}")]
+    UnsupportedDex(String),
+
+    #[error("slippage exceeded: expected at least {min_output}, got {output}")]
+    SlippageExceeded { min_output: String, output: String },
+
+    #[error("insufficient balance: requested {requested}, available {available}")]
+    InsufficientBalance {
+        requested: String,
+        available: String,
+    },
+
+    #[error("math error: {0}")]
+    Math(String),
+
+    #[error("{0} not found")]
+    NotFound(String),
+
+    #[error("unauthorized: {0} is not the owner")]
+    Unauthorized(String),
+}
+
+impl NexusError {
+    /// Stable across releases, new variants get new codes
+    pub fn code(&self) -> u32 {
+        match self {
+            NexusError::Std(_) => 1,
+            NexusError::InvalidInput(_) => 2,
+            NexusError::MissingFisInput(_) => 3,
+            NexusError::InvalidFisInput { .. } => 4,
+            NexusError::UnsupportedPair(_) => 5,
+            NexusError::UnsupportedDex(_) => 6,
+            NexusError::SlippageExceeded { .. } => 7,
+            NexusError::InsufficientBalance { .. } => 8,
+            NexusError::Math(_) => 9,
+            NexusError::NotFound(_) => 10,
+            NexusError::Unauthorized(_) => 11,
+        }
+    }
+
+    pub fn invalid_input(msg: impl ToString) -> Self {
+        NexusError::InvalidInput(msg.to_string())
+    }
+
+    pub fn insufficient_balance(requested: impl ToString, available: impl ToString) -> Self {
+        NexusError::InsufficientBalance {
+            requested: requested.to_string(),
+            available: available.to_string(),
+        }
+    }
+
+    pub fn slippage_exceeded(min_output: impl ToString, output: impl ToString) -> Self {
+        NexusError::SlippageExceeded {
+            min_output: min_output.to_string(),
+            output: output.to_string(),
+        }
+    }
+}
+
+impl From<NexusError> for StdError {
+    fn from(err: NexusError) -> Self {
+        StdError::generic_err(format!("[{}] {}", err.code(), err))
+    }
+}
+
+impl From<OverflowError> for NexusError {
+    fn from(err: OverflowError) -> Self {
+        NexusError::Math(err.to_string())
+    }
+}
+
+impl From<DivideByZeroError> for NexusError {
+    fn from(err: DivideByZeroError) -> Self {
+        NexusError::Math(err.to_string())
+    }
+}
+
+impl From<DivisionError> for NexusError {
+    fn from(err: DivisionError) -> Self {
+        NexusError::Math(err.to_string())
+    }
+}
+
+impl From<ConversionOverflowError> for NexusError {
+    fn from(err: ConversionOverflowError) -> Self {
+        NexusError::Math(err.to_string())
+    }
+}
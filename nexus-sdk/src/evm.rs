@@ -0,0 +1,684 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Binary, Int256, StdError, StdResult, Uint256};
+use std::fmt;
+use tiny_keccak::{Hasher, Keccak};
+
+const WORD: usize = 32;
+
+#[cw_serde]
+pub struct MsgExecuteContract {
+    pub sender: String,
+    pub contract_address: Binary,
+    pub calldata: Binary,
+    pub input_amount: Binary,
+}
+
+impl MsgExecuteContract {
+    pub fn new(
+        sender: String,
+        contract_address: Binary,
+        calldata: Binary,
+        input_amount: Binary,
+    ) -> Self {
+        MsgExecuteContract {
+            sender,
+            contract_address,
+            calldata,
+            input_amount,
+        }
+    }
+}
+
+pub fn keccak256(bz: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    let mut output = [0u8; 32];
+    hasher.update(bz);
+    hasher.finalize(&mut output);
+    output
+}
+
+// === abi types ===
+
+/// Solidity type of a parameter, parsed from its canonical name e.g. `uint24`, `(address,bool)[]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamType {
+    Address,
+    Bool,
+    Uint(usize),
+    Int(usize),
+    FixedBytes(usize),
+    Bytes,
+    String,
+    Array(Box<ParamType>),
+    FixedArray(Box<ParamType>, usize),
+    Tuple(Vec<ParamType>),
+}
+
+impl ParamType {
+    pub fn parse(s: &str) -> StdResult<Self> {
+        let s = s.trim();
+        if let Some(stripped) = s.strip_suffix(']') {
+            let open = stripped
+                .rfind('[')
+                .ok_or_else(|| StdError::generic_err(format!("abi: invalid type: {}", s)))?;
+            let inner = Box::new(ParamType::parse(&stripped[..open])?);
+            let size = &stripped[open + 1..];
+            if size.is_empty() {
+                return Ok(ParamType::Array(inner));
+            }
+
+            let size = size
+                .parse::<usize>()
+                .map_err(|_| StdError::generic_err(format!("abi: invalid array size: {}", s)))?;
+            return Ok(ParamType::FixedArray(inner, size));
+        }
+
+        if let Some(inner) = s.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+            let components = split_top_level(inner, ',')?
+                .into_iter()
+                .map(ParamType::parse)
+                .collect::<StdResult<Vec<_>>>()?;
+            return Ok(ParamType::Tuple(components));
+        }
+
+        let sized = |prefix: &str, valid: fn(usize) -> bool| -> Option<StdResult<usize>> {
+            let size = s.strip_prefix(prefix)?;
+            Some(match size.parse::<usize>() {
+                Ok(size) if valid(size) => Ok(size),
+                _ => Err(StdError::generic_err(format!("abi: invalid type: {}", s))),
+            })
+        };
+
+        match s {
+            "address" => Ok(ParamType::Address),
+            "bool" => Ok(ParamType::Bool),
+            "string" => Ok(ParamType::String),
+            "bytes" => Ok(ParamType::Bytes),
+            "uint" => Ok(ParamType::Uint(256)),
+            "int" => Ok(ParamType::Int(256)),
+            _ => {
+                let int_bits = |n: usize| n > 0 && n <= 256 && n.is_multiple_of(8);
+                if let Some(bits) = sized("uint", int_bits) {
+                    return bits.map(ParamType::Uint);
+                }
+                if let Some(bits) = sized("int", int_bits) {
+                    return bits.map(ParamType::Int);
+                }
+                if let Some(len) = sized("bytes", |n| n > 0 && n <= 32) {
+                    return len.map(ParamType::FixedBytes);
+                }
+                Err(StdError::generic_err(format!("abi: unknown type: {}", s)))
+            }
+        }
+    }
+
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            ParamType::Bytes | ParamType::String | ParamType::Array(_) => true,
+            ParamType::FixedArray(inner, _) => inner.is_dynamic(),
+            ParamType::Tuple(components) => components.iter().any(|c| c.is_dynamic()),
+            _ => false,
+        }
+    }
+
+    // bytes taken in the head of the enclosing tuple
+    fn head_len(&self) -> usize {
+        if self.is_dynamic() {
+            return WORD;
+        }
+
+        match self {
+            ParamType::FixedArray(inner, size) => inner.head_len() * size,
+            ParamType::Tuple(components) => components.iter().map(|c| c.head_len()).sum(),
+            _ => WORD,
+        }
+    }
+}
+
+impl fmt::Display for ParamType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamType::Address => f.write_str("address"),
+            ParamType::Bool => f.write_str("bool"),
+            ParamType::Uint(bits) => write!(f, "uint{}", bits),
+            ParamType::Int(bits) => write!(f, "int{}", bits),
+            ParamType::FixedBytes(len) => write!(f, "bytes{}", len),
+            ParamType::Bytes => f.write_str("bytes"),
+            ParamType::String => f.write_str("string"),
+            ParamType::Array(inner) => write!(f, "{}[]", inner),
+            ParamType::FixedArray(inner, size) => write!(f, "{}[{}]", inner, size),
+            ParamType::Tuple(components) => write!(f, "({})", join(components)),
+        }
+    }
+}
+
+/// Abi value, encoded against the `ParamType` it's declared with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Address([u8; 20]),
+    Bool(bool),
+    Uint(Uint256),
+    Int(Int256),
+    FixedBytes(Vec<u8>),
+    Bytes(Vec<u8>),
+    String(String),
+    Array(Vec<Token>),
+    FixedArray(Vec<Token>),
+    Tuple(Vec<Token>),
+}
+
+impl Token {
+    pub fn type_check(&self, kind: &ParamType) -> bool {
+        match (self, kind) {
+            (Token::Address(_), ParamType::Address)
+            | (Token::Bool(_), ParamType::Bool)
+            | (Token::Bytes(_), ParamType::Bytes)
+            | (Token::String(_), ParamType::String) => true,
+            (Token::Uint(v), ParamType::Uint(bits)) => uint_fits(&v.to_be_bytes(), *bits),
+            (Token::Int(v), ParamType::Int(bits)) => int_fits(&v.to_be_bytes(), *bits),
+            (Token::FixedBytes(v), ParamType::FixedBytes(len)) => v.len() == *len,
+            (Token::Array(items), ParamType::Array(inner)) => {
+                items.iter().all(|item| item.type_check(inner))
+            }
+            (Token::FixedArray(items), ParamType::FixedArray(inner, size)) => {
+                items.len() == *size && items.iter().all(|item| item.type_check(inner))
+            }
+            (Token::Tuple(items), ParamType::Tuple(components)) => {
+                items.len() == components.len()
+                    && items.iter().zip(components).all(|(i, c)| i.type_check(c))
+            }
+            _ => false,
+        }
+    }
+
+    fn is_dynamic(&self) -> bool {
+        match self {
+            Token::Bytes(_) | Token::String(_) | Token::Array(_) => true,
+            Token::FixedArray(items) | Token::Tuple(items) => items.iter().any(|i| i.is_dynamic()),
+            _ => false,
+        }
+    }
+
+    fn head_len(&self) -> usize {
+        match self {
+            _ if self.is_dynamic() => WORD,
+            Token::FixedArray(items) | Token::Tuple(items) => {
+                items.iter().map(|i| i.head_len()).sum()
+            }
+            _ => WORD,
+        }
+    }
+
+    pub fn into_address(self) -> StdResult<[u8; 20]> {
+        match self {
+            Token::Address(v) => Ok(v),
+            t => Err(unexpected_token("address", &t)),
+        }
+    }
+
+    pub fn into_bool(self) -> StdResult<bool> {
+        match self {
+            Token::Bool(v) => Ok(v),
+            t => Err(unexpected_token("bool", &t)),
+        }
+    }
+
+    pub fn into_uint(self) -> StdResult<Uint256> {
+        match self {
+            Token::Uint(v) => Ok(v),
+            t => Err(unexpected_token("uint", &t)),
+        }
+    }
+
+    pub fn into_int(self) -> StdResult<Int256> {
+        match self {
+            Token::Int(v) => Ok(v),
+            t => Err(unexpected_token("int", &t)),
+        }
+    }
+
+    pub fn into_bytes(self) -> StdResult<Vec<u8>> {
+        match self {
+            Token::Bytes(v) | Token::FixedBytes(v) => Ok(v),
+            t => Err(unexpected_token("bytes", &t)),
+        }
+    }
+
+    pub fn into_string(self) -> StdResult<String> {
+        match self {
+            Token::String(v) => Ok(v),
+            t => Err(unexpected_token("string", &t)),
+        }
+    }
+
+    /// Items of an array, fixed array or tuple
+    pub fn into_tokens(self) -> StdResult<Vec<Token>> {
+        match self {
+            Token::Array(v) | Token::FixedArray(v) | Token::Tuple(v) => Ok(v),
+            t => Err(unexpected_token("array or tuple", &t)),
+        }
+    }
+}
+
+fn unexpected_token(expected: &str, got: &Token) -> StdError {
+    StdError::generic_err(format!("abi: expected {}, got {:?}", expected, got))
+}
+
+// === encoding ===
+
+/// Encodes tokens as the arguments of a call, i.e. as a tuple without selector
+pub fn encode(tokens: &[Token]) -> Vec<u8> {
+    let heads_len: usize = tokens.iter().map(|t| t.head_len()).sum();
+    let mut heads = Vec::with_capacity(heads_len);
+    let mut tails = vec![];
+    for token in tokens {
+        if token.is_dynamic() {
+            heads.extend(usize_word(heads_len + tails.len()));
+            tails.extend(encode_token(token));
+        } else {
+            heads.extend(encode_token(token));
+        }
+    }
+
+    heads.extend(tails);
+    heads
+}
+
+fn encode_token(token: &Token) -> Vec<u8> {
+    match token {
+        Token::Address(v) => {
+            let mut word = vec![0u8; 12];
+            word.extend_from_slice(v);
+            word
+        }
+        Token::Bool(v) => usize_word(*v as usize).to_vec(),
+        Token::Uint(v) => v.to_be_bytes().to_vec(),
+        Token::Int(v) => v.to_be_bytes().to_vec(),
+        Token::FixedBytes(v) => right_pad(v),
+        Token::Bytes(v) => {
+            let mut res = usize_word(v.len()).to_vec();
+            res.extend(right_pad(v));
+            res
+        }
+        Token::String(v) => encode_token(&Token::Bytes(v.as_bytes().to_vec())),
+        Token::Array(items) => {
+            let mut res = usize_word(items.len()).to_vec();
+            res.extend(encode(items));
+            res
+        }
+        Token::FixedArray(items) | Token::Tuple(items) => encode(items),
+    }
+}
+
+fn usize_word(v: usize) -> [u8; WORD] {
+    Uint256::from(v as u128).to_be_bytes()
+}
+
+fn right_pad(bz: &[u8]) -> Vec<u8> {
+    let mut res = bz.to_vec();
+    res.resize(bz.len().div_ceil(WORD) * WORD, 0);
+    res
+}
+
+fn uint_fits(word: &[u8; WORD], bits: usize) -> bool {
+    word[..WORD - bits / 8].iter().all(|b| *b == 0)
+}
+
+// value is sign extended from the highest bit of its `bits` wide representation
+fn int_fits(word: &[u8; WORD], bits: usize) -> bool {
+    let start = WORD - bits / 8;
+    let sign_byte = if word[start] & 0x80 == 0 { 0 } else { 0xff };
+    word[..start].iter().all(|b| *b == sign_byte)
+}
+
+// === decoding ===
+
+/// Decodes call arguments or return data encoded as a tuple of `types`
+pub fn decode(types: &[ParamType], data: &[u8]) -> StdResult<Vec<Token>> {
+    decode_params(types, data, 0)
+}
+
+fn decode_params(types: &[ParamType], data: &[u8], base: usize) -> StdResult<Vec<Token>> {
+    let mut tokens = Vec::with_capacity(types.len());
+    let mut offset = base;
+    for kind in types {
+        if kind.is_dynamic() {
+            let ptr = read_usize(data, offset)?;
+            tokens.push(decode_param(kind, data, base.saturating_add(ptr))?);
+        } else {
+            tokens.push(decode_param(kind, data, offset)?);
+        }
+        offset += kind.head_len();
+    }
+
+    Ok(tokens)
+}
+
+fn decode_param(kind: &ParamType, data: &[u8], at: usize) -> StdResult<Token> {
+    match kind {
+        ParamType::Address => {
+            let word = read_word(data, at)?;
+            if !uint_fits(word, 160) {
+                return Err(invalid_value(kind, at));
+            }
+            Ok(Token::Address(word[12..].try_into().unwrap()))
+        }
+        ParamType::Bool => match read_usize(data, at)? {
+            0 => Ok(Token::Bool(false)),
+            1 => Ok(Token::Bool(true)),
+            _ => Err(invalid_value(kind, at)),
+        },
+        ParamType::Uint(bits) => {
+            let word = read_word(data, at)?;
+            if !uint_fits(word, *bits) {
+                return Err(invalid_value(kind, at));
+            }
+            Ok(Token::Uint(Uint256::from_be_bytes(*word)))
+        }
+        ParamType::Int(bits) => {
+            let word = read_word(data, at)?;
+            if !int_fits(word, *bits) {
+                return Err(invalid_value(kind, at));
+            }
+            Ok(Token::Int(Int256::from_be_bytes(*word)))
+        }
+        ParamType::FixedBytes(len) => Ok(Token::FixedBytes(read_word(data, at)?[..*len].to_vec())),
+        ParamType::Bytes => {
+            let len = read_usize(data, at)?;
+            Ok(Token::Bytes(read_bytes(data, at + WORD, len)?.to_vec()))
+        }
+        ParamType::String => {
+            let len = read_usize(data, at)?;
+            let bz = read_bytes(data, at + WORD, len)?;
+            String::from_utf8(bz.to_vec())
+                .map(Token::String)
+                .map_err(|_| invalid_value(kind, at))
+        }
+        ParamType::Array(inner) => {
+            let len = read_usize(data, at)?;
+            // every item takes at least one word, reject lengths the data can't hold
+            if len > data.len() / WORD {
+                return Err(invalid_value(kind, at));
+            }
+            let types = vec![inner.as_ref().clone(); len];
+            decode_params(&types, data, at + WORD).map(Token::Array)
+        }
+        ParamType::FixedArray(inner, size) => {
+            let types = vec![inner.as_ref().clone(); *size];
+            decode_params(&types, data, at).map(Token::FixedArray)
+        }
+        ParamType::Tuple(components) => decode_params(components, data, at).map(Token::Tuple),
+    }
+}
+
+fn read_bytes(data: &[u8], at: usize, len: usize) -> StdResult<&[u8]> {
+    at.checked_add(len)
+        .and_then(|end| data.get(at..end))
+        .ok_or_else(|| {
+            StdError::generic_err(format!(
+                "abi: data too short: need {} bytes at offset {}, data len: {}",
+                len,
+                at,
+                data.len()
+            ))
+        })
+}
+
+fn read_word(data: &[u8], at: usize) -> StdResult<&[u8; WORD]> {
+    Ok(read_bytes(data, at, WORD)?.try_into().unwrap())
+}
+
+fn read_usize(data: &[u8], at: usize) -> StdResult<usize> {
+    let word = read_word(data, at)?;
+    if !uint_fits(word, 64) {
+        return Err(StdError::generic_err(format!(
+            "abi: offset or length out of range at offset {}",
+            at
+        )));
+    }
+    Ok(u64::from_be_bytes(word[24..].try_into().unwrap()) as usize)
+}
+
+fn invalid_value(kind: &ParamType, at: usize) -> StdError {
+    StdError::generic_err(format!("abi: invalid {} at offset {}", kind, at))
+}
+
+// === functions and events ===
+
+/// Contract function declared by its signature, e.g. `approve(address spender, uint256 amount)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    pub name: String,
+    pub inputs: Vec<ParamType>,
+    selector: [u8; 4],
+}
+
+impl Function {
+    pub fn parse(signature: &str) -> StdResult<Self> {
+        let (name, params) = parse_signature(signature)?;
+        let inputs = params
+            .into_iter()
+            .map(|(kind, indexed)| match indexed {
+                true => Err(StdError::generic_err(format!(
+                    "abi: indexed param in function: {}",
+                    signature
+                ))),
+                false => Ok(kind),
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let mut function = Function {
+            name,
+            inputs,
+            selector: [0; 4],
+        };
+        function.selector = keccak256(function.signature().as_bytes())[..4]
+            .try_into()
+            .unwrap();
+        Ok(function)
+    }
+
+    /// Overrides the selector for contracts whose entry point isn't derived from the signature
+    pub fn with_selector(mut self, selector: [u8; 4]) -> Self {
+        self.selector = selector;
+        self
+    }
+
+    /// Canonical signature, without param names
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, join(&self.inputs))
+    }
+
+    pub fn selector(&self) -> [u8; 4] {
+        self.selector
+    }
+
+    pub fn encode_input(&self, tokens: &[Token]) -> StdResult<Vec<u8>> {
+        check_tokens(&self.signature(), &self.inputs, tokens)?;
+        let mut calldata = self.selector.to_vec();
+        calldata.extend(encode(tokens));
+        Ok(calldata)
+    }
+
+    pub fn decode_input(&self, calldata: &[u8]) -> StdResult<Vec<Token>> {
+        match calldata.strip_prefix(self.selector.as_slice()) {
+            Some(data) => decode(&self.inputs, data),
+            None => Err(StdError::generic_err(format!(
+                "abi: calldata is not a call to {}",
+                self.signature()
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventParam {
+    pub kind: ParamType,
+    pub indexed: bool,
+}
+
+/// Contract event declared by its signature, e.g. `Transfer(address indexed from, address indexed to, uint256 value)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub name: String,
+    pub inputs: Vec<EventParam>,
+}
+
+impl Event {
+    pub fn parse(signature: &str) -> StdResult<Self> {
+        let (name, params) = parse_signature(signature)?;
+        let inputs = params
+            .into_iter()
+            .map(|(kind, indexed)| EventParam { kind, indexed })
+            .collect();
+        Ok(Event { name, inputs })
+    }
+
+    /// Canonical signature, without param names and `indexed` markers
+    pub fn signature(&self) -> String {
+        let kinds: Vec<_> = self.inputs.iter().map(|p| p.kind.clone()).collect();
+        format!("{}({})", self.name, join(&kinds))
+    }
+
+    /// First topic of every log emitted by this event
+    pub fn topic(&self) -> [u8; 32] {
+        keccak256(self.signature().as_bytes())
+    }
+
+    /// Decodes log params in declaration order, indexed dynamic params are returned as
+    /// their topic hash in `Token::FixedBytes`
+    pub fn decode_log<T: AsRef<[u8]>>(&self, topics: &[T], data: &[u8]) -> StdResult<Vec<Token>> {
+        let indexed = self.inputs.iter().filter(|p| p.indexed).count();
+        if topics.len() != indexed + 1 {
+            return Err(StdError::generic_err(format!(
+                "abi: {} expects {} topics, got {}",
+                self.signature(),
+                indexed + 1,
+                topics.len()
+            )));
+        }
+
+        if topics[0].as_ref() != self.topic().as_slice() {
+            return Err(StdError::generic_err(format!(
+                "abi: log is not a {} event",
+                self.signature()
+            )));
+        }
+
+        let data_types: Vec<_> = self
+            .inputs
+            .iter()
+            .filter(|p| !p.indexed)
+            .map(|p| p.kind.clone())
+            .collect();
+        let mut data_tokens = decode(&data_types, data)?.into_iter();
+        let mut topics = topics[1..].iter();
+
+        self.inputs
+            .iter()
+            .map(|p| match p.indexed {
+                true => {
+                    let topic = topics.next().unwrap().as_ref();
+                    if p.kind.is_dynamic() {
+                        Ok(Token::FixedBytes(topic.to_vec()))
+                    } else {
+                        decode_param(&p.kind, topic, 0)
+                    }
+                }
+                false => Ok(data_tokens.next().unwrap()),
+            })
+            .collect()
+    }
+
+    pub fn is_emitted_by<T: AsRef<[u8]>>(&self, topics: &[T]) -> bool {
+        topics
+            .first()
+            .is_some_and(|t| t.as_ref() == self.topic().as_slice())
+    }
+}
+
+fn check_tokens(signature: &str, types: &[ParamType], tokens: &[Token]) -> StdResult<()> {
+    if types.len() != tokens.len() {
+        return Err(StdError::generic_err(format!(
+            "abi: {} expects {} params, got {}",
+            signature,
+            types.len(),
+            tokens.len()
+        )));
+    }
+
+    for (i, (kind, token)) in types.iter().zip(tokens).enumerate() {
+        if !token.type_check(kind) {
+            return Err(StdError::generic_err(format!(
+                "abi: {} expects {} at position {}, got {:?}",
+                signature, kind, i, token
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+// returns name and (type, indexed) of each param
+fn parse_signature(signature: &str) -> StdResult<(String, Vec<(ParamType, bool)>)> {
+    let invalid = || StdError::generic_err(format!("abi: invalid signature: {}", signature));
+    let signature = signature.trim();
+    let open = signature.find('(').ok_or_else(invalid)?;
+    let params = signature[open + 1..]
+        .strip_suffix(')')
+        .ok_or_else(invalid)?;
+    let name = signature[..open].trim();
+    if name.is_empty() {
+        return Err(invalid());
+    }
+
+    let params = split_top_level(params, ',')?
+        .into_iter()
+        .map(|param| {
+            let words = split_top_level(param, ' ')?;
+            let kind = ParamType::parse(words.first().ok_or_else(invalid)?)?;
+            let indexed = words.get(1).is_some_and(|w| *w == "indexed");
+            Ok((kind, indexed))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok((name.to_string(), params))
+}
+
+// splits on `sep` outside of parentheses, skipping empty parts
+fn split_top_level(s: &str, sep: char) -> StdResult<Vec<&str>> {
+    let mut parts = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| StdError::generic_err(format!("abi: unbalanced: {}", s)))?
+            }
+            c if c == sep && depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+
+    if depth != 0 {
+        return Err(StdError::generic_err(format!("abi: unbalanced: {}", s)));
+    }
+
+    parts.push(s[start..].trim());
+    parts.retain(|p| !p.is_empty());
+    Ok(parts)
+}
+
+fn join(types: &[ParamType]) -> String {
+    types
+        .iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
@@ -0,0 +1,131 @@
+use crate::{
+    astromesh::FISInput,
+    error::{NexusError, NexusResult},
+};
+use cosmwasm_std::{from_json, Binary};
+use serde::de::DeserializeOwned;
+use std::{fmt, marker::PhantomData};
+
+/// Typed position of a query result in the FIS input, i.e. `fis_input[instruction].data[index]`.
+///
+/// Actions declare their input layout as slots so a missing or malformed result fails with
+/// the slot name instead of a panic.
+pub struct Slot<T> {
+    pub instruction: usize,
+    pub index: usize,
+    pub name: &'static str,
+    ty: PhantomData<fn() -> T>,
+}
+
+impl<T> Slot<T> {
+    pub const fn new(instruction: usize, index: usize, name: &'static str) -> Self {
+        Slot {
+            instruction,
+            index,
+            name,
+            ty: PhantomData,
+        }
+    }
+
+    pub fn raw<'a>(&self, fis_input: &'a [FISInput]) -> NexusResult<&'a Binary> {
+        fis_input
+            .get(self.instruction)
+            .and_then(|input| input.data.get(self.index))
+            .ok_or_else(|| NexusError::MissingFisInput(self.to_string()))
+    }
+}
+
+impl<T: DeserializeOwned> Slot<T> {
+    pub fn load(&self, fis_input: &[FISInput]) -> NexusResult<T> {
+        self.may_load(fis_input)?
+            .ok_or_else(|| NexusError::MissingFisInput(format!("{} (null)", self)))
+    }
+
+    /// "null" results, e.g. svm accounts that don't exist yet, are `None`
+    pub fn may_load(&self, fis_input: &[FISInput]) -> NexusResult<Option<T>> {
+        let bz = self.raw(fis_input)?;
+        if is_null(bz) {
+            return Ok(None);
+        }
+        self.decode(bz).map(Some)
+    }
+
+    fn decode(&self, bz: &Binary) -> NexusResult<T> {
+        from_json(bz).map_err(|e| NexusError::InvalidFisInput {
+            slot: self.to_string(),
+            reason: e.to_string(),
+        })
+    }
+}
+
+impl<T> fmt::Display for Slot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "\"{}\" at [{}][{}]",
+            self.name, self.instruction, self.index
+        )
+    }
+}
+
+impl<T> Clone for Slot<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Slot<T> {}
+
+/// Every result of one query instruction, e.g. the events of a log query
+pub struct Slots<T> {
+    pub instruction: usize,
+    pub name: &'static str,
+    ty: PhantomData<fn() -> T>,
+}
+
+impl<T> Slots<T> {
+    pub const fn new(instruction: usize, name: &'static str) -> Self {
+        Slots {
+            instruction,
+            name,
+            ty: PhantomData,
+        }
+    }
+
+    pub fn raw<'a>(&self, fis_input: &'a [FISInput]) -> NexusResult<&'a [Binary]> {
+        fis_input
+            .get(self.instruction)
+            .map(|input| input.data.as_slice())
+            .ok_or_else(|| NexusError::MissingFisInput(self.to_string()))
+    }
+
+    pub fn at(&self, index: usize) -> Slot<T> {
+        Slot::new(self.instruction, index, self.name)
+    }
+}
+
+impl<T: DeserializeOwned> Slots<T> {
+    pub fn load(&self, fis_input: &[FISInput]) -> NexusResult<Vec<T>> {
+        (0..self.raw(fis_input)?.len())
+            .map(|index| self.at(index).load(fis_input))
+            .collect()
+    }
+}
+
+impl<T> fmt::Display for Slots<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\" at [{}]", self.name, self.instruction)
+    }
+}
+
+impl<T> Clone for Slots<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Slots<T> {}
+
+fn is_null(bz: &[u8]) -> bool {
+    bz.trim_ascii() == b"null"
+}
@@ -0,0 +1,14 @@
+pub mod astromesh;
+pub mod denom;
+pub mod error;
+#[cfg(feature = "evm")]
+pub mod evm;
+pub mod input;
+pub mod query;
+pub mod strategy;
+#[cfg(feature = "svm")]
+pub mod svm;
+mod test;
+
+// re-exported so that `nexus_strategy!` expansions resolve regardless of the bot's imports
+pub use cosmwasm_std;
@@ -0,0 +1,47 @@
+use crate::astromesh::{FisAction, Plane};
+use cosmwasm_std::Binary;
+use serde::{Deserialize, Serialize};
+
+/// Query the chain runs before calling the strategy, its results are the matching `FISInput`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QueryInstruction {
+    pub plane: Plane,
+    pub action: FisAction,
+    pub address: Binary,
+    pub input: Vec<Binary>,
+}
+
+impl QueryInstruction {
+    /// grpc gateway query, `path` may contain prompt placeholders such as `${wallet}`
+    pub fn cosmos_query(path: &str) -> Self {
+        QueryInstruction {
+            plane: Plane::Cosmos,
+            action: FisAction::CosmosQuery,
+            address: Binary::default(),
+            input: vec![Binary::from(path.as_bytes())],
+        }
+    }
+
+    pub fn vm_query(plane: Plane, address: impl Into<Binary>, input: Vec<Binary>) -> Self {
+        QueryInstruction {
+            plane,
+            action: FisAction::VmQuery,
+            address: address.into(),
+            input,
+        }
+    }
+}
+
+/// Ordered query instructions of a prompt, instruction `i` is answered by `fis_input[i]`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Query {
+    pub instructions: Vec<QueryInstruction>,
+}
+
+impl Query {
+    /// Appends the instruction and returns its index in the fis input
+    pub fn push(&mut self, instruction: QueryInstruction) -> usize {
+        self.instructions.push(instruction);
+        self.instructions.len() - 1
+    }
+}
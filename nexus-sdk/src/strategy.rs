@@ -0,0 +1,195 @@
+use crate::{
+    astromesh::{FISInput, QueryMsg, StrategyOutput},
+    error::{NexusError, NexusResult},
+};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    from_json, to_json_binary, to_json_vec, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo,
+    Response, StdError, StdResult, Storage,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+const OWNER_KEY: &[u8] = b"owner";
+const NETWORK_KEY: &[u8] = b"network";
+const CONFIG_KEY: &[u8] = b"config";
+
+/// `owner` is allowed to execute `ExecuteMsg`, defaults to the sender.
+/// `network` holds the deployment specific addresses of the strategy, e.g. pool accounts.
+/// When unset the strategy uses the network embedded at build time.
+/// `config` holds the strategy parameters, `C::default()` when unset.
+#[cw_serde]
+pub struct InstantiateMsg<N = Empty, C = Empty> {
+    pub owner: Option<String>,
+    pub network: Option<N>,
+    pub config: Option<C>,
+}
+
+/// Owner only
+#[cw_serde]
+pub enum ExecuteMsg<N = Empty, C = Empty> {
+    UpdateConfig { config: C },
+    UpdateNetwork { network: N },
+    TransferOwnership { owner: String },
+}
+
+pub fn instantiate<N: Serialize, C: Serialize + Default>(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg<N, C>,
+) -> StdResult<Response> {
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender,
+    };
+    deps.storage.set(OWNER_KEY, &to_json_vec(&owner)?);
+    if let Some(network) = msg.network {
+        deps.storage.set(NETWORK_KEY, &to_json_vec(&network)?);
+    }
+    let config = msg.config.unwrap_or_default();
+    deps.storage.set(CONFIG_KEY, &to_json_vec(&config)?);
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", owner))
+}
+
+/// Owner set at instantiate, `None` for contracts instantiated before owners were stored
+pub fn load_owner(storage: &dyn Storage) -> StdResult<Option<Addr>> {
+    storage.get(OWNER_KEY).map(from_json).transpose()
+}
+
+/// Network stored at instantiate time, `None` if the contract was instantiated without one
+pub fn load_network<N: DeserializeOwned>(storage: &dyn Storage) -> StdResult<Option<N>> {
+    storage
+        .get(NETWORK_KEY)
+        .map(|network| from_json(network))
+        .transpose()
+}
+
+/// Config stored at instantiate or by `UpdateConfig`, `C::default()` if none was stored
+pub fn load_config<C: DeserializeOwned + Default>(storage: &dyn Storage) -> StdResult<C> {
+    Ok(storage
+        .get(CONFIG_KEY)
+        .map(|config| from_json(config))
+        .transpose()?
+        .unwrap_or_default())
+}
+
+pub fn execute<N: Serialize, C: Serialize>(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg<N, C>,
+) -> NexusResult<Response> {
+    if load_owner(deps.storage)?.as_ref() != Some(&info.sender) {
+        return Err(NexusError::Unauthorized(info.sender.to_string()));
+    }
+
+    let response = match msg {
+        ExecuteMsg::UpdateConfig { config } => {
+            deps.storage.set(CONFIG_KEY, &to_json_vec(&config)?);
+            Response::new().add_attribute("method", "update_config")
+        }
+        ExecuteMsg::UpdateNetwork { network } => {
+            deps.storage.set(NETWORK_KEY, &to_json_vec(&network)?);
+            Response::new().add_attribute("method", "update_network")
+        }
+        ExecuteMsg::TransferOwnership { owner } => {
+            let owner = deps.api.addr_validate(&owner)?;
+            deps.storage.set(OWNER_KEY, &to_json_vec(&owner)?);
+            Response::new()
+                .add_attribute("method", "transfer_ownership")
+                .add_attribute("owner", owner)
+        }
+    };
+    Ok(response)
+}
+
+/// Decodes `msg.msg` into the strategy action and serializes the handler output.
+///
+/// Handler errors, e.g. `NexusError`, are converted to `StdError` here.
+pub fn query<A, F, E>(deps: Deps, env: Env, msg: QueryMsg, handler: F) -> StdResult<Binary>
+where
+    A: DeserializeOwned,
+    F: Fn(Deps, Env, A, &[FISInput]) -> Result<StrategyOutput, E>,
+    E: Into<StdError>,
+{
+    let action = from_json::<A>(&msg.msg)?;
+    let output = handler(deps, env, action, &msg.fis_input).map_err(Into::into)?;
+    to_json_binary(&output)
+}
+
+/// Same as `query` for strategies that only look at the FIS input, e.g. crons
+pub fn query_input<F, E>(deps: Deps, env: Env, msg: QueryMsg, handler: F) -> StdResult<Binary>
+where
+    F: Fn(Deps, Env, &[FISInput]) -> Result<StrategyOutput, E>,
+    E: Into<StdError>,
+{
+    let output = handler(deps, env, &msg.fis_input).map_err(Into::into)?;
+    to_json_binary(&output)
+}
+
+/// Expands to the instantiate/execute/query entry points of a strategy contract.
+///
+/// `nexus_strategy!(NexusAction, handle)` decodes the query message as `NexusAction`,
+/// `nexus_strategy!(handle)` hands over the FIS input only. Either form takes a trailing
+/// `network = Network` to accept a `Network` at instantiate, read back with `load_network`,
+/// and `config = Config` to accept a `Config`, read back with `load_config`.
+/// Must be invoked at the crate root, the entry points are left out when the `library`
+/// feature is enabled.
+#[macro_export]
+macro_rules! nexus_strategy {
+    (@type) => { $crate::cosmwasm_std::Empty };
+    (@type $ty:ty) => { $ty };
+    (@entry_points [$($network:ty)?] [$($config:ty)?]) => {
+        #[cfg_attr(not(feature = "library"), $crate::cosmwasm_std::entry_point)]
+        pub fn instantiate(
+            deps: $crate::cosmwasm_std::DepsMut,
+            env: $crate::cosmwasm_std::Env,
+            info: $crate::cosmwasm_std::MessageInfo,
+            msg: $crate::strategy::InstantiateMsg<
+                $crate::nexus_strategy!(@type $($network)?),
+                $crate::nexus_strategy!(@type $($config)?),
+            >,
+        ) -> $crate::cosmwasm_std::StdResult<$crate::cosmwasm_std::Response> {
+            $crate::strategy::instantiate(deps, env, info, msg)
+        }
+
+        #[cfg_attr(not(feature = "library"), $crate::cosmwasm_std::entry_point)]
+        pub fn execute(
+            deps: $crate::cosmwasm_std::DepsMut,
+            env: $crate::cosmwasm_std::Env,
+            info: $crate::cosmwasm_std::MessageInfo,
+            msg: $crate::strategy::ExecuteMsg<
+                $crate::nexus_strategy!(@type $($network)?),
+                $crate::nexus_strategy!(@type $($config)?),
+            >,
+        ) -> $crate::cosmwasm_std::StdResult<$crate::cosmwasm_std::Response> {
+            $crate::strategy::execute(deps, env, info, msg).map_err(Into::into)
+        }
+    };
+    ($action:ty, $handler:path $(, network = $network:ty)? $(, config = $config:ty)?) => {
+        $crate::nexus_strategy!(@entry_points [$($network)?] [$($config)?]);
+
+        #[cfg_attr(not(feature = "library"), $crate::cosmwasm_std::entry_point)]
+        pub fn query(
+            deps: $crate::cosmwasm_std::Deps,
+            env: $crate::cosmwasm_std::Env,
+            msg: $crate::astromesh::QueryMsg,
+        ) -> $crate::cosmwasm_std::StdResult<$crate::cosmwasm_std::Binary> {
+            $crate::strategy::query::<$action, _, _>(deps, env, msg, $handler)
+        }
+    };
+    ($handler:path $(, network = $network:ty)? $(, config = $config:ty)?) => {
+        $crate::nexus_strategy!(@entry_points [$($network)?] [$($config)?]);
+
+        #[cfg_attr(not(feature = "library"), $crate::cosmwasm_std::entry_point)]
+        pub fn query(
+            deps: $crate::cosmwasm_std::Deps,
+            env: $crate::cosmwasm_std::Env,
+            msg: $crate::astromesh::QueryMsg,
+        ) -> $crate::cosmwasm_std::StdResult<$crate::cosmwasm_std::Binary> {
+            $crate::strategy::query_input(deps, env, msg, $handler)
+        }
+    };
+}
@@ -0,0 +1,560 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{from_json, Binary, StdError, StdResult, Uint64};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+const PDA_MARKER: &[u8; 21] = b"ProgramDerivedAddress";
+pub const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+pub const SYS_VAR_RENT_ID: &str = "SysvarRent111111111111111111111111111111111";
+pub const SPL_TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const SPL_TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+pub const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+
+#[cw_serde]
+pub struct Link {
+    pub cosmos_addr: String,
+    pub svm_addr: String,
+    pub height: Uint64,
+}
+
+/// Response of `/flux/svm/v1beta1/account_link/cosmos/{address}`
+#[cw_serde]
+pub struct AccountLink {
+    pub link: Link,
+}
+
+#[cw_serde]
+pub struct Account {
+    pub pubkey: Binary,
+    pub owner: Binary,
+    pub lamports: Uint64, // JSON cdc returns string (with quotes), standard u64 can't be parsed
+    pub data: Binary,
+    pub executable: bool,
+    pub rent_epoch: Uint64,
+}
+
+impl Account {
+    pub fn from_json_bytes(bz: &[u8]) -> StdResult<Self> {
+        from_json(bz)
+    }
+}
+
+#[cw_serde]
+pub struct MsgTransaction {
+    /// Sender is the address of the actor that signed the message
+    pub signers: Vec<String>,
+    /// Accounts are the cosmos addresses that sign this message
+    pub accounts: Vec<String>,
+    /// Instructions are the instructions for the transaction
+    pub instructions: Vec<Instruction>,
+    /// ComputeBudget is the budget for computation
+    pub compute_budget: u64,
+}
+
+#[cw_serde]
+pub struct InstructionAccount {
+    pub id_index: u32,
+    pub caller_index: u32,
+    pub callee_index: u32,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[cw_serde]
+pub struct Instruction {
+    /// ProgramIndex is a list of program indices
+    pub program_index: Vec<u32>,
+    /// Accounts are the accounts involved in the instruction
+    pub accounts: Vec<InstructionAccount>,
+    /// Data is the data for the instruction
+    pub data: Binary,
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct InstructionAccountMeta {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl InstructionAccountMeta {
+    pub fn new(pubkey: impl ToString, is_signer: bool, is_writable: bool) -> Self {
+        InstructionAccountMeta {
+            pubkey: pubkey.to_string(),
+            is_signer,
+            is_writable,
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct InstructionMeta {
+    pub program_id: String,
+    pub account_meta: Vec<InstructionAccountMeta>,
+    pub data: Binary,
+}
+
+/// Collects instructions by pubkey and resolves the account indices of `MsgTransaction`
+#[derive(Default)]
+pub struct TransactionBuilder {
+    instructions: Vec<InstructionMeta>,
+}
+
+impl TransactionBuilder {
+    pub fn new() -> TransactionBuilder {
+        TransactionBuilder {
+            instructions: vec![],
+        }
+    }
+
+    pub fn add_instructions(&mut self, ixs: Vec<InstructionMeta>) -> &mut Self {
+        self.instructions.extend(ixs);
+        self
+    }
+
+    pub fn build(&self, cosmos_signers: Vec<String>, compute_budget: u64) -> MsgTransaction {
+        // Collect unique accounts and assign indices using BTreeMap
+        let mut account_map: BTreeMap<String, u32> = BTreeMap::new();
+        let mut accounts: Vec<String> = Vec::new();
+
+        for ix in &self.instructions {
+            let keys = std::iter::once(&ix.program_id)
+                .chain(ix.account_meta.iter().map(|meta| &meta.pubkey));
+            for key in keys {
+                if !account_map.contains_key(key) {
+                    account_map.insert(key.clone(), accounts.len() as u32);
+                    accounts.push(key.clone());
+                }
+            }
+        }
+
+        // Transform instructions meta into instruction
+        let instructions = self
+            .instructions
+            .iter()
+            .map(|ix| {
+                // callee index is the position of the first occurrence within the instruction
+                let mut instruction_acc_map: BTreeMap<&str, u32> = BTreeMap::new();
+                let accounts = ix
+                    .account_meta
+                    .iter()
+                    .enumerate()
+                    .map(|(i, meta)| {
+                        let callee_index = *instruction_acc_map
+                            .entry(meta.pubkey.as_str())
+                            .or_insert(i as u32);
+                        let id_index = account_map[&meta.pubkey];
+                        InstructionAccount {
+                            id_index,
+                            caller_index: id_index,
+                            callee_index,
+                            is_signer: meta.is_signer,
+                            is_writable: meta.is_writable,
+                        }
+                    })
+                    .collect();
+
+                Instruction {
+                    program_index: vec![account_map[&ix.program_id]],
+                    accounts,
+                    data: ix.data.clone(),
+                }
+            })
+            .collect();
+
+        // Assemble MsgTransaction
+        MsgTransaction {
+            signers: cosmos_signers,
+            accounts,
+            instructions,
+            compute_budget,
+        }
+    }
+}
+
+// === crypto utils ===
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    BorshSerialize,
+    BorshDeserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Default,
+)]
+pub struct Pubkey(pub [u8; 32]);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PubkeyError {
+    MaxSeedLengthExceeded,
+    InvalidSeeds,
+    IllegalOwner,
+}
+
+#[derive(Clone, Default)]
+pub struct Hasher {
+    hasher: Sha256,
+}
+
+pub struct Hash(pub [u8; 32]);
+
+impl Hasher {
+    pub fn hash(&mut self, val: &[u8]) {
+        self.hasher.update(val);
+    }
+    pub fn hashv(&mut self, vals: &[&[u8]]) {
+        for val in vals {
+            self.hash(val);
+        }
+    }
+    pub fn result(self) -> Hash {
+        Hash(self.hasher.finalize().into())
+    }
+}
+
+impl fmt::Display for Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&bs58::encode(self.0).into_string())
+    }
+}
+
+impl FromStr for Pubkey {
+    type Err = StdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Pubkey::from_string(s)
+    }
+}
+
+impl Pubkey {
+    pub fn from_slice(bz: &[u8]) -> StdResult<Self> {
+        if bz.len() != 32 {
+            return Err(StdError::generic_err(format!(
+                "pubkey must be 32 bytes: {}",
+                bz.len()
+            )));
+        }
+
+        let mut pubkey: [u8; 32] = [0; 32];
+        pubkey.copy_from_slice(bz);
+        Ok(Self(pubkey))
+    }
+
+    pub fn from_string(s: &str) -> StdResult<Self> {
+        let bz = bs58::decode(s)
+            .into_vec()
+            .map_err(|e| StdError::generic_err(format!("pubkey from string: {}: {}", s, e)))?;
+        Pubkey::from_slice(bz.as_slice())
+            .map_err(|e| StdError::generic_err(format!("pubkey from string: {}: {}", s, e)))
+    }
+
+    pub fn find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> Option<(Pubkey, u8)> {
+        let mut bump_seed = [u8::MAX];
+        for _ in 0..u8::MAX {
+            {
+                let mut seeds_with_bump = seeds.to_vec();
+                seeds_with_bump.push(&bump_seed);
+                match Self::create_program_address(&seeds_with_bump, program_id) {
+                    Ok(address) => return Some((address, bump_seed[0])),
+                    Err(PubkeyError::InvalidSeeds) => (),
+                    _ => break,
+                }
+            }
+            bump_seed[0] -= 1;
+        }
+        None
+    }
+
+    pub fn create_program_address(
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+    ) -> Result<Pubkey, PubkeyError> {
+        if seeds.len() > 255 {
+            return Err(PubkeyError::MaxSeedLengthExceeded);
+        }
+
+        for seed in seeds.iter() {
+            if seed.len() > 32 {
+                return Err(PubkeyError::MaxSeedLengthExceeded);
+            }
+        }
+
+        let mut hasher = Hasher::default();
+        for seed in seeds.iter() {
+            hasher.hash(seed);
+        }
+        hasher.hashv(&[program_id.0.as_slice(), PDA_MARKER]);
+        let hash = hasher.result();
+
+        if bytes_are_curve_point(hash.0) {
+            return Err(PubkeyError::InvalidSeeds);
+        }
+
+        Ok(Pubkey(hash.0))
+    }
+}
+
+pub fn bytes_are_curve_point<T: AsRef<[u8]>>(bytes: T) -> bool {
+    curve25519_dalek::edwards::CompressedEdwardsY::from_slice(bytes.as_ref())
+        .map(|point| point.decompress().is_some())
+        .unwrap_or(false)
+}
+
+/// Derives the associated token account of `wallet` for `mint` under `token_program`
+pub fn get_associated_token_address(
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> StdResult<Pubkey> {
+    let ata_program = Pubkey::from_string(ASSOCIATED_TOKEN_PROGRAM_ID)?;
+    Pubkey::find_program_address(&[&wallet.0, &token_program.0, &mint.0], &ata_program)
+        .map(|(ata, _)| ata)
+        .ok_or_else(|| {
+            StdError::generic_err(format!(
+                "failed to find associated token account of {} for mint {}",
+                wallet, mint
+            ))
+        })
+}
+
+// === token accounts ===
+
+// spl token and token 2022 share the base layout, token 2022 appends
+// the account type and extensions after it
+const ACCOUNT_TYPE_OFFSET: usize = TokenAccount::LEN;
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+fn read_u64(bz: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bz[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_option_pubkey(bz: &[u8], offset: usize) -> StdResult<Option<Pubkey>> {
+    match bz[offset..offset + 4] {
+        [0, 0, 0, 0] => Ok(None),
+        [1, 0, 0, 0] => Ok(Some(Pubkey::from_slice(&bz[offset + 4..offset + 36])?)),
+        _ => Err(StdError::generic_err("invalid option tag")),
+    }
+}
+
+fn check_account_type(bz: &[u8], base_len: usize, account_type: u8) -> StdResult<()> {
+    if bz.len() < base_len {
+        return Err(StdError::generic_err(format!(
+            "token account data must be at least {} bytes, current len: {}",
+            base_len,
+            bz.len()
+        )));
+    }
+
+    if bz.len() > base_len && bz.get(ACCOUNT_TYPE_OFFSET) != Some(&account_type) {
+        return Err(StdError::generic_err("unexpected token 2022 account type"));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountState {
+    Uninitialized,
+    Initialized,
+    Frozen,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenAccount {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub delegate: Option<Pubkey>,
+    pub state: AccountState,
+    pub is_native: Option<u64>,
+    pub delegated_amount: u64,
+    pub close_authority: Option<Pubkey>,
+}
+
+impl TokenAccount {
+    pub const LEN: usize = 165;
+
+    /// Unpacks an spl token or token 2022 account, extensions are skipped
+    pub fn unpack(bz: &[u8]) -> StdResult<TokenAccount> {
+        check_account_type(bz, Self::LEN, ACCOUNT_TYPE_ACCOUNT)?;
+
+        let state = match bz[108] {
+            0 => AccountState::Uninitialized,
+            1 => AccountState::Initialized,
+            2 => AccountState::Frozen,
+            s => {
+                return Err(StdError::generic_err(format!(
+                    "invalid account state: {}",
+                    s
+                )))
+            }
+        };
+        let is_native = match bz[109..113] {
+            [0, 0, 0, 0] => None,
+            [1, 0, 0, 0] => Some(read_u64(bz, 113)),
+            _ => return Err(StdError::generic_err("invalid option tag")),
+        };
+
+        Ok(TokenAccount {
+            mint: Pubkey::from_slice(&bz[0..32])?,
+            owner: Pubkey::from_slice(&bz[32..64])?,
+            amount: read_u64(bz, 64),
+            delegate: read_option_pubkey(bz, 72)?,
+            state,
+            is_native,
+            delegated_amount: read_u64(bz, 121),
+            close_authority: read_option_pubkey(bz, 129)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mint {
+    pub mint_authority: Option<Pubkey>,
+    pub supply: u64,
+    pub decimals: u8,
+    pub is_initialized: bool,
+    pub freeze_authority: Option<Pubkey>,
+}
+
+impl Mint {
+    pub const LEN: usize = 82;
+
+    /// Unpacks an spl token or token 2022 mint, extensions are skipped
+    pub fn unpack(bz: &[u8]) -> StdResult<Mint> {
+        check_account_type(bz, Self::LEN, ACCOUNT_TYPE_MINT)?;
+
+        Ok(Mint {
+            mint_authority: read_option_pubkey(bz, 0)?,
+            supply: read_u64(bz, 36),
+            decimals: bz[44],
+            is_initialized: bz[45] != 0,
+            freeze_authority: read_option_pubkey(bz, 46)?,
+        })
+    }
+}
+
+// === instruction builders ===
+
+pub mod system_program {
+    use super::{InstructionAccountMeta, InstructionMeta, Pubkey, SYSTEM_PROGRAM_ID};
+    use cosmwasm_std::Binary;
+
+    pub fn create_account(
+        from: &Pubkey,
+        to: &Pubkey,
+        lamports: u64,
+        space: u64,
+        owner: &Pubkey,
+    ) -> InstructionMeta {
+        let mut data = 0u32.to_le_bytes().to_vec();
+        data.extend(lamports.to_le_bytes());
+        data.extend(space.to_le_bytes());
+        data.extend(owner.0);
+
+        InstructionMeta {
+            program_id: SYSTEM_PROGRAM_ID.to_string(),
+            account_meta: vec![
+                InstructionAccountMeta::new(from, true, true),
+                InstructionAccountMeta::new(to, true, true),
+            ],
+            data: Binary::from(data),
+        }
+    }
+
+    pub fn transfer(from: &Pubkey, to: &Pubkey, lamports: u64) -> InstructionMeta {
+        let mut data = 2u32.to_le_bytes().to_vec();
+        data.extend(lamports.to_le_bytes());
+
+        InstructionMeta {
+            program_id: SYSTEM_PROGRAM_ID.to_string(),
+            account_meta: vec![
+                InstructionAccountMeta::new(from, true, true),
+                InstructionAccountMeta::new(to, false, true),
+            ],
+            data: Binary::from(data),
+        }
+    }
+}
+
+pub mod associated_token {
+    use super::{
+        get_associated_token_address, InstructionAccountMeta, InstructionMeta, Pubkey,
+        ASSOCIATED_TOKEN_PROGRAM_ID, SYSTEM_PROGRAM_ID,
+    };
+    use cosmwasm_std::{Binary, StdResult};
+
+    fn create_ix(
+        payer: &Pubkey,
+        wallet: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+        data: Vec<u8>,
+    ) -> StdResult<InstructionMeta> {
+        let ata = get_associated_token_address(wallet, mint, token_program)?;
+        Ok(InstructionMeta {
+            program_id: ASSOCIATED_TOKEN_PROGRAM_ID.to_string(),
+            account_meta: vec![
+                InstructionAccountMeta::new(payer, true, true),
+                InstructionAccountMeta::new(ata, false, true),
+                InstructionAccountMeta::new(wallet, false, false),
+                InstructionAccountMeta::new(mint, false, false),
+                InstructionAccountMeta::new(SYSTEM_PROGRAM_ID, false, false),
+                InstructionAccountMeta::new(token_program, false, false),
+            ],
+            data: Binary::from(data),
+        })
+    }
+
+    pub fn create(
+        payer: &Pubkey,
+        wallet: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> StdResult<InstructionMeta> {
+        create_ix(payer, wallet, mint, token_program, vec![0])
+    }
+
+    /// Same as `create` but doesn't fail when the account already exists
+    pub fn create_idempotent(
+        payer: &Pubkey,
+        wallet: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> StdResult<InstructionMeta> {
+        create_ix(payer, wallet, mint, token_program, vec![1])
+    }
+}
+
+pub mod compute_budget {
+    use super::{InstructionMeta, COMPUTE_BUDGET_PROGRAM_ID};
+    use cosmwasm_std::Binary;
+
+    fn compute_budget_ix(data: Vec<u8>) -> InstructionMeta {
+        InstructionMeta {
+            program_id: COMPUTE_BUDGET_PROGRAM_ID.to_string(),
+            account_meta: vec![],
+            data: Binary::from(data),
+        }
+    }
+
+    pub fn set_compute_unit_limit(units: u32) -> InstructionMeta {
+        let mut data = vec![2u8];
+        data.extend(units.to_le_bytes());
+        compute_budget_ix(data)
+    }
+
+    pub fn set_compute_unit_price(micro_lamports: u64) -> InstructionMeta {
+        let mut data = vec![3u8];
+        data.extend(micro_lamports.to_le_bytes());
+        compute_budget_ix(data)
+    }
+}
@@ -0,0 +1,562 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        astromesh::{
+            FISInput, FISInstruction, FisAction, MsgAstroTransfer, Plane, QueryMsg, StrategyOutput,
+        },
+        error::NexusError,
+        strategy,
+    };
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{
+        from_json,
+        testing::{message_info, mock_dependencies, mock_env},
+        to_json_binary, to_json_string, Addr, Binary, Coin, Deps, Empty, Env, StdResult, Uint128,
+        Uint64,
+    };
+
+    #[cw_serde]
+    enum TestAction {
+        Echo { value: String },
+    }
+
+    fn echo(
+        _deps: Deps,
+        _env: Env,
+        action: TestAction,
+        fis_input: &[FISInput],
+    ) -> StdResult<StrategyOutput> {
+        let TestAction::Echo { value } = action;
+        Ok(StrategyOutput {
+            instructions: vec![],
+            events: vec![],
+            result: format!("{}:{}", value, fis_input.len()),
+        })
+    }
+
+    #[test]
+    fn test_strategy_output_omits_empty_fields() {
+        let output = StrategyOutput::new(vec![FISInstruction::cosmos_invoke(vec![1])]);
+        assert_eq!(
+            to_json_string(&output).unwrap(),
+            r#"{"instructions":[{"plane":"COSMOS","action":"COSMOS_INVOKE","address":"","msg":[1]}]}"#
+        );
+
+        let decoded: StrategyOutput = from_json(r#"{"instructions":[]}"#).unwrap();
+        assert_eq!(decoded, StrategyOutput::default());
+    }
+
+    #[test]
+    fn test_astro_transfer_type_url() {
+        let msg = MsgAstroTransfer::new(
+            "lux1".to_string(),
+            "lux1".to_string(),
+            Plane::Cosmos,
+            Plane::Svm,
+            Coin::new(100u128, "btc"),
+        );
+        let json = to_json_string(&msg).unwrap();
+        assert!(json.starts_with(r#"{"@type":"/flux.astromesh.v1beta1.MsgAstroTransfer""#));
+        assert!(json.contains(r#""src_plane":"COSMOS","dst_plane":"SVM""#));
+        assert!(json.contains(r#""coin":{"denom":"btc","amount":"100"}"#));
+    }
+
+    #[test]
+    fn test_instruction_plane_action() {
+        let ix = FISInstruction::vm_invoke(Plane::Svm, vec![]);
+        assert_eq!((ix.plane(), ix.action()), (Plane::Svm, FisAction::VmInvoke));
+        assert!(to_json_string(&ix)
+            .unwrap()
+            .starts_with(r#"{"plane":"SVM","action":"VM_INVOKE""#));
+
+        // cosmos has no vm, so it never pairs with VM_INVOKE
+        let ix = FISInstruction::vm_invoke(Plane::Cosmos, vec![]);
+        assert_eq!(ix.action(), FisAction::CosmosInvoke);
+
+        assert_eq!(
+            to_json_string(&FisAction::CosmosAstromeshTransfer).unwrap(),
+            r#""COSMOS_ASTROMESH_TRANSFER""#
+        );
+        assert_eq!(from_json::<Plane>(r#""EVM""#).unwrap(), Plane::Evm);
+        assert_eq!("wasm".parse::<Plane>().unwrap(), Plane::Wasm);
+        assert!("BTC".parse::<Plane>().is_err());
+    }
+
+    #[test]
+    fn test_query_dispatches_action() {
+        let deps = mock_dependencies();
+        let msg = QueryMsg {
+            msg: to_json_binary(&TestAction::Echo {
+                value: "hello".to_string(),
+            })
+            .unwrap(),
+            fis_input: vec![FISInput {
+                data: vec![Binary::from(vec![0u8])],
+            }],
+        };
+        let res = strategy::query(deps.as_ref(), mock_env(), msg, echo).unwrap();
+        let output: StrategyOutput = from_json(res).unwrap();
+        assert_eq!(output.result, "hello:1");
+
+        let invalid = QueryMsg {
+            msg: Binary::from(b"{\"unknown\":{}}".to_vec()),
+            fis_input: vec![],
+        };
+        assert!(strategy::query(deps.as_ref(), mock_env(), invalid, echo).is_err());
+    }
+
+    #[test]
+    fn test_error_code_at_entry_point() {
+        fn unsupported(
+            _deps: Deps,
+            _env: Env,
+            _action: TestAction,
+            _fis_input: &[FISInput],
+        ) -> Result<StrategyOutput, NexusError> {
+            Err(NexusError::UnsupportedPair("atom-usdt".to_string()))
+        }
+
+        let deps = mock_dependencies();
+        let msg = QueryMsg {
+            msg: to_json_binary(&TestAction::Echo {
+                value: "hi".to_string(),
+            })
+            .unwrap(),
+            fis_input: vec![],
+        };
+        let err = strategy::query(deps.as_ref(), mock_env(), msg, unsupported).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Generic error: [5] unsupported pair: atom-usdt"
+        );
+        assert_eq!(
+            NexusError::from(cosmwasm_std::OverflowError::new(
+                cosmwasm_std::OverflowOperation::Add
+            ))
+            .code(),
+            9
+        );
+    }
+
+    #[test]
+    fn test_network_stored_at_instantiate() {
+        #[cw_serde]
+        struct TestNetwork {
+            program_id: String,
+        }
+
+        let mut deps = mock_dependencies();
+        let info = message_info(&Addr::unchecked("owner"), &[]);
+        let msg: strategy::InstantiateMsg<TestNetwork> = from_json(b"{}").unwrap();
+        strategy::instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(
+            strategy::load_network::<TestNetwork>(deps.as_ref().storage).unwrap(),
+            None
+        );
+
+        let msg: strategy::InstantiateMsg<TestNetwork> =
+            from_json(br#"{"network":{"program_id":"drift"}}"#).unwrap();
+        strategy::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            strategy::load_network(deps.as_ref().storage).unwrap(),
+            Some(TestNetwork {
+                program_id: "drift".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_owner_gated_config() {
+        #[cw_serde]
+        #[derive(Default)]
+        struct TestConfig {
+            max_leverage: u64,
+        }
+
+        let mut deps = mock_dependencies();
+        let owner = deps.api.addr_make("owner");
+        let other = deps.api.addr_make("other");
+        let msg: strategy::InstantiateMsg<Empty, TestConfig> = from_json(b"{}").unwrap();
+        strategy::instantiate(deps.as_mut(), mock_env(), message_info(&owner, &[]), msg).unwrap();
+        assert_eq!(
+            strategy::load_owner(deps.as_ref().storage).unwrap(),
+            Some(owner.clone())
+        );
+        assert_eq!(
+            strategy::load_config::<TestConfig>(deps.as_ref().storage).unwrap(),
+            TestConfig::default()
+        );
+
+        let update = |max_leverage: u64| strategy::ExecuteMsg::<Empty, _>::UpdateConfig {
+            config: TestConfig { max_leverage },
+        };
+        let err = strategy::execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&other, &[]),
+            update(20),
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), 11);
+
+        strategy::execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&owner, &[]),
+            strategy::ExecuteMsg::<Empty, TestConfig>::TransferOwnership {
+                owner: other.to_string(),
+            },
+        )
+        .unwrap();
+        strategy::execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&other, &[]),
+            update(20),
+        )
+        .unwrap();
+        assert_eq!(
+            strategy::load_config::<TestConfig>(deps.as_ref().storage)
+                .unwrap()
+                .max_leverage,
+            20
+        );
+    }
+
+    #[test]
+    fn test_denom_registry() {
+        use crate::denom::DenomRegistry;
+
+        let registry: DenomRegistry = from_json(
+            br#"{"eth":{
+                "cosmos":{"denom":"eth","decimals":18},
+                "evm":{"denom":"d1738300cda711f4e4c6989856c6b83326c6053e","decimals":18},
+                "svm":{"denom":"4SgGYkKAF4k3uAmkKaqMFnAuZkAhyzUuabRpHhssyW9B","decimals":9}
+            }}"#,
+        )
+        .unwrap();
+
+        assert_eq!(registry.address("eth", Plane::Wasm).unwrap(), "eth");
+        assert_eq!(
+            registry.find(Plane::Svm, "4SgGYkKAF4k3uAmkKaqMFnAuZkAhyzUuabRpHhssyW9B"),
+            Some("eth")
+        );
+        assert_eq!(registry.find(Plane::Cosmos, "btc"), None);
+        assert_eq!(registry.link("btc", Plane::Svm).unwrap_err().code(), 10);
+
+        let amount = Uint128::new(1_234_567_891_234);
+        assert_eq!(
+            registry
+                .convert("eth", Plane::Evm, Plane::Svm, amount)
+                .unwrap(),
+            Uint128::new(1_234)
+        );
+        assert_eq!(
+            registry
+                .convert("eth", Plane::Svm, Plane::Cosmos, Uint128::new(1_234))
+                .unwrap(),
+            Uint128::new(1_234_000_000_000)
+        );
+
+        let msg = registry
+            .astro_transfer(
+                "lux1".to_string(),
+                "lux1".to_string(),
+                Plane::Cosmos,
+                Plane::Svm,
+                "eth",
+                amount,
+            )
+            .unwrap();
+        assert_eq!(msg.coin, Coin::new(1_234_000_000_000u128, "eth"));
+        let coin = registry
+            .transfer_coin("eth", Plane::Svm, Plane::Evm, Uint128::new(1_234))
+            .unwrap();
+        assert_eq!(
+            coin,
+            Coin::new(
+                1_234u128,
+                "astro/4SgGYkKAF4k3uAmkKaqMFnAuZkAhyzUuabRpHhssyW9B"
+            )
+        );
+    }
+
+    #[test]
+    fn test_fis_input_slots() {
+        use crate::input::{Slot, Slots};
+
+        const BALANCE: Slot<Coin> = Slot::new(0, 0, "balance");
+        const ACCOUNT: Slot<Coin> = Slot::new(0, 1, "account");
+        const EVENTS: Slots<Coin> = Slots::new(1, "events");
+
+        let coin = to_json_binary(&Coin::new(10u128, "lux")).unwrap();
+        let fis_input = vec![
+            FISInput {
+                data: vec![coin.clone(), Binary::from(b"null".to_vec())],
+            },
+            FISInput {
+                data: vec![coin.clone(), coin],
+            },
+        ];
+
+        assert_eq!(BALANCE.load(&fis_input).unwrap(), Coin::new(10u128, "lux"));
+        assert_eq!(ACCOUNT.may_load(&fis_input).unwrap(), None);
+        assert_eq!(
+            ACCOUNT.load(&fis_input).unwrap_err(),
+            NexusError::MissingFisInput("\"account\" at [0][1] (null)".to_string())
+        );
+        assert_eq!(EVENTS.load(&fis_input).unwrap().len(), 2);
+
+        let missing = Slot::<Coin>::new(2, 0, "pool");
+        assert!(missing.may_load(&fis_input).is_err());
+        assert_eq!(
+            missing.load(&fis_input).unwrap_err().to_string(),
+            "missing fis input \"pool\" at [2][0]"
+        );
+        assert!(Slot::<Uint64>::new(0, 0, "height")
+            .load(&fis_input)
+            .unwrap_err()
+            .to_string()
+            .contains("\"height\" at [0][0]"));
+    }
+
+    #[cfg(feature = "svm")]
+    #[test]
+    fn test_build_transaction() {
+        use crate::svm::{
+            Instruction, InstructionAccount, InstructionAccountMeta, InstructionMeta,
+            TransactionBuilder,
+        };
+
+        let feepayer = "RM3uwjR7LUugxxfZLe9grNC9HNW7BMU9227KsyFsbfB";
+        let account_1 = "8LBk2doATLb8M6JX4auYe1gGQMqimHi1hwkKSkLzo6f5";
+        let account_2 = "Zs5KiCvJHCN2PwZqEQczvQGizKr6en9AAotSfi9AeWH";
+        let system_program = "11111111111111111111111111111111";
+
+        let create_account1 = InstructionMeta {
+            program_id: system_program.to_string(),
+            account_meta: vec![
+                InstructionAccountMeta::new(feepayer, true, true),
+                InstructionAccountMeta::new(account_1, true, true),
+            ],
+            data: Binary::from(vec![0x01, 0x02, 0x03]),
+        };
+        // account_2 is repeated, the callee index must point to its first occurrence
+        let create_account2 = InstructionMeta {
+            program_id: system_program.to_string(),
+            account_meta: vec![
+                InstructionAccountMeta::new(feepayer, true, true),
+                InstructionAccountMeta::new(account_2, true, true),
+                InstructionAccountMeta::new(account_2, false, true),
+            ],
+            data: Binary::from(vec![0x04, 0x05, 0x06]),
+        };
+
+        let transaction = TransactionBuilder::new()
+            .add_instructions(vec![create_account1, create_account2])
+            .build(
+                vec!["lux1jcltmuhplrdcwp7stlr4hlhlhgd4htqhu86cqx".to_string()],
+                1000,
+            );
+
+        let account = |index: u32, callee_index: u32, is_signer: bool| InstructionAccount {
+            id_index: index,
+            caller_index: index,
+            callee_index,
+            is_signer,
+            is_writable: true,
+        };
+        assert_eq!(
+            transaction.accounts,
+            vec![system_program, feepayer, account_1, account_2]
+        );
+        assert_eq!(
+            transaction.instructions,
+            vec![
+                Instruction {
+                    program_index: vec![0],
+                    accounts: vec![account(1, 0, true), account(2, 1, true)],
+                    data: Binary::from(vec![0x01, 0x02, 0x03]),
+                },
+                Instruction {
+                    program_index: vec![0],
+                    accounts: vec![
+                        account(1, 0, true),
+                        account(3, 1, true),
+                        account(3, 1, false)
+                    ],
+                    data: Binary::from(vec![0x04, 0x05, 0x06]),
+                },
+            ]
+        );
+        assert_eq!(transaction.compute_budget, 1000);
+    }
+
+    #[cfg(feature = "svm")]
+    #[test]
+    fn test_parse_token_account() {
+        use crate::svm::{AccountState, TokenAccount};
+
+        // {"Mint":"AarDASauqWwFsuG9r62pYCH3m9CFtvvQucUs2iLg18AW","Owner":"GonQpn9zzCF2rD521AiYg1RFpC4aFEzJ8RwC9XDi54L6","Amount":399000000,"Delegate":null,"State":1,"IsNative":null,"DelegatedAmount":0,"CloseAuthority":null}
+        let account_data = Binary::from_base64("jmT9mZ6EZ9zYFBt+eBm190VZG6DWEc7ey8OWylSvrTPq214B6Yq6zIE78NDn5DRCobUH5USgLNzQMQKlCAQt78BByBcAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAgcAAAA=").unwrap();
+        let acc = TokenAccount::unpack(account_data.as_slice()).unwrap();
+        assert_eq!(
+            acc.mint.to_string(),
+            "AarDASauqWwFsuG9r62pYCH3m9CFtvvQucUs2iLg18AW"
+        );
+        assert_eq!(
+            acc.owner.to_string(),
+            "GonQpn9zzCF2rD521AiYg1RFpC4aFEzJ8RwC9XDi54L6"
+        );
+        assert_eq!(acc.amount, 399000000);
+        assert_eq!(acc.state, AccountState::Initialized);
+        assert_eq!(acc.delegate, None);
+
+        assert!(TokenAccount::unpack(&account_data.as_slice()[..72]).is_err());
+    }
+
+    #[cfg(feature = "svm")]
+    #[test]
+    fn test_associated_token_address() {
+        use crate::svm::{
+            associated_token, compute_budget, get_associated_token_address, Pubkey,
+            SPL_TOKEN_2022_PROGRAM_ID,
+        };
+
+        let wallet = Pubkey::from_string("DRK5Bi2NwkGRPsqHJSyy6rhUo3uQ8YHtt1xUWbu7Bnsx").unwrap();
+        let mint = Pubkey::from_string("ENyus6yS21v95sreLKcVEA5Wjcyh8jg6w4jBFHzJaPox").unwrap();
+        let token_program = Pubkey::from_string(SPL_TOKEN_2022_PROGRAM_ID).unwrap();
+        let ata = get_associated_token_address(&wallet, &mint, &token_program).unwrap();
+        assert_eq!(
+            ata.to_string(),
+            "CytVtp6RTC9uDpf2wSqwk2WiByXXdUwmH7Hp3WiKUDMa"
+        );
+
+        let ix =
+            associated_token::create_idempotent(&wallet, &wallet, &mint, &token_program).unwrap();
+        assert_eq!(ix.account_meta[1].pubkey, ata.to_string());
+        assert_eq!(ix.data, Binary::from(vec![1]));
+
+        let ix = compute_budget::set_compute_unit_limit(200_000);
+        assert_eq!(ix.data, Binary::from(vec![2, 0x40, 0x0d, 0x03, 0x00]));
+    }
+
+    #[cfg(feature = "evm")]
+    #[test]
+    fn test_abi_encode_call() {
+        use crate::evm::{Function, Token};
+        use cosmwasm_std::{Int256, Uint256};
+
+        let approve = Function::parse("approve(address spender, uint256 amount)").unwrap();
+        assert_eq!(approve.signature(), "approve(address,uint256)");
+        assert_eq!(approve.selector(), [0x09, 0x5e, 0xa7, 0xb3]);
+
+        let initialize =
+            Function::parse("initialize((address,address,uint24,int24,address),uint160,bytes)")
+                .unwrap();
+        assert_eq!(initialize.selector(), [0x69, 0x5c, 0x5b, 0xf5]);
+        let pool_key = Token::Tuple(vec![
+            Token::Address([1; 20]),
+            Token::Address([2; 20]),
+            Token::Uint(Uint256::from(3000u128)),
+            Token::Int(Int256::from(-60i128)),
+            Token::Address([0; 20]),
+        ]);
+        let calldata = initialize
+            .encode_input(&[
+                pool_key.clone(),
+                Token::Uint(Uint256::from(1u128 << 96)),
+                Token::Bytes(vec![]),
+            ])
+            .unwrap();
+        assert_eq!(calldata.len(), 4 + 8 * 32);
+        // int24 is sign extended
+        assert_eq!(
+            calldata[4 + 3 * 32..4 + 4 * 32],
+            [[0xff; 31].as_slice(), &[0xc4]].concat()
+        );
+        // bytes offset points after the 7 head words, followed by its zero length
+        assert_eq!(calldata[4 + 6 * 32 + 31], 224);
+        assert!(calldata[4 + 7 * 32..].iter().all(|b| *b == 0));
+        assert_eq!(initialize.decode_input(&calldata).unwrap()[0], pool_key);
+
+        // values must fit the declared type
+        let too_large = Token::Tuple(vec![
+            Token::Address([1; 20]),
+            Token::Address([2; 20]),
+            Token::Uint(Uint256::from(1u128 << 24)),
+            Token::Int(Int256::zero()),
+            Token::Address([0; 20]),
+        ]);
+        assert!(initialize
+            .encode_input(&[
+                too_large,
+                Token::Uint(Uint256::zero()),
+                Token::Bytes(vec![])
+            ])
+            .is_err());
+        assert!(approve.encode_input(&[Token::Address([1; 20])]).is_err());
+    }
+
+    #[cfg(feature = "evm")]
+    #[test]
+    fn test_abi_decode() {
+        use crate::evm::{decode, encode, Event, ParamType, Token};
+        use cosmwasm_std::Uint256;
+
+        let types = ParamType::parse("(string,uint64[],(bytes,bool)[2],bytes4)").unwrap();
+        assert_eq!(
+            types.to_string(),
+            "(string,uint64[],(bytes,bool)[2],bytes4)"
+        );
+        let value = Token::Tuple(vec![
+            Token::String("nexus".to_string()),
+            Token::Array(vec![
+                Token::Uint(Uint256::one()),
+                Token::Uint(Uint256::zero()),
+            ]),
+            Token::FixedArray(vec![
+                Token::Tuple(vec![Token::Bytes(vec![7; 40]), Token::Bool(true)]),
+                Token::Tuple(vec![Token::Bytes(vec![]), Token::Bool(false)]),
+            ]),
+            Token::FixedBytes(vec![1, 2, 3, 4]),
+        ]);
+        assert!(value.type_check(&types));
+        let encoded = encode(std::slice::from_ref(&value));
+        assert_eq!(
+            decode(std::slice::from_ref(&types), &encoded).unwrap(),
+            vec![value]
+        );
+        assert!(decode(&[types], &encoded[..encoded.len() - 32]).is_err());
+
+        let event = Event::parse(
+            "LiquidityRequest(address user, address srcToken, uint256 srcAmount, address dstToken, uint256 dstAmount)",
+        )
+        .unwrap();
+        assert_eq!(event.topic()[..4], [34, 96, 129, 198],);
+        let data = encode(&[
+            Token::Address([1; 20]),
+            Token::Address([2; 20]),
+            Token::Uint(Uint256::from(100u128)),
+            Token::Address([3; 20]),
+            Token::Uint(Uint256::from(200u128)),
+        ]);
+        let tokens = event.decode_log(&[event.topic()], &data).unwrap();
+        assert_eq!(tokens[3], Token::Address([3; 20]));
+        assert!(event.decode_log(&[[0u8; 32]], &data).is_err());
+
+        let transfer =
+            Event::parse("Transfer(address indexed from, address indexed to, uint256 value)")
+                .unwrap();
+        let mut to = [0u8; 32];
+        to[12..].copy_from_slice(&[9; 20]);
+        let tokens = transfer
+            .decode_log(
+                &[transfer.topic(), [0u8; 32], to],
+                &encode(&[Token::Uint(Uint256::from(5u128))]),
+            )
+            .unwrap();
+        assert_eq!(tokens[1].clone().into_address().unwrap(), [9; 20]);
+        assert_eq!(tokens[2].clone().into_uint().unwrap(), Uint256::from(5u128));
+    }
+}